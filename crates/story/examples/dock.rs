@@ -1,13 +1,16 @@
-use anyhow::{Context as _, Result};
+use anyhow::{anyhow, Context as _, Result};
 use gpui::*;
 use gpui_component::{
     button::{Button, ButtonVariants as _},
-    dock::{ClosePanel, DockArea, DockAreaState, DockEvent, DockItem, DockPlacement, ToggleZoom},
+    dock::{
+        ClosePanel, DockArea, DockAreaState, DockEvent, DockItem, DockLayoutsState, DockPlacement,
+        ToggleZoom,
+    },
     popup_menu::PopupMenuExt,
     IconName, Root, Sizable, Theme,
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{sync::Arc, time::Duration};
 use story::{
     AccordionStory, AppState, AppTitleBar, Assets, ButtonStory, CalendarStory, DropdownStory,
@@ -24,6 +27,10 @@ pub struct AddPanel(DockPlacement);
 #[action(namespace = story, no_json)]
 pub struct TogglePanelVisible(SharedString);
 
+#[derive(Action, Clone, PartialEq, Eq, Deserialize)]
+#[action(namespace = story, no_json)]
+pub struct SwitchLayout(SharedString);
+
 actions!(story, [ToggleDockToggleButton]);
 
 const MAIN_DOCK_AREA: DockAreaTab = DockAreaTab {
@@ -31,6 +38,9 @@ const MAIN_DOCK_AREA: DockAreaTab = DockAreaTab {
     version: 5,
 };
 
+/// The oldest version of the main layout that can be migrated, the older is reset to default.
+const MIN_MIGRATE_VERSION: usize = 4;
+
 #[cfg(debug_assertions)]
 const STATE_FILE: &str = "target/docks.json";
 #[cfg(not(debug_assertions))]
@@ -50,10 +60,28 @@ pub fn init(cx: &mut App) {
     cx.activate(true);
 }
 
+/// The saved state of the workspace, the current layout and the named layouts.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct WorkspaceState {
+    #[serde(flatten)]
+    dock_area: DockAreaState,
+    #[serde(default)]
+    layouts: DockLayoutsState,
+}
+
+impl WorkspaceState {
+    fn new(dock_area: &DockArea, cx: &App) -> Self {
+        Self {
+            dock_area: dock_area.dump(cx),
+            layouts: dock_area.dump_layouts(cx),
+        }
+    }
+}
+
 pub struct StoryWorkspace {
     title_bar: Entity<AppTitleBar>,
     dock_area: Entity<DockArea>,
-    last_layout_state: Option<DockAreaState>,
+    last_layout_state: Option<WorkspaceState>,
     toggle_button_visible: bool,
     _save_layout_task: Option<Task<()>>,
 }
@@ -71,8 +99,15 @@ impl StoryWorkspace {
             })
             .detach();
 
-        let dock_area =
-            cx.new(|cx| DockArea::new(MAIN_DOCK_AREA.id, Some(MAIN_DOCK_AREA.version), window, cx));
+        let dock_area = cx.new(|cx| {
+            DockArea::new(MAIN_DOCK_AREA.id, Some(MAIN_DOCK_AREA.version), window, cx).on_migrate(
+                |state, _, _| match state.version {
+                    // The panels of these versions are still registered, keep the user's layout.
+                    Some(version) if version >= MIN_MIGRATE_VERSION => Ok(state),
+                    version => Err(anyhow!("unsupported layout version: {:?}", version)),
+                },
+            )
+        });
         let weak_dock_area = dock_area.downgrade();
 
         match Self::load_layout(dock_area.clone(), window, cx) {
@@ -95,6 +130,27 @@ impl StoryWorkspace {
         )
         .detach();
 
+        // Ask the panels with unsaved changes before closing the window.
+        window.on_window_should_close(cx, {
+            let dock_area = dock_area.downgrade();
+            move |window, cx| {
+                let Some(dock_area) = dock_area.upgrade() else {
+                    return true;
+                };
+
+                let can_close =
+                    dock_area.update(cx, |dock_area, cx| dock_area.can_close(window, cx));
+                window
+                    .spawn(cx, async move |cx| {
+                        if can_close.await {
+                            _ = cx.update(|window, _| window.remove_window());
+                        }
+                    })
+                    .detach();
+                false
+            }
+        });
+
         cx.on_app_quit({
             let dock_area = dock_area.clone();
             move |_, cx| {
@@ -109,6 +165,7 @@ impl StoryWorkspace {
 
         let title_bar = cx.new(|cx| {
             AppTitleBar::new("Examples", window, cx).child({
+                let dock_area = dock_area.clone();
                 move |_, cx| {
                     Button::new("add-panel")
                         .icon(IconName::LayoutDashboard)
@@ -116,8 +173,13 @@ impl StoryWorkspace {
                         .ghost()
                         .popup_menu({
                             let invisible_panels = AppState::global(cx).invisible_panels.clone();
+                            let dock_area = dock_area.clone();
 
                             move |menu, _, cx| {
+                                let active_layout =
+                                    dock_area.read(cx).active_layout().map(String::from);
+                                let is_active = |name: &str| active_layout.as_deref() == Some(name);
+
                                 menu.menu(
                                     "Add Panel to Center",
                                     Box::new(AddPanel(DockPlacement::Center)),
@@ -166,6 +228,22 @@ impl StoryWorkspace {
                                         .contains(&SharedString::from("List")),
                                     Box::new(TogglePanelVisible(SharedString::from("List"))),
                                 )
+                                .separator()
+                                .menu_with_check(
+                                    "Debug Layout",
+                                    is_active("Debug"),
+                                    Box::new(SwitchLayout(SharedString::from("Debug"))),
+                                )
+                                .menu_with_check(
+                                    "Review Layout",
+                                    is_active("Review"),
+                                    Box::new(SwitchLayout(SharedString::from("Review"))),
+                                )
+                                .menu_with_check(
+                                    "Writing Layout",
+                                    is_active("Writing"),
+                                    Box::new(SwitchLayout(SharedString::from("Writing"))),
+                                )
                             }
                         })
                         .anchor(Corner::TopRight)
@@ -193,8 +271,7 @@ impl StoryWorkspace {
             Timer::after(Duration::from_secs(10)).await;
 
             _ = story.update_in(window, move |this, _, cx| {
-                let dock_area = dock_area.read(cx);
-                let state = dock_area.dump(cx);
+                let state = WorkspaceState::new(dock_area.read(cx), cx);
 
                 let last_layout_state = this.last_layout_state.clone();
                 if Some(&state) == last_layout_state.as_ref() {
@@ -207,7 +284,7 @@ impl StoryWorkspace {
         }));
     }
 
-    fn save_state(state: &DockAreaState) -> Result<()> {
+    fn save_state(state: &WorkspaceState) -> Result<()> {
         println!("Save layout...");
         let json = serde_json::to_string_pretty(state)?;
         std::fs::write(STATE_FILE, json)?;
@@ -220,11 +297,43 @@ impl StoryWorkspace {
         cx: &mut Context<Self>,
     ) -> Result<()> {
         let json = std::fs::read_to_string(STATE_FILE)?;
-        let state = serde_json::from_str::<DockAreaState>(&json)?;
+        let state = serde_json::from_str::<WorkspaceState>(&json).or_else(|_| {
+            // The state file saved before the named layouts.
+            serde_json::from_str::<DockAreaState>(&json).map(|dock_area| WorkspaceState {
+                dock_area,
+                layouts: DockLayoutsState::default(),
+            })
+        })?;
+        let outdated = state.dock_area.version != Some(MAIN_DOCK_AREA.version);
+
+        // The state of an old version is migrated by `on_migrate`.
+        dock_area.update(cx, |dock_area, cx| {
+            dock_area
+                .load_layouts(state.layouts, window, cx)
+                .context("load layouts")?;
+            // The active layout has been loaded with the named layouts.
+            if dock_area.active_layout().is_none() {
+                dock_area
+                    .load(state.dock_area, window, cx)
+                    .context("load layout")?;
+            }
+            dock_area.set_dock_collapsible(
+                Edges {
+                    left: true,
+                    bottom: true,
+                    right: true,
+                    ..Default::default()
+                },
+                window,
+                cx,
+            );
+
+            Ok::<(), anyhow::Error>(())
+        })?;
 
-        // Check if the saved layout version is different from the current version
-        // Notify the user and ask if they want to reset the layout to default.
-        if state.version != Some(MAIN_DOCK_AREA.version) {
+        // The layout is migrated from an old version, notify the user and ask if they want to
+        // reset the layout to default.
+        if outdated {
             let answer = window.prompt(
                 PromptLevel::Info,
                 "The default main layout has been updated.\n\
@@ -245,21 +354,7 @@ impl StoryWorkspace {
             .detach();
         }
 
-        dock_area.update(cx, |dock_area, cx| {
-            dock_area.load(state, window, cx).context("load layout")?;
-            dock_area.set_dock_collapsible(
-                Edges {
-                    left: true,
-                    bottom: true,
-                    right: true,
-                    ..Default::default()
-                },
-                window,
-                cx,
-            );
-
-            Ok::<(), anyhow::Error>(())
-        })
+        Ok(())
     }
 
     fn reset_default_layout(dock_area: WeakEntity<DockArea>, window: &mut Window, cx: &mut App) {
@@ -338,7 +433,7 @@ impl StoryWorkspace {
             view.set_bottom_dock(bottom_panels, Some(px(200.)), true, window, cx);
             view.set_right_dock(right_panels, Some(px(320.)), true, window, cx);
 
-            Self::save_state(&view.dump(cx)).unwrap();
+            Self::save_state(&WorkspaceState::new(view, cx)).unwrap();
         });
     }
 
@@ -483,6 +578,25 @@ impl StoryWorkspace {
         cx.notify();
     }
 
+    /// Switch to the layout, or save the current layout as it for the first time.
+    fn on_action_switch_layout(
+        &mut self,
+        action: &SwitchLayout,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let name = action.0.clone();
+        self.dock_area.update(cx, |dock_area, cx| {
+            if dock_area.layouts().any(|layout| layout == name.as_ref()) {
+                if let Err(err) = dock_area.switch_layout(&name, window, cx) {
+                    tracing::error!("switch layout error: {:?}", err);
+                }
+            } else {
+                dock_area.save_layout(name.to_string(), cx);
+            }
+        });
+    }
+
     fn on_action_toggle_dock_toggle_button(
         &mut self,
         _: &ToggleDockToggleButton,
@@ -522,6 +636,7 @@ impl Render for StoryWorkspace {
             .on_action(cx.listener(Self::on_action_add_panel))
             .on_action(cx.listener(Self::on_action_toggle_panel_visible))
             .on_action(cx.listener(Self::on_action_toggle_dock_toggle_button))
+            .on_action(cx.listener(Self::on_action_switch_layout))
             .relative()
             .size_full()
             .flex()
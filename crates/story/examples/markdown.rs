@@ -47,6 +47,29 @@ impl Example {
     }
 }
 
+/// Toggle the task list item (e.g. `- [ ] Task`) at the `line` of the `source`.
+fn toggle_task(source: &str, line: usize, checked: bool) -> String {
+    source
+        .split('\n')
+        .enumerate()
+        .map(|(ix, text)| {
+            if ix != line {
+                return text.to_string();
+            }
+
+            let (from, to) = if checked {
+                ("[ ]", "[x]")
+            } else if text.contains("[X]") {
+                ("[X]", "[ ]")
+            } else {
+                ("[x]", "[ ]")
+            };
+            text.replacen(from, to, 1)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl Render for Example {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = if cx.theme().mode.is_dark() {
@@ -81,13 +104,21 @@ impl Render for Example {
                         .p_5()
                         .overflow_y_scroll()
                         .child(
-                            TextView::markdown("preview", self.input_state.read(cx).value()).style(
-                                TextViewStyle {
+                            TextView::markdown("preview", self.input_state.read(cx).value())
+                                .style(TextViewStyle {
                                     highlight_theme: Rc::new(theme.clone()),
                                     is_dark,
                                     ..Default::default()
-                                },
-                            ),
+                                })
+                                .on_task_toggle({
+                                    let input_state = self.input_state.clone();
+                                    move |line, checked, window, cx| {
+                                        input_state.update(cx, |state, cx| {
+                                            let value = toggle_task(state.value(), line, checked);
+                                            state.set_value(value, window, cx);
+                                        });
+                                    }
+                                }),
                         ),
                 ),
             )
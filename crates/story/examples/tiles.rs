@@ -1,10 +1,12 @@
 use anyhow::{Context as _, Result};
 use gpui::*;
 use gpui_component::{
+    button::{Button, ButtonVariants as _},
     dock::{
         register_panel, DockArea, DockAreaState, DockEvent, DockItem, Panel, PanelEvent, PanelInfo,
-        PanelRegistry, PanelState, PanelView,
+        PanelRegistry, PanelState, PanelView, TileArrangement,
     },
+    h_flex,
     input::{InputState, TextInput},
     ActiveTheme, Root, Sizable, TitleBar,
 };
@@ -378,6 +380,17 @@ impl StoryTiles {
             Ok(window)
         })
     }
+
+    fn arrange_tiles(
+        &mut self,
+        arrangement: TileArrangement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let DockItem::Tiles { view, .. } = self.dock_area.read(cx).items().clone() {
+            view.update(cx, |tiles, cx| tiles.arrange(arrangement, window, cx));
+        }
+    }
 }
 
 pub fn open_new(
@@ -408,7 +421,46 @@ impl Render for StoryTiles {
             .flex_col()
             .bg(cx.theme().background)
             .text_color(cx.theme().foreground)
-            .child(TitleBar::new().child(div().flex().items_center().child("Story Tiles")))
+            .child(
+                TitleBar::new().child(
+                    h_flex()
+                        .w_full()
+                        .pr_2()
+                        .justify_between()
+                        .child("Story Tiles")
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(Button::new("tile").label("Tile").small().ghost().on_click(
+                                    cx.listener(|this, _, window, cx| {
+                                        this.arrange_tiles(TileArrangement::Tile, window, cx)
+                                    }),
+                                ))
+                                .child(
+                                    Button::new("cascade")
+                                        .label("Cascade")
+                                        .small()
+                                        .ghost()
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.arrange_tiles(TileArrangement::Cascade, window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("maximize")
+                                        .label("Maximize")
+                                        .small()
+                                        .ghost()
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.arrange_tiles(
+                                                TileArrangement::Maximize,
+                                                window,
+                                                cx,
+                                            )
+                                        })),
+                                ),
+                        ),
+                ),
+            )
             .child(self.dock_area.clone())
             .children(drawer_layer)
             .children(modal_layer)
@@ -342,7 +342,7 @@ pub fn init(cx: &mut App) {
     cx.activate(true);
 }
 
-actions!(story, [ShowPanelInfo]);
+actions!(story, [ShowPanelInfo, ToggleUnsaved]);
 
 #[derive(IntoElement)]
 struct StorySection {
@@ -441,6 +441,8 @@ pub struct StoryContainer {
     story: Option<AnyView>,
     story_klass: Option<SharedString>,
     closable: bool,
+    /// Mark the panel has unsaved changes, to confirm before closing.
+    unsaved: bool,
     zoomable: Option<PanelControl>,
     on_active: Option<fn(AnyView, bool, &mut Window, &mut App)>,
 }
@@ -503,6 +505,7 @@ impl StoryContainer {
             story: None,
             story_klass: None,
             closable: true,
+            unsaved: false,
             zoomable: Some(PanelControl::default()),
             on_active: None,
         }
@@ -565,6 +568,16 @@ impl StoryContainer {
         window.push_notification(note, cx);
     }
 
+    fn on_action_toggle_unsaved(
+        &mut self,
+        _: &ToggleUnsaved,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.unsaved = !self.unsaved;
+        cx.notify();
+    }
+
     fn on_action_toggle_search(
         &mut self,
         _: &ToggleSearch,
@@ -662,6 +675,10 @@ impl Panel for StoryContainer {
         self.name.clone().into_any_element()
     }
 
+    fn tab_name(&self, _cx: &App) -> Option<SharedString> {
+        Some(self.name.clone())
+    }
+
     fn title_style(&self, cx: &App) -> Option<TitleStyle> {
         if let Some(bg) = self.title_bg {
             Some(TitleStyle {
@@ -677,6 +694,10 @@ impl Panel for StoryContainer {
         self.closable
     }
 
+    fn is_dirty(&self, _cx: &App) -> bool {
+        self.unsaved
+    }
+
     fn zoomable(&self, _cx: &App) -> Option<PanelControl> {
         self.zoomable
     }
@@ -702,7 +723,11 @@ impl Panel for StoryContainer {
     }
 
     fn popup_menu(&self, menu: PopupMenu, _window: &Window, _cx: &App) -> PopupMenu {
-        menu.menu("Info", Box::new(ShowPanelInfo))
+        menu.menu("Info", Box::new(ShowPanelInfo)).menu_with_check(
+            "Unsaved Changes",
+            self.unsaved,
+            Box::new(ToggleUnsaved),
+        )
     }
 
     fn toolbar_buttons(&self, _window: &mut Window, _cx: &mut App) -> Option<Vec<Button>> {
@@ -744,6 +769,7 @@ impl Render for StoryContainer {
             .overflow_y_scroll()
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(Self::on_action_panel_info))
+            .on_action(cx.listener(Self::on_action_toggle_unsaved))
             .on_action(cx.listener(Self::on_action_toggle_search))
             .when_some(self.story.clone(), |this, story| {
                 this.child(
@@ -1,24 +1,28 @@
 use std::{
+    collections::HashSet,
     ops::Range,
     time::{self, Duration},
 };
 
 use fake::{Fake, Faker};
 use gpui::{
-    div, prelude::FluentBuilder as _, px, Action, AnyElement, App, AppContext, ClickEvent, Context,
-    Edges, Entity, Focusable, InteractiveElement, IntoElement, ParentElement, Pixels, Render,
-    SharedString, StatefulInteractiveElement, Styled, Timer, Window,
+    div, prelude::FluentBuilder as _, px, Action, AnyElement, App, AppContext, ClickEvent,
+    ClipboardItem, Context, Edges, Entity, Focusable, InteractiveElement, IntoElement,
+    ParentElement, Pixels, Render, SharedString, StatefulInteractiveElement, Styled, Timer, Window,
 };
 use gpui_component::{
     button::Button,
     checkbox::Checkbox,
     green, h_flex,
     indicator::Indicator,
-    input::{InputEvent, InputState, TextInput},
+    input::{InputEvent, InputState, NumberInput, TextInput},
     label::Label,
     popup_menu::{PopupMenu, PopupMenuExt},
     red,
-    table::{self, ColFixed, ColSort, Table, TableDelegate, TableEvent},
+    table::{
+        self, Aggregate, ColFilter, ColFilterKind, ColFixed, ColSort, ExportFormat, RowHeight,
+        RowKind, SelectionMode, Table, TableDelegate, TableEvent,
+    },
     v_flex, ActiveTheme as _, Selectable, Sizable as _, Size, StyleSized as _,
 };
 use serde::Deserialize;
@@ -27,6 +31,10 @@ use serde::Deserialize;
 #[action(namespace = table_story, no_json)]
 struct ChangeSize(Size);
 
+#[derive(Action, Clone, PartialEq, Eq, Deserialize)]
+#[action(namespace = table_story, no_json)]
+struct ChangeSelectionMode(SelectionMode);
+
 #[derive(Action, Clone, PartialEq, Eq, Deserialize)]
 #[action(namespace = table_story, no_json)]
 struct OpenDetail(usize);
@@ -98,6 +106,56 @@ impl Stock {
         self.high = self.price * (1.0 + (0.0..1.5).fake::<f64>());
         self.low = self.price * (1.0 + (-1.5..0.0).fake::<f64>());
     }
+
+    fn cell_text(&self, col_id: &str) -> String {
+        match col_id {
+            "id" => self.id.to_string(),
+            "name" => self.name.to_string(),
+            "symbol" => self.symbol.to_string(),
+            "price" => self.price.to_string(),
+            "change" => self.change.to_string(),
+            "change_percent" => self.change_percent.to_string(),
+            "volume" => self.volume.to_string(),
+            "turnover" => self.turnover.to_string(),
+            "market_cap" => self.market_cap.to_string(),
+            "ttm" => self.ttm.to_string(),
+            "five_mins_ranking" => self.five_mins_ranking.to_string(),
+            "th60_days_ranking" => self.th60_days_ranking.to_string(),
+            "year_change_percent" => self.year_change_percent.to_string(),
+            "bid" => self.bid.to_string(),
+            "bid_volume" => self.bid_volume.to_string(),
+            "ask" => self.ask.to_string(),
+            "ask_volume" => self.ask_volume.to_string(),
+            "open" => self.open.to_string(),
+            "prev_close" => self.prev_close.to_string(),
+            "high" => self.high.to_string(),
+            "low" => self.low.to_string(),
+            "turnover_rate" => self.turnover_rate.to_string(),
+            "rise_rate" => self.rise_rate.to_string(),
+            "amplitude" => self.amplitude.to_string(),
+            "pe_status" => self.pe_status.to_string(),
+            "pb_status" => self.pb_status.to_string(),
+            "volume_ratio" => self.volume_ratio.to_string(),
+            "bid_ask_ratio" => self.bid_ask_ratio.to_string(),
+            "latest_pre_close" => self.latest_pre_close.to_string(),
+            "latest_post_close" => self.latest_post_close.to_string(),
+            "pre_market_cap" => self.pre_market_cap.to_string(),
+            "pre_market_percent" => self.pre_market_percent.to_string(),
+            "pre_market_change" => self.pre_market_change.to_string(),
+            "post_market_cap" => self.post_market_cap.to_string(),
+            "post_market_percent" => self.post_market_percent.to_string(),
+            "post_market_change" => self.post_market_change.to_string(),
+            "float_cap" => self.float_cap.to_string(),
+            "shares" => self.shares.to_string(),
+            "shares_float" => self.shares_float.to_string(),
+            "day_5_ranking" => self.day_5_ranking.to_string(),
+            "day_10_ranking" => self.day_10_ranking.to_string(),
+            "day_30_ranking" => self.day_30_ranking.to_string(),
+            "day_120_ranking" => self.day_120_ranking.to_string(),
+            "day_250_ranking" => self.day_250_ranking.to_string(),
+            _ => "--".to_string(),
+        }
+    }
 }
 
 fn random_stocks(size: usize) -> Vec<Stock> {
@@ -173,6 +231,8 @@ impl Column {
 
 struct StockTableDelegate {
     stocks: Vec<Stock>,
+    /// All stocks and the indexes of the `stocks` in them, when the filters are active.
+    unfiltered_stocks: Option<(Vec<Stock>, Vec<usize>)>,
     columns: Vec<Column>,
     size: Size,
     loop_selection: bool,
@@ -183,9 +243,11 @@ struct StockTableDelegate {
     loading: bool,
     full_loading: bool,
     fixed_cols: bool,
+    footer: bool,
     eof: bool,
     visible_rows: Range<usize>,
     visible_cols: Range<usize>,
+    cell_editor: Option<Entity<InputState>>,
 }
 
 impl StockTableDelegate {
@@ -193,6 +255,7 @@ impl StockTableDelegate {
         Self {
             size: Size::default(),
             stocks: random_stocks(size),
+            unfiltered_stocks: None,
             columns: vec![
                 Column::new("id", "ID", None),
                 Column::new("symbol", "Symbol", Some(ColSort::Default)),
@@ -245,16 +308,19 @@ impl StockTableDelegate {
             col_sort: true,
             col_selection: true,
             fixed_cols: false,
+            footer: false,
             loading: false,
             full_loading: false,
             eof: false,
             visible_cols: Range::default(),
             visible_rows: Range::default(),
+            cell_editor: None,
         }
     }
 
     fn update_stocks(&mut self, size: usize) {
         self.stocks = random_stocks(size);
+        self.unfiltered_stocks = None;
         self.eof = size <= 50;
         self.loading = false;
         self.full_loading = false;
@@ -305,6 +371,14 @@ impl TableDelegate for StockTableDelegate {
         }
     }
 
+    fn col_key(&self, col_ix: usize, _: &App) -> SharedString {
+        if let Some(col) = self.columns.get(col_ix) {
+            col.id.clone()
+        } else {
+            "--".into()
+        }
+    }
+
     fn col_width(&self, col_ix: usize, _: &App) -> Pixels {
         if col_ix < 10 {
             120.0.into()
@@ -323,6 +397,21 @@ impl TableDelegate for StockTableDelegate {
         }
     }
 
+    fn has_footer(&self, _: &App) -> bool {
+        self.footer
+    }
+
+    fn col_aggregate(&self, col_ix: usize, _: &App) -> Option<Aggregate> {
+        match self.columns.get(col_ix)?.id.as_ref() {
+            "symbol" => Some(Aggregate::Count),
+            "price" | "change_percent" => Some(Aggregate::Avg),
+            "volume" | "turnover" => Some(Aggregate::Sum),
+            "high" => Some(Aggregate::Max),
+            "low" => Some(Aggregate::Min),
+            _ => None,
+        }
+    }
+
     fn col_fixed(&self, col_ix: usize, _: &App) -> Option<table::ColFixed> {
         if !self.fixed_cols {
             return None;
@@ -465,6 +554,121 @@ impl TableDelegate for StockTableDelegate {
         }
     }
 
+    fn cell_text(&self, row_ix: usize, col_ix: usize, _: &App) -> SharedString {
+        let stock = self.stocks.get(row_ix).unwrap();
+        let col = self.columns.get(col_ix).unwrap();
+        stock.cell_text(&col.id).into()
+    }
+
+    fn paste_cells(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        rows: Vec<Vec<String>>,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        for (row, cells) in self.stocks.iter_mut().skip(row_ix).zip(rows) {
+            for (col, cell) in self.columns.iter().skip(col_ix).zip(cells) {
+                match col.id.as_ref() {
+                    "symbol" => row.symbol = cell.into(),
+                    "name" => row.name = cell.into(),
+                    "price" => {
+                        if let Ok(price) = cell.trim().parse::<f64>() {
+                            row.price = price;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn can_edit_cell(&self, _: usize, col_ix: usize, _: &App) -> bool {
+        let col = self.columns.get(col_ix).unwrap();
+        matches!(col.id.as_ref(), "symbol" | "name" | "price")
+    }
+
+    fn start_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+        let stock = self.stocks.get(row_ix).unwrap();
+        let col = self.columns.get(col_ix).unwrap();
+        let value = match col.id.as_ref() {
+            "symbol" => stock.symbol.clone(),
+            "name" => stock.name.clone(),
+            _ => format!("{:.3}", stock.price).into(),
+        };
+
+        let editor = cx.new(|cx| {
+            let mut input = InputState::new(window, cx);
+            input.set_value(value, window, cx);
+            input.focus(window, cx);
+            input
+        });
+        self.cell_editor = Some(editor);
+    }
+
+    fn render_cell_editor(
+        &self,
+        _: usize,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let Some(editor) = self.cell_editor.as_ref() else {
+            return div().into_any_element();
+        };
+        let col = self.columns.get(col_ix).unwrap();
+
+        match col.id.as_ref() {
+            "price" => NumberInput::new(editor).xsmall().into_any_element(),
+            _ => TextInput::new(editor).xsmall().into_any_element(),
+        }
+    }
+
+    fn commit_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        let Some(editor) = self.cell_editor.as_ref() else {
+            return Ok(());
+        };
+        let value = editor.read(cx).value().trim().to_string();
+        let col = self.columns.get(col_ix).unwrap();
+        let stock = self.stocks.get_mut(row_ix).unwrap();
+
+        match col.id.as_ref() {
+            "symbol" if value.is_empty() => return Err("Symbol can't be empty.".into()),
+            "symbol" => stock.symbol = value.into(),
+            "name" => stock.name = value.into(),
+            _ => match value.parse::<f64>() {
+                Ok(price) if price >= 0. => stock.price = price,
+                _ => return Err("Price must be a positive number.".into()),
+            },
+        }
+
+        self.cell_editor = None;
+        Ok(())
+    }
+
+    fn cancel_cell_edit(
+        &mut self,
+        _: usize,
+        _: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        self.cell_editor = None;
+    }
+
     fn can_loop_select(&self, _: &App) -> bool {
         self.loop_selection
     }
@@ -525,12 +729,55 @@ impl TableDelegate for StockTableDelegate {
         }
     }
 
+    fn col_filter(&self, col_ix: usize, _: &App) -> Option<ColFilterKind> {
+        let col = self.columns.get(col_ix)?;
+        match col.id.as_ref() {
+            "symbol" | "name" => Some(ColFilterKind::Text),
+            "price" | "change" | "change_percent" => Some(ColFilterKind::Number),
+            _ => None,
+        }
+    }
+
+    fn perform_filter(
+        &mut self,
+        filters: &[(usize, ColFilter)],
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        // Restore all stocks with the changes of the filtered stocks.
+        if let Some((mut stocks, ixs)) = self.unfiltered_stocks.take() {
+            for (ix, stock) in ixs.into_iter().zip(self.stocks.drain(..)) {
+                stocks[ix] = stock;
+            }
+            self.stocks = stocks;
+        }
+
+        if filters.is_empty() {
+            return;
+        }
+
+        let stocks = std::mem::take(&mut self.stocks);
+        let ixs = stocks
+            .iter()
+            .enumerate()
+            .filter(|(_, stock)| {
+                filters.iter().all(|(col_ix, filter)| {
+                    let col = self.columns.get(*col_ix).unwrap();
+                    filter.is_match(&stock.cell_text(&col.id))
+                })
+            })
+            .map(|(ix, _)| ix)
+            .collect::<Vec<_>>();
+        self.stocks = ixs.iter().map(|&ix| stocks[ix].clone()).collect();
+        self.unfiltered_stocks = Some((stocks, ixs));
+    }
+
     fn loading(&self, _: &App) -> bool {
         self.full_loading
     }
 
     fn can_load_more(&self, _: &App) -> bool {
-        return !self.loading && !self.eof;
+        return !self.loading && !self.eof && self.unfiltered_stocks.is_none();
     }
 
     fn load_more_threshold(&self) -> usize {
@@ -574,12 +821,443 @@ impl TableDelegate for StockTableDelegate {
     }
 }
 
+#[derive(Clone)]
+struct Resource {
+    id: usize,
+    name: SharedString,
+    kind: SharedString,
+    region: SharedString,
+    cpu: f64,
+    memory: f64,
+    children: Vec<Resource>,
+}
+
+impl Resource {
+    fn new(
+        id: usize,
+        name: &str,
+        kind: &str,
+        region: &str,
+        (cpu, memory): (f64, f64),
+        children: Vec<Resource>,
+    ) -> Self {
+        Self {
+            id,
+            name: SharedString::from(name.to_string()),
+            kind: SharedString::from(kind.to_string()),
+            region: SharedString::from(region.to_string()),
+            cpu,
+            memory,
+            children,
+        }
+    }
+
+    /// The CPU of the resource, or the total CPU of the children.
+    fn cpu(&self) -> f64 {
+        if self.children.is_empty() {
+            self.cpu
+        } else {
+            self.children.iter().map(|child| child.cpu()).sum()
+        }
+    }
+
+    /// The memory of the resource, or the total memory of the children.
+    fn memory(&self) -> f64 {
+        if self.children.is_empty() {
+            self.memory
+        } else {
+            self.children.iter().map(|child| child.memory()).sum()
+        }
+    }
+
+    fn cell_text(&self, col_ix: usize) -> SharedString {
+        match col_ix {
+            0 => self.name.clone(),
+            1 => self.kind.clone(),
+            2 => self.region.clone(),
+            3 => format!("{:.2}", self.cpu()).into(),
+            _ => format!("{} MB", self.memory()).into(),
+        }
+    }
+
+    /// Push the instances (the resources without children) into `instances`.
+    fn flatten_instances(&self, instances: &mut Vec<Resource>) {
+        if self.children.is_empty() {
+            instances.push(self.clone());
+        }
+        for child in &self.children {
+            child.flatten_instances(instances);
+        }
+    }
+}
+
+fn random_resources() -> Vec<Resource> {
+    let instance = |id: usize, name: &str, region: &str| {
+        let usage = (
+            (1..8).fake::<usize>() as f64 * 0.25,
+            (1..16).fake::<usize>() as f64 * 256.,
+        );
+        Resource::new(id, name, "Instance", region, usage, vec![])
+    };
+    let none = (0., 0.);
+
+    vec![
+        Resource::new(
+            1,
+            "storefront",
+            "Project",
+            "global",
+            none,
+            vec![
+                Resource::new(
+                    2,
+                    "web",
+                    "Service",
+                    "us-east",
+                    none,
+                    vec![
+                        instance(3, "web-1", "us-east"),
+                        instance(4, "web-2", "us-west"),
+                    ],
+                ),
+                Resource::new(
+                    5,
+                    "api",
+                    "Service",
+                    "us-east",
+                    none,
+                    vec![
+                        instance(6, "api-1", "us-east"),
+                        instance(7, "api-2", "us-east"),
+                        instance(8, "api-3", "us-west"),
+                    ],
+                ),
+            ],
+        ),
+        Resource::new(
+            9,
+            "analytics",
+            "Project",
+            "global",
+            none,
+            vec![
+                Resource::new(
+                    10,
+                    "ingest",
+                    "Service",
+                    "eu-central",
+                    none,
+                    vec![
+                        instance(11, "ingest-1", "eu-central"),
+                        instance(12, "ingest-2", "eu-central"),
+                    ],
+                ),
+                Resource::new(
+                    13,
+                    "warehouse",
+                    "Service",
+                    "eu-central",
+                    none,
+                    vec![instance(14, "warehouse-1", "eu-central")],
+                ),
+            ],
+        ),
+    ]
+}
+
+#[derive(Clone)]
+enum ResourceRow {
+    Group {
+        name: SharedString,
+        resources: Vec<Resource>,
+    },
+    Resource {
+        resource: Resource,
+        depth: usize,
+    },
+    Detail(Resource),
+}
+
+impl ResourceRow {
+    /// The key to save the expanded state of the row.
+    fn key(&self) -> SharedString {
+        match self {
+            Self::Group { name, .. } => format!("group:{}", name).into(),
+            Self::Resource { resource, .. } | Self::Detail(resource) => {
+                format!("resource:{}", resource.id).into()
+            }
+        }
+    }
+}
+
+/// The resource tree of projects, services and instances, to show the tree rows, group rows
+/// and detail rows.
+struct ResourceTableDelegate {
+    resources: Vec<Resource>,
+    rows: Vec<ResourceRow>,
+    expanded: HashSet<SharedString>,
+    group_by: Option<usize>,
+}
+
+impl ResourceTableDelegate {
+    fn new() -> Self {
+        let mut this = Self {
+            resources: random_resources(),
+            rows: vec![],
+            expanded: HashSet::from_iter(["resource:1".into(), "resource:2".into()]),
+            group_by: None,
+        };
+        this.update_rows();
+        this
+    }
+
+    /// Flatten the visible rows of the tree or the groups.
+    fn update_rows(&mut self) {
+        let mut rows = vec![];
+
+        match self.group_by {
+            None => {
+                for resource in &self.resources {
+                    self.push_tree_rows(&mut rows, resource, 0);
+                }
+            }
+            Some(col_ix) => {
+                let mut instances = vec![];
+                for resource in &self.resources {
+                    resource.flatten_instances(&mut instances);
+                }
+
+                let mut groups: Vec<(SharedString, Vec<Resource>)> = vec![];
+                for resource in instances {
+                    let name = resource.cell_text(col_ix);
+                    match groups.iter_mut().find(|(group, _)| group == &name) {
+                        Some((_, items)) => items.push(resource),
+                        None => groups.push((name, vec![resource])),
+                    }
+                }
+
+                for (name, resources) in groups {
+                    let row = ResourceRow::Group {
+                        name,
+                        resources: resources.clone(),
+                    };
+                    let expanded = self.expanded.contains(&row.key());
+                    rows.push(row);
+                    if !expanded {
+                        continue;
+                    }
+
+                    for resource in resources {
+                        let row = ResourceRow::Resource {
+                            resource: resource.clone(),
+                            depth: 1,
+                        };
+                        let expanded = self.expanded.contains(&row.key());
+                        rows.push(row);
+                        if expanded {
+                            rows.push(ResourceRow::Detail(resource));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.rows = rows;
+    }
+
+    fn push_tree_rows(&self, rows: &mut Vec<ResourceRow>, resource: &Resource, depth: usize) {
+        let row = ResourceRow::Resource {
+            resource: resource.clone(),
+            depth,
+        };
+        let expanded = self.expanded.contains(&row.key());
+        rows.push(row);
+        if !expanded {
+            return;
+        }
+
+        if resource.children.is_empty() {
+            rows.push(ResourceRow::Detail(resource.clone()));
+        } else {
+            for child in &resource.children {
+                self.push_tree_rows(rows, child, depth + 1);
+            }
+        }
+    }
+}
+
+impl TableDelegate for ResourceTableDelegate {
+    fn cols_count(&self, _: &App) -> usize {
+        5
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn col_name(&self, col_ix: usize, _: &App) -> SharedString {
+        match col_ix {
+            0 => "Name",
+            1 => "Kind",
+            2 => "Region",
+            3 => "CPU",
+            _ => "Memory",
+        }
+        .into()
+    }
+
+    fn col_width(&self, col_ix: usize, _: &App) -> Pixels {
+        if col_ix == 0 {
+            px(220.)
+        } else {
+            px(120.)
+        }
+    }
+
+    fn has_footer(&self, _: &App) -> bool {
+        true
+    }
+
+    fn col_aggregate(&self, col_ix: usize, _: &App) -> Option<Aggregate> {
+        match col_ix {
+            0 => Some(Aggregate::Count),
+            3 => Some(Aggregate::Sum),
+            _ => None,
+        }
+    }
+
+    fn row_height(&self, row_ix: usize, _: &App) -> Option<Pixels> {
+        match self.rows.get(row_ix) {
+            Some(ResourceRow::Detail(_)) => Some(px(56.)),
+            _ => None,
+        }
+    }
+
+    fn row_kind(&self, row_ix: usize, _: &App) -> RowKind {
+        match self.rows.get(row_ix) {
+            Some(ResourceRow::Group { .. }) => RowKind::Group,
+            Some(ResourceRow::Detail(_)) => RowKind::Detail,
+            _ => RowKind::Normal,
+        }
+    }
+
+    fn row_depth(&self, row_ix: usize, _: &App) -> usize {
+        match self.rows.get(row_ix) {
+            Some(ResourceRow::Resource { depth, .. }) => *depth,
+            _ => 0,
+        }
+    }
+
+    fn row_expandable(&self, row_ix: usize, _: &App) -> bool {
+        match self.rows.get(row_ix) {
+            Some(ResourceRow::Group { .. }) | Some(ResourceRow::Resource { .. }) => true,
+            _ => false,
+        }
+    }
+
+    fn row_expanded(&self, row_ix: usize, _: &App) -> bool {
+        self.rows
+            .get(row_ix)
+            .map_or(false, |row| self.expanded.contains(&row.key()))
+    }
+
+    fn perform_expand_row(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        let Some(key) = self.rows.get(row_ix).map(|row| row.key()) else {
+            return;
+        };
+
+        if expanded {
+            self.expanded.insert(key);
+        } else {
+            self.expanded.remove(&key);
+        }
+        self.update_rows();
+    }
+
+    /// Group the instances by the region.
+    fn can_group_by_col(&self, col_ix: usize, _: &App) -> bool {
+        col_ix == 2
+    }
+
+    fn perform_group_by(
+        &mut self,
+        col_ix: Option<usize>,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        self.group_by = col_ix;
+        self.update_rows();
+    }
+
+    fn render_td(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        self.cell_text(row_ix, col_ix, cx)
+    }
+
+    fn render_detail_row(
+        &self,
+        row_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let Some(ResourceRow::Detail(resource)) = self.rows.get(row_ix) else {
+            return div();
+        };
+
+        v_flex()
+            .gap_1()
+            .pl(px(36.))
+            .text_color(cx.theme().muted_foreground)
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(format!("IP: 10.0.0.{}", resource.id))
+                    .child(format!("Uptime: {}h", resource.id * 7))
+                    .child("Status: Running"),
+            )
+            .child(format!(
+                "{} in {}, using {:.2} CPU and {:.0} MB memory.",
+                resource.name, resource.region, resource.cpu, resource.memory
+            ))
+    }
+
+    fn cell_text(&self, row_ix: usize, col_ix: usize, _: &App) -> SharedString {
+        match self.rows.get(row_ix) {
+            Some(ResourceRow::Resource { resource, .. }) => resource.cell_text(col_ix),
+            // Show the aggregated values of the group.
+            Some(ResourceRow::Group { name, resources }) => match col_ix {
+                0 => format!("{} ({})", name, resources.len()).into(),
+                3 => format!("{:.2}", resources.iter().map(|r| r.cpu).sum::<f64>()).into(),
+                4 => format!("{} MB", resources.iter().map(|r| r.memory).sum::<f64>()).into(),
+                _ => SharedString::default(),
+            },
+            _ => SharedString::default(),
+        }
+    }
+}
+
 pub struct TableStory {
     table: Entity<Table<StockTableDelegate>>,
+    resource_table: Entity<Table<ResourceTableDelegate>>,
     num_stocks_input: Entity<InputState>,
     stripe: bool,
+    fit_to_width: bool,
     refresh_data: bool,
+    /// The table layout saved as JSON.
+    saved_layout: Option<String>,
     size: Size,
+    selection_mode: SelectionMode,
 }
 
 impl super::Story for TableStory {
@@ -623,6 +1301,9 @@ impl TableStory {
 
         let delegate = StockTableDelegate::new(5000);
         let table = cx.new(|cx| Table::new(delegate, window, cx));
+        let resource_table = cx.new(|cx| {
+            Table::new(ResourceTableDelegate::new(), window, cx).row_height(RowHeight::Variable)
+        });
 
         cx.subscribe_in(&table, window, Self::on_table_event)
             .detach();
@@ -639,7 +1320,7 @@ impl TableStory {
                         return;
                     }
 
-                    this.table.update(cx, |table, _| {
+                    this.table.update(cx, |table, cx| {
                         table.delegate_mut().stocks.iter_mut().enumerate().for_each(
                             |(i, stock)| {
                                 let n = (3..10).fake::<usize>();
@@ -649,6 +1330,7 @@ impl TableStory {
                                 }
                             },
                         );
+                        table.invalidate_aggregates(cx);
                     });
                     cx.notify();
                 })
@@ -659,10 +1341,14 @@ impl TableStory {
 
         Self {
             table,
+            resource_table,
             num_stocks_input,
             stripe: false,
+            fit_to_width: false,
+            saved_layout: None,
             refresh_data: false,
             size: Size::default(),
+            selection_mode: SelectionMode::default(),
         }
     }
 
@@ -737,6 +1423,21 @@ impl TableStory {
         });
     }
 
+    fn toggle_fit_to_width(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.fit_to_width = *checked;
+        let fit_to_width = self.fit_to_width;
+        self.table.update(cx, |table, cx| {
+            table.set_fit_to_width(fit_to_width, cx);
+        });
+    }
+
+    fn toggle_footer(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().footer = *checked;
+            cx.notify();
+        });
+    }
+
     fn toggle_fixed_cols(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
         self.table.update(cx, |table, cx| {
             table.delegate_mut().fixed_cols = *checked;
@@ -753,6 +1454,18 @@ impl TableStory {
         });
     }
 
+    fn on_change_selection_mode(
+        &mut self,
+        a: &ChangeSelectionMode,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.selection_mode = a.0;
+        self.table.update(cx, |table, cx| {
+            table.set_selection_mode(a.0, cx);
+        });
+    }
+
     fn toggle_refresh_data(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
         self.refresh_data = *checked;
         cx.notify();
@@ -772,9 +1485,19 @@ impl TableStory {
             TableEvent::SelectCol(ix) => println!("Select col: {}", ix),
             TableEvent::DoubleClickedRow(ix) => println!("Double clicked row: {}", ix),
             TableEvent::SelectRow(ix) => println!("Select row: {}", ix),
+            TableEvent::SelectRows(rows) => println!("Select rows: {:?}", rows.ranges()),
+            TableEvent::SelectCells(range) => {
+                println!("Select cells: {:?} x {:?}", range.rows, range.cols)
+            }
             TableEvent::MoveCol(origin_idx, target_idx) => {
                 println!("Move col index: {} -> {}", origin_idx, target_idx);
             }
+            TableEvent::ExpandRow(ix, expanded) => {
+                println!("Expand row: {} {}", ix, expanded)
+            }
+            TableEvent::ColVisibilityChanged(ix, visible) => {
+                println!("Col visibility changed: {} {}", ix, visible)
+            }
         }
     }
 }
@@ -784,9 +1507,11 @@ impl Render for TableStory {
         let delegate = self.table.read(cx).delegate();
         let rows_count = delegate.rows_count(cx);
         let size = self.size;
+        let selection_mode = self.selection_mode;
 
         v_flex()
             .on_action(cx.listener(Self::on_change_size))
+            .on_action(cx.listener(Self::on_change_selection_mode))
             .size_full()
             .text_sm()
             .gap_4()
@@ -831,12 +1556,24 @@ impl Render for TableStory {
                             .selected(self.stripe)
                             .on_click(cx.listener(Self::toggle_stripe)),
                     )
+                    .child(
+                        Checkbox::new("fit-to-width")
+                            .label("Fit to Width")
+                            .selected(self.fit_to_width)
+                            .on_click(cx.listener(Self::toggle_fit_to_width)),
+                    )
                     .child(
                         Checkbox::new("fixed-cols")
                             .label("Fixed Columns")
                             .selected(delegate.fixed_cols)
                             .on_click(cx.listener(Self::toggle_fixed_cols)),
                     )
+                    .child(
+                        Checkbox::new("footer")
+                            .label("Footer")
+                            .selected(delegate.footer)
+                            .on_click(cx.listener(Self::toggle_footer)),
+                    )
                     .child(
                         Checkbox::new("loading")
                             .label("Loading")
@@ -886,6 +1623,29 @@ impl Render for TableStory {
                                 )
                             }),
                     )
+                    .child(
+                        Button::new("selection-mode")
+                            .outline()
+                            .small()
+                            .label(format!("selection: {:?}", self.selection_mode))
+                            .popup_menu(move |menu, _, _| {
+                                menu.menu_with_check(
+                                    "Row",
+                                    selection_mode == SelectionMode::Row,
+                                    Box::new(ChangeSelectionMode(SelectionMode::Row)),
+                                )
+                                .menu_with_check(
+                                    "MultiRow",
+                                    selection_mode == SelectionMode::MultiRow,
+                                    Box::new(ChangeSelectionMode(SelectionMode::MultiRow)),
+                                )
+                                .menu_with_check(
+                                    "Cell",
+                                    selection_mode == SelectionMode::Cell,
+                                    Box::new(ChangeSelectionMode(SelectionMode::Cell)),
+                                )
+                            }),
+                    )
                     .child(
                         Button::new("scroll-top")
                             .outline()
@@ -907,6 +1667,68 @@ impl Render for TableStory {
                                     table.scroll_to_row(table.delegate().rows_count(cx) - 1, cx);
                                 })
                             })),
+                    )
+                    .child(
+                        Button::new("clear-filters")
+                            .outline()
+                            .small()
+                            .child("Clear Filters")
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.table.update(cx, |table, cx| {
+                                    table.clear_filters(window, cx);
+                                })
+                            })),
+                    )
+                    .child(
+                        Button::new("save-layout")
+                            .outline()
+                            .small()
+                            .child("Save Layout")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                let state = this.table.read(cx).dump(cx);
+                                this.saved_layout = serde_json::to_string(&state).ok();
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("restore-layout")
+                            .outline()
+                            .small()
+                            .child("Restore Layout")
+                            .disabled(self.saved_layout.is_none())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                let Some(state) = this
+                                    .saved_layout
+                                    .as_ref()
+                                    .and_then(|json| serde_json::from_str(json).ok())
+                                else {
+                                    return;
+                                };
+                                this.table.update(cx, |table, cx| {
+                                    table.load(state, window, cx);
+                                })
+                            })),
+                    )
+                    .child(
+                        Button::new("export-csv")
+                            .outline()
+                            .small()
+                            .child("Export CSV")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                let csv = this.table.read(cx).export(ExportFormat::Csv, cx);
+                                cx.write_to_clipboard(ClipboardItem::new_string(csv));
+                            })),
+                    )
+                    .child(
+                        Button::new("export-markdown")
+                            .outline()
+                            .small()
+                            .child("Export Markdown")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                let markdown =
+                                    this.table.read(cx).export(ExportFormat::Markdown, cx);
+                                cx.write_to_clipboard(ClipboardItem::new_string(markdown));
+                            })),
                     ), // .child(
                        //     Button::new("scroll-first-col")
                        //         .child("Scroll to First Column")
@@ -950,6 +1772,23 @@ impl Render for TableStory {
                         .when(delegate.eof, |this| this.child("All data loaded.")),
                 ),
             )
-            .child(self.table.clone())
+            .child(div().flex_1().w_full().child(self.table.clone()))
+            .child(
+                v_flex()
+                    .flex_shrink_0()
+                    .gap_2()
+                    .child(
+                        Checkbox::new("group-by-region")
+                            .label("Group Instances by Region")
+                            .selected(self.resource_table.read(cx).group_by().is_some())
+                            .on_click(cx.listener(|this, checked: &bool, window, cx| {
+                                let col_ix = if *checked { Some(2) } else { None };
+                                this.resource_table.update(cx, |table, cx| {
+                                    table.set_group_by(col_ix, window, cx);
+                                });
+                            })),
+                    )
+                    .child(div().h(px(280.)).child(self.resource_table.clone())),
+            )
     }
 }
@@ -1,8 +1,8 @@
 use std::rc::Rc;
 
 use gpui::{
-    div, prelude::FluentBuilder as _, px, relative, rems, App, ClickEvent, ElementId, Empty, Hsla,
-    InteractiveElement, IntoElement, ParentElement as _, RenderOnce, SharedString,
+    div, prelude::FluentBuilder as _, px, relative, rems, AnyElement, App, ClickEvent, ElementId,
+    Empty, Hsla, InteractiveElement, IntoElement, ParentElement, RenderOnce, SharedString,
     StatefulInteractiveElement, StyleRefinement, Styled, Window,
 };
 
@@ -67,6 +67,7 @@ pub struct Alert {
     banner: bool,
     on_close: Option<Rc<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>>,
     visible: bool,
+    children: Vec<AnyElement>,
 }
 
 impl Alert {
@@ -83,6 +84,7 @@ impl Alert {
             banner: false,
             visible: true,
             on_close: None,
+            children: Vec::new(),
         }
     }
 
@@ -164,6 +166,12 @@ impl Sizable for Alert {
     }
 }
 
+impl ParentElement for Alert {
+    fn extend(&mut self, elements: impl IntoIterator<Item = AnyElement>) {
+        self.children.extend(elements);
+    }
+}
+
 impl Styled for Alert {
     fn style(&mut self) -> &mut gpui::StyleRefinement {
         &mut self.style
@@ -227,10 +235,13 @@ impl RenderOnce for Alert {
                                     )
                                 })
                             })
-                            .child(
-                                self.message
-                                    .style(TextViewStyle::default().paragraph_gap(rems(0.2))),
-                            ),
+                            .when(!self.message.is_empty(), |this| {
+                                this.child(
+                                    self.message
+                                        .style(TextViewStyle::default().paragraph_gap(rems(0.2))),
+                                )
+                            })
+                            .children(self.children),
                     ),
             )
             .when_some(self.on_close, |this, on_close| {
@@ -1,20 +1,27 @@
 //! Dock is a fixed container that places at left, bottom, right of the Windows.
 
-use std::{ops::Deref, sync::Arc};
+use std::{ops::Deref, sync::Arc, time::Duration};
 
 use gpui::{
-    div, prelude::FluentBuilder as _, px, App, AppContext, Axis, Context, Element, Empty, Entity,
-    IntoElement, MouseMoveEvent, MouseUpEvent, ParentElement as _, Pixels, Point, Render, Style,
-    StyleRefinement, Styled as _, WeakEntity, Window,
+    deferred, div, prelude::FluentBuilder as _, px, Animation, AnimationExt as _, App, AppContext,
+    Axis, Context, Div, Element, Empty, Entity, FocusHandle, FocusOutEvent,
+    InteractiveElement as _, IntoElement, MouseMoveEvent, MouseUpEvent, ParentElement as _, Pixels,
+    Point, Render, SharedString, StatefulInteractiveElement as _, Style, StyleRefinement,
+    Styled as _, Subscription, WeakEntity, Window,
 };
+use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    h_flex,
     resizable::{resize_handle, PANEL_MIN_SIZE},
-    StyledExt,
+    v_flex, ActiveTheme as _, StyledExt,
 };
 
-use super::{DockArea, DockItem, PanelView, TabPanel};
+use super::{navigation::collect_tab_panels, DockArea, DockItem, PanelView, TabPanel};
+
+/// The width or height of the strip of the tab titles of the auto-hide Dock.
+const AUTO_HIDE_STRIP_SIZE: Pixels = px(24.);
 
 #[derive(Clone)]
 struct ResizePanel;
@@ -71,10 +78,20 @@ pub struct Dock {
     pub(super) open: bool,
     /// Whether the Dock is collapsible, default: true
     pub(super) collapsible: bool,
+    /// Whether the Dock is auto-hide, default: false
+    ///
+    /// The auto-hide Dock shows a strip of the tab titles on the edge, and the `open` means
+    /// the panel is slid out over the center.
+    pub(super) auto_hide: bool,
+    focus_handle: FocusHandle,
 
     // Runtime state
     /// Whether the Dock is resizing
     resizing: bool,
+    /// Whether the mouse is over the strip or the slid out panel of the auto-hide Dock.
+    strip_hovered: bool,
+    panel_hovered: bool,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl Dock {
@@ -98,14 +115,22 @@ impl Dock {
 
         Self::subscribe_panel_events(dock_area.clone(), &panel, window, cx);
 
+        let focus_handle = cx.focus_handle();
+        let _subscriptions = vec![cx.on_focus_out(&focus_handle, window, Self::on_focus_out)];
+
         Self {
             placement,
             dock_area,
             panel,
             open: true,
             collapsible: true,
+            auto_hide: false,
+            focus_handle,
             size: px(200.0),
             resizing: false,
+            strip_hovered: false,
+            panel_hovered: false,
+            _subscriptions,
         }
     }
 
@@ -139,22 +164,47 @@ impl Dock {
     pub fn set_collapsible(&mut self, collapsible: bool, _: &mut Window, cx: &mut Context<Self>) {
         self.collapsible = collapsible;
         if !collapsible {
+            self.auto_hide = false;
             self.open = true
         }
         cx.notify();
     }
 
+    /// Update the Dock to be auto-hide or not.
+    ///
+    /// The auto-hide Dock is collapsed to a strip of the tab titles, hover or click a title
+    /// to slide out the panel over the center, and it slides back when the focus leaves.
+    ///
+    /// The Dock that is not collapsible can't be auto-hide.
+    pub fn set_auto_hide(&mut self, auto_hide: bool, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.collapsible {
+            return;
+        }
+
+        self.auto_hide = auto_hide;
+        self.set_open(!auto_hide, window, cx);
+    }
+
+    /// Returns true if the Dock is auto-hide.
+    pub fn is_auto_hide(&self) -> bool {
+        self.auto_hide
+    }
+
     pub(super) fn from_state(
         dock_area: WeakEntity<DockArea>,
         placement: DockPlacement,
         size: Pixels,
         panel: DockItem,
         open: bool,
+        auto_hide: bool,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         Self::subscribe_panel_events(dock_area.clone(), &panel, window, cx);
 
+        // The auto-hide Dock is always restored as collapsed.
+        let open = open && !auto_hide;
+
         if !open {
             match panel.clone() {
                 DockItem::Tabs { view, .. } => {
@@ -171,6 +221,9 @@ impl Dock {
             }
         }
 
+        let focus_handle = cx.focus_handle();
+        let _subscriptions = vec![cx.on_focus_out(&focus_handle, window, Self::on_focus_out)];
+
         Self {
             placement,
             dock_area,
@@ -178,7 +231,12 @@ impl Dock {
             open,
             size,
             collapsible: true,
+            auto_hide,
+            focus_handle,
             resizing: false,
+            strip_hovered: false,
+            panel_hovered: false,
+            _subscriptions,
         }
     }
 
@@ -241,6 +299,19 @@ impl Dock {
         self.set_open(!self.open, window, cx);
     }
 
+    /// Returns the width or height that the Dock takes in the layout of the [`DockArea`].
+    ///
+    /// The slid out panel of the auto-hide Dock is over the center, so only the strip is taken.
+    fn layout_size(&self) -> Pixels {
+        if self.auto_hide {
+            AUTO_HIDE_STRIP_SIZE
+        } else if self.open {
+            self.size
+        } else {
+            px(0.)
+        }
+    }
+
     /// Returns the size of the Dock, the size is means the width or height of
     /// the Dock, if the placement is left or right, the size is width,
     /// otherwise the size is height.
@@ -287,6 +358,231 @@ impl Dock {
         cx.notify();
     }
 
+    /// Slide out the auto-hide Dock with the `panel` active, the panel is focused if `focus`.
+    fn reveal(
+        &mut self,
+        tab_panel: &Entity<TabPanel>,
+        panel: &Arc<dyn PanelView>,
+        focus: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.open {
+            self.set_open(true, window, cx);
+        }
+
+        let tab_panel = tab_panel.clone();
+        let panel = panel.clone();
+        cx.defer_in(window, move |_, window, cx| {
+            tab_panel.update(cx, |tab_panel, cx| {
+                if focus {
+                    tab_panel.activate_panel(&panel, window, cx);
+                } else {
+                    tab_panel.show_panel(&panel, window, cx);
+                }
+            });
+        });
+    }
+
+    /// Slide back the auto-hide Dock if neither the mouse nor the focus is in it.
+    fn hide_if_inactive(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.auto_hide || !self.open || self.resizing {
+            return;
+        }
+        if self.strip_hovered || self.panel_hovered {
+            return;
+        }
+        if self.focus_handle.contains_focused(window, cx) {
+            return;
+        }
+
+        self.set_open(false, window, cx);
+    }
+
+    fn on_focus_out(&mut self, _: FocusOutEvent, window: &mut Window, cx: &mut Context<Self>) {
+        self.hide_if_inactive(window, cx);
+    }
+
+    /// Update the hover state, the check is deferred to wait the mouse to move between the
+    /// strip and the panel.
+    fn set_hovered(
+        &mut self,
+        strip: bool,
+        hovered: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if strip {
+            self.strip_hovered = hovered;
+        } else {
+            self.panel_hovered = hovered;
+        }
+
+        if !hovered {
+            cx.defer_in(window, |this, window, cx| this.hide_if_inactive(window, cx));
+        }
+    }
+
+    fn render_strip(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_vertical = !self.placement.is_bottom();
+        let mut tab_panels = vec![];
+        collect_tab_panels(&self.panel.view(), &mut tab_panels, cx);
+
+        let items = tab_panels
+            .into_iter()
+            .flat_map(|tab_panel| {
+                let active_panel = tab_panel.read(cx).active_panel(cx);
+                tab_panel
+                    .read(cx)
+                    .panels
+                    .iter()
+                    .filter(|panel| panel.visible(cx))
+                    .map(|panel| {
+                        let active = active_panel
+                            .as_ref()
+                            .map_or(false, |active| active.view() == panel.view());
+                        (tab_panel.clone(), panel.clone(), active)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let base = if is_vertical {
+            v_flex().h_full().w(AUTO_HIDE_STRIP_SIZE).py_1()
+        } else {
+            h_flex().w_full().h(AUTO_HIDE_STRIP_SIZE).px_1()
+        };
+
+        base.id("auto-hide-strip")
+            .flex_none()
+            .gap_1()
+            .bg(cx.theme().tab_bar)
+            .border_color(cx.theme().border)
+            .map(|this| match self.placement {
+                DockPlacement::Left => this.border_r_1(),
+                DockPlacement::Right => this.border_l_1(),
+                DockPlacement::Bottom => this.border_t_1(),
+                DockPlacement::Center => unreachable!(),
+            })
+            .on_hover(cx.listener(|this, hovered: &bool, window, cx| {
+                this.set_hovered(true, *hovered, window, cx);
+            }))
+            .children(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, (tab_panel, panel, active))| {
+                        let name = panel
+                            .tab_name(cx)
+                            .unwrap_or_else(|| SharedString::from(t!("Dock.Unnamed")));
+                        let active = active && self.open;
+
+                        div()
+                            .id(ix)
+                            .flex()
+                            .items_center()
+                            .text_xs()
+                            .rounded(cx.theme().radius)
+                            .text_color(cx.theme().muted_foreground)
+                            .hover(|this| this.bg(cx.theme().accent))
+                            .when(active, |this| {
+                                this.bg(cx.theme().tab_active)
+                                    .text_color(cx.theme().foreground)
+                            })
+                            .map(|this| {
+                                // The title is laid out vertically as one char per line.
+                                if is_vertical {
+                                    this.flex_col().py_2().children(
+                                        name.chars().map(|c| SharedString::from(c.to_string())),
+                                    )
+                                } else {
+                                    this.h_full().px_2().child(name)
+                                }
+                            })
+                            .on_hover(cx.listener({
+                                let tab_panel = tab_panel.clone();
+                                let panel = panel.clone();
+                                move |this, hovered: &bool, window, cx| {
+                                    if *hovered {
+                                        this.reveal(&tab_panel, &panel, false, window, cx);
+                                    }
+                                }
+                            }))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.reveal(&tab_panel, &panel, true, window, cx);
+                            }))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+    }
+
+    fn render_auto_hide(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Div {
+        let placement = self.placement;
+        let size = self.size;
+
+        div()
+            .relative()
+            .flex()
+            .map(|this| match placement {
+                DockPlacement::Left => this.h_full().w(AUTO_HIDE_STRIP_SIZE),
+                DockPlacement::Right => this.h_full().w(AUTO_HIDE_STRIP_SIZE),
+                DockPlacement::Bottom => this.w_full().h(AUTO_HIDE_STRIP_SIZE),
+                DockPlacement::Center => unreachable!(),
+            })
+            .child(self.render_strip(cx))
+            .when(self.open, |this| {
+                this.child(
+                    deferred(
+                        div()
+                            .id("auto-hide-panel")
+                            .absolute()
+                            .occlude()
+                            .overflow_hidden()
+                            .track_focus(&self.focus_handle)
+                            .bg(cx.theme().background)
+                            .border_color(cx.theme().border)
+                            .shadow_lg()
+                            .map(|this| match placement {
+                                DockPlacement::Left => this.top_0().h_full().w(size).border_r_1(),
+                                DockPlacement::Right => this.top_0().h_full().w(size).border_l_1(),
+                                DockPlacement::Bottom => {
+                                    this.left_0().w_full().h(size).border_t_1()
+                                }
+                                DockPlacement::Center => unreachable!(),
+                            })
+                            .on_hover(cx.listener(|this, hovered: &bool, window, cx| {
+                                this.set_hovered(false, *hovered, window, cx);
+                            }))
+                            .map(|this| match &self.panel {
+                                DockItem::Split { view, .. } => this.child(view.clone()),
+                                DockItem::Tabs { view, .. } => this.child(view.clone()),
+                                DockItem::Panel { view, .. } => this.child(view.clone().view()),
+                                DockItem::Tiles { .. } => this,
+                            })
+                            .child(self.render_resize_handle(window, cx))
+                            .with_animation(
+                                "slide",
+                                Animation::new(Duration::from_secs_f64(0.15)),
+                                move |this, delta| {
+                                    // Slide out from the strip.
+                                    let offset = AUTO_HIDE_STRIP_SIZE - size * (1. - delta);
+                                    this.map(|this| match placement {
+                                        DockPlacement::Left => this.left(offset),
+                                        DockPlacement::Right => this.right(offset),
+                                        DockPlacement::Bottom => this.bottom(offset),
+                                        DockPlacement::Center => unreachable!(),
+                                    })
+                                },
+                            ),
+                    )
+                    .with_priority(1),
+                )
+            })
+            .child(DockElement {
+                view: cx.entity().clone(),
+            })
+    }
+
     fn render_resize_handle(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let axis = self.placement.axis();
         let view = cx.entity().clone();
@@ -315,32 +611,32 @@ impl Dock {
         let mut left_dock_size = Pixels(0.0);
         let mut right_dock_size = Pixels(0.0);
 
-        // Get the size of the left dock if it's open and not the current dock
+        // Get the size of the left dock if it's not the current dock
         if let Some(left_dock) = &dock_area.left_dock {
             if left_dock.entity_id() != cx.entity().entity_id() {
-                let left_dock_read = left_dock.read(cx);
-                if left_dock_read.is_open() {
-                    left_dock_size = left_dock_read.size;
-                }
+                left_dock_size = left_dock.read(cx).layout_size();
             }
         }
 
-        // Get the size of the right dock if it's open and not the current dock
+        // Get the size of the right dock if it's not the current dock
         if let Some(right_dock) = &dock_area.right_dock {
             if right_dock.entity_id() != cx.entity().entity_id() {
-                let right_dock_read = right_dock.read(cx);
-                if right_dock_read.is_open() {
-                    right_dock_size = right_dock_read.size;
-                }
+                right_dock_size = right_dock.read(cx).layout_size();
             }
         }
 
+        // The slid out panel of the auto-hide Dock is next to the strip.
+        let strip_size = if self.auto_hide {
+            AUTO_HIDE_STRIP_SIZE
+        } else {
+            px(0.)
+        };
         let size = match self.placement {
             DockPlacement::Left => mouse_position.x - area_bounds.left(),
             DockPlacement::Right => area_bounds.right() - mouse_position.x,
             DockPlacement::Bottom => area_bounds.bottom() - mouse_position.y,
             DockPlacement::Center => unreachable!(),
-        };
+        } - strip_size;
         match self.placement {
             DockPlacement::Left => {
                 let max_size = area_bounds.size.width - PANEL_MIN_SIZE - right_dock_size;
@@ -360,13 +656,22 @@ impl Dock {
         cx.notify();
     }
 
-    fn done_resizing(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+    fn done_resizing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.resizing {
+            return;
+        }
+
         self.resizing = false;
+        self.hide_if_inactive(window, cx);
     }
 }
 
 impl Render for Dock {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
+        if self.auto_hide {
+            return self.render_auto_hide(window, cx);
+        }
+
         if !self.open && !self.placement.is_bottom() {
             return div();
         }
@@ -0,0 +1,343 @@
+use std::{rc::Rc, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use gpui::{App, Context, Window};
+
+use super::{
+    navigation::collect_tab_panels, DockArea, DockAreaState, DockEvent, DockLayoutState,
+    DockLayoutsState, PanelState, PanelView,
+};
+
+/// The migration of the [`DockAreaState`] when the version is changed, see
+/// [`DockArea::on_migrate`].
+pub(super) type MigrateFn = dyn Fn(DockAreaState, &mut Window, &mut App) -> Result<DockAreaState>;
+
+/// The existing panels that can be reused when loading a [`DockAreaState`], to avoid recreating
+/// the panels that exist in both layouts.
+pub(super) struct PanelPool<P = Arc<dyn PanelView>> {
+    panels: Vec<(PanelState, P)>,
+}
+
+impl<P> Default for PanelPool<P> {
+    fn default() -> Self {
+        Self { panels: vec![] }
+    }
+}
+
+impl<P> PanelPool<P> {
+    /// Take the panel that is dumped to the same `state`, each panel can be taken only once.
+    pub(super) fn take(&mut self, state: &PanelState) -> Option<P> {
+        let ix = self.panels.iter().position(|(s, _)| s == state)?;
+        Some(self.panels.remove(ix).1)
+    }
+}
+
+impl PanelPool {
+    fn collect(&mut self, view: &Arc<dyn PanelView>, cx: &App) {
+        let mut tab_panels = vec![];
+        collect_tab_panels(view, &mut tab_panels, cx);
+        for tab_panel in tab_panels {
+            for panel in tab_panel.read(cx).panels.iter() {
+                self.panels.push((panel.dump(cx), panel.clone()));
+            }
+        }
+    }
+}
+
+impl DockArea {
+    /// Set a migration to convert the [`DockAreaState`] of an old version to the current version.
+    ///
+    /// When the `version` of the loaded state is different from the version of the dock area,
+    /// the migration is invoked with the old state, the returned state is loaded instead,
+    /// returns an error to refuse the old state.
+    ///
+    /// Without the migration, the state of a different version is loaded as it is, with a
+    /// warning in the log.
+    pub fn on_migrate(
+        mut self,
+        migrate: impl Fn(DockAreaState, &mut Window, &mut App) -> Result<DockAreaState> + 'static,
+    ) -> Self {
+        self.migrate = Some(Rc::new(migrate));
+        self
+    }
+
+    /// Migrate the `state` to the current version if the version is not match.
+    pub(super) fn migrate_state(
+        &self,
+        state: DockAreaState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<DockAreaState> {
+        let Some(migrate) = self.migrate.clone() else {
+            if self.version.is_some() && state.version != self.version {
+                tracing::warn!(
+                    "load the dock area state of version {:?} without migration, expected {:?}",
+                    state.version,
+                    self.version
+                );
+            }
+            return Ok(state);
+        };
+
+        migrate_version(state, self.version, |state| migrate(state, window, cx))
+    }
+
+    /// Collect the panels that will be replaced by loading the `state`.
+    pub(super) fn reusable_panels(&self, state: &DockAreaState, cx: &App) -> PanelPool {
+        let mut pool = PanelPool::default();
+        pool.collect(&self.items.view(), cx);
+
+        let docks = [
+            (&self.left_dock, &state.left_dock),
+            (&self.bottom_dock, &state.bottom_dock),
+            (&self.right_dock, &state.right_dock),
+        ];
+        for (dock, dock_state) in docks {
+            if let (Some(dock), Some(_)) = (dock, dock_state) {
+                pool.collect(&dock.read(cx).panel.view(), cx);
+            }
+        }
+
+        if !self.is_floating() {
+            for dock_area in self.floating_windows() {
+                pool.collect(&dock_area.read(cx).items.view(), cx);
+            }
+        }
+
+        pool
+    }
+
+    /// Return the names of the saved layouts.
+    pub fn layouts(&self) -> impl Iterator<Item = &str> {
+        self.layouts.iter().map(|layout| layout.name.as_str())
+    }
+
+    /// Return the name of the active layout, if any.
+    pub fn active_layout(&self) -> Option<&str> {
+        self.active_layout.as_deref()
+    }
+
+    /// Set the default layout, used by [`DockArea::reset_layout`].
+    pub fn set_default_layout(&mut self, state: DockAreaState) {
+        self.default_layout = Some(state);
+    }
+
+    /// Save the current layout as the `name` layout and make it active, the layout with the same
+    /// name will be replaced.
+    pub fn save_layout(&mut self, name: impl Into<String>, cx: &mut Context<Self>) {
+        let name = name.into();
+        let state = self.dump(cx);
+        match self.layouts.iter_mut().find(|layout| layout.name == name) {
+            Some(layout) => layout.state = state,
+            None => self.layouts.push(DockLayoutState {
+                name: name.clone(),
+                state,
+            }),
+        }
+        self.active_layout = Some(name);
+
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+    }
+
+    /// Remove the `name` layout, the current layout is kept if it is active.
+    pub fn remove_layout(&mut self, name: &str, cx: &mut Context<Self>) {
+        self.layouts.retain(|layout| layout.name != name);
+        if self.active_layout.as_deref() == Some(name) {
+            self.active_layout = None;
+        }
+
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+    }
+
+    /// Switch to the `name` layout, the panels exist in both layouts are reused.
+    ///
+    /// The current layout is saved to the active layout before switching, so switching back
+    /// will restore the changes.
+    pub fn switch_layout(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let Some(state) = self
+            .layouts
+            .iter()
+            .find(|layout| layout.name == name)
+            .map(|layout| layout.state.clone())
+        else {
+            return Err(anyhow!("layout `{}` not found", name));
+        };
+
+        if let Some(active) = self.active_layout.clone() {
+            self.save_layout(active, cx);
+        }
+
+        self.load(state, window, cx)?;
+        self.active_layout = Some(name.to_string());
+
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+        Ok(())
+    }
+
+    /// Reset the current layout to the default layout set by [`DockArea::set_default_layout`].
+    ///
+    /// If there is an active layout, it will be replaced by the default layout.
+    pub fn reset_layout(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Result<()> {
+        let Some(state) = self.default_layout.clone() else {
+            return Err(anyhow!("the default layout is not set"));
+        };
+
+        self.load(state, window, cx)?;
+        if let Some(active) = self.active_layout.clone() {
+            self.save_layout(active, cx);
+        }
+
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+        Ok(())
+    }
+
+    /// Dump the named layouts, the active layout is updated to the current layout.
+    ///
+    /// See also [`DockArea::load_layouts`].
+    pub fn dump_layouts(&self, cx: &App) -> DockLayoutsState {
+        let mut layouts = self.layouts.clone();
+        if let Some(layout) = layouts
+            .iter_mut()
+            .find(|layout| Some(&layout.name) == self.active_layout.as_ref())
+        {
+            layout.state = self.dump(cx);
+        }
+
+        DockLayoutsState {
+            active: self.active_layout.clone(),
+            layouts,
+        }
+    }
+
+    /// Load the named layouts from the [`DockLayoutsState`], and load the active layout.
+    ///
+    /// The layouts of an old version are migrated by [`DockArea::on_migrate`], the layouts
+    /// failed to migrate are dropped.
+    pub fn load_layouts(
+        &mut self,
+        state: DockLayoutsState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let mut layouts = vec![];
+        for layout in state.layouts {
+            match self.migrate_state(layout.state, window, cx) {
+                Ok(state) => layouts.push(DockLayoutState {
+                    name: layout.name,
+                    state,
+                }),
+                Err(err) => {
+                    tracing::warn!("failed to migrate layout `{}`: {:?}", layout.name, err)
+                }
+            }
+        }
+
+        let active = state
+            .active
+            .and_then(|name| layouts.iter().find(|layout| layout.name == name))
+            .cloned();
+        self.layouts = layouts;
+        self.active_layout = None;
+
+        if let Some(active) = active {
+            self.load(active.state, window, cx)?;
+            self.active_layout = Some(active.name);
+        }
+
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+        Ok(())
+    }
+}
+
+/// Migrate the `state` to the `version` by the `migrate` if the version is not match, the
+/// version of the migrated state is set to the `version`.
+fn migrate_version(
+    state: DockAreaState,
+    version: Option<usize>,
+    migrate: impl FnOnce(DockAreaState) -> Result<DockAreaState>,
+) -> Result<DockAreaState> {
+    if version.is_none() || state.version == version {
+        return Ok(state);
+    }
+
+    let mut state = migrate(state)?;
+    state.version = version;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::{migrate_version, PanelPool};
+    use crate::dock::{DockAreaState, PanelState};
+
+    fn panel_state(name: &str) -> PanelState {
+        PanelState {
+            panel_name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn dock_area_state(version: Option<usize>, center: &str) -> DockAreaState {
+        DockAreaState {
+            version,
+            center: panel_state(center),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_migrate_version() {
+        let migrate = |mut state: DockAreaState| {
+            state.center.panel_name = format!("{}V2", state.center.panel_name);
+            Ok(state)
+        };
+
+        // The same version or the dock area has no version, not migrated.
+        let state = migrate_version(dock_area_state(Some(2), "A"), Some(2), migrate).unwrap();
+        assert_eq!(state, dock_area_state(Some(2), "A"));
+        let state = migrate_version(dock_area_state(Some(1), "A"), None, migrate).unwrap();
+        assert_eq!(state, dock_area_state(Some(1), "A"));
+
+        let state = migrate_version(dock_area_state(Some(1), "A"), Some(2), migrate).unwrap();
+        assert_eq!(state, dock_area_state(Some(2), "AV2"));
+        let state = migrate_version(dock_area_state(None, "A"), Some(2), migrate).unwrap();
+        assert_eq!(state, dock_area_state(Some(2), "AV2"));
+
+        let result = migrate_version(dock_area_state(Some(1), "A"), Some(2), |_| {
+            Err(anyhow!("unsupported version"))
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_panel_pool_take() {
+        let mut pool = PanelPool {
+            panels: vec![
+                (panel_state("A"), 1),
+                (panel_state("B"), 2),
+                (panel_state("A"), 3),
+            ],
+        };
+
+        assert_eq!(pool.take(&panel_state("A")), Some(1));
+        assert_eq!(pool.take(&panel_state("C")), None);
+        assert_eq!(pool.take(&panel_state("A")), Some(3));
+        assert_eq!(pool.take(&panel_state("A")), None);
+        assert_eq!(pool.take(&panel_state("B")), Some(2));
+        assert!(PanelPool::<usize>::default()
+            .take(&panel_state("B"))
+            .is_none());
+    }
+}
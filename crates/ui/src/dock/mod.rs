@@ -1,18 +1,22 @@
 mod dock;
 mod invalid_panel;
+mod layout;
+mod navigation;
 mod panel;
 mod stack_panel;
 mod state;
 mod tab_panel;
 mod tiles;
+mod window;
 
 use anyhow::Result;
 use gpui::{
-    actions, canvas, div, prelude::FluentBuilder, AnyElement, AnyView, App, AppContext, Axis,
-    Bounds, Context, Edges, Entity, EntityId, EventEmitter, InteractiveElement as _, IntoElement,
-    ParentElement as _, Pixels, Render, SharedString, Styled, Subscription, WeakEntity, Window,
+    actions, canvas, div, prelude::FluentBuilder, AnyElement, AnyView, AnyWindowHandle, App,
+    AppContext, Axis, Bounds, Context, Edges, Entity, EntityId, EventEmitter,
+    InteractiveElement as _, IntoElement, KeyBinding, MouseUpEvent, ParentElement as _, Pixels,
+    Render, SharedString, Styled, Subscription, WeakEntity, Window,
 };
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 
 pub use dock::*;
 pub use panel::*;
@@ -21,11 +25,47 @@ pub use state::*;
 pub use tab_panel::*;
 pub use tiles::*;
 
+const CONTEXT: &str = "DockArea";
+
 pub fn init(cx: &mut App) {
     PanelRegistry::init(cx);
+    tiles::init(cx);
+
+    cx.bind_keys([
+        KeyBinding::new("f6", FocusNextPanel, Some(CONTEXT)),
+        KeyBinding::new("shift-f6", FocusPrevPanel, Some(CONTEXT)),
+        KeyBinding::new("secondary-k left", FocusPanelLeft, Some(CONTEXT)),
+        KeyBinding::new("secondary-k right", FocusPanelRight, Some(CONTEXT)),
+        KeyBinding::new("secondary-k up", FocusPanelUp, Some(CONTEXT)),
+        KeyBinding::new("secondary-k down", FocusPanelDown, Some(CONTEXT)),
+        KeyBinding::new("ctrl-pagedown", NextTab, Some(CONTEXT)),
+        KeyBinding::new("ctrl-pageup", PrevTab, Some(CONTEXT)),
+        KeyBinding::new("secondary-\\", SplitHorizontal, Some(CONTEXT)),
+        KeyBinding::new("secondary-k secondary-\\", SplitVertical, Some(CONTEXT)),
+        KeyBinding::new("secondary-k secondary-p", GoToPanel, Some(CONTEXT)),
+    ]);
 }
 
-actions!(dock, [ToggleZoom, ClosePanel]);
+actions!(
+    dock,
+    [
+        ToggleZoom,
+        ClosePanel,
+        MoveToNewWindow,
+        FocusNextPanel,
+        FocusPrevPanel,
+        FocusPanelLeft,
+        FocusPanelRight,
+        FocusPanelUp,
+        FocusPanelDown,
+        NextTab,
+        PrevTab,
+        SplitHorizontal,
+        SplitVertical,
+        GoToPanel,
+        ToggleAutoHide
+    ]
+);
 
 pub enum DockEvent {
     /// The layout of the dock has changed, subscribers this to save the layout.
@@ -68,6 +108,25 @@ pub struct DockArea {
     /// The panel style, default is [`PanelStyle::Default`](PanelStyle::Default).
     pub(crate) panel_style: PanelStyle,
 
+    /// The window of the dock area, and its bounds in screen coordinates.
+    window_handle: AnyWindowHandle,
+    window_bounds: Bounds<Pixels>,
+    /// The dock area owns this floating window, None for the main dock area.
+    owner: Option<WeakEntity<DockArea>>,
+    /// The floating windows torn off from this dock area.
+    windows: Vec<window::DockWindow>,
+    /// The dragging panel out of the window, it will be moved to another window when drop.
+    tear_off: Option<DragPanel>,
+
+    /// The saved named layouts.
+    layouts: Vec<DockLayoutState>,
+    /// The name of the active layout in the `layouts`.
+    active_layout: Option<String>,
+    /// The layout to reset to.
+    default_layout: Option<DockAreaState>,
+    /// The migration of the state of an old version.
+    migrate: Option<Rc<layout::MigrateFn>>,
+
     _subscriptions: Vec<Subscription>,
 }
 
@@ -464,10 +523,25 @@ impl DockArea {
             bottom_dock: None,
             locked: false,
             panel_style: PanelStyle::Default,
+            window_handle: window.window_handle(),
+            window_bounds: window.bounds(),
+            owner: None,
+            windows: vec![],
+            tear_off: None,
+            layouts: vec![],
+            active_layout: None,
+            default_layout: None,
+            migrate: None,
             _subscriptions: vec![],
         };
 
         this.subscribe_panel(&stack_panel, window, cx);
+        this._subscriptions
+            .push(cx.observe_window_bounds(window, |this, window, _| {
+                this.window_bounds = window.bounds();
+            }));
+        // Close the floating windows with the window of this dock area.
+        cx.on_release(|this, cx| this.close_windows(cx)).detach();
 
         this
     }
@@ -683,6 +757,51 @@ impl DockArea {
         }
     }
 
+    /// Set the dock at the given placement to be auto-hide or not, see [`Dock::set_auto_hide`].
+    pub fn set_dock_auto_hide(
+        &mut self,
+        placement: DockPlacement,
+        auto_hide: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let dock = match placement {
+            DockPlacement::Left => &self.left_dock,
+            DockPlacement::Bottom => &self.bottom_dock,
+            DockPlacement::Right => &self.right_dock,
+            DockPlacement::Center => return,
+        };
+
+        if let Some(dock) = dock {
+            dock.update(cx, |dock, cx| {
+                dock.set_auto_hide(auto_hide, window, cx);
+            });
+            cx.emit(DockEvent::LayoutChanged);
+        }
+    }
+
+    /// Determine if the dock at the given placement is auto-hide.
+    pub fn is_dock_auto_hide(&self, placement: DockPlacement, cx: &App) -> bool {
+        match placement {
+            DockPlacement::Left => self
+                .left_dock
+                .as_ref()
+                .map(|dock| dock.read(cx).is_auto_hide())
+                .unwrap_or(false),
+            DockPlacement::Bottom => self
+                .bottom_dock
+                .as_ref()
+                .map(|dock| dock.read(cx).is_auto_hide())
+                .unwrap_or(false),
+            DockPlacement::Right => self
+                .right_dock
+                .as_ref()
+                .map(|dock| dock.read(cx).is_auto_hide())
+                .unwrap_or(false),
+            DockPlacement::Center => false,
+        }
+    }
+
     /// Toggle the dock at the given placement.
     pub fn toggle_dock(
         &self,
@@ -818,6 +937,9 @@ impl DockArea {
 
     /// Load the state of the DockArea from the DockAreaState.
     ///
+    /// If the version of the state is different, it will be migrated by [`DockArea::on_migrate`].
+    /// The existing panels that are dumped to the same state will be reused.
+    ///
     /// See also [DockeArea::dump].
     pub fn load(
         &mut self,
@@ -825,24 +947,29 @@ impl DockArea {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Result<()> {
+        let state = self.migrate_state(state, window, cx)?;
         self.version = state.version;
         let weak_self = cx.entity().downgrade();
+        let mut pool = self.reusable_panels(&state, cx);
 
         if let Some(left_dock_state) = state.left_dock {
-            self.left_dock = Some(left_dock_state.to_dock(weak_self.clone(), window, cx));
+            self.left_dock =
+                Some(left_dock_state.build_dock(weak_self.clone(), &mut pool, window, cx));
         }
 
         if let Some(right_dock_state) = state.right_dock {
-            self.right_dock = Some(right_dock_state.to_dock(weak_self.clone(), window, cx));
+            self.right_dock =
+                Some(right_dock_state.build_dock(weak_self.clone(), &mut pool, window, cx));
         }
 
         if let Some(bottom_dock_state) = state.bottom_dock {
-            self.bottom_dock = Some(bottom_dock_state.to_dock(weak_self.clone(), window, cx));
+            self.bottom_dock =
+                Some(bottom_dock_state.build_dock(weak_self.clone(), &mut pool, window, cx));
         }
 
-        self.items = state.center.to_item(weak_self, window, cx);
+        self.items = state.center.build_item(weak_self, &mut pool, window, cx);
         self.update_toggle_button_tab_panels(window, cx);
-        Ok(())
+        self.load_windows(state.windows, &mut pool, cx)
     }
 
     /// Dump the dock panels layout to PanelState.
@@ -871,6 +998,7 @@ impl DockArea {
             left_dock,
             right_dock,
             bottom_dock,
+            windows: self.dump_windows(cx),
         }
     }
 
@@ -1013,13 +1141,34 @@ impl Render for DockArea {
 
         div()
             .id("dock-area")
+            .key_context(CONTEXT)
             .relative()
             .size_full()
             .overflow_hidden()
+            .on_action(cx.listener(Self::on_action_focus_next_panel))
+            .on_action(cx.listener(Self::on_action_focus_prev_panel))
+            .on_action(cx.listener(Self::on_action_focus_panel_left))
+            .on_action(cx.listener(Self::on_action_focus_panel_right))
+            .on_action(cx.listener(Self::on_action_focus_panel_up))
+            .on_action(cx.listener(Self::on_action_focus_panel_down))
+            .on_action(cx.listener(Self::on_action_go_to_panel))
+            .on_drag_move(cx.listener(Self::on_panel_drag_move))
             .child(
                 canvas(
-                    move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
-                    |_, _, _, _| {},
+                    {
+                        let view = view.clone();
+                        move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds)
+                    },
+                    move |_, _, window, _| {
+                        // The drop out of the window is not handled by any drop target.
+                        window.on_mouse_event(move |event: &MouseUpEvent, phase, window, cx| {
+                            if phase.bubble() {
+                                view.update(cx, |view, cx| {
+                                    view.on_panel_drag_end(event, window, cx)
+                                });
+                            }
+                        })
+                    },
                 )
                 .absolute()
                 .size_full(),
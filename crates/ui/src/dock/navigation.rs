@@ -0,0 +1,504 @@
+use std::sync::Arc;
+
+use gpui::{
+    px, App, AppContext as _, Bounds, Context, Entity, FocusHandle, Focusable, ParentElement as _,
+    Pixels, SharedString, Styled as _, Task, WeakEntity, Window,
+};
+use rust_i18n::t;
+
+use super::{
+    Dock, DockArea, DockPlacement, FocusNextPanel, FocusPanelDown, FocusPanelLeft, FocusPanelRight,
+    FocusPanelUp, FocusPrevPanel, GoToPanel, PanelView, StackPanel, TabPanel, Tiles,
+};
+use crate::{
+    h_flex,
+    list::{List, ListDelegate, ListItem},
+    v_flex, ActiveTheme as _, ContextModal as _, FocusableCycle, Placement,
+};
+
+impl DockArea {
+    /// Return all the [`TabPanel`]s in the dock area with the placement, in the order of left
+    /// dock, center, bottom dock and right dock.
+    fn tab_panels(&self, cx: &App) -> Vec<(DockPlacement, Entity<TabPanel>)> {
+        let dock_view =
+            |dock: &Option<Entity<Dock>>| dock.as_ref().map(|dock| dock.read(cx).panel.view());
+        let views = [
+            (DockPlacement::Left, dock_view(&self.left_dock)),
+            (DockPlacement::Center, Some(self.items.view())),
+            (DockPlacement::Bottom, dock_view(&self.bottom_dock)),
+            (DockPlacement::Right, dock_view(&self.right_dock)),
+        ];
+
+        let mut tab_panels = vec![];
+        for (placement, view) in views {
+            let Some(view) = view else {
+                continue;
+            };
+
+            let mut items = vec![];
+            collect_tab_panels(&view, &mut items, cx);
+            tab_panels.extend(items.into_iter().map(|item| (placement, item)));
+        }
+
+        tab_panels
+    }
+
+    /// Return the placement of the dock that contains the `tab_panel`, None if it is in the center.
+    pub(super) fn dock_placement_of(
+        &self,
+        tab_panel: &Entity<TabPanel>,
+        cx: &App,
+    ) -> Option<DockPlacement> {
+        self.tab_panels(cx)
+            .into_iter()
+            .find(|(_, item)| item == tab_panel)
+            .map(|(placement, _)| placement)
+            .filter(|placement| *placement != DockPlacement::Center)
+    }
+
+    /// Return the [`TabPanel`]s that are visible, the panels in the collapsed docks are skipped.
+    fn visible_tab_panels(&self, cx: &App) -> Vec<Entity<TabPanel>> {
+        self.tab_panels(cx)
+            .into_iter()
+            .filter(|(placement, tab_panel)| {
+                (*placement == DockPlacement::Center || self.is_dock_open(*placement, cx))
+                    && tab_panel.read(cx).active_panel(cx).is_some()
+            })
+            .map(|(_, tab_panel)| tab_panel)
+            .collect()
+    }
+
+    /// Return the [`TabPanel`] that contains the focus.
+    fn focused_tab_panel(&self, window: &Window, cx: &App) -> Option<Entity<TabPanel>> {
+        self.visible_tab_panels(cx)
+            .into_iter()
+            .find(|tab_panel| tab_panel.focus_handle(cx).contains_focused(window, cx))
+    }
+
+    pub(super) fn on_action_focus_next_panel(
+        &mut self,
+        _: &FocusNextPanel,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.cycle_focus(true, window, cx);
+    }
+
+    pub(super) fn on_action_focus_prev_panel(
+        &mut self,
+        _: &FocusPrevPanel,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.cycle_focus(false, window, cx);
+    }
+
+    pub(super) fn on_action_focus_panel_left(
+        &mut self,
+        _: &FocusPanelLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_panel_in_direction(Placement::Left, window, cx);
+    }
+
+    pub(super) fn on_action_focus_panel_right(
+        &mut self,
+        _: &FocusPanelRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_panel_in_direction(Placement::Right, window, cx);
+    }
+
+    pub(super) fn on_action_focus_panel_up(
+        &mut self,
+        _: &FocusPanelUp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_panel_in_direction(Placement::Top, window, cx);
+    }
+
+    pub(super) fn on_action_focus_panel_down(
+        &mut self,
+        _: &FocusPanelDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_panel_in_direction(Placement::Bottom, window, cx);
+    }
+
+    /// Focus the nearest panel in the `direction` of the focused panel, by the bounds of the
+    /// [`TabPanel`]s.
+    pub fn focus_panel_in_direction(
+        &mut self,
+        direction: Placement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(focused) = self.focused_tab_panel(window, cx) else {
+            self.cycle_focus(true, window, cx);
+            return;
+        };
+
+        let tab_panels = self
+            .visible_tab_panels(cx)
+            .into_iter()
+            .filter(|tab_panel| tab_panel != &focused)
+            .collect::<Vec<_>>();
+        let candidates = tab_panels
+            .iter()
+            .map(|tab_panel| tab_panel.read(cx).bounds())
+            .collect::<Vec<_>>();
+
+        if let Some(ix) = nearest_in_direction(focused.read(cx).bounds(), &candidates, direction) {
+            tab_panels[ix].focus_handle(cx).focus(window);
+            cx.stop_propagation();
+        }
+    }
+
+    pub(super) fn on_action_go_to_panel(
+        &mut self,
+        _: &GoToPanel,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.open_go_to_panel(window, cx);
+    }
+
+    /// Open a palette to fuzzy search all the panels by the name and focus the confirmed one.
+    pub fn open_go_to_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let items = self
+            .tab_panels(cx)
+            .into_iter()
+            .flat_map(|(placement, tab_panel)| {
+                tab_panel
+                    .read(cx)
+                    .panels
+                    .iter()
+                    .filter(|panel| panel.visible(cx))
+                    .map(|panel| PanelItem {
+                        label: panel
+                            .tab_name(cx)
+                            .unwrap_or_else(|| panel.panel_name(cx).into()),
+                        placement,
+                        tab_panel: tab_panel.downgrade(),
+                        panel: panel.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let delegate = GoToPanelDelegate {
+            dock_area: cx.entity().downgrade(),
+            matches: (0..items.len()).collect(),
+            items,
+            selected_index: Some(0),
+        };
+        let list = cx.new(|cx| {
+            let mut list = List::new(delegate, window, cx);
+            if let Some(query_input) = list.query_input() {
+                query_input.update(cx, |input, cx| {
+                    input.set_placeholder(t!("Dock.Go to Panel"), window, cx);
+                });
+            }
+            list.set_selected_index(Some(0), window, cx);
+            list.focus(window, cx);
+            list
+        });
+
+        window.open_modal(cx, move |modal, _, _| {
+            modal
+                .show_close(false)
+                .child(v_flex().h(px(320.)).child(list.clone()))
+        });
+    }
+}
+
+impl FocusableCycle for DockArea {
+    fn cycle_focus_handles(&self, _: &mut Window, cx: &mut App) -> Vec<FocusHandle> {
+        self.visible_tab_panels(cx)
+            .into_iter()
+            .map(|tab_panel| tab_panel.focus_handle(cx))
+            .collect()
+    }
+}
+
+/// Collect the [`TabPanel`]s in the view recursively.
+pub(super) fn collect_tab_panels(
+    view: &Arc<dyn PanelView>,
+    out: &mut Vec<Entity<TabPanel>>,
+    cx: &App,
+) {
+    let view = view.view();
+    if let Ok(tab_panel) = view.clone().downcast::<TabPanel>() {
+        out.push(tab_panel);
+    } else if let Ok(stack_panel) = view.clone().downcast::<StackPanel>() {
+        for panel in stack_panel.read(cx).panels.iter() {
+            collect_tab_panels(panel, out, cx);
+        }
+    } else if let Ok(tiles) = view.downcast::<Tiles>() {
+        for item in tiles.read(cx).panels.iter() {
+            collect_tab_panels(&item.panel, out, cx);
+        }
+    }
+}
+
+struct PanelItem {
+    label: SharedString,
+    placement: DockPlacement,
+    tab_panel: WeakEntity<TabPanel>,
+    panel: Arc<dyn PanelView>,
+}
+
+struct GoToPanelDelegate {
+    dock_area: WeakEntity<DockArea>,
+    items: Vec<PanelItem>,
+    /// The indexes of the matched items.
+    matches: Vec<usize>,
+    selected_index: Option<usize>,
+}
+
+impl ListDelegate for GoToPanelDelegate {
+    type Item = ListItem;
+
+    fn perform_search(
+        &mut self,
+        query: &str,
+        _: &mut Window,
+        _: &mut Context<List<Self>>,
+    ) -> Task<()> {
+        self.matches = fuzzy_matches(query, self.items.iter().map(|item| item.label.as_ref()));
+        self.selected_index = (!self.matches.is_empty()).then_some(0);
+        Task::ready(())
+    }
+
+    fn items_count(&self, _: &App) -> usize {
+        self.matches.len()
+    }
+
+    fn render_item(
+        &self,
+        ix: usize,
+        _: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) -> Option<Self::Item> {
+        let item = self.items.get(*self.matches.get(ix)?)?;
+        let placement = match item.placement {
+            DockPlacement::Left => Some(t!("Dock.Left")),
+            DockPlacement::Right => Some(t!("Dock.Right")),
+            DockPlacement::Bottom => Some(t!("Dock.Bottom")),
+            DockPlacement::Center => None,
+        };
+
+        Some(
+            ListItem::new(ix)
+                .selected(self.selected_index == Some(ix))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .justify_between()
+                        .child(item.label.clone())
+                        .children(placement.map(|placement| {
+                            h_flex()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(placement)
+                        })),
+                ),
+        )
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: Option<usize>,
+        _: &mut Window,
+        _: &mut Context<List<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
+        let Some(item) = self
+            .selected_index
+            .and_then(|ix| self.matches.get(ix))
+            .and_then(|ix| self.items.get(*ix))
+        else {
+            return;
+        };
+
+        window.close_modal(cx);
+        let placement = item.placement;
+        if placement != DockPlacement::Center {
+            _ = self.dock_area.update(cx, |dock_area, cx| {
+                if !dock_area.is_dock_open(placement, cx) {
+                    dock_area.toggle_dock(placement, window, cx);
+                }
+            });
+        }
+
+        let panel = item.panel.clone();
+        _ = item.tab_panel.update(cx, |tab_panel, cx| {
+            tab_panel.activate_panel(&panel, window, cx);
+        });
+    }
+
+    fn cancel(&mut self, window: &mut Window, cx: &mut Context<List<Self>>) {
+        window.close_modal(cx);
+    }
+}
+
+/// Returns the score of matching the `query` as a subsequence of the `text` ignoring case,
+/// the consecutive and word start matches get higher score, `None` if not matched.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let mut score = 0;
+    let mut start = 0;
+    let mut last_pos: Option<usize> = None;
+
+    for c in query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let pos = start + text[start..].iter().position(|t| *t == c)?;
+        score += 1;
+        if pos > 0 && last_pos == Some(pos - 1) {
+            score += 4;
+        }
+        if pos == 0 || !text[pos - 1].is_alphanumeric() {
+            score += 2;
+        }
+        last_pos = Some(pos);
+        start = pos + 1;
+    }
+
+    Some(score)
+}
+
+/// Returns the indexes of the matched `texts` by [`fuzzy_score`], sorted by the score.
+fn fuzzy_matches<'a>(query: &str, texts: impl Iterator<Item = &'a str>) -> Vec<usize> {
+    let mut matches = texts
+        .enumerate()
+        .filter_map(|(ix, text)| fuzzy_score(query, text).map(|score| (ix, score)))
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| b.1.cmp(&a.1));
+    matches.into_iter().map(|(ix, _)| ix).collect()
+}
+
+/// Find the nearest bounds of the `candidates` in the `direction` of the `from` bounds,
+/// the candidates overlapped in the cross axis are preferred.
+fn nearest_in_direction(
+    from: Bounds<Pixels>,
+    candidates: &[Bounds<Pixels>],
+    direction: Placement,
+) -> Option<usize> {
+    // The panels are adjacent, allow a little overlap for the resize handle.
+    let tolerance = px(2.);
+
+    candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, bounds)| {
+            let (distance, overlap, offset) = match direction {
+                Placement::Left => (
+                    from.left() - bounds.right(),
+                    from.bottom().min(bounds.bottom()) - from.top().max(bounds.top()),
+                    (from.center().y - bounds.center().y).abs(),
+                ),
+                Placement::Right => (
+                    bounds.left() - from.right(),
+                    from.bottom().min(bounds.bottom()) - from.top().max(bounds.top()),
+                    (from.center().y - bounds.center().y).abs(),
+                ),
+                Placement::Top => (
+                    from.top() - bounds.bottom(),
+                    from.right().min(bounds.right()) - from.left().max(bounds.left()),
+                    (from.center().x - bounds.center().x).abs(),
+                ),
+                Placement::Bottom => (
+                    bounds.top() - from.bottom(),
+                    from.right().min(bounds.right()) - from.left().max(bounds.left()),
+                    (from.center().x - bounds.center().x).abs(),
+                ),
+            };
+
+            (distance >= -tolerance).then_some((ix, overlap > px(0.), distance, offset))
+        })
+        .min_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then(a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
+                .then(a.3.partial_cmp(&b.3).unwrap_or(std::cmp::Ordering::Equal))
+        })
+        .map(|(ix, ..)| ix)
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{point, px, size, Bounds};
+
+    use super::{fuzzy_matches, fuzzy_score, nearest_in_direction};
+    use crate::Placement;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("", "Button").is_some());
+        assert!(fuzzy_score("btn", "Button").is_some());
+        assert!(fuzzy_score("BUT", "button").is_some());
+        assert!(fuzzy_score("nb", "Button").is_none());
+        assert!(fuzzy_score("but", "Button") > fuzzy_score("btn", "Button"));
+        assert!(fuzzy_score("tp", "Tab Panel") > fuzzy_score("tp", "Stepper"));
+
+        assert_eq!(
+            fuzzy_matches("in", ["Button", "Icon", "Input"].into_iter()),
+            vec![2, 1]
+        );
+        assert_eq!(
+            fuzzy_matches("", ["Button", "Input"].into_iter()),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn test_nearest_in_direction() {
+        let bounds = |x: f32, y: f32, w: f32, h: f32| Bounds {
+            origin: point(px(x), px(y)),
+            size: size(px(w), px(h)),
+        };
+
+        // +---+---+
+        // | 0 | 1 |
+        // |   +---+
+        // |   | 2 |
+        // +---+---+
+        let left = bounds(0., 0., 100., 200.);
+        let top_right = bounds(100., 0., 100., 100.);
+        let bottom_right = bounds(100., 100., 100., 100.);
+        let candidates = [left, top_right, bottom_right];
+
+        assert_eq!(
+            nearest_in_direction(left, &candidates[1..], Placement::Right),
+            Some(0)
+        );
+        assert_eq!(
+            nearest_in_direction(left, &candidates[1..], Placement::Left),
+            None
+        );
+        assert_eq!(
+            nearest_in_direction(bottom_right, &candidates[..2], Placement::Top),
+            Some(1)
+        );
+        assert_eq!(
+            nearest_in_direction(bottom_right, &candidates[..2], Placement::Left),
+            Some(0)
+        );
+        assert_eq!(
+            nearest_in_direction(top_right, &[left, bottom_right], Placement::Bottom),
+            Some(1)
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use std::{collections::HashMap, sync::Arc};
 
-use crate::{button::Button, popup_menu::PopupMenu};
+use crate::{
+    button::{Button, ButtonVariant},
+    modal::ModalButtonProps,
+    popup_menu::PopupMenu,
+    ContextModal as _,
+};
 use gpui::{
-    AnyElement, AnyView, App, AppContext as _, Entity, EntityId, EventEmitter, FocusHandle,
-    Focusable, Global, Hsla, IntoElement, Render, SharedString, WeakEntity, Window,
+    prelude::FluentBuilder as _, AnyElement, AnyView, App, AppContext as _, Entity, EntityId,
+    EventEmitter, FocusHandle, Focusable, Global, Hsla, IntoElement, ParentElement as _, Pixels,
+    Render, SharedString, Size, Task, WeakEntity, Window,
 };
 
 use rust_i18n::t;
@@ -61,7 +67,8 @@ pub trait Panel: EventEmitter<PanelEvent> + Render + Focusable {
 
     /// The name of the tab of the panel, default is `None`.
     ///
-    /// Used to display in the already collapsed tab panel.
+    /// Used to display in the already collapsed tab panel, and to search the panel in
+    /// [`DockArea::open_go_to_panel`].
     fn tab_name(&self, cx: &App) -> Option<SharedString> {
         None
     }
@@ -90,6 +97,26 @@ pub trait Panel: EventEmitter<PanelEvent> + Render + Focusable {
         true
     }
 
+    /// Whether the panel has unsaved changes, default is `false`.
+    ///
+    /// The dirty panel will show a dot on the tab.
+    ///
+    /// This method called in Panel render, we should make sure it is fast.
+    fn is_dirty(&self, cx: &App) -> bool {
+        false
+    }
+
+    /// Called before the panel is closed, return `false` to prevent the panel from closing.
+    ///
+    /// Default to ask for confirmation by a modal if the panel [`Panel::is_dirty`].
+    fn can_close(&mut self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        if !self.is_dirty(cx) {
+            return Task::ready(true);
+        }
+
+        confirm_close(self.tab_name(cx), window, cx)
+    }
+
     /// Return `PanelControl` if the panel is zoomable, default is `PanelControl::Menu`.
     ///
     /// This method called in Panel render, we should make sure it is fast.
@@ -137,6 +164,12 @@ pub trait Panel: EventEmitter<PanelEvent> + Render + Focusable {
     fn inner_padding(&self, cx: &App) -> bool {
         true
     }
+
+    /// The minimum size of the panel in the [`Tiles`](super::Tiles), default is `None` to use
+    /// the default minimum size of the tile.
+    fn min_size(&self, cx: &App) -> Option<Size<Pixels>> {
+        None
+    }
 }
 
 /// The PanelView trait used to define the panel view.
@@ -149,6 +182,8 @@ pub trait PanelView: 'static + Send + Sync {
     fn title_suffix(&self, window: &mut Window, cx: &mut App) -> Option<AnyElement>;
     fn title_style(&self, cx: &App) -> Option<TitleStyle>;
     fn closable(&self, cx: &App) -> bool;
+    fn is_dirty(&self, cx: &App) -> bool;
+    fn can_close(&self, window: &mut Window, cx: &mut App) -> Task<bool>;
     fn zoomable(&self, cx: &App) -> Option<PanelControl>;
     fn visible(&self, cx: &App) -> bool;
     fn set_active(&self, active: bool, window: &mut Window, cx: &mut App);
@@ -159,6 +194,7 @@ pub trait PanelView: 'static + Send + Sync {
     fn focus_handle(&self, cx: &App) -> FocusHandle;
     fn dump(&self, cx: &App) -> PanelState;
     fn inner_padding(&self, cx: &App) -> bool;
+    fn min_size(&self, cx: &App) -> Option<Size<Pixels>>;
 }
 
 impl<T: Panel> PanelView for Entity<T> {
@@ -190,6 +226,14 @@ impl<T: Panel> PanelView for Entity<T> {
         self.read(cx).closable(cx)
     }
 
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.read(cx).is_dirty(cx)
+    }
+
+    fn can_close(&self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        self.update(cx, |this, cx| this.can_close(window, cx))
+    }
+
     fn zoomable(&self, cx: &App) -> Option<PanelControl> {
         self.read(cx).zoomable(cx)
     }
@@ -233,6 +277,64 @@ impl<T: Panel> PanelView for Entity<T> {
     fn inner_padding(&self, cx: &App) -> bool {
         self.read(cx).inner_padding(cx)
     }
+
+    fn min_size(&self, cx: &App) -> Option<Size<Pixels>> {
+        self.read(cx).min_size(cx)
+    }
+}
+
+/// Ask the `panels` one by one whether they can be closed, stop at the first refused.
+pub(super) fn can_close_panels(
+    panels: Vec<Arc<dyn PanelView>>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Task<bool> {
+    window.spawn(cx, async move |cx| {
+        for panel in panels {
+            let Ok(can_close) = cx.update(|window, cx| panel.can_close(window, cx)) else {
+                return false;
+            };
+            if !can_close.await {
+                return false;
+            }
+        }
+        true
+    })
+}
+
+/// Ask for confirmation to close the panel with unsaved changes.
+fn confirm_close(name: Option<SharedString>, window: &mut Window, cx: &mut App) -> Task<bool> {
+    let (tx, rx) = smol::channel::bounded(1);
+    let message = SharedString::from(t!("Dock.Unsaved changes"));
+
+    window.open_modal(cx, move |modal, _, _| {
+        modal
+            .confirm()
+            .when_some(name.clone(), |this, name| this.title(name))
+            .child(message.clone())
+            .button_props(
+                ModalButtonProps::default()
+                    .ok_text(t!("Dock.Close"))
+                    .ok_variant(ButtonVariant::Danger),
+            )
+            .on_ok({
+                let tx = tx.clone();
+                move |_, _, _| {
+                    _ = tx.try_send(true);
+                    true
+                }
+            })
+            .on_cancel({
+                let tx = tx.clone();
+                move |_, _, _| {
+                    _ = tx.try_send(false);
+                    true
+                }
+            })
+    });
+
+    // The modal is dismissed without any choice, if the sender is dropped.
+    cx.background_spawn(async move { rx.recv().await.unwrap_or(false) })
 }
 
 impl From<&dyn PanelView> for AnyView {
@@ -10,10 +10,12 @@ use crate::{
     ActiveTheme, AxisExt as _, Placement,
 };
 
-use super::{DockArea, Panel, PanelEvent, PanelState, PanelView, TabPanel};
+use super::{
+    panel::can_close_panels, DockArea, Panel, PanelEvent, PanelState, PanelView, TabPanel,
+};
 use gpui::{
     App, Axis, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, IntoElement,
-    ParentElement, Pixels, Render, Styled, Subscription, WeakEntity, Window,
+    ParentElement, Pixels, Render, Styled, Subscription, Task, WeakEntity, Window,
 };
 use smallvec::SmallVec;
 
@@ -34,6 +36,14 @@ impl Panel for StackPanel {
     fn title(&self, _window: &gpui::Window, _cx: &gpui::App) -> gpui::AnyElement {
         "StackPanel".into_any_element()
     }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.panels.iter().any(|panel| panel.is_dirty(cx))
+    }
+
+    fn can_close(&mut self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        can_close_panels(self.panels.to_vec(), window, cx)
+    }
     fn set_active(&mut self, active: bool, window: &mut Window, cx: &mut App) {
         for panel in &self.panels {
             panel.set_active(active, window, cx);
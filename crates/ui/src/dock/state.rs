@@ -2,7 +2,7 @@ use gpui::{point, px, size, App, AppContext, Axis, Bounds, Entity, Pixels, WeakE
 use itertools::Itertools as _;
 use serde::{Deserialize, Serialize};
 
-use super::{Dock, DockArea, DockItem, DockPlacement, Panel, PanelRegistry};
+use super::{layout::PanelPool, Dock, DockArea, DockItem, DockPlacement, Panel, PanelRegistry};
 
 /// Used to serialize and deserialize the DockArea
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +19,34 @@ pub struct DockAreaState {
     pub right_dock: Option<DockState>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bottom_dock: Option<DockState>,
+    /// The floating windows torn off from the DockArea.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<DockWindowState>,
+}
+
+/// Used to serialize and deserialize the named layouts of the DockArea
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DockLayoutsState {
+    /// The name of the active layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub layouts: Vec<DockLayoutState>,
+}
+
+/// A named layout of the DockArea, e.g. "Debug", "Review".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DockLayoutState {
+    pub name: String,
+    pub state: DockAreaState,
+}
+
+/// Used to serialize and deserialize the floating window of the DockArea
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DockWindowState {
+    /// The bounds of the window in screen coordinates.
+    pub bounds: Bounds<Pixels>,
+    pub center: PanelState,
 }
 
 /// Used to serialize and deserialize the Dock
@@ -28,6 +56,9 @@ pub struct DockState {
     placement: DockPlacement,
     size: Pixels,
     open: bool,
+    /// Whether the Dock is auto-hide, the auto-hide Dock is always restored as collapsed.
+    #[serde(default)]
+    auto_hide: bool,
 }
 
 impl DockState {
@@ -38,6 +69,7 @@ impl DockState {
             placement: dock.placement,
             size: dock.size,
             open: dock.open,
+            auto_hide: dock.auto_hide,
             panel: dock.panel.view().dump(cx),
         }
     }
@@ -49,7 +81,18 @@ impl DockState {
         window: &mut Window,
         cx: &mut App,
     ) -> Entity<Dock> {
-        let item = self.panel.to_item(dock_area.clone(), window, cx);
+        self.build_dock(dock_area, &mut PanelPool::default(), window, cx)
+    }
+
+    /// Convert the DockState to Dock, reuse the panels in the `pool` if matched.
+    pub(super) fn build_dock(
+        &self,
+        dock_area: WeakEntity<DockArea>,
+        pool: &mut PanelPool,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Dock> {
+        let item = self.panel.build_item(dock_area.clone(), pool, window, cx);
         cx.new(|cx| {
             Dock::from_state(
                 dock_area.clone(),
@@ -57,6 +100,7 @@ impl DockState {
                 self.size,
                 item,
                 self.open,
+                self.auto_hide,
                 window,
                 cx,
             )
@@ -184,13 +228,24 @@ impl PanelState {
         dock_area: WeakEntity<DockArea>,
         window: &mut Window,
         cx: &mut App,
+    ) -> DockItem {
+        self.build_item(dock_area, &mut PanelPool::default(), window, cx)
+    }
+
+    /// Convert the PanelState to DockItem, reuse the panels in the `pool` if matched.
+    pub(super) fn build_item(
+        &self,
+        dock_area: WeakEntity<DockArea>,
+        pool: &mut PanelPool,
+        window: &mut Window,
+        cx: &mut App,
     ) -> DockItem {
         let info = self.info.clone();
 
         let items: Vec<DockItem> = self
             .children
             .iter()
-            .map(|child| child.to_item(dock_area.clone(), window, cx))
+            .map(|child| child.build_item(dock_area.clone(), pool, window, cx))
             .collect();
 
         match info {
@@ -222,15 +277,18 @@ impl PanelState {
                 DockItem::tabs(items, Some(active_index), &dock_area, window, cx)
             }
             PanelInfo::Panel(_) => {
-                let view = PanelRegistry::build_panel(
-                    &self.panel_name,
-                    dock_area.clone(),
-                    self,
-                    &info,
-                    window,
-                    cx,
-                );
-                DockItem::tabs(vec![view.into()], None, &dock_area, window, cx)
+                let view = pool.take(self).unwrap_or_else(|| {
+                    PanelRegistry::build_panel(
+                        &self.panel_name,
+                        dock_area.clone(),
+                        self,
+                        &info,
+                        window,
+                        cx,
+                    )
+                    .into()
+                });
+                DockItem::tabs(vec![view], None, &dock_area, window, cx)
             }
             PanelInfo::Tiles { metas } => DockItem::tiles(items, metas, &dock_area, window, cx),
         }
@@ -259,6 +317,7 @@ mod tests {
 
         let left_dock = state.left_dock.unwrap();
         assert_eq!(left_dock.open, true);
+        assert_eq!(left_dock.auto_hide, false);
         assert_eq!(left_dock.size, px(350.0));
         assert_eq!(left_dock.placement, DockPlacement::Left);
         assert_eq!(left_dock.panel.panel_name, "TabPanel");
@@ -278,5 +337,74 @@ mod tests {
         assert_eq!(right_dock.panel.panel_name, "TabPanel");
         assert_eq!(right_dock.panel.children.len(), 1);
         assert_eq!(right_dock.panel.children[0].panel_name, "StoryContainer");
+
+        assert!(state.windows.is_empty());
+    }
+
+    #[test]
+    fn test_serialize_window_state() {
+        let state = DockAreaState {
+            windows: vec![DockWindowState {
+                bounds: Bounds {
+                    origin: point(px(100.), px(50.)),
+                    size: size(px(800.), px(600.)),
+                },
+                center: PanelState {
+                    panel_name: "StackPanel".into(),
+                    ..Default::default()
+                },
+            }],
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<DockAreaState>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_serialize_layouts_state() {
+        let state = DockLayoutsState {
+            active: Some("Debug".into()),
+            layouts: vec![
+                DockLayoutState {
+                    name: "Debug".into(),
+                    state: DockAreaState {
+                        version: Some(2),
+                        ..Default::default()
+                    },
+                },
+                DockLayoutState {
+                    name: "Review".into(),
+                    state: DockAreaState::default(),
+                },
+            ],
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DockLayoutsState>(&json).unwrap(),
+            state
+        );
+
+        let state: DockLayoutsState = serde_json::from_str("{}").unwrap();
+        assert_eq!(state.active, None);
+        assert!(state.layouts.is_empty());
+    }
+
+    #[test]
+    fn test_serialize_auto_hide_dock_state() {
+        let state = DockAreaState {
+            left_dock: Some(DockState {
+                panel: PanelState::default(),
+                placement: DockPlacement::Left,
+                size: px(240.),
+                open: false,
+                auto_hide: true,
+            }),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<DockAreaState>(&json).unwrap(), state);
     }
 }
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use gpui::{
-    div, prelude::FluentBuilder, px, relative, rems, App, AppContext, Context, Corner,
-    DismissEvent, Div, DragMoveEvent, Empty, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement as _, IntoElement, ParentElement, Pixels, Render, ScrollHandle,
-    SharedString, StatefulInteractiveElement, StyleRefinement, Styled, WeakEntity, Window,
+    canvas, div, prelude::FluentBuilder, px, relative, rems, size, App, AppContext, Bounds,
+    Context, Corner, DismissEvent, Div, DragMoveEvent, Empty, Entity, EventEmitter, FocusHandle,
+    Focusable, InteractiveElement as _, IntoElement, ParentElement, Pixels, Render, ScrollHandle,
+    SharedString, Size, StatefulInteractiveElement, StyleRefinement, Styled, Task, WeakEntity,
+    Window,
 };
 use rust_i18n::t;
 
@@ -18,8 +19,9 @@ use crate::{
 };
 
 use super::{
-    ClosePanel, DockArea, DockPlacement, Panel, PanelControl, PanelEvent, PanelState, PanelStyle,
-    PanelView, StackPanel, ToggleZoom,
+    panel::can_close_panels, ClosePanel, DockArea, DockPlacement, MoveToNewWindow, NextTab, Panel,
+    PanelControl, PanelEvent, PanelState, PanelStyle, PanelView, PrevTab, SplitHorizontal,
+    SplitVertical, StackPanel, ToggleAutoHide, ToggleZoom,
 };
 
 #[derive(Clone)]
@@ -83,6 +85,8 @@ pub struct TabPanel {
     will_split_placement: Option<Placement>,
     /// Is TabPanel used in Tiles.
     in_tiles: bool,
+    /// The bounds of the TabPanel, used to find the panel in a direction.
+    bounds: Bounds<Pixels>,
 }
 
 impl Panel for TabPanel {
@@ -127,6 +131,14 @@ impl Panel for TabPanel {
             .and_then(|panel| panel.toolbar_buttons(window, cx))
     }
 
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.panels.iter().any(|panel| panel.is_dirty(cx))
+    }
+
+    fn can_close(&mut self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        can_close_panels(self.panels.clone(), window, cx)
+    }
+
     fn dump(&self, cx: &App) -> PanelState {
         let mut state = PanelState::new(self);
         for panel in self.panels.iter() {
@@ -140,6 +152,12 @@ impl Panel for TabPanel {
         self.active_panel(cx)
             .map_or(true, |panel| panel.inner_padding(cx))
     }
+
+    fn min_size(&self, cx: &App) -> Option<Size<Pixels>> {
+        self.visible_panels(cx)
+            .filter_map(|panel| panel.min_size(cx))
+            .reduce(|a, b| size(a.width.max(b.width), a.height.max(b.height)))
+    }
 }
 
 impl TabPanel {
@@ -161,6 +179,7 @@ impl TabPanel {
             collapsed: false,
             closable: true,
             in_tiles: false,
+            bounds: Bounds::default(),
         }
     }
 
@@ -173,6 +192,14 @@ impl TabPanel {
         self.stack_panel = Some(view);
     }
 
+    pub(super) fn dock_area(&self) -> &WeakEntity<DockArea> {
+        &self.dock_area
+    }
+
+    pub(super) fn bounds(&self) -> Bounds<Pixels> {
+        self.bounds
+    }
+
     /// Return current active_panel View
     pub fn active_panel(&self, cx: &App) -> Option<Arc<dyn PanelView>> {
         let panel = self.panels.get(self.active_ix);
@@ -194,11 +221,20 @@ impl TabPanel {
             return;
         }
 
+        self.show_tab(ix, window, cx);
+        self.focus_active_panel(window, cx);
+    }
+
+    /// Switch the active tab to `ix` without moving the focus.
+    fn show_tab(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix == self.active_ix {
+            return;
+        }
+
         let last_active_ix = self.active_ix;
 
         self.active_ix = ix;
         self.tab_bar_scroll_handle.scroll_to_item(ix);
-        self.focus_active_panel(window, cx);
 
         // Sync the active state to all panels
         cx.spawn_in(window, async move |view, cx| {
@@ -399,9 +435,17 @@ impl TabPanel {
 
     /// Return true if the tab panel is draggable.
     ///
-    /// E.g. if the parent and self only have one panel, it is not draggable.
+    /// E.g. if the parent and self only have one panel, it is not draggable,
+    /// except in a floating window, the last panel can be moved back to another window.
     fn draggable(&self, cx: &App) -> bool {
-        !self.is_locked(cx) && !self.is_last_panel(cx)
+        !self.is_locked(cx) && (!self.is_last_panel(cx) || self.is_floating(cx))
+    }
+
+    /// Return true if the tab panel is in a floating window.
+    fn is_floating(&self, cx: &App) -> bool {
+        self.dock_area
+            .upgrade()
+            .map_or(false, |dock_area| dock_area.read(cx).is_floating())
     }
 
     /// Return true if the tab panel is droppable.
@@ -461,6 +505,14 @@ impl TabPanel {
                     .popup_menu({
                         let zoomable = state.zoomable.map_or(false, |v| v.menu_visible());
                         let closable = state.closable;
+                        let draggable = state.draggable;
+                        let auto_hide = self.dock_placement(cx).and_then(|placement| {
+                            let dock_area = self.dock_area.upgrade()?;
+                            dock_area
+                                .read(cx)
+                                .is_dock_collapsible(placement, cx)
+                                .then(|| dock_area.read(cx).is_dock_auto_hide(placement, cx))
+                        });
 
                         move |this, window, cx| {
                             view.read(cx)
@@ -475,6 +527,19 @@ impl TabPanel {
                                     Box::new(ToggleZoom),
                                     !zoomable,
                                 )
+                                .when(draggable, |this| {
+                                    this.menu(
+                                        t!("Dock.Move to New Window"),
+                                        Box::new(MoveToNewWindow),
+                                    )
+                                })
+                                .when_some(auto_hide, |this, auto_hide| {
+                                    this.menu_with_check(
+                                        t!("Dock.Auto Hide"),
+                                        auto_hide,
+                                        Box::new(ToggleAutoHide),
+                                    )
+                                })
                                 .when(closable, |this| {
                                     this.separator()
                                         .menu(t!("Dock.Close"), Box::new(ClosePanel))
@@ -643,6 +708,9 @@ impl TabPanel {
                             )
                         }),
                 )
+                .when(panel.is_dirty(cx), |this| {
+                    this.child(Self::render_dirty_dot(cx))
+                })
                 .children(panel.title_suffix(window, cx))
                 .child(
                     h_flex()
@@ -703,6 +771,9 @@ impl TabPanel {
                             }
                         })
                         .py_2()
+                        .when(panel.is_dirty(cx), |this| {
+                            this.suffix(Self::render_dirty_dot(cx))
+                        })
                         .selected(active)
                         .disabled(disabled)
                         .when(!disabled, |this| {
@@ -785,6 +856,16 @@ impl TabPanel {
             .into_any_element()
     }
 
+    /// The dot to mark the panel has unsaved changes.
+    fn render_dirty_dot(cx: &App) -> impl IntoElement {
+        div()
+            .flex_shrink_0()
+            .mx_1()
+            .size_1p5()
+            .rounded_full()
+            .bg(cx.theme().tab_foreground)
+    }
+
     fn render_active_panel(
         &self,
         state: &TabState,
@@ -1054,6 +1135,106 @@ impl TabPanel {
         }
     }
 
+    /// Activate the panel and focus it.
+    pub(super) fn activate_panel(
+        &mut self,
+        panel: &Arc<dyn PanelView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel_view = panel.view();
+        if let Some(ix) = self.panels.iter().position(|p| p.view() == panel_view) {
+            self.set_active_ix(ix, window, cx);
+        }
+        self.focus_active_panel(window, cx);
+    }
+
+    /// Show the `panel` as the active tab, unlike [`TabPanel::activate_panel`], the focus is not
+    /// moved.
+    pub(super) fn show_panel(
+        &mut self,
+        panel: &Arc<dyn PanelView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel_view = panel.view();
+        if let Some(ix) = self.panels.iter().position(|p| p.view() == panel_view) {
+            self.show_tab(ix, window, cx);
+        }
+    }
+
+    /// Activate the next or previous visible tab.
+    fn cycle_active_tab(&mut self, is_next: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let visible_ixs = self
+            .panels
+            .iter()
+            .enumerate()
+            .filter(|(_, panel)| panel.visible(cx))
+            .map(|(ix, _)| ix)
+            .collect::<Vec<_>>();
+        let len = visible_ixs.len();
+        if len < 2 {
+            return;
+        }
+
+        let pos = visible_ixs
+            .iter()
+            .position(|ix| *ix == self.active_ix)
+            .unwrap_or_default();
+        let pos = if is_next {
+            (pos + 1) % len
+        } else {
+            (pos + len - 1) % len
+        };
+        self.set_active_ix(visible_ixs[pos], window, cx);
+    }
+
+    fn on_action_next_tab(&mut self, _: &NextTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.cycle_active_tab(true, window, cx);
+    }
+
+    fn on_action_prev_tab(&mut self, _: &PrevTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.cycle_active_tab(false, window, cx);
+    }
+
+    /// Move the active panel out to a new split at the `placement`.
+    fn split_active_panel(
+        &mut self,
+        placement: Placement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_locked(cx) || self.panels.len() < 2 {
+            return;
+        }
+        let Some(panel) = self.active_panel(cx) else {
+            return;
+        };
+
+        self.detach_panel(panel.clone(), window, cx);
+        self.split_panel(panel.clone(), placement, None, window, cx);
+        panel.focus_handle(cx).focus(window);
+        cx.emit(PanelEvent::LayoutChanged);
+    }
+
+    fn on_action_split_horizontal(
+        &mut self,
+        _: &SplitHorizontal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.split_active_panel(Placement::Right, window, cx);
+    }
+
+    fn on_action_split_vertical(
+        &mut self,
+        _: &SplitVertical,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.split_active_panel(Placement::Bottom, window, cx);
+    }
+
     fn on_action_toggle_zoom(
         &mut self,
         _: &ToggleZoom,
@@ -1090,9 +1271,28 @@ impl TabPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some(panel) = self.active_panel(cx) {
-            self.remove_panel(panel, window, cx);
-        }
+        let Some(panel) = self.active_panel(cx) else {
+            return;
+        };
+
+        let can_close = panel.can_close(window, cx);
+        cx.spawn_in(window, async move |view, cx| {
+            if !can_close.await {
+                return;
+            }
+            _ = view.update_in(cx, |view, window, cx| view.close_panel(panel, window, cx));
+        })
+        .detach();
+    }
+
+    /// Remove the panel after it is confirmed to close.
+    fn close_panel(
+        &mut self,
+        panel: Arc<dyn PanelView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.remove_panel(panel, window, cx);
 
         // Remove self from the parent DockArea.
         // This is ensure to remove from Tiles
@@ -1107,6 +1307,63 @@ impl TabPanel {
                 }
             });
         }
+
+        // Close the floating window, if the last panel is closed.
+        if self.panels.is_empty() && self.is_floating(cx) {
+            window.defer(cx, {
+                let dock_area = self.dock_area.clone();
+                move |window, cx| {
+                    _ = dock_area.update(cx, |this, cx| this.close_if_empty(window, cx));
+                }
+            });
+        }
+    }
+
+    /// Return the placement of the dock that contains this tab panel, None if it is in the center.
+    fn dock_placement(&self, cx: &Context<Self>) -> Option<DockPlacement> {
+        let dock_area = self.dock_area.upgrade()?;
+        dock_area.read(cx).dock_placement_of(&cx.entity(), cx)
+    }
+
+    fn on_action_toggle_auto_hide(
+        &mut self,
+        _: &ToggleAutoHide,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(placement) = self.dock_placement(cx) else {
+            return;
+        };
+
+        let dock_area = self.dock_area.clone();
+        window.defer(cx, move |window, cx| {
+            _ = dock_area.update(cx, |dock_area, cx| {
+                let auto_hide = dock_area.is_dock_auto_hide(placement, cx);
+                dock_area.set_dock_auto_hide(placement, !auto_hide, window, cx);
+            });
+        });
+    }
+
+    fn on_action_move_to_new_window(
+        &mut self,
+        _: &MoveToNewWindow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.draggable(cx) {
+            return;
+        }
+        let Some(panel) = self.active_panel(cx) else {
+            return;
+        };
+        let Some(dock_area) = self.dock_area.upgrade() else {
+            return;
+        };
+
+        let drag = DragPanel::new(panel, cx.entity());
+        window.defer(cx, move |window, cx| {
+            DockArea::move_panel_to_window(dock_area, drag, None, window, cx);
+        });
     }
 
     // Bind actions to the tab panel, only when the tab panel is not collapsed.
@@ -1114,6 +1371,12 @@ impl TabPanel {
         v_flex().when(!self.collapsed, |this| {
             this.on_action(cx.listener(Self::on_action_toggle_zoom))
                 .on_action(cx.listener(Self::on_action_close_panel))
+                .on_action(cx.listener(Self::on_action_move_to_new_window))
+                .on_action(cx.listener(Self::on_action_toggle_auto_hide))
+                .on_action(cx.listener(Self::on_action_next_tab))
+                .on_action(cx.listener(Self::on_action_prev_tab))
+                .on_action(cx.listener(Self::on_action_split_horizontal))
+                .on_action(cx.listener(Self::on_action_split_vertical))
         })
     }
 }
@@ -1147,12 +1410,23 @@ impl Render for TabPanel {
             state.closable = false;
         }
 
+        let view = cx.entity().clone();
+
         self.bind_actions(cx)
             .id("tab-panel")
             .track_focus(&focus_handle)
+            .relative()
             .size_full()
             .overflow_hidden()
             .bg(cx.theme().background)
+            .child(
+                canvas(
+                    move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
             .child(self.render_title_bar(&state, window, cx))
             .child(self.render_active_panel(&state, window, cx))
     }
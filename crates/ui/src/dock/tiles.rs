@@ -12,21 +12,72 @@ use crate::{
 };
 
 use super::{
-    DockArea, Panel, PanelEvent, PanelInfo, PanelState, PanelView, StackPanel, TabPanel, TileMeta,
+    panel::can_close_panels, DockArea, Panel, PanelEvent, PanelInfo, PanelState, PanelView,
+    StackPanel, TabPanel, TileMeta,
 };
 use gpui::{
     actions, canvas, div, point, px, size, AnyElement, App, AppContext, Bounds, Context,
     DismissEvent, DragMoveEvent, Empty, EntityId, EventEmitter, FocusHandle, Focusable, Half,
-    InteractiveElement, IntoElement, MouseButton, MouseDownEvent, MouseUpEvent, ParentElement,
-    Pixels, Point, Render, ScrollHandle, Size, StatefulInteractiveElement, Styled, WeakEntity,
-    Window,
+    InteractiveElement, IntoElement, KeyBinding, MouseButton, MouseDownEvent, MouseUpEvent,
+    ParentElement, Pixels, Point, Render, ScrollHandle, Size, StatefulInteractiveElement, Styled,
+    Task, WeakEntity, Window,
 };
 
-actions!(tiles, [Undo, Redo,]);
-
+actions!(
+    tiles,
+    [
+        Undo,
+        Redo,
+        TileAll,
+        CascadeAll,
+        MaximizeAll,
+        NudgeLeft,
+        NudgeRight,
+        NudgeUp,
+        NudgeDown
+    ]
+);
+
+const CONTEXT: &str = "Tiles";
 const MINIMUM_SIZE: Size<Pixels> = size(px(100.), px(100.));
 const DRAG_BAR_HEIGHT: Pixels = px(30.);
 const HANDLE_SIZE: Pixels = px(5.0);
+/// The distance to snap the edge of the tile to the edges of the other tiles.
+const SNAP_THRESHOLD: Pixels = px(8.);
+
+pub(super) fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("secondary-z", Undo, Some(CONTEXT)),
+        KeyBinding::new("secondary-shift-z", Redo, Some(CONTEXT)),
+        KeyBinding::new("secondary-k t", TileAll, Some(CONTEXT)),
+        KeyBinding::new("secondary-k c", CascadeAll, Some(CONTEXT)),
+        KeyBinding::new("secondary-k m", MaximizeAll, Some(CONTEXT)),
+        KeyBinding::new("ctrl-alt-left", NudgeLeft, Some(CONTEXT)),
+        KeyBinding::new("ctrl-alt-right", NudgeRight, Some(CONTEXT)),
+        KeyBinding::new("ctrl-alt-up", NudgeUp, Some(CONTEXT)),
+        KeyBinding::new("ctrl-alt-down", NudgeDown, Some(CONTEXT)),
+    ]);
+}
+
+/// The ways to arrange all the tiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileArrangement {
+    /// Arrange the tiles in a grid to fill the visible area.
+    Tile,
+    /// Arrange the tiles overlapped with an offset of the title bar.
+    Cascade,
+    /// Resize all the tiles to fill the visible area.
+    Maximize,
+}
+
+/// The guide line shown while the tile is snapped to the edge of another tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SnapGuide {
+    /// A vertical line at the x position.
+    Vertical(Pixels),
+    /// A horizontal line at the y position.
+    Horizontal(Pixels),
+}
 
 #[derive(Clone, PartialEq, Debug)]
 struct TileChange {
@@ -139,6 +190,8 @@ pub struct Tiles {
     history: History<TileChange>,
     scroll_state: ScrollbarState,
     scroll_handle: ScrollHandle,
+    /// The snap guides of the moving or resizing tile.
+    guides: Vec<SnapGuide>,
 }
 
 impl Panel for Tiles {
@@ -150,6 +203,15 @@ impl Panel for Tiles {
         "Tiles".into_any_element()
     }
 
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.panels.iter().any(|item| item.panel.is_dirty(cx))
+    }
+
+    fn can_close(&mut self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        let panels = self.panels.iter().map(|item| item.panel.clone()).collect();
+        can_close_panels(panels, window, cx)
+    }
+
     fn dump(&self, cx: &App) -> PanelState {
         let panels = self
             .panels
@@ -193,6 +255,7 @@ impl Tiles {
             history: History::new().group_interval(std::time::Duration::from_millis(100)),
             scroll_state: ScrollbarState::default(),
             scroll_handle: ScrollHandle::default(),
+            guides: vec![],
         }
     }
 
@@ -244,6 +307,7 @@ impl Tiles {
         let Some(index) = self.dragging_index else {
             return;
         };
+        let (xs, ys) = self.snap_targets(index);
 
         let Some(item) = self.panels.get_mut(index) else {
             return;
@@ -263,7 +327,34 @@ impl Tiles {
             new_origin.x = min_left;
         }
 
-        let final_origin = round_point_to_nearest_ten(new_origin, cx);
+        // Snap to the edges of the other tiles first, otherwise snap to the grid.
+        let mut guides = vec![];
+        let tile_size = previous_bounds.size;
+        let final_origin = point(
+            match snap_offset(
+                &[new_origin.x, new_origin.x + tile_size.width],
+                &xs,
+                SNAP_THRESHOLD,
+            ) {
+                Some((offset, target)) => {
+                    guides.push(SnapGuide::Vertical(target));
+                    new_origin.x + offset
+                }
+                None => round_to_nearest_ten(new_origin.x, cx),
+            },
+            match snap_offset(
+                &[new_origin.y, new_origin.y + tile_size.height],
+                &ys,
+                SNAP_THRESHOLD,
+            ) {
+                Some((offset, target)) => {
+                    guides.push(SnapGuide::Horizontal(target));
+                    new_origin.y + offset
+                }
+                None => round_to_nearest_ten(new_origin.y, cx),
+            },
+        );
+        self.guides = guides;
         // Only push to history if bounds have changed
         if final_origin != previous_bounds.origin {
             item.bounds.origin = final_origin;
@@ -306,58 +397,216 @@ impl Tiles {
         _: &mut Window,
         cx: &mut Context<'_, Self>,
     ) {
-        if let Some(index) = self.resizing_index {
-            if let Some(item) = self.panels.get_mut(index) {
-                let previous_bounds = item.bounds;
-                let final_x = if let Some(x) = new_x {
-                    round_to_nearest_ten(x, cx)
-                } else {
-                    previous_bounds.origin.x
-                };
-                let final_y = if let Some(y) = new_y {
-                    round_to_nearest_ten(y, cx)
-                } else {
-                    previous_bounds.origin.y
-                };
-                let final_width = if let Some(width) = new_width {
-                    round_to_nearest_ten(width, cx)
-                } else {
-                    previous_bounds.size.width
-                };
-
-                let final_height = if let Some(height) = new_height {
-                    round_to_nearest_ten(height, cx)
-                } else {
-                    previous_bounds.size.height
-                };
-
-                // Only push to history if size has changed
-                if final_width != item.bounds.size.width
-                    || final_height != item.bounds.size.height
-                    || final_x != item.bounds.origin.x
-                    || final_y != item.bounds.origin.y
-                {
-                    item.bounds.origin.x = final_x;
-                    item.bounds.origin.y = final_y;
-                    item.bounds.size.width = final_width;
-                    item.bounds.size.height = final_height;
-
-                    // Only push if not during history operations
-                    if !self.history.ignore {
-                        self.history.push(TileChange {
-                            tile_id: item.panel.view().entity_id(),
-                            old_bounds: Some(previous_bounds),
-                            new_bounds: Some(item.bounds),
-                            old_order: None,
-                            new_order: None,
-                            version: 0,
-                        });
-                    }
-                }
+        let Some(index) = self.resizing_index else {
+            return;
+        };
+        let (xs, ys) = self.snap_targets(index);
+        let Some(item) = self.panels.get_mut(index) else {
+            return;
+        };
 
-                cx.notify();
+        let previous_bounds = item.bounds;
+        let min_size = item.panel.min_size(cx).unwrap_or(MINIMUM_SIZE);
+        let mut guides = vec![];
+
+        // Snap the moving edge, and keep the opposite edge when the size is less than minimum.
+        let (left, right) = match (new_x, new_width) {
+            (Some(x), Some(width)) => {
+                let right = x + width;
+                let left = snap_edge(x, &xs, SnapGuide::Vertical, &mut guides, cx);
+                (left.min(right - min_size.width), right)
+            }
+            (None, Some(width)) => {
+                let left = previous_bounds.left();
+                let right = snap_edge(left + width, &xs, SnapGuide::Vertical, &mut guides, cx);
+                (left, right.max(left + min_size.width))
+            }
+            _ => (previous_bounds.left(), previous_bounds.right()),
+        };
+        let (top, bottom) = match (new_y, new_height) {
+            (Some(y), Some(height)) => {
+                let bottom = y + height;
+                let top = snap_edge(y, &ys, SnapGuide::Horizontal, &mut guides, cx);
+                (top.min(bottom - min_size.height), bottom)
+            }
+            (None, Some(height)) => {
+                let top = previous_bounds.top();
+                let bottom = snap_edge(top + height, &ys, SnapGuide::Horizontal, &mut guides, cx);
+                (top, bottom.max(top + min_size.height))
+            }
+            _ => (previous_bounds.top(), previous_bounds.bottom()),
+        };
+        let new_bounds = Bounds::from_corners(point(left, top), point(right, bottom));
+
+        // Only push to history if bounds have changed
+        if new_bounds != previous_bounds {
+            item.bounds = new_bounds;
+
+            // Only push if not during history operations
+            if !self.history.ignore {
+                self.history.push(TileChange {
+                    tile_id: item.panel.view().entity_id(),
+                    old_bounds: Some(previous_bounds),
+                    new_bounds: Some(item.bounds),
+                    old_order: None,
+                    new_order: None,
+                    version: 0,
+                });
             }
         }
+
+        self.guides = guides;
+        cx.notify();
+    }
+
+    /// Return the edges of the other tiles and the container to snap the tile of `index`,
+    /// the first is the vertical edges (x), the second is the horizontal edges (y).
+    fn snap_targets(&self, index: usize) -> (Vec<Pixels>, Vec<Pixels>) {
+        let mut xs = vec![px(0.)];
+        let mut ys = vec![px(0.)];
+        for (ix, item) in self.panels.iter().enumerate() {
+            if ix == index {
+                continue;
+            }
+            xs.extend([item.bounds.left(), item.bounds.right()]);
+            ys.extend([item.bounds.top(), item.bounds.bottom()]);
+        }
+        (xs, ys)
+    }
+
+    /// Return the index of the active tile, that is the tile contains the focus, or the front one.
+    fn active_index(&self, window: &Window, cx: &App) -> Option<usize> {
+        self.panels
+            .iter()
+            .rposition(|item| item.panel.focus_handle(cx).contains_focused(window, cx))
+            .or_else(|| self.panels.len().checked_sub(1))
+    }
+
+    /// Set the bounds of the tiles, and push the changes to the history as one undo step.
+    fn set_bounds(&mut self, bounds: Vec<(usize, Bounds<Pixels>)>, cx: &mut Context<Self>) {
+        let mut changed = false;
+        for (ix, new_bounds) in bounds {
+            let Some(item) = self.panels.get_mut(ix) else {
+                continue;
+            };
+            if item.bounds == new_bounds {
+                continue;
+            }
+
+            self.history.push(TileChange {
+                tile_id: item.panel.view().entity_id(),
+                old_bounds: Some(item.bounds),
+                new_bounds: Some(new_bounds),
+                old_order: None,
+                new_order: None,
+                version: 0,
+            });
+            item.bounds = new_bounds;
+            changed = true;
+        }
+
+        if changed {
+            cx.emit(PanelEvent::LayoutChanged);
+            cx.notify();
+        }
+    }
+
+    /// Arrange all the tiles in the visible area.
+    pub fn arrange(
+        &mut self,
+        arrangement: TileArrangement,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let offset = self.scroll_handle.offset();
+        let area = Bounds::new(point(-offset.x, -offset.y), self.bounds.size);
+        let count = self.panels.len();
+        let arranged = match arrangement {
+            TileArrangement::Tile => tile_bounds(area, count),
+            TileArrangement::Cascade => cascade_bounds(area, count),
+            TileArrangement::Maximize => vec![area; count],
+        };
+
+        let bounds = arranged
+            .into_iter()
+            .enumerate()
+            .map(|(ix, bounds)| {
+                let min_size = self.panels[ix].panel.min_size(cx).unwrap_or(MINIMUM_SIZE);
+                let tile_size = size(
+                    bounds.size.width.max(min_size.width),
+                    bounds.size.height.max(min_size.height),
+                );
+                (ix, Bounds::new(bounds.origin, tile_size))
+            })
+            .collect();
+        self.set_bounds(bounds, cx);
+    }
+
+    /// Move the active tile by the `delta`.
+    fn nudge(&mut self, delta: Point<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.active_index(window, cx) else {
+            return;
+        };
+
+        let bounds = self.panels[ix].bounds;
+        let origin = bounds.origin + delta;
+        let origin = point(origin.x.max(px(0.)), origin.y.max(px(0.)));
+        self.set_bounds(vec![(ix, Bounds::new(origin, bounds.size))], cx);
+    }
+
+    fn on_action_undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        self.undo(window, cx);
+    }
+
+    fn on_action_redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        self.redo(window, cx);
+    }
+
+    fn on_action_tile_all(&mut self, _: &TileAll, window: &mut Window, cx: &mut Context<Self>) {
+        self.arrange(TileArrangement::Tile, window, cx);
+    }
+
+    fn on_action_cascade_all(
+        &mut self,
+        _: &CascadeAll,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.arrange(TileArrangement::Cascade, window, cx);
+    }
+
+    fn on_action_maximize_all(
+        &mut self,
+        _: &MaximizeAll,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.arrange(TileArrangement::Maximize, window, cx);
+    }
+
+    fn on_action_nudge_left(&mut self, _: &NudgeLeft, window: &mut Window, cx: &mut Context<Self>) {
+        let step = cx.theme().tile_grid_size;
+        self.nudge(point(-step, px(0.)), window, cx);
+    }
+
+    fn on_action_nudge_right(
+        &mut self,
+        _: &NudgeRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let step = cx.theme().tile_grid_size;
+        self.nudge(point(step, px(0.)), window, cx);
+    }
+
+    fn on_action_nudge_up(&mut self, _: &NudgeUp, window: &mut Window, cx: &mut Context<Self>) {
+        let step = cx.theme().tile_grid_size;
+        self.nudge(point(px(0.), -step), window, cx);
+    }
+
+    fn on_action_nudge_down(&mut self, _: &NudgeDown, window: &mut Window, cx: &mut Context<Self>) {
+        let step = cx.theme().tile_grid_size;
+        self.nudge(point(px(0.), step), window, cx);
     }
 
     pub fn add_item(
@@ -597,8 +846,7 @@ impl Tiles {
                             let delta = drag_data.last_position.x - pos.x;
                             let new_x = (drag_data.last_bounds.origin.x - delta).max(px(0.0));
                             let size_delta = drag_data.last_bounds.origin.x - new_x;
-                            let new_width = (drag_data.last_bounds.size.width + size_delta)
-                                .max(MINIMUM_SIZE.width);
+                            let new_width = drag_data.last_bounds.size.width + size_delta;
                             this.resize(Some(new_x), None, Some(new_width), None, window, cx);
                         }
                     },
@@ -657,8 +905,7 @@ impl Tiles {
 
                             let pos = e.event.position;
                             let delta = pos.x - drag_data.last_position.x;
-                            let new_width =
-                                (drag_data.last_bounds.size.width + delta).max(MINIMUM_SIZE.width);
+                            let new_width = drag_data.last_bounds.size.width + delta;
                             this.resize(None, None, Some(new_width), None, window, cx);
                         }
                     },
@@ -717,9 +964,8 @@ impl Tiles {
                             let pos = e.event.position;
                             let delta = drag_data.last_position.y - pos.y;
                             let new_y = (drag_data.last_bounds.origin.y - delta).max(px(0.));
-                            let size_delta = drag_data.last_position.y - new_y;
-                            let new_height = (drag_data.last_bounds.size.height + size_delta)
-                                .max(MINIMUM_SIZE.width);
+                            let size_delta = drag_data.last_bounds.origin.y - new_y;
+                            let new_height = drag_data.last_bounds.size.height + size_delta;
                             this.resize(None, Some(new_y), None, Some(new_height), window, cx);
                         }
                     },
@@ -778,8 +1024,7 @@ impl Tiles {
 
                             let pos = e.event.position;
                             let delta = pos.y - drag_data.last_position.y;
-                            let new_height =
-                                (drag_data.last_bounds.size.height + delta).max(MINIMUM_SIZE.width);
+                            let new_height = drag_data.last_bounds.size.height + delta;
                             this.resize(None, None, None, Some(new_height), window, cx);
                         }
                     },
@@ -850,12 +1095,9 @@ impl Tiles {
                                         let pos = e.event.position;
                                         let delta_x = pos.x - drag_data.last_position.x;
                                         let delta_y = pos.y - drag_data.last_position.y;
-                                        let new_width = (drag_data.last_bounds.size.width
-                                            + delta_x)
-                                            .max(MINIMUM_SIZE.width);
-                                        let new_height = (drag_data.last_bounds.size.height
-                                            + delta_y)
-                                            .max(MINIMUM_SIZE.height);
+                                        let new_width = drag_data.last_bounds.size.width + delta_x;
+                                        let new_height =
+                                            drag_data.last_bounds.size.height + delta_y;
                                         this.resize(
                                             None,
                                             None,
@@ -1040,6 +1282,7 @@ impl Tiles {
             // Reset drag and resize state
             self.reset_current_index();
             self.resizing_drag_data = None;
+            self.guides.clear();
             cx.emit(PanelEvent::LayoutChanged);
             cx.notify();
         }
@@ -1051,12 +1294,80 @@ fn round_to_nearest_ten(value: Pixels, cx: &App) -> Pixels {
     (value / cx.theme().tile_grid_size).round() * cx.theme().tile_grid_size
 }
 
-#[inline]
-fn round_point_to_nearest_ten(point: Point<Pixels>, cx: &App) -> Point<Pixels> {
-    Point::new(
-        round_to_nearest_ten(point.x, cx),
-        round_to_nearest_ten(point.y, cx),
-    )
+/// Returns the offset to align one of the `edges` to the nearest of the `targets` within the
+/// `threshold`, and the aligned target.
+fn snap_offset(
+    edges: &[Pixels],
+    targets: &[Pixels],
+    threshold: Pixels,
+) -> Option<(Pixels, Pixels)> {
+    edges
+        .iter()
+        .flat_map(|edge| targets.iter().map(move |target| (*target - *edge, *target)))
+        .filter(|(offset, _)| offset.abs() <= threshold)
+        .min_by(|a, b| {
+            a.0.abs()
+                .partial_cmp(&b.0.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
+/// Snap the `edge` to the `targets` with a guide, otherwise snap it to the grid.
+fn snap_edge(
+    edge: Pixels,
+    targets: &[Pixels],
+    guide: fn(Pixels) -> SnapGuide,
+    guides: &mut Vec<SnapGuide>,
+    cx: &App,
+) -> Pixels {
+    match snap_offset(&[edge], targets, SNAP_THRESHOLD) {
+        Some((offset, target)) => {
+            guides.push(guide(target));
+            edge + offset
+        }
+        None => round_to_nearest_ten(edge, cx),
+    }
+}
+
+/// Split the `area` into a grid for `count` tiles by rows, the tiles in the last row are
+/// stretched to fill the row.
+fn tile_bounds(area: Bounds<Pixels>, count: usize) -> Vec<Bounds<Pixels>> {
+    if count == 0 {
+        return vec![];
+    }
+
+    let cols = (count as f32).sqrt().ceil() as usize;
+    let rows = count.div_ceil(cols);
+    let height = (area.size.height / rows as f32).floor();
+
+    (0..count)
+        .map(|ix| {
+            let (row, col) = (ix / cols, ix % cols);
+            let cols_in_row = cols.min(count - row * cols);
+            let width = (area.size.width / cols_in_row as f32).floor();
+            Bounds::new(
+                area.origin + point(width * col as f32, height * row as f32),
+                size(width, height),
+            )
+        })
+        .collect()
+}
+
+/// Arrange `count` tiles overlapped in the `area`, each one is offset by the drag bar height.
+fn cascade_bounds(area: Bounds<Pixels>, count: usize) -> Vec<Bounds<Pixels>> {
+    let tile_size = size(
+        (area.size.width * 0.6).floor(),
+        (area.size.height * 0.6).floor(),
+    );
+    // Restart from the origin when the tile is out of the area.
+    let steps = ((area.size.height - tile_size.height) / DRAG_BAR_HEIGHT).floor() as usize + 1;
+
+    (0..count)
+        .map(|ix| {
+            let offset = DRAG_BAR_HEIGHT * (ix % steps) as f32;
+            Bounds::new(area.origin + point(offset, offset), tile_size)
+        })
+        .collect()
 }
 
 impl Focusable for Tiles {
@@ -1086,6 +1397,17 @@ impl Render for Tiles {
         let scroll_size = scroll_bounds.size - size(scroll_bounds.origin.x, scroll_bounds.origin.y);
 
         div()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::on_action_undo))
+            .on_action(cx.listener(Self::on_action_redo))
+            .on_action(cx.listener(Self::on_action_tile_all))
+            .on_action(cx.listener(Self::on_action_cascade_all))
+            .on_action(cx.listener(Self::on_action_maximize_all))
+            .on_action(cx.listener(Self::on_action_nudge_left))
+            .on_action(cx.listener(Self::on_action_nudge_right))
+            .on_action(cx.listener(Self::on_action_nudge_up))
+            .on_action(cx.listener(Self::on_action_nudge_down))
             .relative()
             .bg(cx.theme().tiles)
             .child(
@@ -1101,6 +1423,13 @@ impl Render for Tiles {
                             .enumerate()
                             .map(|(ix, item)| self.render_panel(&item, ix, window, cx)),
                     )
+                    .children(self.guides.iter().map(|guide| {
+                        let line = div().absolute().bg(cx.theme().drag_border);
+                        match *guide {
+                            SnapGuide::Vertical(x) => line.top_0().bottom_0().left(x).w(px(1.)),
+                            SnapGuide::Horizontal(y) => line.left_0().right_0().top(y).h(px(1.)),
+                        }
+                    }))
                     .child({
                         canvas(
                             move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
@@ -1146,3 +1475,56 @@ impl Render for Tiles {
             .size_full()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::{point, px, size, Bounds};
+
+    use super::{cascade_bounds, snap_offset, tile_bounds, DRAG_BAR_HEIGHT};
+
+    #[test]
+    fn test_snap_offset() {
+        let targets = [px(0.), px(100.), px(300.)];
+        assert_eq!(
+            snap_offset(&[px(95.), px(195.)], &targets, px(8.)),
+            Some((px(5.), px(100.)))
+        );
+        // The right edge is closer to the target.
+        assert_eq!(
+            snap_offset(&[px(94.), px(298.)], &targets, px(8.)),
+            Some((px(2.), px(300.)))
+        );
+        assert_eq!(snap_offset(&[px(50.)], &targets, px(8.)), None);
+        assert_eq!(snap_offset(&[px(50.)], &[], px(8.)), None);
+    }
+
+    #[test]
+    fn test_tile_bounds() {
+        let area = Bounds::new(point(px(10.), px(20.)), size(px(300.), px(200.)));
+        assert!(tile_bounds(area, 0).is_empty());
+        assert_eq!(tile_bounds(area, 1), vec![area]);
+
+        // 2 columns, the last row has one tile to fill the row.
+        let bounds = tile_bounds(area, 3);
+        assert_eq!(
+            bounds,
+            vec![
+                Bounds::new(point(px(10.), px(20.)), size(px(150.), px(100.))),
+                Bounds::new(point(px(160.), px(20.)), size(px(150.), px(100.))),
+                Bounds::new(point(px(10.), px(120.)), size(px(300.), px(100.))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cascade_bounds() {
+        let area = Bounds::new(point(px(0.), px(0.)), size(px(500.), px(200.)));
+        let bounds = cascade_bounds(area, 4);
+        assert_eq!(bounds.len(), 4);
+        assert_eq!(bounds[0].size, size(px(300.), px(120.)));
+        assert_eq!(bounds[0].origin, point(px(0.), px(0.)));
+        assert_eq!(bounds[1].origin, point(DRAG_BAR_HEIGHT, DRAG_BAR_HEIGHT));
+        // Only 3 steps are fit in the height, then restart from the origin.
+        assert_eq!(bounds[3].origin, point(px(0.), px(0.)));
+    }
+}
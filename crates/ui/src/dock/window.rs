@@ -0,0 +1,332 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use gpui::{
+    px, size, App, AppContext, Axis, Bounds, Context, DragMoveEvent, Entity, MouseUpEvent, Pixels,
+    Point, Size, Subscription, Task, WeakEntity, Window, WindowBounds, WindowHandle, WindowKind,
+    WindowOptions,
+};
+
+use super::{
+    layout::PanelPool, panel::can_close_panels, DockArea, DockEvent, DockItem, DockPlacement,
+    DockWindowState, DragPanel, PanelView,
+};
+use crate::Root;
+
+/// A window torn off from the [`DockArea`], it hosts its own [`DockArea`] as the center.
+pub(super) struct DockWindow {
+    handle: WindowHandle<Root>,
+    dock_area: Entity<DockArea>,
+    /// Forward the layout changes of the window, dropped when the window is closed.
+    _subscription: Subscription,
+}
+
+/// The default size of the new window when a panel is moved out.
+fn default_window_size() -> Size<Pixels> {
+    size(px(800.), px(600.))
+}
+
+impl DockArea {
+    /// Return true if the dock area is hosted in a window torn off from another dock area.
+    pub fn is_floating(&self) -> bool {
+        self.owner.is_some()
+    }
+
+    /// Return the dock areas of the floating windows.
+    pub fn floating_windows(&self) -> impl Iterator<Item = &Entity<DockArea>> {
+        self.windows.iter().map(|w| &w.dock_area)
+    }
+
+    /// Ask all the panels whether they can be closed, include the panels in the docks and the
+    /// floating windows.
+    ///
+    /// Use this before closing the window of the dock area, e.g. in
+    /// `Window::on_window_should_close`, to prevent losing the unsaved changes.
+    pub fn can_close(&self, window: &mut Window, cx: &mut App) -> Task<bool> {
+        let panels = std::iter::once(self.items.view())
+            .chain(
+                [&self.left_dock, &self.bottom_dock, &self.right_dock]
+                    .into_iter()
+                    .flatten()
+                    .map(|dock| dock.read(cx).panel.view()),
+            )
+            .collect();
+        let can_close = can_close_panels(panels, window, cx);
+        let windows = self
+            .windows
+            .iter()
+            .map(|w| (w.handle, w.dock_area.clone()))
+            .collect::<Vec<_>>();
+
+        cx.spawn(async move |cx| {
+            if !can_close.await {
+                return false;
+            }
+
+            for (handle, dock_area) in windows {
+                let Ok(can_close) = handle.update(cx, |_, window, cx| {
+                    dock_area.update(cx, |this, cx| this.can_close(window, cx))
+                }) else {
+                    continue;
+                };
+                if !can_close.await {
+                    return false;
+                }
+            }
+            true
+        })
+    }
+
+    /// Track the dragging panel, if the mouse is out of the window, the panel will be moved
+    /// to another window when drop.
+    pub(super) fn on_panel_drag_move(
+        &mut self,
+        event: &DragMoveEvent<DragPanel>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let drag = event.drag(cx);
+        let is_self = drag.tab_panel.read(cx).dock_area() == &cx.entity().downgrade();
+        let viewport = Bounds::new(Point::default(), window.viewport_size());
+
+        self.tear_off = (is_self && !self.locked && !viewport.contains(&event.event.position))
+            .then(|| drag.clone());
+    }
+
+    /// Handle the mouse up of the dragging panel out of the window.
+    pub(super) fn on_panel_drag_end(
+        &mut self,
+        event: &MouseUpEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(drag) = self.tear_off.take() else {
+            return;
+        };
+        if !cx.has_active_drag() {
+            return;
+        }
+
+        // To screen coordinates, to find the window at the drop position.
+        let position = window.bounds().origin + event.position;
+        let dock_area = cx.entity();
+        window.defer(cx, move |window, cx| {
+            DockArea::move_panel_to_window(dock_area, drag, Some(position), window, cx);
+        });
+    }
+
+    /// Move the dragged panel out of the `dock_area`.
+    ///
+    /// The panel is added to the dock area window at the `position` (in screen coordinates),
+    /// if there is no window at the position or `position` is None, a new window is opened.
+    pub(super) fn move_panel_to_window(
+        dock_area: Entity<DockArea>,
+        drag: DragPanel,
+        position: Option<Point<Pixels>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let owner = dock_area
+            .read(cx)
+            .owner
+            .as_ref()
+            .and_then(|owner| owner.upgrade())
+            .unwrap_or(dock_area.clone());
+
+        let target = position.and_then(|position| {
+            std::iter::once(owner.clone())
+                .chain(owner.read(cx).floating_windows().cloned())
+                .filter(|area| area != &dock_area)
+                .find(|area| area.read(cx).window_bounds.contains(&position))
+        });
+
+        let panel = drag.panel.clone();
+        drag.tab_panel.update(cx, |tab_panel, cx| {
+            tab_panel.remove_panel(panel.clone(), window, cx);
+        });
+
+        if let Some(target) = target {
+            let handle = target.read(cx).window_handle;
+            _ = handle.update(cx, |_, window, cx| {
+                target.update(cx, |this, cx| this.add_panel_to_center(panel, window, cx));
+                window.activate_window();
+            });
+        } else {
+            let window_size = default_window_size();
+            let bounds = match position {
+                Some(position) => Bounds::new(position, window_size),
+                None => Bounds::centered_at(window.bounds().center(), window_size),
+            };
+            owner.update(cx, |owner, cx| {
+                _ = owner.open_window(
+                    bounds,
+                    move |dock_area, window, cx| {
+                        DockItem::split(
+                            Axis::Horizontal,
+                            vec![DockItem::tabs(vec![panel], None, &dock_area, window, cx)],
+                            &dock_area,
+                            window,
+                            cx,
+                        )
+                    },
+                    cx,
+                );
+            });
+        }
+
+        dock_area.update(cx, |this, cx| this.close_if_empty(window, cx));
+    }
+
+    /// Add the panel to the first tabs of the center.
+    fn add_panel_to_center(
+        &mut self,
+        panel: Arc<dyn PanelView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.items.left_top_tab_panel(cx) {
+            Some(tab_panel) => tab_panel.update(cx, |tab_panel, cx| {
+                tab_panel.add_panel(panel, window, cx);
+            }),
+            None => self.add_panel(panel, DockPlacement::Center, None, window, cx),
+        }
+    }
+
+    /// Close the floating window if the last panel has been moved out.
+    pub(super) fn close_if_empty(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_floating() || self.items.left_top_tab_panel(cx).is_some() {
+            return;
+        }
+
+        window.remove_window();
+    }
+
+    /// Open a new window with a [`DockArea`], the `center` is used to build the center item.
+    fn open_window(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        center: impl FnOnce(WeakEntity<DockArea>, &mut Window, &mut App) -> DockItem,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let owner = cx.entity().downgrade();
+        let id = self.id.clone();
+        let version = self.version;
+        let panel_style = self.panel_style;
+
+        let options = WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(bounds)),
+            kind: WindowKind::Normal,
+            ..Default::default()
+        };
+
+        let mut dock_area = None;
+        let handle = cx.open_window(options, |window, cx| {
+            let view = cx.new(|cx| {
+                let mut this = DockArea::new(id, version, window, cx).panel_style(panel_style);
+                this.owner = Some(owner.clone());
+                this
+            });
+            let item = center(view.downgrade(), window, cx);
+            view.update(cx, |view, cx| view.set_center(item, window, cx));
+
+            // Ask the panels before closing the window.
+            window.on_window_should_close(cx, {
+                let view = view.downgrade();
+                move |window, cx| {
+                    let Some(view) = view.upgrade() else {
+                        return true;
+                    };
+
+                    let can_close = view.update(cx, |this, cx| this.can_close(window, cx));
+                    window
+                        .spawn(cx, async move |cx| {
+                            if can_close.await {
+                                _ = cx.update(|window, _| window.remove_window());
+                            }
+                        })
+                        .detach();
+                    false
+                }
+            });
+
+            let root = cx.new(|cx| Root::new(view.clone().into(), window, cx));
+            // Forget the window when it is closed.
+            let entity_id = view.entity_id();
+            cx.observe_release(&root, move |_, cx| {
+                _ = owner.update(cx, |this, cx| {
+                    this.windows
+                        .retain(|w| w.dock_area.entity_id() != entity_id);
+                    cx.emit(DockEvent::LayoutChanged);
+                    cx.notify();
+                });
+            })
+            .detach();
+
+            dock_area = Some(view);
+            root
+        })?;
+
+        let Some(dock_area) = dock_area else {
+            return Ok(());
+        };
+
+        let _subscription = cx.subscribe(&dock_area, |_, _, event: &DockEvent, cx| {
+            if let DockEvent::LayoutChanged = event {
+                cx.emit(DockEvent::LayoutChanged);
+            }
+        });
+        self.windows.push(DockWindow {
+            handle,
+            dock_area,
+            _subscription,
+        });
+        cx.emit(DockEvent::LayoutChanged);
+        cx.notify();
+
+        Ok(())
+    }
+
+    /// Close all the floating windows, e.g.: when the window of this dock area is closed.
+    pub(super) fn close_windows(&mut self, cx: &mut App) {
+        for w in self.windows.drain(..) {
+            _ = w.handle.update(cx, |_, window, _| window.remove_window());
+        }
+    }
+
+    /// Dump the floating windows to [`DockWindowState`].
+    pub(super) fn dump_windows(&self, cx: &App) -> Vec<DockWindowState> {
+        self.windows
+            .iter()
+            .map(|w| {
+                let dock_area = w.dock_area.read(cx);
+                DockWindowState {
+                    bounds: dock_area.window_bounds,
+                    center: dock_area.items.view().dump(cx),
+                }
+            })
+            .collect()
+    }
+
+    /// Close the current floating windows, and open the windows in the `states`.
+    pub(super) fn load_windows(
+        &mut self,
+        states: Vec<DockWindowState>,
+        pool: &mut PanelPool,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        if self.is_floating() {
+            return Ok(());
+        }
+
+        self.close_windows(cx);
+        for state in states {
+            self.open_window(
+                state.bounds,
+                |dock_area, window, cx| state.center.build_item(dock_area, pool, window, cx),
+                cx,
+            )?;
+        }
+
+        Ok(())
+    }
+}
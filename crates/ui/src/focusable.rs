@@ -18,20 +18,21 @@ pub trait FocusableCycle {
     where
         Self: Sized,
     {
-        let focused_handle = window.focused(cx);
         let handles = self.cycle_focus_handles(window, cx);
-        let handles = if is_next {
+        let handles: Vec<FocusHandle> = if is_next {
             handles
         } else {
             handles.into_iter().rev().collect()
         };
+        let Some(fallback_handle) = handles.first().cloned() else {
+            return;
+        };
 
-        let fallback_handle = handles[0].clone();
+        // The focus may be in the descendants of the handle, e.g. a panel in the `TabPanel`.
         let target_focus_handle = handles
-            .into_iter()
-            .skip_while(|handle| Some(handle) != focused_handle.as_ref())
-            .skip(1)
-            .next()
+            .iter()
+            .position(|handle| handle.contains_focused(window, cx))
+            .and_then(|ix| handles.get(ix + 1).cloned())
             .unwrap_or(fallback_handle);
 
         target_focus_handle.focus(window);
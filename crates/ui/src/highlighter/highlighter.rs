@@ -1,14 +1,16 @@
 use super::HighlightTheme;
-use crate::highlighter::LanguageRegistry;
+use crate::{highlighter::LanguageRegistry, input::RopeExt as _};
 use anyhow::{anyhow, Context, Result};
 use gpui::{App, HighlightStyle, SharedString};
 use indexset::BTreeMap;
+use ropey::Rope;
 use std::{
     collections::HashMap,
     ops::{Bound, Range},
 };
 use tree_sitter::{
-    InputEdit, Node, Parser, Point, Query, QueryCursor, QueryMatch, StreamingIterator, Tree,
+    InputEdit, Node, Parser, Point, Query, QueryCursor, QueryMatch, StreamingIterator,
+    TextProvider, Tree,
 };
 
 /// A syntax highlighter that supports incremental parsing, multiline text,
@@ -20,7 +22,7 @@ pub struct SyntaxHighlighter {
     injection_queries: HashMap<SharedString, Query>,
     parser: Parser,
     old_tree: Option<Tree>,
-    text: SharedString,
+    text: Rope,
 
     locals_pattern_index: usize,
     highlights_pattern_index: usize,
@@ -40,6 +42,13 @@ pub struct SyntaxHighlighter {
     /// - The `key` is the `start` of the range.
     /// -The `value` is a tuple of the range (in the entire text) and the highlight name.
     cache: BTreeMap<usize, (Range<usize>, SharedString)>,
+    /// The byte ranges that have been queried into the `cache`, sorted and not overlapped.
+    ///
+    /// The highlights are queried lazily by [`Self::highlight`] for the visible ranges.
+    highlighted_ranges: Vec<Range<usize>>,
+    /// The byte ranges (in the current text) of the syntax changed by the last
+    /// [`Self::update_text`].
+    changed_ranges: Vec<Range<usize>>,
 }
 
 impl SyntaxHighlighter {
@@ -179,8 +188,10 @@ impl SyntaxHighlighter {
             injection_queries,
             parser,
             old_tree: None,
-            text: SharedString::new(""),
+            text: Rope::new(),
             cache: BTreeMap::new(),
+            highlighted_ranges: vec![],
+            changed_ranges: vec![],
             locals_pattern_index,
             highlights_pattern_index,
             non_local_variable_patterns,
@@ -194,11 +205,22 @@ impl SyntaxHighlighter {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.text.is_empty()
+        self.text.len_bytes() == 0
+    }
+
+    /// Returns the syntax tree of the last parsed text.
+    pub(crate) fn tree(&self) -> Option<&Tree> {
+        self.old_tree.as_ref()
+    }
+
+    /// Returns the byte ranges of the syntax changed by the last [`Self::update_text`], include the
+    /// edited range. Empty if the text has not been changed.
+    pub(crate) fn changed_ranges(&self) -> &[Range<usize>] {
+        &self.changed_ranges
     }
 
-    /// Highlight the given text, returning a map from byte ranges to highlight captures.
-    /// Uses incremental parsing, detects changed ranges, and caches unchanged results.
+    /// Update the highlighter by the `new_text` replaced the `selected_range` of the last text,
+    /// the `full_text` is the text after the replacement.
     pub fn update(
         &mut self,
         selected_range: &Range<usize>,
@@ -206,139 +228,185 @@ impl SyntaxHighlighter {
         new_text: &str,
         cx: &mut App,
     ) {
-        if &self.text == full_text {
+        self.update_text(Some((selected_range, new_text)), &Rope::from_str(full_text));
+        self.highlight(&(0..full_text.len()), cx);
+    }
+
+    /// Update the syntax tree for the new `text`.
+    ///
+    /// The `edit` is the `range` (in the last text) replaced by the `new_text`, with it the tree
+    /// will be reparsed incrementally and only the highlights of the changed ranges are
+    /// invalidated. If `None` (or the edit does not match the last text), the entire text will
+    /// be reparsed.
+    ///
+    /// NOTE: The highlights are not queried here, use [`Self::highlight`] for the range to display.
+    pub(crate) fn update_text(&mut self, edit: Option<(&Range<usize>, &str)>, text: &Rope) {
+        // The rope is cloned from the input state, so it is the same instance if there is no change.
+        if self.text.is_instance(text) {
+            self.changed_ranges.clear();
             return;
         }
 
-        // If insert a chart, this is 1.
-        // If backspace or delete, this is -1.
-        // If selected to delete, this is the length of the selected text.
-        let changed_len = new_text.len() as isize - selected_range.len() as isize;
-
-        let new_tree = match &self.old_tree {
-            None => self.parser.parse(full_text.as_ref(), None),
-            Some(old) => {
-                let edit = InputEdit {
-                    start_byte: selected_range.start,
-                    old_end_byte: selected_range.end,
-                    new_end_byte: (selected_range.end as isize + changed_len) as usize,
-                    start_position: Point::new(0, 0),
-                    old_end_position: Point::new(0, 0),
-                    new_end_position: Point::new(0, 0),
-                };
-                let mut old_cloned = old.clone();
-                old_cloned.edit(&edit);
-                // NOTE: 10K lines, about 4.5ms
-                self.parser.parse(full_text.as_ref(), Some(&old_cloned))
+        let old_len = self.text.len_bytes();
+        let edit = edit
+            .filter(|(range, new_text)| {
+                range.start <= range.end
+                    && range.end <= old_len
+                    && old_len - range.len() + new_text.len() == text.len_bytes()
+            })
+            .map(|(range, new_text)| input_edit(&self.text, text, range, new_text.len()));
+
+        // Read the text from the rope chunks to avoid copy the entire text.
+        let mut read_chunk = |offset: usize, _: Point| {
+            if offset >= text.len_bytes() {
+                return &[] as &[u8];
+            }
+
+            let (chunk, chunk_start, _, _) = text.chunk_at_byte(offset);
+            &chunk.as_bytes()[offset - chunk_start..]
+        };
+
+        let old_tree = match (&self.old_tree, &edit) {
+            (Some(old_tree), Some(edit)) => {
+                let mut old_tree = old_tree.clone();
+                old_tree.edit(edit);
+                Some(old_tree)
             }
+            _ => None,
         };
 
-        let Some(new_tree) = new_tree else {
+        // NOTE: 10K lines, about 4.5ms
+        let Some(new_tree) =
+            self.parser
+                .parse_with_options(&mut read_chunk, old_tree.as_ref(), None)
+        else {
             return;
         };
 
-        let mut changed_ranges = None;
-        if let Some(old_tree) = &self.old_tree {
-            changed_ranges = Some(new_tree.changed_ranges(old_tree));
+        self.text = text.clone();
+        match (old_tree, edit) {
+            (Some(old_tree), Some(edit)) => {
+                let mut changed_ranges = new_tree
+                    .changed_ranges(&old_tree)
+                    .map(|range| range.start_byte..range.end_byte)
+                    .collect::<Vec<_>>();
+                // The changed ranges only contains the syntax changes, the edited text may be
+                // in the same node, e.g.: typing in an identifier.
+                changed_ranges.push(edit.start_byte..edit.new_end_byte);
+                self.changed_ranges = changed_ranges.clone();
+                self.invalidate(&edit, changed_ranges);
+            }
+            _ => {
+                self.cache.clear();
+                self.highlighted_ranges.clear();
+                self.changed_ranges = vec![0..text.len_bytes()];
+            }
         }
-
-        // Update state
         self.old_tree = Some(new_tree);
-        self.text = full_text.clone();
-
-        // let measure = Measure::new("build_styles");
-        self.build_styles(changed_ranges, changed_len, cx);
-        // measure.end();
     }
 
-    /// NOTE: 10K lines, about 180ms
-    fn build_styles(
-        &mut self,
-        changed_ranges: Option<impl ExactSizeIterator<Item = tree_sitter::Range>>,
-        changed_len: isize,
-        cx: &mut App,
-    ) {
-        let Some(tree) = &self.old_tree else {
-            return;
-        };
-
-        let Some(query) = &self.query else {
-            return;
+    /// Move the highlights by the `edit`, and remove the highlights in the `changed_ranges`
+    /// (in the new text) to query them again.
+    fn invalidate(&mut self, edit: &InputEdit, changed_ranges: Vec<Range<usize>>) {
+        let map_offset = |offset: usize| {
+            if offset <= edit.start_byte {
+                offset
+            } else if offset >= edit.old_end_byte {
+                offset - edit.old_end_byte + edit.new_end_byte
+            } else {
+                edit.new_end_byte
+            }
         };
 
-        let source = self.text.as_bytes();
-        let mut query_cursor = QueryCursor::new();
-        let mut root_node = tree.root_node();
-
-        // Incremental parsing to only update changed ranges.
-        if let Some(changed_ranges) = changed_ranges {
-            let mut total_range = (None, None);
-            for change_range in changed_ranges {
-                // dbg!(change_range);
-                if total_range.0.is_none() {
-                    total_range.0 = Some(change_range.start_byte);
-                }
-                if total_range.1.is_none() {
-                    total_range.1 = Some(change_range.end_byte);
-                }
+        let mut changed_ranges = changed_ranges;
+        let mut moved = vec![];
+        self.cache.retain(|_, (range, name)| {
+            if range.end <= edit.start_byte {
+                return true;
             }
-            let total_range = total_range.0.unwrap_or(0)..total_range.1.unwrap_or(0);
 
-            // println!("------- total_range: {:?}", total_range);
-
-            if total_range.len() == 0 {
-                return;
+            if range.start >= edit.old_end_byte {
+                moved.push((map_offset(range.start)..map_offset(range.end), name.clone()));
+            } else {
+                changed_ranges.push(range.start.min(edit.start_byte)..map_offset(range.end));
             }
+            false
+        });
+        for (range, name) in moved {
+            self.cache.insert(range.start, (range, name));
+        }
 
-            if let Some(node) =
-                root_node.descendant_for_byte_range(total_range.start, total_range.end)
-            {
-                root_node = node;
+        // Expand to the entire lines, and the highlights cross the changed ranges also need to
+        // be removed, e.g.: a multi-line comment.
+        let text = &self.text;
+        let mut changed_ranges = merge_ranges(
+            changed_ranges
+                .into_iter()
+                .map(|range| {
+                    let start =
+                        text.line_start_offset(text.byte_to_line(text.clip_offset(range.start)));
+                    let end =
+                        text.line_start_offset(text.byte_to_line(text.clip_offset(range.end)) + 1);
+                    start..end
+                })
+                .collect(),
+        );
+        loop {
+            let mut removed = vec![];
+            self.cache.retain(|_, (range, _)| {
+                if is_intersecting(&changed_ranges, range) {
+                    removed.push(range.clone());
+                    return false;
+                }
+                true
+            });
+            if removed.is_empty() {
+                break;
             }
+            changed_ranges.extend(removed);
+            changed_ranges = merge_ranges(changed_ranges);
+        }
 
-            let byte_range = root_node.byte_range();
+        let highlighted_ranges = self
+            .highlighted_ranges
+            .iter()
+            .map(|range| map_offset(range.start)..map_offset(range.end))
+            .collect::<Vec<_>>();
+        self.highlighted_ranges = subtract_ranges(&highlighted_ranges, &changed_ranges);
+    }
 
-            // let measure = Measure::new("update cache to change range offset");
+    /// Query the highlights of the `range` (usually the visible range) into the cache, the parts
+    /// that already highlighted will be skipped.
+    pub(crate) fn highlight(&mut self, range: &Range<usize>, cx: &App) {
+        let len = self.text.len_bytes();
+        let range = range.start.min(len)..range.end.min(len);
+        if range.is_empty() {
+            return;
+        }
 
-            // FIXME: If we delete 1 char in a node, that node will not highlighted.
+        for range in subtract_ranges(&[range.clone()], &self.highlighted_ranges) {
+            self.query_highlights(range, cx);
+        }
 
-            // Remove the cache entries that are range is intersecting with the byte_range.
-            self.cache.retain(|_, (range, _)| {
-                if range.start < byte_range.end && range.end > byte_range.start {
-                    // Remove the item if it is intersecting with the byte_range.
-                    false
-                } else {
-                    // Keep the item if it is not intersecting with the byte_range.
-                    true
-                }
-            });
+        let mut highlighted_ranges = std::mem::take(&mut self.highlighted_ranges);
+        highlighted_ranges.push(range);
+        self.highlighted_ranges = merge_ranges(highlighted_ranges);
+    }
 
-            // Apply changed_len to reorder the cache to move the range offset
-            let mut old_cache: BTreeMap<usize, (Range<usize>, SharedString)> = BTreeMap::new();
-            std::mem::swap(&mut self.cache, &mut old_cache);
-
-            // NOTE: 10K lines, about 35ms
-            for (start, (old_range, highlight_name)) in old_cache.into_iter() {
-                if old_range.end >= byte_range.start {
-                    let new_range = Range {
-                        start: (old_range.start as isize + changed_len).max(0) as usize,
-                        end: (old_range.end as isize + changed_len).max(0) as usize,
-                    };
-
-                    if new_range.len() > 0 {
-                        self.cache
-                            .insert(new_range.start, (new_range, highlight_name));
-                    }
-                } else {
-                    self.cache.insert(start, (old_range, highlight_name));
-                }
-            }
-            // measure.end();
-        } else {
-            self.cache.clear();
-        }
+    /// Run the highlight query for the nodes intersecting with the `range`.
+    fn query_highlights(&mut self, range: Range<usize>, cx: &App) {
+        let Some(tree) = &self.old_tree else {
+            return;
+        };
+
+        let Some(query) = &self.query else {
+            return;
+        };
 
-        let mut matches = query_cursor.matches(&query, root_node, source);
+        let source = &self.text;
+        let mut query_cursor = QueryCursor::new();
+        query_cursor.set_byte_range(range);
+        let mut matches = query_cursor.matches(&query, tree.root_node(), RopeProvider(source));
 
         while let Some(m) = matches.next() {
             // Ref:
@@ -366,32 +434,22 @@ impl SyntaxHighlighter {
                 };
 
                 let node_range: Range<usize> = node.start_byte()..node.end_byte();
-                let highlight_name = SharedString::from(highlight_name.to_string());
-
-                // Merge near range and same highlight name
-                let last_item = self.cache.last_key_value().map(|kv| kv.1);
-                let last_range = last_item.map(|(range, _)| range).unwrap_or(&(0..0));
-                let last_highlight_name = last_item.map(|(_, name)| name.clone());
-
-                if last_range.end <= node_range.start
-                    && last_highlight_name.as_ref() == Some(&highlight_name)
-                {
-                    self.cache.insert(
-                        last_range.start,
-                        (last_range.start..node_range.end, highlight_name.clone()),
-                    );
-                } else if last_range == &node_range {
-                    // case:
-                    // last_range: 213..220, last_highlight_name: Some("property")
-                    // last_range: 213..220, last_highlight_name: Some("string")
-                    self.cache.insert(
-                        node_range.start,
-                        (node_range, last_highlight_name.unwrap_or(highlight_name)),
-                    );
-                } else {
-                    self.cache
-                        .insert(node_range.start, (node_range, highlight_name.clone()));
+
+                // case:
+                // last_range: 213..220, last_highlight_name: Some("property")
+                // last_range: 213..220, last_highlight_name: Some("string")
+                //
+                // The first matched pattern has the higher priority.
+                if let Some((range, _)) = self.cache.get(&node_range.start) {
+                    if range == &node_range {
+                        continue;
+                    }
                 }
+
+                self.cache.insert(
+                    node_range.start,
+                    (node_range, SharedString::from(highlight_name.to_string())),
+                );
             }
         }
 
@@ -406,7 +464,7 @@ impl SyntaxHighlighter {
         &self,
         injection_language: &str,
         node: Node,
-        source: &[u8],
+        source: &Rope,
         cx: &App,
     ) -> Vec<(Range<usize>, String)> {
         let start_offset = node.start_byte();
@@ -415,9 +473,7 @@ impl SyntaxHighlighter {
         let Some(query) = &self.injection_queries.get(injection_language) else {
             return cache;
         };
-        let Some(content) = source.get(node.start_byte()..node.end_byte()) else {
-            return cache;
-        };
+        let content = source.slice_text(node.start_byte()..node.end_byte());
         if content.is_empty() {
             return cache;
         };
@@ -428,12 +484,12 @@ impl SyntaxHighlighter {
         if parser.set_language(&config.language).is_err() {
             return cache;
         }
-        let Some(tree) = parser.parse(content, None) else {
+        let Some(tree) = parser.parse(&content, None) else {
             return cache;
         };
 
         let mut query_cursor = QueryCursor::new();
-        let mut matches = query_cursor.matches(query, tree.root_node(), content);
+        let mut matches = query_cursor.matches(query, tree.root_node(), content.as_bytes());
 
         let mut last_end = start_offset;
         while let Some(m) = matches.next() {
@@ -472,7 +528,7 @@ impl SyntaxHighlighter {
         parent_name: Option<SharedString>,
         query: &'a Query,
         query_match: &QueryMatch<'a, 'a>,
-        source: &'a [u8],
+        source: &'a Rope,
     ) -> (Option<SharedString>, Option<Node<'a>>, bool) {
         let content_capture_index = self.injection_content_capture_index;
         let language_capture_index = self.injection_language_capture_index;
@@ -483,12 +539,7 @@ impl SyntaxHighlighter {
         for capture in query_match.captures {
             let index = Some(capture.index);
             if index == language_capture_index {
-                language_name = capture
-                    .node
-                    .utf8_text(source)
-                    .ok()
-                    .map(ToString::to_string)
-                    .map(SharedString::from);
+                language_name = Some(source.slice_text(capture.node.byte_range()).into());
             } else if index == content_capture_index {
                 content_node = Some(capture.node);
             }
@@ -613,6 +664,95 @@ impl SyntaxHighlighter {
     }
 }
 
+/// A [`TextProvider`] to let tree-sitter query to read the node text from the [`Rope`] chunks.
+struct RopeProvider<'a>(&'a Rope);
+
+struct ChunkBytes<'a>(ropey::iter::Chunks<'a>);
+
+impl<'a> Iterator for ChunkBytes<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(str::as_bytes)
+    }
+}
+
+impl<'a> TextProvider<&'a [u8]> for RopeProvider<'a> {
+    type I = ChunkBytes<'a>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        ChunkBytes(self.0.byte_slice(node.byte_range()).chunks())
+    }
+}
+
+/// Returns the tree-sitter [`Point`] (the column is in bytes) of the `offset` in the `text`.
+fn point_for_offset(text: &Rope, offset: usize) -> Point {
+    let offset = offset.min(text.len_bytes());
+    let row = text.byte_to_line(offset);
+    Point::new(row, offset - text.line_to_byte(row))
+}
+
+/// Build the [`InputEdit`] for the `range` in the `old_text` is replaced by `new_len` bytes.
+fn input_edit(old_text: &Rope, new_text: &Rope, range: &Range<usize>, new_len: usize) -> InputEdit {
+    let new_end_byte = range.start + new_len;
+    InputEdit {
+        start_byte: range.start,
+        old_end_byte: range.end,
+        new_end_byte,
+        start_position: point_for_offset(old_text, range.start),
+        old_end_position: point_for_offset(old_text, range.end),
+        new_end_position: point_for_offset(new_text, new_end_byte),
+    }
+}
+
+/// Sort and merge the overlapped or adjacent ranges.
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if range.is_empty() {
+            continue;
+        }
+
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Remove the `other` ranges from the `ranges`, both of them must be sorted and not overlapped.
+fn subtract_ranges(ranges: &[Range<usize>], other: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut result = vec![];
+    for range in ranges {
+        let mut start = range.start;
+        let ix = other.partition_point(|other| other.end <= range.start);
+        for other in &other[ix..] {
+            if other.start >= range.end {
+                break;
+            }
+            if other.start > start {
+                result.push(start..other.start);
+            }
+            start = start.max(other.end);
+        }
+        if start < range.end {
+            result.push(start..range.end);
+        }
+    }
+    result
+}
+
+/// Returns true if the `range` is intersecting with any of the sorted `ranges`.
+fn is_intersecting(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+    let ix = ranges.partition_point(|other| other.end <= range.start);
+    ranges
+        .get(ix)
+        .map_or(false, |other| other.start < range.end.max(range.start + 1))
+}
+
 /// To merge intersection ranges
 ///
 /// ```
@@ -747,6 +887,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_input_edit() {
+        let old_text = Rope::from_str("fn main() {\n    let 你好 = 1;\n}");
+        let mut new_text = old_text.clone();
+        new_text.replace(20..27, "a\nb");
+
+        let edit = input_edit(&old_text, &new_text, &(20..27), 3);
+        assert_eq!(edit.start_byte, 20);
+        assert_eq!(edit.old_end_byte, 27);
+        assert_eq!(edit.new_end_byte, 23);
+        assert_eq!(edit.start_position, Point::new(1, 8));
+        assert_eq!(edit.old_end_position, Point::new(1, 15));
+        assert_eq!(edit.new_end_position, Point::new(2, 1));
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(
+            merge_ranges(vec![5..8, 0..2, 2..3, 7..10, 12..12]),
+            vec![0..3, 5..10]
+        );
+        assert_eq!(
+            subtract_ranges(&[0..10, 20..30], &[2..4, 8..22, 25..26]),
+            vec![0..2, 4..8, 22..25, 26..30]
+        );
+        assert!(is_intersecting(&[2..4, 8..10], &(3..5)));
+        assert!(!is_intersecting(&[2..4, 8..10], &(4..8)));
+        assert!(is_intersecting(&[2..4, 8..10], &(9..9)));
+    }
+
     #[test]
     fn test_unique_styles() {
         let red = color_style(gpui::red());
@@ -1,4 +1,7 @@
+use anyhow::{Context as _, Result};
 use gpui::SharedString;
+use std::path::Path;
+use tree_sitter::Query;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, enum_iterator::Sequence)]
 pub enum Language {
@@ -37,7 +40,62 @@ pub enum Language {
     Zig,
 }
 
+/// The bracket (or quote) pair of a language, for matching brackets, auto-closing and
+/// surrounding the selection in the code editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketPair {
+    pub start: char,
+    pub end: char,
+    /// Insert the `end` when typing the `start`, default is true.
+    pub close: bool,
+    /// Wrap the selected text with the pair when typing the `start`, default is true.
+    pub surround: bool,
+}
+
+impl BracketPair {
+    pub const fn new(start: char, end: char) -> Self {
+        Self {
+            start,
+            end,
+            close: true,
+            surround: true,
+        }
+    }
+
+    /// Set whether to insert the `end` when typing the `start`.
+    pub const fn close(mut self, close: bool) -> Self {
+        self.close = close;
+        self
+    }
+
+    /// Set whether to wrap the selected text with the pair when typing the `start`.
+    pub const fn surround(mut self, surround: bool) -> Self {
+        self.surround = surround;
+        self
+    }
+
+    /// Returns true if the `start` and `end` are the same, e.g.: the quotes.
+    pub fn is_quote(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The default bracket pairs: `()`, `[]`, `{}`, `""`, `''` and ` `` `.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new('(', ')'),
+            Self::new('[', ']'),
+            Self::new('{', '}'),
+            Self::new('"', '"'),
+            Self::new('\'', '\''),
+            Self::new('`', '`'),
+        ]
+    }
+}
+
+/// The config of a language, use [`LanguageConfig::new`] or [`LanguageConfig::from_dir`] to
+/// create it, and the builder methods to set the optional fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct LanguageConfig {
     pub name: SharedString,
     pub language: tree_sitter::Language,
@@ -45,6 +103,21 @@ pub struct LanguageConfig {
     pub highlights: SharedString,
     pub injections: SharedString,
     pub locals: SharedString,
+    /// The file extensions (without the `.`) or the file names to detect the language,
+    /// e.g.: `["rs"]`, `["Dockerfile"]`.
+    pub path_suffixes: Vec<SharedString>,
+    /// The regex to match the first line of the file to detect the language, e.g.: `^#!.*\blua`.
+    pub first_line_pattern: Option<SharedString>,
+    /// The bracket pairs of the language, default is [`BracketPair::defaults`].
+    pub brackets: Vec<BracketPair>,
+    /// The token to start a line comment, e.g.: `//`.
+    pub line_comment: Option<SharedString>,
+    /// The start and end tokens of a block comment, e.g.: `("/*", "*/")`.
+    pub block_comment: Option<(SharedString, SharedString)>,
+    /// The regex to match the text before the cursor to indent the next line, e.g.: `\{\s*$`.
+    pub increase_indent_pattern: Option<SharedString>,
+    /// The regex to match the line to outdent it, e.g.: `^\s*\}`.
+    pub decrease_indent_pattern: Option<SharedString>,
 }
 
 impl LanguageConfig {
@@ -63,8 +136,166 @@ impl LanguageConfig {
             highlights: SharedString::from(highlights.to_string()),
             injections: SharedString::from(injections.to_string()),
             locals: SharedString::from(locals.to_string()),
+            path_suffixes: vec![],
+            first_line_pattern: None,
+            brackets: BracketPair::defaults(),
+            line_comment: None,
+            block_comment: None,
+            increase_indent_pattern: None,
+            decrease_indent_pattern: None,
         }
     }
+
+    /// Create the language config with the queries in the `dir`.
+    ///
+    /// The queries are read from the `highlights.scm`, `injections.scm` and `locals.scm` files,
+    /// the missing files are treated as empty.
+    pub fn from_dir(
+        name: impl Into<SharedString>,
+        language: tree_sitter::Language,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let dir = dir.as_ref();
+        let read_query = |file_name: &str| -> Result<String> {
+            let path = dir.join(file_name);
+            if !path.exists() {
+                return Ok(String::new());
+            }
+
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            if !source.trim().is_empty() {
+                Query::new(&language, &source)
+                    .with_context(|| format!("invalid query {}", path.display()))?;
+            }
+            Ok(source)
+        };
+
+        let highlights = read_query("highlights.scm")?;
+        let injections = read_query("injections.scm")?;
+        let locals = read_query("locals.scm")?;
+
+        Ok(Self::new(
+            name,
+            language,
+            vec![],
+            &highlights,
+            &injections,
+            &locals,
+        ))
+    }
+
+    /// Load the language from a compiled tree-sitter grammar shared library (e.g.:
+    /// `libtree-sitter-lua.so`, `lua.dylib`, `lua.dll`), with the queries in the `queries_dir`.
+    ///
+    /// The library must export the `tree_sitter_{name}` function, the `-` in the name is
+    /// replaced by `_`. See also [`LanguageConfig::from_dir`].
+    ///
+    /// NOTE: The library is never unloaded, and it must be a trusted grammar built for a
+    /// compatible tree-sitter ABI version, because the code in it will be executed.
+    #[cfg(feature = "dynamic-grammar")]
+    pub fn load(
+        name: impl Into<SharedString>,
+        library_path: impl AsRef<Path>,
+        queries_dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let language = load_grammar(&name, library_path.as_ref())?;
+        Self::from_dir(name, language, queries_dir)
+    }
+
+    /// Set the languages that may be injected in this language, e.g.: `["css", "javascript"]`
+    /// in HTML.
+    pub fn injection_languages(
+        mut self,
+        languages: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> Self {
+        self.injection_languages = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the file extensions (without the `.`) or the file names to detect the language.
+    pub fn path_suffixes(
+        mut self,
+        suffixes: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> Self {
+        self.path_suffixes = suffixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the regex to match the first line (e.g.: the shebang) to detect the language.
+    pub fn first_line_pattern(mut self, pattern: impl Into<SharedString>) -> Self {
+        self.first_line_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Set the bracket pairs of the language.
+    pub fn brackets(mut self, brackets: impl IntoIterator<Item = BracketPair>) -> Self {
+        self.brackets = brackets.into_iter().collect();
+        self
+    }
+
+    /// Set the token to start a line comment, e.g.: `//`.
+    pub fn line_comment(mut self, token: impl Into<SharedString>) -> Self {
+        self.line_comment = Some(token.into());
+        self
+    }
+
+    /// Set the start and end tokens of a block comment, e.g.: `/*` and `*/`.
+    pub fn block_comment(
+        mut self,
+        start: impl Into<SharedString>,
+        end: impl Into<SharedString>,
+    ) -> Self {
+        self.block_comment = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Set the regexes for the auto indentation.
+    ///
+    /// - `increase`: Match the text before the cursor, to indent the next line when press Enter.
+    /// - `decrease`: Match the current line, to outdent it when typing, e.g.: `}`.
+    pub fn indent_patterns(
+        mut self,
+        increase: impl Into<SharedString>,
+        decrease: impl Into<SharedString>,
+    ) -> Self {
+        self.increase_indent_pattern = Some(increase.into());
+        self.decrease_indent_pattern = Some(decrease.into());
+        self
+    }
+}
+
+/// Load the `tree_sitter_{name}` language function from the grammar shared library.
+#[cfg(feature = "dynamic-grammar")]
+fn load_grammar(name: &str, path: &Path) -> Result<tree_sitter::Language> {
+    use anyhow::anyhow;
+    use tree_sitter::{LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+    let symbol = format!("tree_sitter_{}", name.replace('-', "_"));
+    let library = unsafe { libloading::Library::new(path) }
+        .with_context(|| format!("failed to load grammar {}", path.display()))?;
+    let language = unsafe {
+        let language_fn = library
+            .get::<unsafe extern "C" fn() -> *const ()>(symbol.as_bytes())
+            .with_context(|| format!("`{}` not found in {}", symbol, path.display()))?;
+        tree_sitter::Language::new(tree_sitter_language::LanguageFn::from_raw(*language_fn))
+    };
+
+    let version = language.abi_version();
+    if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+        return Err(anyhow!(
+            "incompatible grammar ABI version {} in {}, expected {}..={}",
+            version,
+            path.display(),
+            MIN_COMPATIBLE_LANGUAGE_VERSION,
+            LANGUAGE_VERSION
+        ));
+    }
+
+    // The language is referenced to the static data in the library, keep it loaded.
+    std::mem::forget(library);
+    Ok(language)
 }
 impl From<Language> for SharedString {
     fn from(language: Language) -> Self {
@@ -122,6 +353,7 @@ impl Language {
             "cmake" => Self::CMake,
             "cpp" | "c++" => Self::Cpp,
             "csharp" | "cs" => Self::CSharp,
+            // No scss grammar, highlight it by the css grammar that is mostly compatible.
             "css" | "scss" => Self::Css,
             "diff" => Self::Diff,
             "ejs" => Self::Ejs,
@@ -153,6 +385,155 @@ impl Language {
         }
     }
 
+    /// The file extensions or the file names of the language.
+    pub(super) fn path_suffixes(&self) -> &'static [&'static str] {
+        match self {
+            Self::Plain => &["txt"],
+            Self::Bash => &["sh", "bash", "zsh"],
+            Self::C => &["c", "h"],
+            Self::CMake => &["cmake", "CMakeLists.txt"],
+            Self::CSharp => &["cs"],
+            Self::Cpp => &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+            Self::Css => &["css"],
+            Self::Diff => &["diff", "patch"],
+            Self::Ejs => &["ejs"],
+            Self::Elixir => &["ex", "exs"],
+            Self::Erb => &["erb"],
+            Self::Go => &["go"],
+            Self::GraphQL => &["graphql", "gql"],
+            Self::Html => &["html", "htm"],
+            Self::Java => &["java"],
+            Self::JavaScript => &["js", "mjs", "cjs", "jsx"],
+            Self::JsDoc => &[],
+            Self::Json => &["json", "jsonc"],
+            Self::Make => &["mk", "Makefile", "makefile", "GNUmakefile"],
+            Self::Markdown => &["md", "markdown", "mdx"],
+            Self::MarkdownInline => &[],
+            Self::Proto => &["proto"],
+            Self::Python => &["py", "pyi"],
+            Self::Ruby => &["rb", "Gemfile", "Rakefile"],
+            Self::Rust => &["rs"],
+            Self::Scala => &["scala", "sc"],
+            Self::Sql => &["sql"],
+            Self::Swift => &["swift"],
+            Self::Toml => &["toml", "Cargo.lock"],
+            Self::Tsx => &["tsx"],
+            Self::TypeScript => &["ts", "mts", "cts"],
+            Self::Yaml => &["yaml", "yml"],
+            Self::Zig => &["zig"],
+        }
+    }
+
+    /// The regex to match the first line of the language, e.g.: the shebang.
+    pub(super) fn first_line_pattern(&self) -> Option<&'static str> {
+        match self {
+            Self::Bash => Some(r"^#!.*\b(bash|sh|zsh)\b"),
+            Self::JavaScript => Some(r"^#!.*\bnode\b"),
+            Self::Python => Some(r"^#!.*\bpython[\d.]*\b"),
+            Self::Ruby => Some(r"^#!.*\bruby\b"),
+            _ => None,
+        }
+    }
+
+    /// The bracket pairs of the language.
+    pub(super) fn brackets(&self) -> Vec<BracketPair> {
+        let mut brackets = BracketPair::defaults();
+        match self {
+            // The `'` is used for the lifetime in Rust, and the apostrophe in the prose.
+            Self::Rust | Self::Plain | Self::Markdown => {
+                for bracket in brackets.iter_mut().filter(|bracket| bracket.start == '\'') {
+                    bracket.close = false;
+                }
+            }
+            // Only to surround the selection, the tags are closed by the user.
+            Self::Html => brackets.push(BracketPair::new('<', '>').close(false)),
+            _ => {}
+        }
+        brackets
+    }
+
+    /// The line comment token of the language.
+    pub(super) fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Self::C
+            | Self::Cpp
+            | Self::CSharp
+            | Self::Go
+            | Self::Java
+            | Self::JavaScript
+            | Self::Proto
+            | Self::Rust
+            | Self::Scala
+            | Self::Swift
+            | Self::Tsx
+            | Self::TypeScript
+            | Self::Zig => Some("//"),
+            Self::Bash
+            | Self::CMake
+            | Self::Elixir
+            | Self::GraphQL
+            | Self::Make
+            | Self::Python
+            | Self::Ruby
+            | Self::Toml
+            | Self::Yaml => Some("#"),
+            Self::Sql => Some("--"),
+            _ => None,
+        }
+    }
+
+    /// The block comment tokens of the language.
+    pub(super) fn block_comment(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::C
+            | Self::Cpp
+            | Self::CSharp
+            | Self::Css
+            | Self::Go
+            | Self::Java
+            | Self::JavaScript
+            | Self::JsDoc
+            | Self::Proto
+            | Self::Rust
+            | Self::Scala
+            | Self::Sql
+            | Self::Swift
+            | Self::Tsx
+            | Self::TypeScript => Some(("/*", "*/")),
+            Self::Html | Self::Markdown => Some(("<!--", "-->")),
+            Self::Ejs | Self::Erb => Some(("<%#", "%>")),
+            _ => None,
+        }
+    }
+
+    /// The increase and decrease indent patterns of the language.
+    pub(super) fn indent_patterns(&self) -> Option<(&'static str, &'static str)> {
+        const BRACKETS: (&str, &str) = (r"[\{\[\(]\s*$", r"^\s*[\}\]\)]");
+
+        match self {
+            Self::Plain | Self::Diff | Self::Markdown | Self::MarkdownInline => None,
+            Self::Python => Some((
+                r"^[^#]*[:\{\[\(]\s*(#.*)?$",
+                r"^\s*((elif|else|except|finally)\b.*:|[\}\]\)])",
+            )),
+            Self::Yaml => Some((r"^[^#]*[:\{\[]\s*(#.*)?$", r"^\s*[\}\]]")),
+            Self::Ruby => Some((
+                r"^\s*(class|module|def|if|unless|case|while|until|for|begin|else|elsif|when|rescue|ensure)\b|(\bdo(\s*\|[^|]*\|)?|[\{\[\(])\s*$",
+                r"^\s*((end|else|elsif|when|rescue|ensure)\b|[\}\]\)])",
+            )),
+            Self::Elixir => Some((
+                r"(\b(do|else|after|rescue|catch)|->|[\{\[\(])\s*$",
+                r"^\s*((end|else|after|rescue|catch)\b|[\}\]\)])",
+            )),
+            Self::Bash => Some((
+                r"(\b(then|do|else)|[\{\(])\s*$",
+                r"^\s*((fi|done|else|elif|esac)\b|[\}\)])",
+            )),
+            Self::Html => Some((r"<([a-zA-Z][\w-]*)([^<>]*[^/<>])?>\s*$", r"^\s*</")),
+            _ => Some(BRACKETS),
+        }
+    }
+
     #[allow(unused)]
     pub(super) fn injection_languages(&self) -> Vec<SharedString> {
         match self {
@@ -356,7 +737,7 @@ impl Language {
 
         let language = tree_sitter::Language::new(language);
 
-        LanguageConfig::new(
+        let config = LanguageConfig::new(
             self.name(),
             language,
             self.injection_languages(),
@@ -364,6 +745,25 @@ impl Language {
             injection,
             locals,
         )
+        .path_suffixes(self.path_suffixes().iter().copied())
+        .brackets(self.brackets());
+
+        let config = match self.first_line_pattern() {
+            Some(pattern) => config.first_line_pattern(pattern),
+            None => config,
+        };
+        let config = match self.line_comment() {
+            Some(token) => config.line_comment(token),
+            None => config,
+        };
+        let config = match self.block_comment() {
+            Some((start, end)) => config.block_comment(start, end),
+            None => config,
+        };
+        match self.indent_patterns() {
+            Some((increase, decrease)) => config.indent_patterns(increase, decrease),
+            None => config,
+        }
     }
 }
 
@@ -371,6 +771,25 @@ impl Language {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_indent_patterns() {
+        for language in Language::all() {
+            let Some((increase, decrease)) = language.indent_patterns() else {
+                continue;
+            };
+            assert!(regex::Regex::new(increase).is_ok(), "{:?}", language);
+            assert!(regex::Regex::new(decrease).is_ok(), "{:?}", language);
+        }
+
+        let (increase, decrease) = Language::Python.indent_patterns().unwrap();
+        let increase = regex::Regex::new(increase).unwrap();
+        let decrease = regex::Regex::new(decrease).unwrap();
+        assert!(increase.is_match("def foo():  # comment"));
+        assert!(!increase.is_match("# foo:"));
+        assert!(decrease.is_match("    else:"));
+        assert!(!decrease.is_match("    elsewhere = 1"));
+    }
+
     #[test]
     fn test_language_name() {
         assert_eq!(Language::MarkdownInline.name(), "markdown_inline");
@@ -392,4 +811,35 @@ mod tests {
         assert_eq!(Language::Erb.name(), "erb");
         assert_eq!(Language::Ejs.name(), "ejs");
     }
+
+    #[test]
+    fn test_config_from_dir() {
+        // Unique for each process, to not conflict with the concurrent test runs.
+        let dir = std::env::temp_dir().join(format!(
+            "gpui-component-test-config-from-dir-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("highlights.scm"), "(string) @string").unwrap();
+        _ = std::fs::remove_file(dir.join("injections.scm"));
+
+        let language: tree_sitter::Language = tree_sitter_json::LANGUAGE.into();
+        let config = LanguageConfig::from_dir("my-json", language, &dir)
+            .unwrap()
+            .path_suffixes(["myjson"]);
+        assert_eq!(&*config.name, "my-json");
+        assert_eq!(&*config.highlights, "(string) @string");
+        assert_eq!(&*config.injections, "");
+        assert_eq!(config.path_suffixes, vec![SharedString::from("myjson")]);
+
+        // Invalid query
+        std::fs::write(
+            dir.join("injections.scm"),
+            "(unknown_node) @injection.content",
+        )
+        .unwrap();
+        let language: tree_sitter::Language = tree_sitter_json::LANGUAGE.into();
+        assert!(LanguageConfig::from_dir("my-json", language, &dir).is_err());
+        _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,9 +1,11 @@
 use gpui::{App, FontWeight, HighlightStyle, Hsla};
+use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     ops::Deref,
+    path::Path,
     sync::{Arc, LazyLock},
 };
 
@@ -364,6 +366,10 @@ pub struct HighlightThemeStyle {
     pub line_number: Option<Hsla>,
     #[serde(rename = "editor.active_line_number")]
     pub active_line_number: Option<Hsla>,
+    #[serde(rename = "editor.search_match.background")]
+    pub search_match: Option<Hsla>,
+    #[serde(rename = "editor.bracket_match.background")]
+    pub bracket_match: Option<Hsla>,
     #[serde(flatten)]
     pub status: StatusColors,
     #[serde(rename = "syntax")]
@@ -403,10 +409,21 @@ impl HighlightTheme {
     }
 }
 
+/// The compiled indent patterns of a language, see [`LanguageConfig::indent_patterns`].
+#[derive(Debug, Clone)]
+pub(crate) struct IndentRules {
+    pub(crate) increase: Regex,
+    pub(crate) decrease: Regex,
+}
+
 /// Registry for code highlighter languages.
 #[derive(Clone)]
 pub struct LanguageRegistry {
     languages: HashMap<String, LanguageConfig>,
+    /// The compiled `first_line_pattern` of the languages.
+    first_line_patterns: HashMap<String, Regex>,
+    /// The compiled indent patterns of the languages.
+    indent_rules: HashMap<String, IndentRules>,
     pub(crate) light_theme: Arc<HighlightTheme>,
     pub(crate) dark_theme: Arc<HighlightTheme>,
 }
@@ -425,15 +442,54 @@ impl LanguageRegistry {
     pub fn new() -> Self {
         Self {
             languages: HashMap::new(),
+            first_line_patterns: HashMap::new(),
+            indent_rules: HashMap::new(),
             light_theme: Arc::new(HighlightTheme::default_light()),
             dark_theme: Arc::new(HighlightTheme::default_dark()),
         }
     }
 
+    /// Register the language, the existing one with the same name will be replaced.
+    ///
+    /// This can be called at runtime, e.g.: to register a language loaded by
+    /// [`LanguageConfig::from_dir`].
     pub fn register(&mut self, lang: &str, config: &LanguageConfig) {
+        self.first_line_patterns.remove(lang);
+        if let Some(pattern) = &config.first_line_pattern {
+            match Regex::new(pattern) {
+                Ok(regex) => {
+                    self.first_line_patterns.insert(lang.to_string(), regex);
+                }
+                Err(err) => {
+                    tracing::warn!("invalid first line pattern of {:?}: {}", lang, err);
+                }
+            }
+        }
+
+        self.indent_rules.remove(lang);
+        if let (Some(increase), Some(decrease)) = (
+            &config.increase_indent_pattern,
+            &config.decrease_indent_pattern,
+        ) {
+            match (Regex::new(increase), Regex::new(decrease)) {
+                (Ok(increase), Ok(decrease)) => {
+                    self.indent_rules
+                        .insert(lang.to_string(), IndentRules { increase, decrease });
+                }
+                (Err(err), _) | (_, Err(err)) => {
+                    tracing::warn!("invalid indent pattern of {:?}: {}", lang, err);
+                }
+            }
+        }
+
         self.languages.insert(lang.to_string(), config.clone());
     }
 
+    /// Returns the compiled indent patterns of the language.
+    pub(crate) fn indent_rules(&self, name: &str) -> Option<&IndentRules> {
+        self.indent_rules.get(name)
+    }
+
     /// Set highlighter theme.
     pub fn set_theme(&mut self, light: &HighlightTheme, dark: &HighlightTheme) {
         self.light_theme = Arc::new(light.clone());
@@ -457,6 +513,59 @@ impl LanguageRegistry {
     pub fn language(&self, name: &str) -> Option<&LanguageConfig> {
         self.languages.get(name)
     }
+
+    /// Returns the language matched the file name or the extension of the `path`.
+    ///
+    /// If multiple languages are matched, the longest suffix wins, e.g.: `d.ts` over `ts`.
+    pub fn language_for_path(&self, path: &Path) -> Option<&LanguageConfig> {
+        let file_name = path.file_name()?.to_str()?;
+
+        self.languages
+            .iter()
+            .filter_map(|(name, config)| {
+                config
+                    .path_suffixes
+                    .iter()
+                    .filter(|suffix| {
+                        let suffix: &str = suffix;
+                        file_name == suffix
+                            || file_name
+                                .strip_suffix(suffix)
+                                .map_or(false, |prefix| prefix.ends_with('.'))
+                    })
+                    .map(|suffix| suffix.len())
+                    .max()
+                    .map(|len| (len, name, config))
+            })
+            .max_by(|(a_len, a_name, _), (b_len, b_name, _)| {
+                a_len.cmp(b_len).then_with(|| b_name.cmp(a_name))
+            })
+            .map(|(_, _, config)| config)
+    }
+
+    /// Returns the language matched the `first_line_pattern` by the first line of the `content`,
+    /// e.g.: `#!/usr/bin/env python3`.
+    pub fn language_for_first_line(&self, content: &str) -> Option<&LanguageConfig> {
+        let first_line = content.lines().next()?;
+
+        let mut matched = self
+            .first_line_patterns
+            .iter()
+            .filter(|(_, regex)| regex.is_match(first_line))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        matched.sort();
+        matched
+            .first()
+            .and_then(|name| self.languages.get(name.as_str()))
+    }
+
+    /// Detect the language of the file by the `path`, fallback to the first line of the
+    /// `content` if the path is not matched.
+    pub fn language_for_file(&self, path: &Path, content: &str) -> Option<&LanguageConfig> {
+        self.language_for_path(path)
+            .or_else(|| self.language_for_first_line(content))
+    }
 }
 
 #[cfg(test)]
@@ -475,4 +584,61 @@ mod tests {
         assert_eq!(syntax.style("keyword.repeat"), Some(rgb(0x0433ff).into()));
         assert_eq!(syntax.style("foo"), None);
     }
+
+    #[test]
+    fn test_language_for_file() {
+        use super::{LanguageConfig, LanguageRegistry};
+        use crate::highlighter::Language;
+        use std::path::Path;
+
+        let mut registry = LanguageRegistry::new();
+        for language in Language::all() {
+            registry.register(language.name(), &language.config());
+        }
+        registry.register(
+            "dockerfile",
+            &LanguageConfig::new(
+                "dockerfile",
+                tree_sitter_bash::LANGUAGE.into(),
+                vec![],
+                "",
+                "",
+                "",
+            )
+            .path_suffixes(["Dockerfile"]),
+        );
+        registry.register(
+            "dts",
+            &LanguageConfig::new(
+                "dts",
+                tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+                vec![],
+                "",
+                "",
+                "",
+            )
+            .path_suffixes(["d.ts"]),
+        );
+
+        let name = |path: &str, content: &str| {
+            registry
+                .language_for_file(Path::new(path), content)
+                .map(|config| config.name.to_string())
+        };
+
+        assert_eq!(name("src/main.rs", ""), Some("rust".into()));
+        assert_eq!(name("Cargo.toml", ""), Some("toml".into()));
+        assert_eq!(name("index.ts", ""), Some("typescript".into()));
+        assert_eq!(name("index.d.ts", ""), Some("dts".into()));
+        assert_eq!(name("docker/Dockerfile", ""), Some("dockerfile".into()));
+        assert_eq!(name("Makefile", ""), Some("make".into()));
+        assert_eq!(
+            name("script", "#!/usr/bin/env python3\nprint(1)"),
+            Some("python".into())
+        );
+        assert_eq!(name("script", "#!/bin/bash\necho 1"), Some("bash".into()));
+        assert_eq!(name("main.rs", "#!/bin/bash"), Some("rust".into()));
+        assert_eq!(name("script", "echo 1"), None);
+        assert_eq!(name("rs", ""), None);
+    }
 }
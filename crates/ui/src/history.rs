@@ -28,6 +28,7 @@ pub struct History<I: HistoryItem> {
     max_undo: usize,
     group_interval: Option<Duration>,
     unique: bool,
+    grouping: bool,
 }
 
 impl<I> History<I>
@@ -44,6 +45,7 @@ where
             max_undo: 1000,
             group_interval: None,
             unique: false,
+            grouping: false,
         }
     }
 
@@ -69,7 +71,7 @@ where
     /// Increment the version number if the last change was made more than `GROUP_INTERVAL` milliseconds ago.
     fn inc_version(&mut self) -> usize {
         let t = Instant::now();
-        if Some(self.last_changed_at.elapsed()) > self.group_interval {
+        if !self.grouping && Some(self.last_changed_at.elapsed()) > self.group_interval {
             self.version += 1;
         }
 
@@ -82,6 +84,20 @@ where
         self.version
     }
 
+    /// Start a group of changes, all changes pushed before [`Self::end_group`] will have the same
+    /// version, so they will be undone or redone in one step.
+    ///
+    /// For example, the multi-cursor editing in Input.
+    pub fn start_group(&mut self) {
+        self.inc_version();
+        self.grouping = true;
+    }
+
+    /// End the group of changes started by [`Self::start_group`].
+    pub fn end_group(&mut self) {
+        self.grouping = false;
+    }
+
     pub fn push(&mut self, item: I) {
         let version = self.inc_version();
 
@@ -236,6 +252,32 @@ mod tests {
         assert_eq!(history.undo().is_none(), true);
     }
 
+    #[test]
+    fn test_group_history() {
+        let mut history: History<TabIndex> = History::new().max_undo(100);
+        history.push(0.into());
+        history.start_group();
+        history.push(1.into());
+        history.push(2.into());
+        history.push(3.into());
+        history.end_group();
+        history.push(4.into());
+
+        let changes = history.undo().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].tab_index, 4);
+
+        let changes = history.undo().unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].tab_index, 3);
+        assert_eq!(changes[2].tab_index, 1);
+
+        let changes = history.redo().unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].tab_index, 1);
+        assert_eq!(changes[2].tab_index, 3);
+    }
+
     #[test]
     fn test_unique_history() {
         let mut history: History<TabIndex> = History::new().max_undo(100).unique();
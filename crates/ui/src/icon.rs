@@ -38,6 +38,7 @@ pub enum IconName {
     Eye,
     EyeOff,
     Frame,
+    Funnel,
     GalleryVerticalEnd,
     GitHub,
     Globe,
@@ -119,6 +120,7 @@ impl IconName {
             Self::Eye => "icons/eye.svg",
             Self::EyeOff => "icons/eye-off.svg",
             Self::Frame => "icons/frame.svg",
+            Self::Funnel => "icons/funnel.svg",
             Self::GalleryVerticalEnd => "icons/gallery-vertical-end.svg",
             Self::GitHub => "icons/github.svg",
             Self::Globe => "icons/globe.svg",
@@ -0,0 +1,575 @@
+//! The bracket matching, auto-closing pairs and surrounding the selection of the code editor.
+//!
+//! The bracket pairs are configured by the [`LanguageConfig::brackets`] of the language:
+//!
+//! - Highlight the matching bracket when the cursor is next to a bracket.
+//! - `MoveToMatchingBracket` action to jump to the matching (or enclosing) bracket.
+//! - Insert the closing bracket when typing the opening one, and overtype the closing bracket.
+//! - Delete the empty pair when backspace between them, e.g.: `(|)`.
+//! - Wrap the selected text when typing the opening bracket.
+//!
+//! [`LanguageConfig::brackets`]: crate::highlighter::LanguageConfig::brackets
+use std::ops::Range;
+
+use gpui::{App, Context, EntityInputHandler as _, Window};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+
+use super::{mode::InputMode, Cursor, InputState, MoveToMatchingBracket, RopeExt as _};
+use crate::highlighter::{BracketPair, LanguageRegistry};
+
+/// The max chars to scan for the matching bracket if there is no syntax tree.
+const MAX_SCAN_CHARS: usize = 100_000;
+
+/// The matching brackets of the cursor, cached to not find them on every render.
+pub(super) struct MatchingBracketsCache {
+    /// The text to find in, it is cloned from the input state, so the same instance means no
+    /// change.
+    text: Rope,
+    offset: usize,
+    matches: Option<(Range<usize>, Range<usize>)>,
+}
+
+/// The action of typing a char with the bracket pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AutoPair {
+    /// Wrap the selected text with the pair.
+    Surround(BracketPair),
+    /// Insert the pair, and put the cursor between them.
+    Close(BracketPair),
+    /// Move over the next char, it is same as the typed closing char.
+    Overtype,
+}
+
+impl InputState {
+    /// Returns the bracket pairs of the language, empty if not a code editor.
+    fn bracket_pairs(&self, cx: &App) -> Vec<BracketPair> {
+        let Some(language) = self.mode.language() else {
+            return vec![];
+        };
+
+        LanguageRegistry::global(cx)
+            .language(language)
+            .map(|config| config.brackets.clone())
+            .unwrap_or_else(BracketPair::defaults)
+    }
+
+    /// Handle the typed `new_text` with the bracket pairs, returns true if it is handled.
+    pub(super) fn handle_bracket_input(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let mut chars = new_text.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return false;
+        };
+
+        let pairs = self.bracket_pairs(cx);
+        let range: Range<usize> = self.selected_range.into();
+        let (new_text, selected_range) = match auto_pair(&self.text, &range, c, &pairs) {
+            Some(AutoPair::Surround(pair)) => {
+                let text = self.text.slice_text(range.clone());
+                let start = range.start + pair.start.len_utf8();
+                (
+                    format!("{}{}{}", pair.start, text, pair.end),
+                    start..start + text.len(),
+                )
+            }
+            Some(AutoPair::Close(pair)) => {
+                let offset = range.start + pair.start.len_utf8();
+                (format!("{}{}", pair.start, pair.end), offset..offset)
+            }
+            Some(AutoPair::Overtype) => {
+                self.move_to(Cursor::new(range.start + c.len_utf8()), window, cx);
+                return true;
+            }
+            None => return false,
+        };
+
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        self.selected_range = selected_range.into();
+        self.selection_reversed = false;
+        true
+    }
+
+    /// Returns the range of the empty bracket pair around the cursor to delete them together,
+    /// e.g.: `(|)`.
+    pub(super) fn empty_bracket_pair(&self, cx: &App) -> Option<Range<usize>> {
+        if !self.selected_range.is_empty() {
+            return None;
+        }
+
+        empty_pair_range(&self.text, self.cursor().offset, &self.bracket_pairs(cx))
+    }
+
+    /// Returns the ranges of the bracket next to the cursor and its matching bracket, to
+    /// highlight them.
+    pub(super) fn matching_brackets(&self, cx: &App) -> Option<(Range<usize>, Range<usize>)> {
+        if !self.selected_range.is_empty() || self.marked_range.is_some() {
+            return None;
+        }
+
+        let offset = self.cursor().offset;
+        if let Some(cache) = self.bracket_matches.borrow().as_ref() {
+            if cache.offset == offset && cache.text.is_instance(&self.text) {
+                return cache.matches.clone();
+            }
+        }
+
+        let pairs = self.bracket_pairs(cx);
+        let matches = self.find_matching_brackets(offset, &pairs, false);
+        self.bracket_matches.replace(Some(MatchingBracketsCache {
+            text: self.text.clone(),
+            offset,
+            matches: matches.clone(),
+        }));
+        matches
+    }
+
+    pub(super) fn move_to_matching_bracket(
+        &mut self,
+        _: &MoveToMatchingBracket,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let pairs = self.bracket_pairs(cx);
+        if pairs.is_empty() {
+            return;
+        }
+
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.cursor().offset;
+            if let Some((_, target)) = this.find_matching_brackets(offset, &pairs, true) {
+                this.move_to(Cursor::new(target.start), window, cx);
+            }
+        });
+    }
+
+    /// Find the matching brackets by the syntax tree if possible, and fallback to scan the text.
+    ///
+    /// If `enclosing` is true and the `offset` is not next to a bracket, returns the nearest
+    /// brackets around the `offset` (the closing one is the second).
+    fn find_matching_brackets(
+        &self,
+        offset: usize,
+        pairs: &[BracketPair],
+        enclosing: bool,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let InputMode::CodeEditor { highlighter, .. } = &self.mode else {
+            return None;
+        };
+
+        let highlighter = highlighter.borrow();
+        let tree = highlighter
+            .as_ref()
+            .and_then(|highlighter| highlighter.tree());
+        matching_brackets(&self.text, tree, offset, pairs).or_else(|| {
+            enclosing
+                .then(|| enclosing_brackets(&self.text, tree, offset, pairs))
+                .flatten()
+        })
+    }
+}
+
+fn char_at(text: &Rope, offset: usize) -> Option<char> {
+    let ix = text.byte_to_char(text.clip_offset(offset));
+    (ix < text.len_chars()).then(|| text.char(ix))
+}
+
+fn char_before(text: &Rope, offset: usize) -> Option<char> {
+    let ix = text.byte_to_char(text.clip_offset(offset));
+    ix.checked_sub(1).map(|ix| text.char(ix))
+}
+
+fn is_char(kind: &str, c: char) -> bool {
+    let mut chars = kind.chars();
+    chars.next() == Some(c) && chars.next().is_none()
+}
+
+/// Returns what to do when typing the `c` with the `selection`.
+fn auto_pair(
+    text: &Rope,
+    selection: &Range<usize>,
+    c: char,
+    pairs: &[BracketPair],
+) -> Option<AutoPair> {
+    if !selection.is_empty() {
+        return pairs
+            .iter()
+            .find(|pair| pair.surround && pair.start == c)
+            .map(|pair| AutoPair::Surround(*pair));
+    }
+
+    let offset = selection.start;
+    let next = char_at(text, offset);
+    if next == Some(c) && pairs.iter().any(|pair| pair.close && pair.end == c) {
+        return Some(AutoPair::Overtype);
+    }
+
+    let pair = pairs.iter().find(|pair| pair.close && pair.start == c)?;
+    // Only close the pair before the whitespace, punctuation or other closing brackets.
+    let close_before = match next {
+        None => true,
+        Some(next) => {
+            next.is_whitespace()
+                || matches!(next, ';' | ',' | ':')
+                || pairs
+                    .iter()
+                    .any(|pair| !pair.is_quote() && pair.end == next)
+        }
+    };
+    if !close_before {
+        return None;
+    }
+
+    // Avoid to close the quote after a word, e.g.: `don't`.
+    if pair.is_quote() {
+        if let Some(prev) = char_before(text, offset) {
+            if prev.is_alphanumeric() || prev == '_' || prev == c {
+                return None;
+            }
+        }
+    }
+
+    Some(AutoPair::Close(*pair))
+}
+
+/// Returns the range of the empty pair around the `offset`, e.g.: `(|)`.
+fn empty_pair_range(text: &Rope, offset: usize, pairs: &[BracketPair]) -> Option<Range<usize>> {
+    let prev = char_before(text, offset)?;
+    let next = char_at(text, offset)?;
+
+    pairs
+        .iter()
+        .any(|pair| pair.close && pair.start == prev && pair.end == next)
+        .then(|| offset - prev.len_utf8()..offset + next.len_utf8())
+}
+
+/// Returns the ranges of the bracket next to the `offset` and its matching bracket.
+///
+/// The bracket after the `offset` is preferred, the quotes are not matched.
+fn matching_brackets(
+    text: &Rope,
+    tree: Option<&Tree>,
+    offset: usize,
+    pairs: &[BracketPair],
+) -> Option<(Range<usize>, Range<usize>)> {
+    let after = char_at(text, offset).map(|c| (offset, c));
+    let before = char_before(text, offset).map(|c| (offset - c.len_utf8(), c));
+
+    for (start, c) in after.into_iter().chain(before) {
+        let Some((pair, open)) = pairs
+            .iter()
+            .filter(|pair| !pair.is_quote())
+            .find_map(|pair| match c {
+                c if c == pair.start => Some((pair, true)),
+                c if c == pair.end => Some((pair, false)),
+                _ => None,
+            })
+        else {
+            continue;
+        };
+
+        let range = start..start + c.len_utf8();
+        if let Some(other) = find_match(text, tree, &range, pair, open) {
+            return Some((range, other));
+        }
+    }
+
+    None
+}
+
+fn find_match(
+    text: &Rope,
+    tree: Option<&Tree>,
+    range: &Range<usize>,
+    pair: &BracketPair,
+    open: bool,
+) -> Option<Range<usize>> {
+    if let Some(tree) = tree {
+        let node = tree
+            .root_node()
+            .descendant_for_byte_range(range.start, range.end)?;
+        if !node.is_named() && node.byte_range() == *range {
+            let other = syntax_match(node, pair, open)?;
+            let expected = if open { pair.end } else { pair.start };
+            // The tree may be outdated, check the text again.
+            return (char_at(text, other.start) == Some(expected)).then_some(other);
+        }
+
+        // The bracket in the string or comment.
+        let kind = node.kind();
+        if kind.contains("string") || kind.contains("comment") {
+            return None;
+        }
+    }
+
+    text_match(text, range, pair, open)
+}
+
+/// Find the matching bracket in the siblings of the bracket `node`.
+fn syntax_match(node: Node, pair: &BracketPair, open: bool) -> Option<Range<usize>> {
+    let (this, other) = if open {
+        (pair.start, pair.end)
+    } else {
+        (pair.end, pair.start)
+    };
+
+    let mut depth = 0;
+    let mut sibling = node;
+    loop {
+        sibling = if open {
+            sibling.next_sibling()?
+        } else {
+            sibling.prev_sibling()?
+        };
+        if sibling.is_named() || sibling.is_missing() {
+            continue;
+        }
+
+        if is_char(sibling.kind(), this) {
+            depth += 1;
+        } else if is_char(sibling.kind(), other) {
+            if depth == 0 {
+                return Some(sibling.byte_range());
+            }
+            depth -= 1;
+        }
+    }
+}
+
+/// Find the matching bracket by scanning the text.
+fn text_match(
+    text: &Rope,
+    range: &Range<usize>,
+    pair: &BracketPair,
+    open: bool,
+) -> Option<Range<usize>> {
+    let mut depth = 0;
+    if open {
+        let mut offset = range.end;
+        for c in text.byte_slice(range.end..).chars().take(MAX_SCAN_CHARS) {
+            if c == pair.start {
+                depth += 1;
+            } else if c == pair.end {
+                if depth == 0 {
+                    return Some(offset..offset + c.len_utf8());
+                }
+                depth -= 1;
+            }
+            offset += c.len_utf8();
+        }
+    } else {
+        let slice = text.byte_slice(..range.start);
+        let mut offset = range.start;
+        for c in slice
+            .chars_at(slice.len_chars())
+            .reversed()
+            .take(MAX_SCAN_CHARS)
+        {
+            offset -= c.len_utf8();
+            if c == pair.end {
+                depth += 1;
+            } else if c == pair.start {
+                if depth == 0 {
+                    return Some(offset..offset + c.len_utf8());
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the nearest brackets around the `offset`, the opening bracket is the first.
+fn enclosing_brackets(
+    text: &Rope,
+    tree: Option<&Tree>,
+    offset: usize,
+    pairs: &[BracketPair],
+) -> Option<(Range<usize>, Range<usize>)> {
+    let pairs = pairs
+        .iter()
+        .filter(|pair| !pair.is_quote())
+        .collect::<Vec<_>>();
+
+    if let Some(tree) = tree {
+        let mut node = tree.root_node().descendant_for_byte_range(offset, offset);
+        while let Some(parent) = node {
+            let mut cursor = parent.walk();
+            let children = parent.children(&mut cursor).collect::<Vec<_>>();
+            // The last opening bracket before the offset, that the matching bracket is after it.
+            for child in children.iter().rev() {
+                if child.is_named() || child.end_byte() > offset {
+                    continue;
+                }
+                let Some(pair) = pairs.iter().find(|pair| is_char(child.kind(), pair.start)) else {
+                    continue;
+                };
+                if let Some(end) = syntax_match(*child, pair, true) {
+                    if end.start >= offset {
+                        return Some((child.byte_range(), end));
+                    }
+                }
+            }
+            node = parent.parent();
+        }
+        return None;
+    }
+
+    // Scan backward for the unmatched opening bracket.
+    let slice = text.byte_slice(..text.clip_offset(offset));
+    let mut depths = vec![0usize; pairs.len()];
+    let mut start = slice.len_bytes();
+    for c in slice
+        .chars_at(slice.len_chars())
+        .reversed()
+        .take(MAX_SCAN_CHARS)
+    {
+        start -= c.len_utf8();
+        for (ix, pair) in pairs.iter().enumerate() {
+            if c == pair.end {
+                depths[ix] += 1;
+            } else if c == pair.start {
+                if depths[ix] == 0 {
+                    let range = start..start + c.len_utf8();
+                    return text_match(text, &range, pair, true).map(|end| (range, end));
+                }
+                depths[ix] -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs() -> Vec<BracketPair> {
+        BracketPair::defaults()
+    }
+
+    #[test]
+    fn test_auto_pair() {
+        let text = Rope::from_str("foo() bar \"x\" don");
+        let pairs = pairs();
+        let paren = BracketPair::new('(', ')');
+        let quote = BracketPair::new('"', '"');
+
+        assert_eq!(
+            auto_pair(&text, &(0..3), '(', &pairs),
+            Some(AutoPair::Surround(paren))
+        );
+        assert_eq!(auto_pair(&text, &(0..3), 'a', &pairs), None);
+        // Before the word.
+        assert_eq!(auto_pair(&text, &(0..0), '(', &pairs), None);
+        // Before the closing bracket and the end.
+        assert_eq!(
+            auto_pair(&text, &(4..4), '[', &pairs),
+            Some(AutoPair::Close(BracketPair::new('[', ']')))
+        );
+        assert_eq!(
+            auto_pair(&text, &(4..4), ')', &pairs),
+            Some(AutoPair::Overtype)
+        );
+        assert_eq!(
+            auto_pair(&text, &(5..5), '"', &pairs),
+            Some(AutoPair::Close(quote))
+        );
+        assert_eq!(
+            auto_pair(&text, &(12..12), '"', &pairs),
+            Some(AutoPair::Overtype)
+        );
+        // After the word.
+        assert_eq!(auto_pair(&text, &(17..17), '\'', &pairs), None);
+        assert_eq!(
+            auto_pair(&text, &(17..17), '(', &pairs),
+            Some(AutoPair::Close(paren))
+        );
+
+        let pairs = vec![paren.close(false).surround(false)];
+        assert_eq!(auto_pair(&text, &(0..3), '(', &pairs), None);
+        assert_eq!(auto_pair(&text, &(17..17), '(', &pairs), None);
+        assert_eq!(auto_pair(&text, &(4..4), ')', &pairs), None);
+    }
+
+    #[test]
+    fn test_empty_pair_range() {
+        let text = Rope::from_str("a() [x] \"\"");
+        let pairs = pairs();
+        assert_eq!(empty_pair_range(&text, 2, &pairs), Some(1..3));
+        assert_eq!(empty_pair_range(&text, 1, &pairs), None);
+        assert_eq!(empty_pair_range(&text, 5, &pairs), None);
+        assert_eq!(empty_pair_range(&text, 9, &pairs), Some(8..10));
+    }
+
+    #[test]
+    fn test_matching_brackets_by_text() {
+        let text = Rope::from_str("fn foo(a: [u8; 2]) {\n    (1, (2))\n}");
+        let pairs = pairs();
+        assert_eq!(
+            matching_brackets(&text, None, 6, &pairs),
+            Some((6..7, 17..18))
+        );
+        assert_eq!(
+            matching_brackets(&text, None, 18, &pairs),
+            Some((17..18, 6..7))
+        );
+        assert_eq!(
+            matching_brackets(&text, None, 19, &pairs),
+            Some((19..20, 34..35))
+        );
+        assert_eq!(
+            matching_brackets(&text, None, 33, &pairs),
+            Some((32..33, 25..26))
+        );
+        assert_eq!(matching_brackets(&text, None, 2, &pairs), None);
+
+        assert_eq!(
+            enclosing_brackets(&text, None, 8, &pairs),
+            Some((6..7, 17..18))
+        );
+        assert_eq!(
+            enclosing_brackets(&text, None, 30, &pairs),
+            Some((29..30, 31..32))
+        );
+        assert_eq!(enclosing_brackets(&text, None, 2, &pairs), None);
+    }
+
+    #[test]
+    fn test_matching_brackets_by_syntax() {
+        let source = "fn foo() { let a = \"(\"; bar(a, \")\") }";
+        let text = Rope::from_str(source);
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let pairs = pairs();
+
+        assert_eq!(
+            matching_brackets(&text, Some(&tree), 9, &pairs),
+            Some((9..10, 36..37))
+        );
+        assert_eq!(
+            matching_brackets(&text, Some(&tree), 27, &pairs),
+            Some((27..28, 34..35))
+        );
+        // In the string.
+        assert_eq!(matching_brackets(&text, Some(&tree), 20, &pairs), None);
+
+        assert_eq!(
+            enclosing_brackets(&text, Some(&tree), 29, &pairs),
+            Some((27..28, 34..35))
+        );
+        assert_eq!(
+            enclosing_brackets(&text, Some(&tree), 12, &pairs),
+            Some((9..10, 36..37))
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Toggle the line and block comments of the code editor.
+//!
+//! The comment tokens are configured by [`LanguageConfig::line_comment`] and
+//! [`LanguageConfig::block_comment`], the line comment will fallback to wrap each line in the
+//! block comment if the language has no line comment, e.g.: HTML.
+//!
+//! [`LanguageConfig::line_comment`]: crate::highlighter::LanguageConfig::line_comment
+//! [`LanguageConfig::block_comment`]: crate::highlighter::LanguageConfig::block_comment
+use std::ops::Range;
+
+use gpui::{App, Context, EntityInputHandler as _, SharedString, Window};
+use ropey::Rope;
+
+use super::{InputState, RopeExt as _, ToggleBlockComment, ToggleLineComment};
+use crate::highlighter::LanguageRegistry;
+
+/// The replacement of the `range` (in the text) with the `text`.
+type Edit = (Range<usize>, String);
+
+impl InputState {
+    /// Returns the line comment and block comment tokens of the language.
+    fn comment_tokens(
+        &self,
+        cx: &App,
+    ) -> (Option<SharedString>, Option<(SharedString, SharedString)>) {
+        self.mode
+            .language()
+            .and_then(|language| LanguageRegistry::global(cx).language(language))
+            .map(|config| (config.line_comment.clone(), config.block_comment.clone()))
+            .unwrap_or_default()
+    }
+
+    pub(super) fn toggle_line_comment(
+        &mut self,
+        _: &ToggleLineComment,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (line_comment, block_comment) = self.comment_tokens(cx);
+        if line_comment.is_none() && block_comment.is_none() {
+            return;
+        }
+
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let rows = this.selected_rows();
+            let edits = match (&line_comment, &block_comment) {
+                (Some(token), _) => line_comment_edits(&this.text, rows, token),
+                (None, Some((start, end))) => {
+                    let range = this.text.line_start_offset(rows.start)
+                        ..this.text.line_end_offset(rows.end - 1);
+                    block_comment_edits(&this.text, range, start, end)
+                }
+                (None, None) => return,
+            };
+            this.apply_comment_edits(edits, window, cx);
+        });
+    }
+
+    pub(super) fn toggle_block_comment(
+        &mut self,
+        _: &ToggleBlockComment,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (_, Some((start, end))) = self.comment_tokens(cx) else {
+            return;
+        };
+
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let range: Range<usize> = if this.selected_range.is_empty() {
+                let row = this.text.byte_to_line(this.cursor().offset);
+                this.text.line_start_offset(row)..this.text.line_end_offset(row)
+            } else {
+                this.selected_range.into()
+            };
+            let edits = block_comment_edits(&this.text, range, &start, &end);
+            this.apply_comment_edits(edits, window, cx);
+        });
+    }
+
+    /// Returns the rows of the selection, the last row is excluded if the selection ends at
+    /// the start of it.
+    fn selected_rows(&self) -> Range<usize> {
+        let range: Range<usize> = self.selected_range.into();
+        let start_row = self.text.byte_to_line(range.start);
+        let mut end_row = self.text.byte_to_line(range.end);
+        if end_row > start_row && self.text.line_start_offset(end_row) == range.end {
+            end_row -= 1;
+        }
+        start_row..end_row + 1
+    }
+
+    /// Apply the sorted `edits` in one change, and keep the selection.
+    fn apply_comment_edits(
+        &mut self,
+        edits: Vec<Edit>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+            return;
+        };
+
+        let range = first.0.start..last.0.end;
+        let new_text = apply_edits(&self.text, &range, &edits);
+        let selected_range: Range<usize> = self.selected_range.into();
+        let selection_reversed = self.selection_reversed;
+
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        self.selected_range = (map_offset(selected_range.start, &edits)
+            ..map_offset(selected_range.end, &edits))
+            .into();
+        self.selection_reversed = selection_reversed;
+    }
+}
+
+/// Returns the edits to toggle the line comment of the `rows`.
+///
+/// The lines are uncommented if all the non-blank lines are commented, otherwise the `token`
+/// is inserted at the minimum indent of them.
+fn line_comment_edits(text: &Rope, rows: Range<usize>, token: &str) -> Vec<Edit> {
+    let lines = rows
+        .map(|row| (text.line_start_offset(row), text.line_text(row)))
+        .collect::<Vec<_>>();
+    let mut targets = lines
+        .iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        targets = lines.iter().collect();
+    }
+
+    let indent_len = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+    let commented = targets
+        .iter()
+        .all(|(_, line)| line.trim_start().starts_with(token));
+
+    if commented {
+        targets
+            .iter()
+            .map(|(start, line)| {
+                let offset = start + indent_len(line);
+                let mut len = token.len();
+                if line[indent_len(line) + len..].starts_with(' ') {
+                    len += 1;
+                }
+                (offset..offset + len, String::new())
+            })
+            .collect()
+    } else {
+        let min_indent = targets
+            .iter()
+            .map(|(_, line)| indent_len(line))
+            .min()
+            .unwrap_or(0);
+        targets
+            .iter()
+            .map(|(start, _)| {
+                let offset = start + min_indent;
+                (offset..offset, format!("{} ", token))
+            })
+            .collect()
+    }
+}
+
+/// Returns the edits to toggle the block comment around the `range`, the leading and trailing
+/// whitespace of the range is ignored.
+fn block_comment_edits(text: &Rope, range: Range<usize>, start: &str, end: &str) -> Vec<Edit> {
+    let content = text.slice_text(range.clone());
+    let trimmed = content.trim_start();
+    let content_start = range.start + content.len() - trimmed.len();
+    let trimmed = trimmed.trim_end();
+    let content_end = content_start + trimmed.len();
+
+    if trimmed.len() >= start.len() + end.len()
+        && trimmed.starts_with(start)
+        && trimmed.ends_with(end)
+    {
+        let inner = &trimmed[start.len()..trimmed.len() - end.len()];
+        let start_len = start.len() + inner.starts_with(' ') as usize;
+        let end_len = end.len() + (inner.len() > 1 && inner.ends_with(' ')) as usize;
+        return vec![
+            (content_start..content_start + start_len, String::new()),
+            (content_end - end_len..content_end, String::new()),
+        ];
+    }
+
+    vec![
+        (content_start..content_start, format!("{} ", start)),
+        (content_end..content_end, format!(" {}", end)),
+    ]
+}
+
+/// Returns the new text of the `range` after applying the sorted `edits` in it.
+fn apply_edits(text: &Rope, range: &Range<usize>, edits: &[Edit]) -> String {
+    let mut new_text = String::new();
+    let mut offset = range.start;
+    for (edit_range, edit_text) in edits {
+        new_text.push_str(&text.slice_text(offset..edit_range.start));
+        new_text.push_str(edit_text);
+        offset = edit_range.end;
+    }
+    new_text.push_str(&text.slice_text(offset..range.end));
+    new_text
+}
+
+/// Map the `offset` to the new text after applying the sorted `edits`.
+fn map_offset(offset: usize, edits: &[Edit]) -> usize {
+    let mut delta = 0isize;
+    for (range, text) in edits {
+        if offset >= range.end {
+            delta += text.len() as isize - range.len() as isize;
+        } else if offset > range.start {
+            delta -= (offset - range.start) as isize;
+        }
+    }
+    (offset as isize + delta) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::{apply_edits, block_comment_edits, line_comment_edits, map_offset};
+
+    fn toggle_lines(text: &str, rows: std::ops::Range<usize>) -> String {
+        let rope = Rope::from_str(text);
+        let edits = line_comment_edits(&rope, rows, "//");
+        apply_edits(&rope, &(0..rope.len_bytes()), &edits)
+    }
+
+    #[test]
+    fn test_line_comment_edits() {
+        let text = "fn foo() {\n    let a = 1;\n\n  bar();\n}";
+        let commented = toggle_lines(text, 1..4);
+        assert_eq!(commented, "fn foo() {\n  //   let a = 1;\n\n  // bar();\n}");
+        assert_eq!(toggle_lines(&commented, 1..4), text);
+
+        // Partially commented lines will be commented.
+        assert_eq!(toggle_lines("// a\nb", 0..2), "// // a\n// b");
+        // Without the space after the token.
+        assert_eq!(toggle_lines("  //a\n  //b", 0..2), "  a\n  b");
+        // Blank line.
+        assert_eq!(toggle_lines("  ", 0..1), "  // ");
+    }
+
+    #[test]
+    fn test_block_comment_edits() {
+        let rope = Rope::from_str("  let a = 1;  ");
+        let edits = block_comment_edits(&rope, 0..rope.len_bytes(), "/*", "*/");
+        let commented = apply_edits(&rope, &(0..rope.len_bytes()), &edits);
+        assert_eq!(commented, "  /* let a = 1; */  ");
+        assert_eq!(map_offset(2, &edits), 5);
+        assert_eq!(map_offset(12, &edits), 18);
+
+        let rope = Rope::from_str(&commented);
+        let edits = block_comment_edits(&rope, 0..rope.len_bytes(), "/*", "*/");
+        assert_eq!(
+            apply_edits(&rope, &(0..rope.len_bytes()), &edits),
+            "  let a = 1;  "
+        );
+        assert_eq!(map_offset(4, &edits), 2);
+        assert_eq!(map_offset(20, &edits), 14);
+
+        let rope = Rope::from_str("<!--div-->");
+        let edits = block_comment_edits(&rope, 0..rope.len_bytes(), "<!--", "-->");
+        assert_eq!(apply_edits(&rope, &(0..rope.len_bytes()), &edits), "div");
+    }
+}
@@ -0,0 +1,259 @@
+use gpui::{
+    anchored, deferred, div, point, prelude::FluentBuilder as _, px, rems, App, AppContext as _,
+    Context, Empty, Entity, InteractiveElement as _, IntoElement, ParentElement as _, Pixels,
+    Point, Render, StatefulInteractiveElement as _, Styled as _, WeakEntity, Window,
+};
+
+use super::{match_score, CompletionItem};
+use crate::{
+    actions::{SelectNext, SelectPrev},
+    h_flex,
+    input::InputState,
+    list::{List, ListDelegate, ListItem},
+    text::TextView,
+    v_flex, ActiveTheme as _, StyledExt as _,
+};
+
+/// The popup list of the completion items, show under the word to complete.
+pub(crate) struct CompletionMenu {
+    state: WeakEntity<InputState>,
+    /// The start offset of the word to complete.
+    pub(super) start: usize,
+    /// The offset of the cursor when the items were filtered last time.
+    pub(super) offset: usize,
+    list: Entity<List<CompletionListDelegate>>,
+}
+
+impl CompletionMenu {
+    pub(super) fn new(
+        start: usize,
+        items: Vec<CompletionItem>,
+        state: WeakEntity<InputState>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let delegate = CompletionListDelegate {
+            state: state.clone(),
+            items,
+            matches: vec![],
+            selected_index: None,
+        };
+        let list = cx.new(|cx| List::new(delegate, window, cx).no_query().max_h(rems(15.)));
+
+        cx.new(|_| Self {
+            state,
+            start,
+            offset: start,
+            list,
+        })
+    }
+
+    /// Filter the items by the `query`, returns false if nothing matched.
+    pub(super) fn set_query(
+        &mut self,
+        offset: usize,
+        query: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.offset = offset;
+        self.list.update(cx, |list, cx| {
+            list.delegate_mut().filter(query);
+            let matched = list.delegate().items_count(cx) > 0;
+            list.set_selected_index(matched.then_some(0), window, cx);
+            list.scroll_to_item(0, window, cx);
+            matched
+        })
+    }
+
+    /// Select the previous (if `backward`) or next item.
+    pub(super) fn select(&mut self, backward: bool, window: &mut Window, cx: &mut Context<Self>) {
+        self.list.update(cx, |list, cx| {
+            if backward {
+                list.on_action_select_prev(&SelectPrev, window, cx);
+            } else {
+                list.on_action_select_next(&SelectNext, window, cx);
+            }
+        });
+    }
+
+    pub(super) fn selected_item(&self, cx: &App) -> Option<CompletionItem> {
+        self.list.read(cx).delegate().selected_item().cloned()
+    }
+
+    /// The position (in window) under the start of the word.
+    fn origin(&self, cx: &App) -> Option<Point<Pixels>> {
+        let state = self.state.upgrade()?;
+        let state = state.read(cx);
+        let bounds = state.last_bounds?;
+        let line_height = state.last_layout.as_ref()?.line_height;
+        let (_, _, pos) = state.line_and_position_for_offset(self.start);
+
+        pos.map(|pos| bounds.origin + pos + point(state.line_number_width, line_height))
+    }
+}
+
+impl Render for CompletionMenu {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(origin) = self.origin(cx) else {
+            return Empty.into_any_element();
+        };
+
+        let item = self.selected_item(cx);
+        let has_documentation = item.as_ref().map_or(false, |item| {
+            item.documentation.is_some() || item.detail.is_some()
+        });
+
+        deferred(
+            anchored()
+                .position(origin)
+                .snap_to_window_with_margin(px(8.))
+                .child(
+                    h_flex()
+                        .id("completion-menu")
+                        .occlude()
+                        .mt_1()
+                        .items_start()
+                        .gap_1()
+                        .text_sm()
+                        .child(
+                            v_flex()
+                                .w(px(300.))
+                                .py_1()
+                                .popover_style(cx)
+                                .child(self.list.clone()),
+                        )
+                        .when_some(item.filter(|_| has_documentation), |this, item| {
+                            this.child(
+                                v_flex()
+                                    .id("documentation")
+                                    .w(px(320.))
+                                    .max_h(rems(15.))
+                                    .overflow_y_scroll()
+                                    .px_2()
+                                    .py_1()
+                                    .gap_1()
+                                    .popover_style(cx)
+                                    .when_some(item.detail, |this, detail| {
+                                        this.child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(cx.theme().muted_foreground)
+                                                .child(detail),
+                                        )
+                                    })
+                                    .when_some(item.documentation, |this, documentation| {
+                                        this.child(TextView::markdown(
+                                            "documentation",
+                                            documentation,
+                                        ))
+                                    }),
+                            )
+                        }),
+                ),
+        )
+        .with_priority(1)
+        .into_any_element()
+    }
+}
+
+struct CompletionListDelegate {
+    state: WeakEntity<InputState>,
+    items: Vec<CompletionItem>,
+    /// The indices of the matched items, sorted by the match score.
+    matches: Vec<usize>,
+    selected_index: Option<usize>,
+}
+
+impl CompletionListDelegate {
+    fn filter(&mut self, query: &str) {
+        let mut matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, item)| {
+                let text = item.filter_text.as_ref().unwrap_or(&item.label);
+                match_score(text, query).map(|score| (score, ix))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, _)| *score);
+        self.matches = matches.into_iter().map(|(_, ix)| ix).collect();
+    }
+
+    fn selected_item(&self) -> Option<&CompletionItem> {
+        let ix = *self.matches.get(self.selected_index?)?;
+        self.items.get(ix)
+    }
+}
+
+impl ListDelegate for CompletionListDelegate {
+    type Item = ListItem;
+
+    fn items_count(&self, _: &App) -> usize {
+        self.matches.len()
+    }
+
+    fn render_item(
+        &self,
+        ix: usize,
+        _: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) -> Option<Self::Item> {
+        let item = self.items.get(*self.matches.get(ix)?)?;
+        let suffix = item
+            .detail
+            .clone()
+            .or(item.kind.map(|kind| kind.label().into()));
+
+        Some(
+            ListItem::new(("completion-item", ix))
+                .py_0p5()
+                .text_sm()
+                .selected(self.selected_index == Some(ix))
+                .child(
+                    div()
+                        .flex_1()
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .child(item.label.clone()),
+                )
+                .when_some(suffix, |this, suffix| {
+                    this.child(
+                        div()
+                            .max_w(px(150.))
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(suffix),
+                    )
+                }),
+        )
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: Option<usize>,
+        _: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
+        let Some(item) = self.selected_item().cloned() else {
+            return;
+        };
+
+        // Keep the focus in the input, the list will not be focused by the click.
+        window.prevent_default();
+        _ = self.state.update(cx, |state, cx| {
+            state.accept_completion(item, window, cx);
+        });
+    }
+
+    fn can_load_more(&self, _: &App) -> bool {
+        false
+    }
+}
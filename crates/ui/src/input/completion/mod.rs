@@ -0,0 +1,592 @@
+//! The completion popup of the [`InputState`].
+//!
+//! Set a [`CompletionProvider`] by [`InputState::completion_provider`], then the completions will
+//! be requested when typing (see [`CompletionProvider::is_completion_trigger`]) or by the
+//! `ShowCompletions` action (`ctrl-space`), and shown in a popup list under the cursor:
+//!
+//! - Filter the items by the typed word.
+//! - `up` / `down` to select, `enter` / `tab` to accept, `escape` to hide.
+//! - The documentation of the selected item will be shown next to the list.
+//! - Insert the snippet with the tabstops, use `tab` / `shift-tab` to move between them.
+mod menu;
+mod snippet;
+
+use std::{ops::Range, rc::Rc};
+
+use anyhow::Result;
+use gpui::{App, Context, Entity, EntityInputHandler as _, SharedString, Task, Window};
+use ropey::Rope;
+
+use super::{
+    multi_cursor::CursorSelection, ConfirmCompletion, InputState, NextTabstop, PreviousTabstop,
+    RopeExt as _, SelectNextCompletion, SelectPreviousCompletion, ShowCompletions,
+};
+pub(super) use menu::CompletionMenu;
+use snippet::{Snippet, SnippetState};
+
+/// The kind of the [`CompletionItem`], same as the `CompletionItemKind` of the LSP.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompletionKind {
+    Text,
+    Method,
+    Function,
+    Constructor,
+    Field,
+    Variable,
+    Class,
+    Interface,
+    Module,
+    Property,
+    Unit,
+    Value,
+    Enum,
+    Keyword,
+    Snippet,
+    Color,
+    File,
+    Reference,
+    Folder,
+    EnumMember,
+    Constant,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl CompletionKind {
+    /// The label to show in the completion list.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Method => "method",
+            Self::Function => "function",
+            Self::Constructor => "constructor",
+            Self::Field => "field",
+            Self::Variable => "variable",
+            Self::Class => "class",
+            Self::Interface => "interface",
+            Self::Module => "module",
+            Self::Property => "property",
+            Self::Unit => "unit",
+            Self::Value => "value",
+            Self::Enum => "enum",
+            Self::Keyword => "keyword",
+            Self::Snippet => "snippet",
+            Self::Color => "color",
+            Self::File => "file",
+            Self::Reference => "reference",
+            Self::Folder => "folder",
+            Self::EnumMember => "enum member",
+            Self::Constant => "constant",
+            Self::Struct => "struct",
+            Self::Event => "event",
+            Self::Operator => "operator",
+            Self::TypeParameter => "type parameter",
+        }
+    }
+}
+
+/// An item of the completion list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompletionItem {
+    /// The label to show in the list, also the text to insert if `insert_text` is None.
+    pub label: SharedString,
+    pub kind: Option<CompletionKind>,
+    /// The short detail to show next to the label, e.g.: the type signature.
+    pub detail: Option<SharedString>,
+    /// The documentation in Markdown.
+    pub documentation: Option<SharedString>,
+    /// The text to insert, default is the `label`.
+    pub insert_text: Option<SharedString>,
+    /// Whether the `insert_text` is a snippet, e.g.: `fn ${1:name}($2) {\n\t$0\n}`.
+    pub snippet: bool,
+    /// The text to filter the items by the typed word, default is the `label`.
+    pub filter_text: Option<SharedString>,
+    /// The byte range of the text to replace, default is the word before the cursor.
+    pub replace_range: Option<Range<usize>>,
+}
+
+impl CompletionItem {
+    /// Create a completion item with the `label`.
+    pub fn new(label: impl Into<SharedString>) -> Self {
+        Self {
+            label: label.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the kind of the item.
+    pub fn kind(mut self, kind: CompletionKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Set the detail to show next to the label.
+    pub fn detail(mut self, detail: impl Into<SharedString>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the documentation (Markdown) to show next to the list when the item is selected.
+    pub fn documentation(mut self, documentation: impl Into<SharedString>) -> Self {
+        self.documentation = Some(documentation.into());
+        self
+    }
+
+    /// Set the text to insert.
+    pub fn insert_text(mut self, text: impl Into<SharedString>) -> Self {
+        self.insert_text = Some(text.into());
+        self.snippet = false;
+        self
+    }
+
+    /// Set the snippet to insert, see [`CompletionItem::snippet`].
+    pub fn insert_snippet(mut self, snippet: impl Into<SharedString>) -> Self {
+        self.insert_text = Some(snippet.into());
+        self.snippet = true;
+        self
+    }
+
+    /// Set the text to filter the items.
+    pub fn filter_text(mut self, text: impl Into<SharedString>) -> Self {
+        self.filter_text = Some(text.into());
+        self
+    }
+
+    /// Set the byte range of the text to replace.
+    pub fn replace_range(mut self, range: Range<usize>) -> Self {
+        self.replace_range = Some(range);
+        self
+    }
+}
+
+/// How the completions are triggered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionTrigger {
+    /// Triggered by the `ShowCompletions` action.
+    Invoked,
+    /// Triggered by typing the text.
+    Typing(SharedString),
+}
+
+/// Provide the completion items to the [`InputState`].
+#[allow(unused_variables)]
+pub trait CompletionProvider {
+    /// Fetch the completion items at the `offset` of the `text`.
+    ///
+    /// The items will be filtered by the word before the cursor in the list, so it is ok
+    /// to return all the items.
+    fn completions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        trigger: CompletionTrigger,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Vec<CompletionItem>>>;
+
+    /// Returns true to trigger the completions after the `new_text` is typed at the `offset`.
+    ///
+    /// Default is true when a word character is typed.
+    fn is_completion_trigger(
+        &self,
+        offset: usize,
+        new_text: &str,
+        cx: &mut Context<InputState>,
+    ) -> bool {
+        let mut chars = new_text.chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if is_word_char(c))
+    }
+}
+
+/// The completion state of the [`InputState`].
+pub(super) struct CompletionState {
+    provider: Option<Rc<dyn CompletionProvider>>,
+    pub(super) menu: Option<Entity<CompletionMenu>>,
+    /// The active snippet to move between the tabstops.
+    pub(super) snippet: Option<SnippetState>,
+    /// Whether inserting the text by code (e.g.: the accepted item), the completions should not
+    /// be triggered by it.
+    pub(super) inserting: bool,
+    task: Task<()>,
+}
+
+impl CompletionState {
+    pub(super) fn new() -> Self {
+        Self {
+            provider: None,
+            menu: None,
+            snippet: None,
+            inserting: false,
+            task: Task::ready(()),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the start offset of the word before the `offset`.
+fn word_start(text: &Rope, offset: usize) -> usize {
+    let offset = text.clip_offset(offset);
+    let row = text.byte_to_line(offset);
+    let line_start = text.line_start_offset(row);
+    let word_len = text
+        .slice_text(line_start..offset)
+        .chars()
+        .rev()
+        .take_while(|c| is_word_char(*c))
+        .map(|c| c.len_utf8())
+        .sum::<usize>();
+    offset - word_len
+}
+
+/// Returns the score (lower is better) of the `text` matched by the `query`, None if not matched.
+///
+/// - Starts with the query.
+/// - Starts with the query, ignore case.
+/// - Contains the query, ignore case.
+/// - Contains all the chars of the query in order, ignore case.
+fn match_score(text: &str, query: &str) -> Option<usize> {
+    if text.starts_with(query) {
+        return Some(0);
+    }
+
+    let text = text.to_lowercase();
+    let query = query.to_lowercase();
+    if text.starts_with(&query) {
+        return Some(1);
+    }
+    if let Some(ix) = text.find(&query) {
+        return Some(2 + ix);
+    }
+
+    let mut chars = text.chars();
+    let mut gaps = 0;
+    for q in query.chars() {
+        loop {
+            let c = chars.next()?;
+            if c == q {
+                break;
+            }
+            gaps += 1;
+        }
+    }
+    Some(text.len() + gaps)
+}
+
+impl InputState {
+    /// Set the completion provider to show the completions.
+    pub fn completion_provider(mut self, provider: impl CompletionProvider + 'static) -> Self {
+        self.completion.provider = Some(Rc::new(provider));
+        self
+    }
+
+    /// Set or remove the completion provider.
+    pub fn set_completion_provider(
+        &mut self,
+        provider: Option<Rc<dyn CompletionProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.completion.provider = provider;
+        self.hide_completions(cx);
+    }
+
+    pub(super) fn show_completions(
+        &mut self,
+        _: &ShowCompletions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.request_completions(CompletionTrigger::Invoked, window, cx);
+    }
+
+    fn request_completions(
+        &mut self,
+        trigger: CompletionTrigger,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.completion.provider.clone() else {
+            return;
+        };
+
+        let offset = self.cursor().offset;
+        let start = word_start(&self.text, offset);
+        let task = provider.completions(&self.text, offset, trigger, window, cx);
+        self.completion.task = cx.spawn_in(window, async move |this, cx| {
+            let items = match task.await {
+                Ok(items) => items,
+                Err(err) => {
+                    tracing::error!("failed to fetch completions: {:?}", err);
+                    return;
+                }
+            };
+
+            _ = this.update_in(cx, |this, window, cx| {
+                if items.is_empty() {
+                    this.hide_completions(cx);
+                    return;
+                }
+
+                let state = cx.entity().downgrade();
+                this.completion.menu = Some(CompletionMenu::new(start, items, state, window, cx));
+                this.update_completion_menu(window, cx);
+                cx.notify();
+            });
+        });
+    }
+
+    /// Filter the completion items by the word typed since the menu opened, the menu will be
+    /// hidden if the cursor moved out of the word or nothing matched.
+    fn update_completion_menu(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(menu) = self.completion.menu.clone() else {
+            return;
+        };
+
+        let offset = self.cursor().offset;
+        let start = menu.read(cx).start;
+        let query = (self.selected_range.is_empty() && offset >= start)
+            .then(|| self.text.slice_text(start..offset))
+            .filter(|query| query.chars().all(is_word_char));
+        let matched = query.map_or(false, |query| {
+            menu.update(cx, |menu, cx| menu.set_query(offset, &query, window, cx))
+        });
+        if !matched {
+            self.hide_completions(cx);
+        }
+    }
+
+    /// Update the completion menu, or trigger the completions after the `new_text` is typed.
+    pub(super) fn completion_did_change(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.completion.inserting {
+            return;
+        }
+        let Some(provider) = self.completion.provider.clone() else {
+            return;
+        };
+
+        if self.completion.menu.is_some() {
+            self.update_completion_menu(window, cx);
+            if self.completion.menu.is_some() {
+                return;
+            }
+        }
+
+        let offset = self.cursor().offset;
+        if provider.is_completion_trigger(offset, new_text, cx) {
+            self.request_completions(
+                CompletionTrigger::Typing(new_text.to_string().into()),
+                window,
+                cx,
+            );
+        }
+    }
+
+    /// Hide the completion menu, returns true if it was showing.
+    pub(super) fn hide_completions(&mut self, cx: &mut Context<Self>) -> bool {
+        self.completion.task = Task::ready(());
+        if self.completion.menu.take().is_some() {
+            cx.notify();
+            return true;
+        }
+        false
+    }
+
+    /// Hide the completion menu and finish the snippet if the cursor has been moved out of them,
+    /// e.g.: by the mouse or the arrow keys.
+    pub(super) fn update_completion_if_need(&mut self, cx: &App) {
+        let range: Range<usize> = self.selected_range.into();
+        if let Some(menu) = self.completion.menu.as_ref() {
+            if !range.is_empty() || menu.read(cx).offset != range.end {
+                self.completion.menu = None;
+                self.completion.task = Task::ready(());
+            }
+        }
+
+        if let Some(snippet) = self.completion.snippet.as_ref() {
+            if !snippet.contains(range.start) || !snippet.contains(range.end) {
+                self.completion.snippet = None;
+            }
+        }
+    }
+
+    pub(super) fn select_previous_completion(
+        &mut self,
+        _: &SelectPreviousCompletion,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.completion.menu.clone() {
+            Some(menu) => menu.update(cx, |menu, cx| menu.select(true, window, cx)),
+            None => cx.propagate(),
+        }
+    }
+
+    pub(super) fn select_next_completion(
+        &mut self,
+        _: &SelectNextCompletion,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.completion.menu.clone() {
+            Some(menu) => menu.update(cx, |menu, cx| menu.select(false, window, cx)),
+            None => cx.propagate(),
+        }
+    }
+
+    pub(super) fn confirm_completion(
+        &mut self,
+        _: &ConfirmCompletion,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(menu) = self.completion.menu.as_ref() else {
+            cx.propagate();
+            return;
+        };
+
+        match menu.read(cx).selected_item(cx) {
+            Some(item) => self.accept_completion(item, window, cx),
+            None => {
+                self.hide_completions(cx);
+            }
+        }
+    }
+
+    /// Replace the word before the cursor with the `item`.
+    fn accept_completion(
+        &mut self,
+        item: CompletionItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(menu) = self.completion.menu.take() else {
+            return;
+        };
+        self.completion.task = Task::ready(());
+
+        let offset = self.cursor().offset;
+        let range = match item.replace_range {
+            // The word may be typed more after the completions requested.
+            Some(range) => range.start..range.end.max(offset),
+            None => menu.read(cx).start..offset,
+        };
+        let insert_text = item.insert_text.unwrap_or(item.label);
+        let snippet = item.snippet.then(|| Snippet::parse(&insert_text));
+        let new_text = snippet
+            .as_ref()
+            .map_or(insert_text.as_ref(), |snippet| snippet.text.as_str());
+
+        self.completion.inserting = true;
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), new_text, window, cx);
+        self.completion.inserting = false;
+
+        if let Some(snippet) = snippet {
+            self.completion.snippet = Some(SnippetState::new(&snippet, range.start));
+            self.select_tabstop(cx);
+        }
+        cx.notify();
+    }
+
+    /// Update the tabstops of the snippet before the `range` (in the old text) is replaced by
+    /// the `new_text`.
+    pub(super) fn edit_snippet(&mut self, range: &Range<usize>, new_text: &str) {
+        if let Some(snippet) = self.completion.snippet.as_mut() {
+            snippet.edit(range, new_text.len());
+        }
+    }
+
+    /// Select the ranges of the active tabstop, the snippet will be finished at the last one.
+    fn select_tabstop(&mut self, cx: &mut Context<Self>) {
+        let Some(snippet) = self.completion.snippet.as_ref() else {
+            return;
+        };
+
+        let mut ranges = snippet.active_ranges().to_vec();
+        if snippet.is_last() {
+            self.completion.snippet = None;
+        }
+
+        let primary = ranges.remove(0);
+        self.select_range(primary, cx);
+        // The mirrored tabstops are edited by the multiple cursors.
+        self.extra_selections = ranges
+            .into_iter()
+            .map(|range| CursorSelection::new(range, false))
+            .collect();
+    }
+
+    pub(super) fn next_tabstop(&mut self, _: &NextTabstop, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(snippet) = self.completion.snippet.as_mut() else {
+            cx.propagate();
+            return;
+        };
+
+        snippet.move_to(false);
+        self.select_tabstop(cx);
+    }
+
+    pub(super) fn previous_tabstop(
+        &mut self,
+        _: &PreviousTabstop,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(snippet) = self.completion.snippet.as_mut() else {
+            cx.propagate();
+            return;
+        };
+
+        snippet.move_to(true);
+        self.select_tabstop(cx);
+    }
+
+    /// Finish the snippet, returns true if there was an active snippet.
+    pub(super) fn finish_snippet(&mut self) -> bool {
+        self.completion.snippet.take().is_some()
+    }
+
+    pub(super) fn completion_menu(&self) -> Option<Entity<CompletionMenu>> {
+        self.completion.menu.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::{match_score, word_start};
+
+    #[test]
+    fn test_word_start() {
+        let text = Rope::from_str("let foo_bar\n  你好.println");
+        assert_eq!(word_start(&text, 0), 0);
+        assert_eq!(word_start(&text, 3), 0);
+        assert_eq!(word_start(&text, 4), 4);
+        assert_eq!(word_start(&text, 11), 4);
+        assert_eq!(word_start(&text, 20), 14);
+        assert_eq!(word_start(&text, 21), 21);
+        assert_eq!(word_start(&text, 25), 21);
+        assert_eq!(word_start(&text, 99), 21);
+    }
+
+    #[test]
+    fn test_match_score() {
+        assert_eq!(match_score("println", ""), Some(0));
+        assert_eq!(match_score("println", "print"), Some(0));
+        assert_eq!(match_score("println", "Print"), Some(1));
+        assert_eq!(match_score("eprintln", "print"), Some(3));
+        assert_eq!(match_score("println", "pln"), Some(11));
+        assert_eq!(match_score("println", "lnp"), None);
+        assert_eq!(match_score("println", "printlnx"), None);
+    }
+}
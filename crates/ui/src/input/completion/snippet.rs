@@ -0,0 +1,331 @@
+use std::{collections::BTreeMap, ops::Range};
+
+/// A snippet parsed from the TextMate / LSP snippet syntax.
+///
+/// Supported syntax:
+///
+/// - `$1`, `${1}`: The tabstop.
+/// - `${1:placeholder}`: The tabstop with a placeholder, the placeholder can be nested.
+/// - `${1|one,two|}`: The choice, the first one will be inserted.
+/// - `$0`: The final cursor position, default is the end of the snippet.
+/// - `$name`, `${name:default}`: The variable, it will be resolved to the default value.
+/// - `\$`, `\}`, `\\`: The escaped characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Snippet {
+    /// The text to insert.
+    pub(crate) text: String,
+    /// The byte ranges (in the `text`) of each tabstop in the navigation order, the ranges
+    /// of the same tabstop are mirrored, `$0` is always the last.
+    pub(crate) tabstops: Vec<Vec<Range<usize>>>,
+}
+
+impl Snippet {
+    pub(crate) fn parse(source: &str) -> Self {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            text: String::new(),
+            tabstops: BTreeMap::new(),
+        };
+        parser.parse(false);
+
+        let mut final_tabstop = parser.tabstops.remove(&0);
+        let mut tabstops = parser.tabstops.into_values().collect::<Vec<_>>();
+        let len = parser.text.len();
+        tabstops.push(final_tabstop.take().unwrap_or_else(|| vec![len..len]));
+
+        Self {
+            text: parser.text,
+            tabstops,
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    text: String,
+    tabstops: BTreeMap<usize, Vec<Range<usize>>>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            return true;
+        }
+        false
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let len = self.source[self.pos..]
+            .bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let number = self.source[self.pos..self.pos + len].parse().ok()?;
+        self.pos += len;
+        Some(number)
+    }
+
+    fn variable(&mut self) -> bool {
+        let rest = &self.source[self.pos..];
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return false;
+        }
+
+        self.pos += rest
+            .bytes()
+            .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+            .count();
+        true
+    }
+
+    /// Parse the text until the end, or the `}` if `nested`.
+    fn parse(&mut self, nested: bool) {
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => match self.peek() {
+                    Some(c @ ('$' | '}' | '\\')) => {
+                        self.pos += 1;
+                        self.text.push(c);
+                    }
+                    _ => self.text.push('\\'),
+                },
+                '}' if nested => return,
+                '$' => self.parse_dollar(),
+                c => self.text.push(c),
+            }
+        }
+    }
+
+    fn parse_dollar(&mut self) {
+        if let Some(ix) = self.number() {
+            let offset = self.text.len();
+            self.tabstops.entry(ix).or_default().push(offset..offset);
+            return;
+        }
+        if self.variable() {
+            return;
+        }
+        if !self.eat('{') {
+            self.text.push('$');
+            return;
+        }
+
+        // The offset of the `$` in the source.
+        let dollar = self.pos - 2;
+
+        if let Some(ix) = self.number() {
+            let start = self.text.len();
+            if self.eat(':') {
+                self.parse(true);
+            } else if self.eat('|') {
+                self.parse_choice();
+            } else if !self.eat('}') {
+                self.text.push_str(&self.source[dollar..self.pos]);
+                return;
+            }
+            self.tabstops
+                .entry(ix)
+                .or_default()
+                .push(start..self.text.len());
+        } else if self.variable() {
+            if self.eat(':') {
+                self.parse(true);
+            } else if !self.eat('}') {
+                self.text.push_str(&self.source[dollar..self.pos]);
+            }
+        } else {
+            // Not a snippet syntax, keep it as the text.
+            self.text.push_str("${");
+        }
+    }
+
+    /// Parse the choices after `${1|` and insert the first one.
+    fn parse_choice(&mut self) {
+        let mut choices = vec![String::new()];
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => {
+                    if let Some(c) = self.next() {
+                        choices.last_mut().unwrap().push(c);
+                    }
+                }
+                ',' => choices.push(String::new()),
+                '|' => {
+                    self.eat('}');
+                    break;
+                }
+                c => choices.last_mut().unwrap().push(c),
+            }
+        }
+        self.text.push_str(&choices[0]);
+    }
+}
+
+/// The active snippet session in the editor, to navigate the tabstops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SnippetState {
+    /// The byte ranges of each tabstop in the editor text.
+    tabstops: Vec<Vec<Range<usize>>>,
+    /// The index of the active tabstop.
+    active: usize,
+}
+
+impl SnippetState {
+    /// Create the snippet state for the `snippet` inserted at the `offset`.
+    pub(crate) fn new(snippet: &Snippet, offset: usize) -> Self {
+        Self {
+            tabstops: snippet
+                .tabstops
+                .iter()
+                .map(|ranges| {
+                    ranges
+                        .iter()
+                        .map(|range| range.start + offset..range.end + offset)
+                        .collect()
+                })
+                .collect(),
+            active: 0,
+        }
+    }
+
+    /// The ranges of the active tabstop.
+    pub(crate) fn active_ranges(&self) -> &[Range<usize>] {
+        &self.tabstops[self.active]
+    }
+
+    /// Returns true if the active tabstop is the final one (`$0`).
+    pub(crate) fn is_last(&self) -> bool {
+        self.active + 1 >= self.tabstops.len()
+    }
+
+    /// Move to the next (or previous if `backward`) tabstop, returns false if there is no more.
+    pub(crate) fn move_to(&mut self, backward: bool) -> bool {
+        if backward {
+            if self.active == 0 {
+                return false;
+            }
+            self.active -= 1;
+        } else {
+            if self.is_last() {
+                return false;
+            }
+            self.active += 1;
+        }
+        true
+    }
+
+    /// Returns true if the `offset` is in the active tabstop.
+    pub(crate) fn contains(&self, offset: usize) -> bool {
+        self.active_ranges()
+            .iter()
+            .any(|range| range.start <= offset && offset <= range.end)
+    }
+
+    /// Update the tabstops after the `range` of the text is replaced by `new_len` bytes.
+    ///
+    /// The editing in (or at the edges of) the active tabstop will expand it, the other tabstops
+    /// will be moved.
+    pub(crate) fn edit(&mut self, range: &Range<usize>, new_len: usize) {
+        let map = |offset: usize, left: bool| {
+            if offset < range.start || (left && offset == range.start) {
+                offset
+            } else if offset >= range.end {
+                offset - range.len() + new_len
+            } else if left {
+                range.start
+            } else {
+                range.start + new_len
+            }
+        };
+
+        for (ix, ranges) in self.tabstops.iter_mut().enumerate() {
+            let active = ix == self.active;
+            for tabstop in ranges.iter_mut() {
+                let start = map(tabstop.start, active);
+                let end = map(tabstop.end, !active);
+                *tabstop = start..end.max(start);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Snippet, SnippetState};
+
+    #[test]
+    fn test_parse_snippet() {
+        let snippet = Snippet::parse("fn ${1:name}($2) {\n    $0\n}");
+        assert_eq!(snippet.text, "fn name() {\n    \n}");
+        assert_eq!(snippet.tabstops, vec![vec![3..7], vec![8..8], vec![16..16]]);
+
+        // No final tabstop, the end of the text is the final one.
+        let snippet = Snippet::parse("println!(\"$1\")");
+        assert_eq!(snippet.text, "println!(\"\")");
+        assert_eq!(snippet.tabstops, vec![vec![10..10], vec![12..12]]);
+
+        // Nested, mirrored and ordered by the index.
+        let snippet = Snippet::parse("${2:a} ${1:foo(${3:bar})} $2");
+        assert_eq!(snippet.text, "a foo(bar) ");
+        assert_eq!(
+            snippet.tabstops,
+            vec![vec![2..10], vec![0..1, 11..11], vec![6..9], vec![11..11]]
+        );
+
+        // Choice, variables and escapes.
+        let snippet = Snippet::parse("${1|let,const|} $TM_FILENAME${USER:你好} \\$1 \\} ${x $");
+        assert_eq!(snippet.text, "let 你好 $1 } ${x $");
+        assert_eq!(snippet.tabstops, vec![vec![0..3], vec![21..21]]);
+
+        let snippet = Snippet::parse("");
+        assert_eq!(snippet.text, "");
+        assert_eq!(snippet.tabstops, vec![vec![0..0]]);
+    }
+
+    #[test]
+    fn test_snippet_state() {
+        let snippet = Snippet::parse("${1:a}($2, $1)$0");
+        assert_eq!(snippet.text, "a(, )");
+        let mut state = SnippetState::new(&snippet, 10);
+        assert_eq!(state.active_ranges(), &[10..11, 14..14]);
+        assert!(state.contains(11));
+        assert!(!state.contains(12));
+
+        // Replace the `a` with `foo` in the both mirrors, from the last one.
+        state.edit(&(14..14), 3);
+        state.edit(&(10..11), 3);
+        assert_eq!(state.active_ranges(), &[10..13, 16..19]);
+
+        assert!(state.move_to(false));
+        assert_eq!(state.active_ranges(), &[14..14]);
+        // Typing in the empty tabstop.
+        state.edit(&(14..14), 1);
+        state.edit(&(15..15), 1);
+        assert_eq!(state.active_ranges(), &[14..16]);
+
+        assert!(state.move_to(false));
+        assert!(state.is_last());
+        assert_eq!(state.active_ranges(), &[22..22]);
+        assert!(!state.move_to(false));
+
+        assert!(state.move_to(true));
+        assert!(state.move_to(true));
+        assert_eq!(state.active_ranges(), &[10..13, 18..21]);
+        // Editing before the snippet.
+        state.edit(&(0..5), 0);
+        assert_eq!(state.active_ranges(), &[5..8, 13..16]);
+        assert!(!state.move_to(true));
+    }
+}
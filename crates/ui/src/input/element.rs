@@ -10,15 +10,19 @@ use smallvec::SmallVec;
 
 use crate::{
     highlighter::{LanguageRegistry, SyntaxHighlighter},
-    input::blink_cursor::CURSOR_WIDTH,
+    input::{blink_cursor::CURSOR_WIDTH, RopeExt as _},
     ActiveTheme as _, Root,
 };
 
-use super::{mode::InputMode, InputState, LastLayout};
+use super::{mode::InputMode, InputState, LastLayout, Selection};
 
-const RIGHT_MARGIN: Pixels = px(5.);
+pub(super) const RIGHT_MARGIN: Pixels = px(5.);
+/// The placeholder displayed after the folded lines.
+const FOLD_PLACEHOLDER: &str = "⋯";
 const BOTTOM_MARGIN_ROWS: usize = 1;
-const LINE_NUMBER_MARGIN_RIGHT: Pixels = px(10.);
+pub(super) const LINE_NUMBER_MARGIN_RIGHT: Pixels = px(10.);
+/// The width of the fold toggles between the line numbers and the text.
+pub(super) const FOLD_GUTTER_WIDTH: Pixels = px(14.);
 
 pub(super) struct TextElement {
     state: Entity<InputState>,
@@ -60,13 +64,13 @@ impl TextElement {
     /// - current line index
     fn layout_cursor(
         &self,
-        lines: &[WrappedLine],
-        line_height: Pixels,
+        last_layout: &LastLayout,
         bounds: &mut Bounds<Pixels>,
         line_number_width: Pixels,
         window: &mut Window,
         cx: &mut App,
     ) -> (Option<Bounds<Pixels>>, Point<Pixels>, Option<usize>) {
+        let line_height = last_layout.line_height;
         let state = self.state.read(cx);
         let mut selected_range = state.selected_range;
         if let Some(marked_range) = &state.marked_range {
@@ -89,20 +93,21 @@ impl TextElement {
         let mut cursor_start = None;
         let mut cursor_end = None;
 
-        let mut prev_lines_offset = 0;
-        let mut offset_y = px(0.);
-        for (line_ix, line) in lines.iter().enumerate() {
+        let mut offset_y = last_layout.visible_top;
+        for (line_ix, line) in last_layout.lines.iter().enumerate() {
             // break loop if all cursor positions are found
             if cursor_pos.is_some() && cursor_start.is_some() && cursor_end.is_some() {
                 break;
             }
 
+            let prev_lines_offset = last_layout.line_offsets[line_ix];
+
             let line_origin = point(px(0.), offset_y);
             if cursor_pos.is_none() {
                 let offset = cursor.offset.saturating_sub(prev_lines_offset);
 
                 if let Some(pos) = line.position_for_index(offset, line_height) {
-                    current_line_index = Some(line_ix);
+                    current_line_index = Some(last_layout.rows[line_ix]);
                     cursor_pos = Some(line_origin + pos);
                 }
             }
@@ -120,8 +125,6 @@ impl TextElement {
             }
 
             offset_y += line.size(line_height).height;
-            // +1 for skip the last `\n`
-            prev_lines_offset += line.len() + 1;
         }
 
         if let (Some(cursor_pos), Some(cursor_start), Some(cursor_end)) =
@@ -192,22 +195,46 @@ impl TextElement {
         (cursor_bounds, scroll_offset, current_line_index)
     }
 
+    /// Returns the cursor bounds of the secondary cursors for multi-cursor editing.
+    ///
+    /// The `bounds` must be the bounds after the scroll offset applied (by `layout_cursor`).
+    fn layout_extra_cursors(
+        &self,
+        last_layout: &LastLayout,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<Bounds<Pixels>> {
+        let state = self.state.read(cx);
+        if !state.has_extra_selections() || !state.show_cursor(window, cx) {
+            return vec![];
+        }
+
+        let line_height = last_layout.line_height;
+        state
+            .extra_selections
+            .iter()
+            .filter_map(|selection| {
+                let pos = position_for_offset(last_layout, selection.cursor().offset)?;
+                Some(Bounds::new(
+                    bounds.origin + point(line_number_width + pos.x, pos.y),
+                    size(CURSOR_WIDTH, line_height),
+                ))
+            })
+            .collect()
+    }
+
     fn layout_selections(
         &self,
-        lines: &[WrappedLine],
-        line_height: Pixels,
+        selected_range: Selection,
+        last_layout: &LastLayout,
         bounds: &mut Bounds<Pixels>,
         line_number_width: Pixels,
         _: &mut Window,
-        cx: &mut App,
+        _: &mut App,
     ) -> Option<Path<Pixels>> {
-        let state = self.state.read(cx);
-        let mut selected_range = state.selected_range;
-        if let Some(marked_range) = &state.marked_range {
-            if !marked_range.is_empty() {
-                selected_range = (marked_range.end..marked_range.end).into();
-            }
-        }
+        let line_height = last_layout.line_height;
         if selected_range.is_empty() {
             return None;
         }
@@ -218,11 +245,19 @@ impl TextElement {
             (selected_range.end, selected_range.start)
         };
 
-        let mut prev_lines_offset = 0;
+        // Only the visible part of the selection will be painted.
+        let visible_range_offset = &last_layout.visible_range_offset;
+        if end_ix < visible_range_offset.start || start_ix > visible_range_offset.end {
+            return None;
+        }
+        let start_ix = start_ix.max(visible_range_offset.start);
+        let end_ix = end_ix.min(visible_range_offset.end);
+
         let mut line_corners = vec![];
 
-        let mut offset_y = px(0.);
-        for line in lines.iter() {
+        let mut offset_y = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            let prev_lines_offset = last_layout.line_offsets[ix];
             let line_size = line.size(line_height);
             let line_wrap_width = line_size.width;
 
@@ -281,8 +316,6 @@ impl TextElement {
             }
 
             offset_y += line_size.height;
-            // +1 for skip the last `\n`
-            prev_lines_offset += line.len() + 1;
         }
 
         let mut points = vec![];
@@ -327,45 +360,75 @@ impl TextElement {
         builder.build().ok()
     }
 
+    /// Scroll to let the line of the cursor to be visible, if the cursor has been moved.
+    ///
+    /// The lines out of the viewport are not laid out, so we must scroll to the cursor line
+    /// before calculating the visible range, then the `layout_cursor` can adjust the scroll offset.
+    fn scroll_to_cursor_line(&self, line_height: Pixels, input_height: Pixels, cx: &App) {
+        let state = self.state.read(cx);
+        if state.mode.is_single_line() {
+            return;
+        }
+
+        let cursor = state.cursor();
+        if state.last_cursor == Some(cursor) {
+            return;
+        }
+
+        let row = state
+            .text
+            .byte_to_line(state.text.clip_offset(cursor.offset));
+        let line_top = state.text_wrapper.line_top(row, line_height);
+        let line_bottom = line_top
+            + state
+                .text_wrapper
+                .lines
+                .get(row)
+                .map(|line| line.height(line_height))
+                .unwrap_or(line_height);
+
+        let mut scroll_offset = state.scroll_handle.offset();
+        if scroll_offset.y + line_top < px(0.) {
+            scroll_offset.y = -line_top;
+        } else if scroll_offset.y + line_bottom > input_height {
+            scroll_offset.y = input_height - line_bottom;
+        } else {
+            return;
+        }
+
+        state.scroll_handle.set_offset(scroll_offset);
+    }
+
     /// Calculate the visible range of lines in the viewport.
     ///
     /// The visible range is based on unwrapped lines (Zero based).
+    ///
+    /// Returns the visible range and the top position of the first visible line.
     fn calculate_visible_range(
         &self,
         state: &InputState,
         line_height: Pixels,
         input_height: Pixels,
-    ) -> Range<usize> {
+    ) -> (Range<usize>, Pixels) {
         if state.mode.is_single_line() {
-            return 0..1;
+            return (0..state.text.len_lines(), px(0.));
         }
 
         let scroll_top = -state.scroll_handle.offset().y;
-        let total_lines = state.text_wrapper.lines.len();
-        let mut visible_range = 0..total_lines;
-        let mut line_top = px(0.);
-        for (ix, line) in state.text_wrapper.lines.iter().enumerate() {
-            line_top += line.height(line_height);
-
-            if line_top < scroll_top {
-                visible_range.start = ix;
-            }
-
-            if line_top > scroll_top + input_height {
-                visible_range.end = (ix + 1).min(total_lines);
-                break;
-            }
-        }
-
-        visible_range
+        state
+            .text_wrapper
+            .visible_range(scroll_top, input_height, line_height)
     }
 
-    /// First usize is the offset of skipped.
+    /// Returns the highlight styles of the visible lines.
+    ///
+    /// The style ranges are relative to the start of the `visible_range_offset`.
     fn highlight_lines(
         &mut self,
         visible_range: &Range<usize>,
+        visible_range_offset: &Range<usize>,
         cx: &mut App,
-    ) -> Option<(usize, Vec<(Range<usize>, HighlightStyle)>)> {
+    ) -> Option<Vec<(Range<usize>, HighlightStyle)>> {
         let theme = LanguageRegistry::global(cx)
             .theme(cx.theme().is_dark())
             .clone();
@@ -381,72 +444,141 @@ impl TextElement {
                 if highlighter.is_none() {
                     highlighter.replace(SyntaxHighlighter::new(language, cx));
                 };
-                let Some(highlighter) = highlighter.as_ref() else {
+                let Some(highlighter) = highlighter.as_mut() else {
                     return None;
                 };
+                highlighter.highlight(visible_range_offset, cx);
 
-                let mut offset = 0;
-                let mut skipped_offset = 0;
                 let mut styles = vec![];
-
-                for (ix, line) in state.text.split('\n').enumerate() {
-                    // +1 for last `\n`.
-                    let line_len = line.len() + 1;
-                    if ix < visible_range.start {
-                        offset += line_len;
-                        skipped_offset = offset;
-                        continue;
-                    }
-                    if ix > visible_range.end {
-                        break;
-                    }
-
-                    let range = offset..offset + line_len;
+                for row in visible_range.clone() {
+                    // Include the last `\n` of the line.
+                    let range =
+                        state.text.line_start_offset(row)..state.text.line_start_offset(row + 1);
                     let line_styles = highlighter.styles(&range, &theme);
                     styles = gpui::combine_highlights(styles, line_styles).collect();
-
-                    offset = range.end;
                 }
 
                 let mut marker_styles = vec![];
                 for marker in markers.iter() {
                     if let Some(range) = &marker.range {
-                        if range.start < skipped_offset {
+                        if range.start < visible_range_offset.start
+                            || range.end > visible_range_offset.end
+                        {
                             continue;
                         }
 
-                        let node_range = range.start..range.end;
-                        if node_range.start >= visible_range.start
-                            || node_range.end <= visible_range.end
-                        {
-                            marker_styles
-                                .push((node_range, marker.severity.highlight_style(&theme)));
-                        }
+                        marker_styles
+                            .push((range.clone(), marker.severity.highlight_style(&theme)));
                     }
                 }
 
                 styles = gpui::combine_highlights(marker_styles, styles).collect();
 
-                Some((skipped_offset, styles))
+                let base_offset = visible_range_offset.start;
+                Some(
+                    styles
+                        .into_iter()
+                        .filter(|(range, _)| range.end > base_offset)
+                        .map(|(range, style)| {
+                            (
+                                range.start.saturating_sub(base_offset)
+                                    ..range.end.saturating_sub(base_offset),
+                                style,
+                            )
+                        })
+                        .collect(),
+                )
             }
             _ => None,
         })
     }
 }
 
+/// Remove the byte `ranges` (sorted and not overlapped) from the `text` and the `runs`.
+fn remove_folded_ranges(
+    text: &str,
+    runs: Vec<TextRun>,
+    ranges: &[Range<usize>],
+) -> (SharedString, Vec<TextRun>) {
+    let mut new_text = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for range in ranges {
+        new_text.push_str(&text[last_end..range.start]);
+        last_end = range.end;
+    }
+    new_text.push_str(&text[last_end..]);
+
+    let mut run_start = 0;
+    let runs = runs
+        .into_iter()
+        .filter_map(|mut run| {
+            let run_range = run_start..run_start + run.len;
+            run_start = run_range.end;
+            let removed_len = ranges
+                .iter()
+                .map(|range| {
+                    range
+                        .end
+                        .min(run_range.end)
+                        .saturating_sub(range.start.max(run_range.start))
+                })
+                .sum::<usize>();
+            run.len -= removed_len;
+            (run.len > 0).then_some(run)
+        })
+        .collect();
+
+    (new_text.into(), runs)
+}
+
+/// Returns the position of the offset (in the entire text) relative to the text origin,
+/// None if the offset is not in the visible lines.
+fn position_for_offset(last_layout: &LastLayout, offset: usize) -> Option<Point<Pixels>> {
+    if offset < last_layout.visible_range_offset.start
+        || offset > last_layout.visible_range_offset.end
+    {
+        return None;
+    }
+
+    let line_height = last_layout.line_height;
+    let mut offset_y = last_layout.visible_top;
+    for (ix, line) in last_layout.lines.iter().enumerate() {
+        let local_offset = offset.saturating_sub(last_layout.line_offsets[ix]);
+        if let Some(pos) = line.position_for_index(local_offset, line_height) {
+            return Some(point(pos.x, pos.y + offset_y));
+        }
+
+        offset_y += line.size(line_height).height;
+    }
+
+    None
+}
+
 pub(super) struct PrepaintState {
     /// The lines of entire lines.
     last_layout: LastLayout,
     /// The lines only contains the visible lines in the viewport, based on `visible_range`.
     line_numbers: Option<Vec<SmallVec<[WrappedLine; 1]>>>,
+    /// The fold toggles of the line numbers, None if the line can't be folded.
+    fold_toggles: Vec<Option<SmallVec<[WrappedLine; 1]>>>,
+    /// The placeholders after the folded lines, with the index of the line in `last_layout`.
+    fold_placeholders: Vec<(usize, SmallVec<[WrappedLine; 1]>)>,
     line_number_width: Pixels,
     /// Size of the scrollable area by entire lines.
     scroll_size: Size<Pixels>,
     cursor_bounds: Option<Bounds<Pixels>>,
     cursor_scroll_offset: Point<Pixels>,
+    /// The cursor bounds of the secondary cursors, the scroll offset is already applied.
+    extra_cursor_bounds: Vec<Bounds<Pixels>>,
     /// line index (zero based), no wrap, same line as the cursor.
     current_line_index: Option<usize>,
     selection_path: Option<Path<Pixels>>,
+    /// The selection paths of the secondary selections.
+    extra_selection_paths: Vec<Path<Pixels>>,
+    /// The paths of the visible search matches.
+    search_match_paths: Vec<Path<Pixels>>,
+    /// The paths of the bracket next to the cursor and its matching bracket.
+    bracket_match_paths: Vec<Path<Pixels>>,
     bounds: Bounds<Pixels>,
 }
 
@@ -536,16 +668,51 @@ impl Element for TextElement {
         window: &mut Window,
         cx: &mut App,
     ) -> Self::PrepaintState {
-        let state = self.state.read(cx);
         let line_height = window.line_height();
+        self.scroll_to_cursor_line(line_height, bounds.size.height, cx);
 
-        let visible_range = self.calculate_visible_range(&state, line_height, bounds.size.height);
-        let highlight_styles = self.highlight_lines(&visible_range, cx);
+        let state = self.state.read(cx);
+        let (visible_range, visible_top) =
+            self.calculate_visible_range(&state, line_height, bounds.size.height);
+        let visible_range_offset = state.text.line_start_offset(visible_range.start)
+            ..state
+                .text
+                .line_end_offset(visible_range.end.saturating_sub(1));
+        let highlight_styles = self.highlight_lines(&visible_range, &visible_range_offset, cx);
 
         let state = self.state.read(cx);
+        // The rows of the displayed lines, the rows hidden by the folded ranges are excluded.
+        let hidden_rows = state
+            .folds
+            .hidden_rows()
+            .into_iter()
+            .filter(|rows| rows.start < visible_range.end && rows.end > visible_range.start)
+            .collect::<Vec<_>>();
+        let rows = visible_range
+            .clone()
+            .filter(|row| !hidden_rows.iter().any(|rows| rows.contains(row)))
+            .collect::<Vec<_>>();
+        let line_offsets = rows
+            .iter()
+            .map(|row| state.text.line_start_offset(*row))
+            .collect::<Vec<_>>();
+        // The byte ranges (relative to the visible text) to remove from the visible text,
+        // from the end of the fold header line to the end of the last hidden line.
+        let folded_ranges = hidden_rows
+            .iter()
+            .map(|rows| {
+                let start = state.text.line_end_offset(rows.start.saturating_sub(1));
+                let end = state
+                    .text
+                    .line_end_offset(rows.end.min(visible_range.end).saturating_sub(1));
+                start.saturating_sub(visible_range_offset.start)
+                    ..end.saturating_sub(visible_range_offset.start)
+            })
+            .collect::<Vec<_>>();
         let multi_line = state.mode.is_multi_line();
-        let text = state.text.clone();
-        let is_empty = text.is_empty();
+        // Only the visible lines will be shaped.
+        let text: SharedString = state.text.slice_text(visible_range_offset.clone()).into();
+        let is_empty = state.text.len_bytes() == 0;
         let placeholder = self.placeholder.clone();
         let style = window.text_style();
         let font_size = style.font_size.to_pixels(window.rem_size());
@@ -583,7 +750,7 @@ impl Element for TextElement {
             )
             .unwrap();
         let line_number_width = if state.mode.line_number() {
-            empty_line_number.last().unwrap().width() + LINE_NUMBER_MARGIN_RIGHT
+            empty_line_number.last().unwrap().width() + FOLD_GUTTER_WIDTH + LINE_NUMBER_MARGIN_RIGHT
         } else {
             px(0.)
         };
@@ -609,19 +776,20 @@ impl Element for TextElement {
             strikethrough: None,
         };
 
+        // The marked range relative to the visible text.
+        let marked_range = state.marked_range.map(|marked_range| {
+            marked_range
+                .start
+                .saturating_sub(visible_range_offset.start)
+                ..marked_range.end.saturating_sub(visible_range_offset.start)
+        });
+
         let runs = if !is_empty {
-            if let Some((skipped_offset, highlight_styles)) = highlight_styles {
+            if let Some(highlight_styles) = highlight_styles {
                 let mut runs = vec![];
-                if skipped_offset > 0 {
-                    runs.push(TextRun {
-                        len: skipped_offset,
-                        ..run.clone()
-                    });
-                }
-
                 runs.extend(highlight_styles.iter().map(|(range, style)| {
                     let mut run = text_style.clone().highlight(*style).to_run(range.len());
-                    if let Some(marked_range) = &state.marked_range {
+                    if let Some(marked_range) = &marked_range {
                         if range.start >= marked_range.start && range.end <= marked_range.end {
                             run.color = marked_run.color;
                             run.strikethrough = marked_run.strikethrough;
@@ -636,20 +804,20 @@ impl Element for TextElement {
             } else {
                 vec![run]
             }
-        } else if let Some(marked_range) = &state.marked_range {
+        } else if let Some(marked_range) = &marked_range {
             // IME marked text
             vec![
                 TextRun {
-                    len: marked_range.start.offset,
+                    len: marked_range.start,
                     ..run.clone()
                 },
                 TextRun {
-                    len: marked_range.end.offset - marked_range.start.offset,
+                    len: marked_range.end - marked_range.start,
                     underline: marked_run.underline,
                     ..run.clone()
                 },
                 TextRun {
-                    len: display_text.len() - marked_range.end.offset,
+                    len: display_text.len().saturating_sub(marked_range.end),
                     ..run.clone()
                 },
             ]
@@ -666,6 +834,13 @@ impl Element for TextElement {
             None
         };
 
+        // Remove the folded lines, the fold header lines will be followed by the next visible lines.
+        let (display_text, runs) = if folded_ranges.is_empty() {
+            (display_text, runs)
+        } else {
+            remove_folded_ranges(&display_text, runs, &folded_ranges)
+        };
+
         // NOTE: If there have about 10K lines, this will take about 5~6ms.
         // let measure = Measure::new("shape_text");
         let lines = window
@@ -674,13 +849,27 @@ impl Element for TextElement {
             .expect("failed to shape text");
         // measure.end();
 
-        let total_wrapped_lines = lines
+        let visible_wrapped_lines = lines
             .iter()
             .map(|line| {
                 // +1 is the first line, `wrap_boundaries` is the wrapped lines after the `\n`.
                 1 + line.wrap_boundaries.len()
             })
             .sum::<usize>();
+        // The invisible lines are not shaped, so use the wrapped lines count of the `TextWrapper`.
+        let total_wrapped_lines = if multi_line {
+            let invisible_wrapped_lines = state
+                .text_wrapper
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| !visible_range.contains(ix))
+                .map(|(_, line)| line.rows())
+                .sum::<usize>();
+            invisible_wrapped_lines + visible_wrapped_lines
+        } else {
+            visible_wrapped_lines
+        };
 
         let max_line_width = lines
             .iter()
@@ -723,25 +912,91 @@ impl Element for TextElement {
 
         // Calculate the scroll offset to keep the cursor in view
 
-        let (cursor_bounds, cursor_scroll_offset, current_line_index) = self.layout_cursor(
-            &lines,
+        let last_layout = LastLayout {
+            lines: Rc::new(lines),
             line_height,
-            &mut bounds,
-            line_number_width,
-            window,
-            cx,
-        );
+            visible_range,
+            visible_range_offset,
+            visible_top,
+            rows: Rc::new(rows),
+            line_offsets: Rc::new(line_offsets),
+        };
+
+        let (cursor_bounds, cursor_scroll_offset, current_line_index) =
+            self.layout_cursor(&last_layout, &mut bounds, line_number_width, window, cx);
+
+        let state = self.state.read(cx);
+        let mut selected_range = state.selected_range;
+        if let Some(marked_range) = &state.marked_range {
+            if !marked_range.is_empty() {
+                selected_range = (marked_range.end..marked_range.end).into();
+            }
+        }
+        let extra_selections = state.extra_selections.clone();
 
         let selection_path = self.layout_selections(
-            &lines,
-            line_height,
+            selected_range,
+            &last_layout,
             &mut bounds,
             line_number_width,
             window,
             cx,
         );
+        let extra_selection_paths = extra_selections
+            .iter()
+            .filter_map(|selection| {
+                self.layout_selections(
+                    selection.range,
+                    &last_layout,
+                    &mut bounds,
+                    line_number_width,
+                    window,
+                    cx,
+                )
+            })
+            .collect();
+        let search_matches = state
+            .search
+            .as_ref()
+            .map(|search| {
+                search
+                    .matches_in_range(&last_layout.visible_range_offset)
+                    .to_vec()
+            })
+            .unwrap_or_default();
+        let bracket_matches = state.matching_brackets(cx);
+        let search_match_paths = search_matches
+            .into_iter()
+            .filter_map(|range| {
+                self.layout_selections(
+                    range.into(),
+                    &last_layout,
+                    &mut bounds,
+                    line_number_width,
+                    window,
+                    cx,
+                )
+            })
+            .collect();
+        let bracket_match_paths = bracket_matches
+            .into_iter()
+            .flat_map(|(start, end)| [start, end])
+            .filter_map(|range| {
+                self.layout_selections(
+                    range.into(),
+                    &last_layout,
+                    &mut bounds,
+                    line_number_width,
+                    window,
+                    cx,
+                )
+            })
+            .collect();
+        let extra_cursor_bounds =
+            self.layout_extra_cursors(&last_layout, &bounds, line_number_width, window, cx);
 
         let state = self.state.read(cx);
+        let mut fold_toggles = vec![];
         let line_numbers = if state.mode.line_number() {
             let mut line_numbers = vec![];
             let run_len = 4;
@@ -762,14 +1017,18 @@ impl Element for TextElement {
                 strikethrough: None,
             }];
 
+            let fold_toggle_runs = vec![TextRun {
+                len: "▸".len(),
+                font: style.font(),
+                color: cx.theme().muted_foreground,
+                background_color: None,
+                underline: None,
+                strikethrough: None,
+            }];
+
             // build line numbers
-            for (ix, line) in lines
-                .iter()
-                .skip(visible_range.start)
-                .take(visible_range.len())
-                .enumerate()
-            {
-                let ix = ix + visible_range.start;
+            for (ix, line) in last_layout.lines.iter().enumerate() {
+                let ix = last_layout.rows[ix];
                 let line_no = ix + 1;
 
                 let mut line_no_text = format!("{:>4}", line_no);
@@ -788,26 +1047,71 @@ impl Element for TextElement {
                     .shape_text(line_no_text.into(), font_size, &runs, None, None)
                     .unwrap();
                 line_numbers.push(shape_line);
+
+                let fold_toggle = if state.folds.is_folded(ix) {
+                    Some("▸")
+                } else if state.folds.candidate_at(ix).is_some() {
+                    Some("▾")
+                } else {
+                    None
+                };
+                fold_toggles.push(fold_toggle.map(|text| {
+                    window
+                        .text_system()
+                        .shape_text(text.into(), font_size, &fold_toggle_runs, None, None)
+                        .unwrap()
+                }));
             }
             Some(line_numbers)
         } else {
             None
         };
 
+        // The placeholder after the folded lines.
+        let fold_placeholder_runs = vec![TextRun {
+            len: FOLD_PLACEHOLDER.len(),
+            font: style.font(),
+            color: cx.theme().muted_foreground,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        }];
+        let fold_placeholders = last_layout
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| state.folds.is_folded(**row))
+            .map(|(ix, _)| {
+                let line = window
+                    .text_system()
+                    .shape_text(
+                        FOLD_PLACEHOLDER.into(),
+                        font_size,
+                        &fold_placeholder_runs,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+                (ix, line)
+            })
+            .collect();
+
         PrepaintState {
             bounds,
-            last_layout: LastLayout {
-                lines: Rc::new(lines),
-                line_height,
-                visible_range,
-            },
+            last_layout,
             scroll_size,
             line_numbers,
+            fold_toggles,
+            fold_placeholders,
             line_number_width,
             cursor_bounds,
             cursor_scroll_offset,
+            extra_cursor_bounds,
             current_line_index,
             selection_path,
+            extra_selection_paths,
+            search_match_paths,
+            bracket_match_paths,
         }
     }
 
@@ -825,7 +1129,6 @@ impl Element for TextElement {
         let focused = focus_handle.is_focused(window);
         let bounds = prepaint.bounds;
         let selected_range = self.state.read(cx).selected_range;
-        let visible_range = &prepaint.last_layout.visible_range;
 
         window.handle_input(
             &focus_handle,
@@ -861,10 +1164,7 @@ impl Element for TextElement {
         let line_height = window.line_height();
         let origin = bounds.origin;
 
-        let mut invisible_top_padding = px(0.);
-        for line in prepaint.last_layout.lines.iter().take(visible_range.start) {
-            invisible_top_padding += line.size(line_height).height;
-        }
+        let invisible_top_padding = prepaint.last_layout.visible_top;
 
         let mut mask_offset_y = px(0.);
         if self.state.read(cx).masked {
@@ -876,10 +1176,16 @@ impl Element for TextElement {
             }
         }
 
-        let active_line_color = LanguageRegistry::global(cx)
+        let highlight_style = &LanguageRegistry::global(cx)
             .theme(cx.theme().is_dark())
-            .style
-            .active_line;
+            .style;
+        let active_line_color = highlight_style.active_line;
+        let search_match_color = highlight_style
+            .search_match
+            .unwrap_or(cx.theme().warning.opacity(0.3));
+        let bracket_match_color = highlight_style
+            .bracket_match
+            .unwrap_or(cx.theme().muted_foreground.opacity(0.2));
 
         let mut offset_y = px(0.);
         if let Some(line_numbers) = prepaint.line_numbers.as_ref() {
@@ -887,7 +1193,22 @@ impl Element for TextElement {
 
             // Each item is the normal lines.
             for (ix, lines) in line_numbers.iter().enumerate() {
-                let is_active = prepaint.current_line_index == Some(visible_range.start + ix);
+                let is_active = prepaint.current_line_index == Some(prepaint.last_layout.rows[ix]);
+                if let Some(Some(fold_toggle)) = prepaint.fold_toggles.get(ix) {
+                    let x =
+                        prepaint.line_number_width - LINE_NUMBER_MARGIN_RIGHT - FOLD_GUTTER_WIDTH
+                            + px(3.);
+                    for line in fold_toggle {
+                        _ = line.paint(
+                            point(origin.x + x, origin.y + offset_y),
+                            line_height,
+                            TextAlign::Left,
+                            None,
+                            window,
+                            cx,
+                        );
+                    }
+                }
                 for line in lines {
                     let p = point(origin.x, origin.y + offset_y);
                     let line_size = line.size(line_height);
@@ -906,22 +1227,49 @@ impl Element for TextElement {
             }
         }
 
+        // Paint search matches
+        for path in prepaint.search_match_paths.drain(..) {
+            window.paint_path(path, search_match_color);
+        }
+
+        // Paint bracket matches
+        for path in prepaint.bracket_match_paths.drain(..) {
+            window.paint_path(path, bracket_match_color);
+        }
+
         // Paint selections
         if let Some(path) = prepaint.selection_path.take() {
             window.paint_path(path, cx.theme().selection);
         }
+        for path in prepaint.extra_selection_paths.drain(..) {
+            window.paint_path(path, cx.theme().selection);
+        }
 
         // Paint text
         let mut offset_y = mask_offset_y + invisible_top_padding;
 
-        for line in prepaint
-            .last_layout
-            .iter()
-            .skip(visible_range.start)
-            .take(visible_range.len())
-        {
+        let mut fold_placeholders = prepaint.fold_placeholders.iter().peekable();
+        for (ix, line) in prepaint.last_layout.iter().enumerate() {
             let p = point(origin.x + prepaint.line_number_width, origin.y + offset_y);
             _ = line.paint(p, line_height, TextAlign::Left, None, window, cx);
+
+            if let Some((_, placeholder)) = fold_placeholders.next_if(|(line_ix, _)| *line_ix == ix)
+            {
+                let end = line
+                    .position_for_index(line.len(), line_height)
+                    .unwrap_or_default();
+                for placeholder_line in placeholder {
+                    _ = placeholder_line.paint(
+                        p + point(end.x + px(4.), end.y),
+                        line_height,
+                        TextAlign::Left,
+                        None,
+                        window,
+                        cx,
+                    );
+                }
+            }
+
             offset_y += line.size(line_height).height;
         }
 
@@ -930,16 +1278,19 @@ impl Element for TextElement {
                 cursor_bounds.origin.y += prepaint.cursor_scroll_offset.y;
                 window.paint_quad(fill(cursor_bounds, cx.theme().caret));
             }
+            for cursor_bounds in prepaint.extra_cursor_bounds.drain(..) {
+                window.paint_quad(fill(cursor_bounds, cx.theme().caret));
+            }
         }
 
         self.state.update(cx, |state, cx| {
             state.last_layout = Some(prepaint.last_layout.clone());
             state.last_bounds = Some(bounds);
             state.last_cursor = Some(state.cursor());
+            state.line_number_width = prepaint.line_number_width;
             state.set_input_bounds(input_bounds, cx);
             state.last_selected_range = Some(selected_range);
             state.scroll_size = prepaint.scroll_size;
-            state.line_number_width = prepaint.line_number_width;
             state
                 .scroll_handle
                 .set_offset(prepaint.cursor_scroll_offset);
@@ -0,0 +1,483 @@
+use std::ops::Range;
+
+use ropey::Rope;
+use tree_sitter::{Tree, TreeCursor};
+
+use crate::input::RopeExt as _;
+
+/// A foldable range of rows (zero-based).
+///
+/// The `start` row is the header of the fold that is always visible,
+/// the rows `start + 1..=end` will be hidden when folded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct FoldRange {
+    pub(super) start: usize,
+    pub(super) end: usize,
+}
+
+impl FoldRange {
+    pub(super) fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns true if the `row` will be hidden when this range is folded.
+    #[inline]
+    pub(super) fn hides(&self, row: usize) -> bool {
+        row > self.start && row <= self.end
+    }
+
+    #[inline]
+    fn contains(&self, row: usize) -> bool {
+        row >= self.start && row <= self.end
+    }
+
+    /// Returns true if any row of the `rows` (end exclusive) is in this range.
+    #[inline]
+    fn intersects(&self, rows: &Range<usize>) -> bool {
+        self.start < rows.end && self.end >= rows.start
+    }
+}
+
+/// The fold state of the code editor, keep the foldable ranges and the folded ranges.
+#[derive(Debug, Clone, Default)]
+pub(super) struct FoldState {
+    /// The foldable ranges sorted by the start row, at most one range per start row.
+    candidates: Vec<FoldRange>,
+    /// The folded ranges sorted by the start row, the nested folds are kept.
+    folded: Vec<FoldRange>,
+    /// Set true to update all the `candidates` in next render.
+    pub(super) dirty: bool,
+    /// The rows (end exclusive) changed after the `candidates` updated, the candidates in these
+    /// rows will be updated in next render.
+    dirty_rows: Option<Range<usize>>,
+}
+
+impl FoldState {
+    pub(super) fn new() -> Self {
+        Self {
+            dirty: true,
+            ..Default::default()
+        }
+    }
+
+    pub(super) fn set_candidates(&mut self, candidates: Vec<FoldRange>) {
+        self.candidates = candidates;
+        self.dirty = false;
+        self.dirty_rows = None;
+    }
+
+    /// Returns the changed rows that the candidates need to be updated, see [`Self::update_candidates`].
+    pub(super) fn dirty_rows(&self) -> Option<Range<usize>> {
+        self.dirty_rows.clone()
+    }
+
+    /// Mark the `rows` (end exclusive) as changed, e.g.: the syntax tree of the rows changed.
+    pub(super) fn invalidate_rows(&mut self, rows: Range<usize>) {
+        self.dirty_rows = Some(match self.dirty_rows.take() {
+            Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+            None => rows,
+        });
+    }
+
+    /// Replace the candidates intersect with the `rows` by the `candidates` intersect with them.
+    pub(super) fn update_candidates(&mut self, rows: Range<usize>, candidates: Vec<FoldRange>) {
+        self.candidates.retain(|range| !range.intersects(&rows));
+        self.candidates.extend(candidates);
+        self.candidates
+            .sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        self.candidates.dedup_by_key(|range| range.start);
+        self.dirty_rows = None;
+    }
+
+    /// Returns the folded ranges.
+    #[cfg(test)]
+    pub(super) fn folded(&self) -> &[FoldRange] {
+        &self.folded
+    }
+
+    pub(super) fn has_folded(&self) -> bool {
+        !self.folded.is_empty()
+    }
+
+    /// Returns the foldable range starts at the `row`.
+    pub(super) fn candidate_at(&self, row: usize) -> Option<FoldRange> {
+        self.candidates
+            .binary_search_by_key(&row, |range| range.start)
+            .ok()
+            .map(|ix| self.candidates[ix])
+    }
+
+    /// Returns true if the `row` is the header of a folded range.
+    pub(super) fn is_folded(&self, row: usize) -> bool {
+        self.folded.iter().any(|range| range.start == row)
+    }
+
+    /// Returns true if the `row` is hidden by a folded range.
+    pub(super) fn is_hidden(&self, row: usize) -> bool {
+        self.folded.iter().any(|range| range.hides(row))
+    }
+
+    /// Returns the hidden rows of the folded ranges, merged and sorted.
+    pub(super) fn hidden_rows(&self) -> Vec<Range<usize>> {
+        let mut hidden: Vec<Range<usize>> = vec![];
+        for range in self.folded.iter() {
+            let rows = range.start + 1..range.end + 1;
+            match hidden.last_mut() {
+                Some(last) if rows.start <= last.end => last.end = last.end.max(rows.end),
+                _ => hidden.push(rows),
+            }
+        }
+        hidden
+    }
+
+    /// Fold the range starts at the `row`, or the innermost foldable range contains the `row`.
+    ///
+    /// Returns the folded range, None if there is no foldable range or it is already folded.
+    pub(super) fn fold(&mut self, row: usize) -> Option<FoldRange> {
+        let range = self.candidate_at(row).or_else(|| {
+            self.candidates
+                .iter()
+                .filter(|range| range.contains(row))
+                .min_by_key(|range| range.end - range.start)
+                .copied()
+        })?;
+
+        if self.folded.contains(&range) {
+            return None;
+        }
+
+        let ix = self
+            .folded
+            .partition_point(|folded| (folded.start, folded.end) < (range.start, range.end));
+        self.folded.insert(ix, range);
+        Some(range)
+    }
+
+    /// Unfold the ranges start at the `row` or hide the `row`.
+    ///
+    /// Returns true if any range has been unfolded.
+    pub(super) fn unfold(&mut self, row: usize) -> bool {
+        let len = self.folded.len();
+        self.folded.retain(|range| !range.contains(row));
+        self.folded.len() != len
+    }
+
+    /// Unfold the ranges that hide any row in the `rows`.
+    pub(super) fn unfold_rows(&mut self, rows: Range<usize>) -> bool {
+        let len = self.folded.len();
+        self.folded
+            .retain(|range| !rows.clone().any(|row| range.hides(row)));
+        self.folded.len() != len
+    }
+
+    pub(super) fn fold_all(&mut self) {
+        self.folded = self.candidates.clone();
+    }
+
+    pub(super) fn unfold_all(&mut self) {
+        self.folded.clear();
+    }
+
+    /// Update the rows after the rows `start_row..=old_end_row` is replaced by `start_row..=new_end_row`.
+    ///
+    /// The folded ranges after the edit will be moved, the folded ranges touched by the edit
+    /// will be unfolded, except the edit is only in the header row.
+    ///
+    /// The candidates are moved in the same way, and the edited rows are marked as changed.
+    pub(super) fn edit(&mut self, start_row: usize, old_end_row: usize, new_end_row: usize) {
+        let delta = new_end_row as isize - old_end_row as isize;
+        let move_row = |row: usize| {
+            if row <= start_row {
+                row
+            } else if row > old_end_row {
+                row.saturating_add_signed(delta)
+            } else {
+                new_end_row
+            }
+        };
+
+        for range in self.candidates.iter_mut() {
+            range.start = move_row(range.start);
+            range.end = move_row(range.end);
+        }
+        if let Some(rows) = self.dirty_rows.take() {
+            self.dirty_rows = Some(move_row(rows.start)..move_row(rows.end.saturating_sub(1)) + 1);
+        }
+        self.invalidate_rows(start_row..new_end_row + 1);
+
+        self.folded.retain_mut(|range| {
+            if range.end < start_row {
+                true
+            } else if range.start > old_end_row {
+                range.start = range.start.saturating_add_signed(delta);
+                range.end = range.end.saturating_add_signed(delta);
+                true
+            } else {
+                range.start == start_row && old_end_row == start_row && new_end_row == start_row
+            }
+        });
+    }
+
+    /// Returns the nearest visible row from the `row` in the direction, the `row` itself if it
+    /// is visible.
+    ///
+    /// Returns None if there is no visible row in the direction.
+    pub(super) fn visible_row(&self, row: usize, forward: bool) -> Option<usize> {
+        let hidden = self.hidden_rows();
+        match hidden.iter().find(|rows| rows.contains(&row)) {
+            None => Some(row),
+            Some(rows) if forward => Some(rows.end),
+            Some(rows) => rows.start.checked_sub(1),
+        }
+    }
+}
+
+/// Returns the foldable ranges intersect with the `rows` from the syntax tree, the named nodes
+/// across multiple lines can be folded.
+///
+/// Only the nodes intersect with the `rows` are visited.
+pub(super) fn syntax_fold_ranges(tree: &Tree, rows: &Range<usize>) -> Vec<FoldRange> {
+    let mut ranges = vec![];
+    let mut cursor = tree.walk();
+    // Skip the root node, it always covers the entire text.
+    if cursor.goto_first_child() {
+        collect_fold_ranges(&mut cursor, rows, &mut ranges);
+    }
+
+    ranges.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+    // Keep the largest range of each start row.
+    ranges.dedup_by_key(|range| range.start);
+    ranges
+}
+
+fn collect_fold_ranges(cursor: &mut TreeCursor, rows: &Range<usize>, ranges: &mut Vec<FoldRange>) {
+    loop {
+        let node = cursor.node();
+        let start_row = node.start_position().row;
+        if start_row >= rows.end {
+            break;
+        }
+
+        let mut end_row = node.end_position().row;
+        // The node ends with the `\n`, e.g.: Markdown section.
+        if node.end_position().column == 0 {
+            end_row = end_row.saturating_sub(1);
+        }
+
+        if end_row > start_row && end_row >= rows.start {
+            if node.is_named() {
+                ranges.push(FoldRange::new(start_row, end_row));
+            }
+
+            if cursor.goto_first_child() {
+                collect_fold_ranges(cursor, rows, ranges);
+                cursor.goto_parent();
+            }
+        }
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+/// Returns the foldable ranges intersect with the `rows` by indentation, the rows followed by
+/// more indented rows can be folded.
+///
+/// The blank rows are ignored, the `tab_size` is used to count the width of the `\t`.
+///
+/// Only the rows between the not indented rows around the `rows` are visited, the ranges can't
+/// cross a not indented row.
+pub(super) fn indent_fold_ranges(
+    text: &Rope,
+    tab_size: usize,
+    rows: &Range<usize>,
+) -> Vec<FoldRange> {
+    let total_rows = text.len_lines();
+    let line_indent = |row: usize| -> Option<usize> {
+        let line = text.line_text(row);
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        Some(
+            line.chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { tab_size } else { 1 })
+                .sum::<usize>(),
+        )
+    };
+
+    let mut first_row = rows.start.min(total_rows.saturating_sub(1));
+    while first_row > 0 && line_indent(first_row) != Some(0) {
+        first_row -= 1;
+    }
+
+    let mut ranges = vec![];
+    // The stack of (row, indent) of the rows that may start a fold.
+    let mut stack: Vec<(usize, usize)> = vec![];
+    let mut last_row = first_row;
+
+    for row in first_row..total_rows {
+        let Some(indent) = line_indent(row) else {
+            continue;
+        };
+
+        while let Some(&(start, start_indent)) = stack.last() {
+            if start_indent < indent {
+                break;
+            }
+
+            stack.pop();
+            if last_row > start {
+                ranges.push(FoldRange::new(start, last_row));
+            }
+        }
+
+        // The stack is empty after a not indented row, the rest ranges start after the `rows`.
+        if indent == 0 && row >= rows.end {
+            break;
+        }
+
+        stack.push((row, indent));
+        last_row = row;
+    }
+
+    for (start, _) in stack {
+        if last_row > start {
+            ranges.push(FoldRange::new(start, last_row));
+        }
+    }
+
+    ranges.retain(|range| range.intersects(rows));
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::{indent_fold_ranges, FoldRange, FoldState};
+
+    fn ranges(items: &[(usize, usize)]) -> Vec<FoldRange> {
+        items
+            .iter()
+            .map(|(start, end)| FoldRange::new(*start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn test_indent_fold_ranges() {
+        let text = Rope::from_str(
+            "fn main() {\n    if true {\n        a();\n\n        b();\n    }\n}\nlet c = 1;\n",
+        );
+        assert_eq!(
+            indent_fold_ranges(&text, 4, &(0..usize::MAX)),
+            ranges(&[(0, 5), (1, 4)])
+        );
+
+        let text = Rope::from_str("a\n\tb\n\t\tc\nd");
+        assert_eq!(
+            indent_fold_ranges(&text, 4, &(0..usize::MAX)),
+            ranges(&[(0, 2), (1, 2)])
+        );
+        assert!(indent_fold_ranges(&Rope::from_str(""), 4, &(0..usize::MAX)).is_empty());
+    }
+
+    #[test]
+    fn test_indent_fold_ranges_in_rows() {
+        let text = Rope::from_str("a\n  b\n    c\n  d\ne\n  f\n    g\nh\n  i\n");
+        let all = indent_fold_ranges(&text, 4, &(0..usize::MAX));
+        assert_eq!(all, ranges(&[(0, 3), (1, 2), (4, 6), (5, 6), (7, 8)]));
+
+        for rows in [0..1, 2..3, 3..5, 5..6, 6..8, 8..9, 9..10] {
+            let expected = all
+                .iter()
+                .copied()
+                .filter(|range| range.start < rows.end && range.end >= rows.start)
+                .collect::<Vec<_>>();
+            assert_eq!(indent_fold_ranges(&text, 4, &rows), expected, "{:?}", rows);
+        }
+    }
+
+    #[test]
+    fn test_fold_and_unfold() {
+        let mut state = FoldState::new();
+        state.set_candidates(ranges(&[(0, 10), (2, 5), (6, 8)]));
+        assert!(!state.dirty);
+
+        assert_eq!(state.fold(2), Some(FoldRange::new(2, 5)));
+        assert_eq!(state.fold(2), None);
+        assert!(state.is_folded(2));
+        assert!(!state.is_hidden(2));
+        assert!(state.is_hidden(3));
+        assert!(state.is_hidden(5));
+        assert!(!state.is_hidden(6));
+
+        // Fold the innermost range contains the row.
+        assert_eq!(state.fold(7), Some(FoldRange::new(6, 8)));
+        assert_eq!(state.hidden_rows(), vec![3..6, 7..9]);
+        assert_eq!(state.visible_row(4, true), Some(6));
+        assert_eq!(state.visible_row(4, false), Some(2));
+        assert_eq!(state.visible_row(9, true), Some(9));
+
+        assert_eq!(state.fold(0), Some(FoldRange::new(0, 10)));
+        assert_eq!(state.hidden_rows(), vec![1..11]);
+        assert_eq!(state.visible_row(10, true), Some(11));
+
+        // Unfold the outer range keeps the nested folds.
+        assert!(state.unfold(0));
+        assert_eq!(state.hidden_rows(), vec![3..6, 7..9]);
+        assert!(!state.unfold(1));
+
+        state.fold_all();
+        assert_eq!(
+            state.folded(),
+            ranges(&[(0, 10), (2, 5), (6, 8)]).as_slice()
+        );
+        state.unfold_all();
+        assert!(!state.has_folded());
+    }
+
+    #[test]
+    fn test_fold_edit() {
+        let mut state = FoldState::new();
+        state.set_candidates(ranges(&[(2, 5), (8, 10), (12, 14)]));
+        state.fold(2);
+        state.fold(8);
+        state.fold(12);
+
+        // Insert 2 rows at row 6.
+        state.edit(6, 6, 8);
+        assert_eq!(
+            state.folded(),
+            ranges(&[(2, 5), (10, 12), (14, 16)]).as_slice()
+        );
+        assert_eq!(state.candidate_at(10), Some(FoldRange::new(10, 12)));
+        assert_eq!(state.dirty_rows(), Some(6..9));
+
+        // Edit in the header row keeps the fold.
+        state.edit(10, 10, 10);
+        assert_eq!(
+            state.folded(),
+            ranges(&[(2, 5), (10, 12), (14, 16)]).as_slice()
+        );
+
+        // Edit in the folded rows unfolds the range, remove 1 row.
+        state.edit(4, 5, 4);
+        assert_eq!(state.folded(), ranges(&[(9, 11), (13, 15)]).as_slice());
+
+        assert!(state.unfold_rows(10..11));
+        assert_eq!(state.folded(), ranges(&[(13, 15)]).as_slice());
+        assert_eq!(state.dirty_rows(), Some(4..10));
+
+        // Only the candidates in the changed rows are replaced.
+        state.update_candidates(4..10, ranges(&[(4, 6)]));
+        assert_eq!(state.dirty_rows(), None);
+        assert_eq!(state.candidate_at(2), None);
+        assert_eq!(state.candidate_at(4), Some(FoldRange::new(4, 6)));
+        assert_eq!(state.candidate_at(9), None);
+        assert_eq!(state.candidate_at(13), Some(FoldRange::new(13, 15)));
+    }
+}
@@ -17,6 +17,8 @@ pub struct DiagnosticPopover {
     pub(super) marker: Rc<Marker>,
     bounds: Bounds<Pixels>,
     open: bool,
+    /// Show the hover contents (e.g.: from the language server) instead of a diagnostic.
+    hover: bool,
 }
 
 impl DiagnosticPopover {
@@ -28,9 +30,38 @@ impl DiagnosticPopover {
             state,
             bounds: Bounds::default(),
             open: true,
+            hover: false,
         })
     }
 
+    /// Create a popover to show the hover `message` (in Markdown) of the `range`.
+    #[cfg(feature = "lsp")]
+    pub(super) fn hover(
+        range: std::ops::Range<usize>,
+        message: impl Into<gpui::SharedString>,
+        state: Entity<InputState>,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let marker = Rc::new(Marker {
+            range: Some(range),
+            message: message.into(),
+            ..Default::default()
+        });
+
+        cx.new(|_| Self {
+            marker,
+            state,
+            bounds: Bounds::default(),
+            open: true,
+            hover: true,
+        })
+    }
+
+    #[cfg(feature = "lsp")]
+    pub(super) fn is_hover(&self) -> bool {
+        self.hover
+    }
+
     fn origin(&self, cx: &App) -> Option<Point<Pixels>> {
         let Some(range) = self.marker.range.as_ref() else {
             return None;
@@ -85,11 +116,19 @@ impl Render for DiagnosticPopover {
         let Some(pos) = self.origin(cx) else {
             return Empty.into_any_element();
         };
-        let (border, bg, fg) = (
-            self.marker.severity.border(theme),
-            self.marker.severity.bg(theme),
-            self.marker.severity.fg(theme),
-        );
+        let (border, bg, fg) = if self.hover {
+            (
+                cx.theme().border,
+                cx.theme().popover,
+                cx.theme().popover_foreground,
+            )
+        } else {
+            (
+                self.marker.severity.border(theme),
+                self.marker.severity.bg(theme),
+                self.marker.severity.fg(theme),
+            )
+        };
 
         let scroll_origin = self.state.read(cx).scroll_handle.offset();
 
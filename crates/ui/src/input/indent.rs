@@ -0,0 +1,199 @@
+//! The language-aware auto indentation of the code editor.
+//!
+//! The indent patterns are configured by [`LanguageConfig::indent_patterns`]:
+//!
+//! - Indent the new line when press Enter after the text matched the increase pattern, e.g.: `{`.
+//! - Move the closing text to another line when press Enter between the brackets, e.g.: `{|}`.
+//! - Outdent the line when typing makes it match the decrease pattern, e.g.: `}`.
+//!
+//! [`LanguageConfig::indent_patterns`]: crate::highlighter::LanguageConfig::indent_patterns
+use std::ops::Range;
+
+use gpui::{App, Context, EntityInputHandler as _, Window};
+
+use super::{InputState, RopeExt as _};
+use crate::highlighter::{IndentRules, LanguageRegistry};
+
+impl InputState {
+    fn with_indent_rules<R>(&self, cx: &App, f: impl FnOnce(&IndentRules, &str) -> R) -> Option<R> {
+        let language = self.mode.language()?;
+        let tab = self.mode.tab_size()?.to_string();
+        let rules = LanguageRegistry::global(cx).indent_rules(language)?;
+        Some(f(rules, &tab))
+    }
+
+    /// Returns the text to insert for the new line, and the cursor offset in the text.
+    ///
+    /// Returns `None` if the language has no indent patterns.
+    pub(super) fn auto_indent_new_line(&self, cx: &App) -> Option<(String, usize)> {
+        let range: Range<usize> = self.selected_range.into();
+        let start_row = self.text.byte_to_line(range.start);
+        let end_row = self.text.byte_to_line(range.end);
+        let before = self
+            .text
+            .slice_text(self.text.line_start_offset(start_row)..range.start);
+        let after = self
+            .text
+            .slice_text(range.end..self.text.line_end_offset(end_row));
+
+        self.with_indent_rules(cx, |rules, tab| new_line_text(&before, &after, rules, tab))
+    }
+
+    /// Outdent the line if the typed `new_text` makes it match the decrease pattern, returns
+    /// true if it is handled.
+    pub(super) fn handle_indent_input(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if new_text.chars().count() != 1 || !self.selected_range.is_empty() {
+            return false;
+        }
+
+        let offset = self.cursor().offset;
+        let row = self.text.byte_to_line(offset);
+        let line_start = self.text.line_start_offset(row);
+        let before = self.text.slice_text(line_start..offset);
+        let after = self.text.slice_text(offset..self.text.line_end_offset(row));
+        let prev_indent = (0..row)
+            .rev()
+            .map(|row| self.text.line_text(row))
+            .find(|line| !line.trim().is_empty())
+            .map(|line| indent_of(&line).to_string())
+            .unwrap_or_default();
+
+        let Some(Some(indent)) = self.with_indent_rules(cx, |rules, tab| {
+            outdent_for_input(&before, new_text, &after, &prev_indent, rules, tab)
+        }) else {
+            return false;
+        };
+
+        let new_text = format!("{}{}{}", indent, before.trim_start(), new_text);
+        let range = line_start..offset;
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        true
+    }
+}
+
+/// Returns the leading whitespace of the `line`.
+fn indent_of(line: &str) -> &str {
+    let len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..len]
+}
+
+/// Returns the text to insert for the new line between `before` and `after` (the text of the
+/// line before and after the cursor), and the cursor offset in the text.
+fn new_line_text(before: &str, after: &str, rules: &IndentRules, tab: &str) -> (String, usize) {
+    let indent = indent_of(before);
+    if !rules.increase.is_match(before) {
+        let text = format!("\n{}", indent);
+        let len = text.len();
+        return (text, len);
+    }
+
+    let inner = format!("\n{}{}", indent, tab);
+    let cursor = inner.len();
+    if rules.decrease.is_match(after) {
+        (format!("{}\n{}", inner, indent), cursor)
+    } else {
+        (inner, cursor)
+    }
+}
+
+/// Returns the new indent of the line, if typing `c` makes the line match the decrease pattern.
+///
+/// The line is only outdented once, it is not outdented if its indent is already less than
+/// the `prev_indent` (the indent of the previous non-blank line).
+fn outdent_for_input(
+    before: &str,
+    c: &str,
+    after: &str,
+    prev_indent: &str,
+    rules: &IndentRules,
+    tab: &str,
+) -> Option<String> {
+    let indent = indent_of(before);
+    if indent.is_empty() || indent.len() < prev_indent.len() {
+        return None;
+    }
+
+    let old_line = format!("{}{}", before, after);
+    let new_line = format!("{}{}{}", before, c, after);
+    if rules.decrease.is_match(&old_line) || !rules.decrease.is_match(&new_line) {
+        return None;
+    }
+
+    let new_indent = match indent.strip_suffix(tab) {
+        Some(new_indent) => new_indent,
+        // Mixed tabs and spaces, remove the last one.
+        None => &indent[..indent.len() - 1],
+    };
+    Some(new_indent.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::{new_line_text, outdent_for_input};
+    use crate::highlighter::IndentRules;
+
+    fn rules() -> IndentRules {
+        IndentRules {
+            increase: Regex::new(r"[\{\[\(]\s*$").unwrap(),
+            decrease: Regex::new(r"^\s*[\}\]\)]").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_new_line_text() {
+        let rules = rules();
+        assert_eq!(
+            new_line_text("    let a = 1;", "", &rules, "  "),
+            ("\n    ".into(), 5)
+        );
+        assert_eq!(
+            new_line_text("  fn foo() {", "", &rules, "  "),
+            ("\n    ".into(), 5)
+        );
+        assert_eq!(
+            new_line_text("  fn foo() {", "}", &rules, "  "),
+            ("\n    \n  ".into(), 5)
+        );
+        assert_eq!(
+            new_line_text("\tfoo(", ")", &rules, "\t"),
+            ("\n\t\t\n\t".into(), 3)
+        );
+        assert_eq!(new_line_text("", "}", &rules, "  "), ("\n".into(), 1));
+    }
+
+    #[test]
+    fn test_outdent_for_input() {
+        let rules = rules();
+        assert_eq!(
+            outdent_for_input("    ", "}", "", "    ", &rules, "  "),
+            Some("  ".into())
+        );
+        assert_eq!(
+            outdent_for_input("\t\t", "]", "", "\t\t", &rules, "\t"),
+            Some("\t".into())
+        );
+        // Not matched, or already matched before typing.
+        assert_eq!(
+            outdent_for_input("    ", "a", "", "    ", &rules, "  "),
+            None
+        );
+        assert_eq!(
+            outdent_for_input("    }", ";", "", "    ", &rules, "  "),
+            None
+        );
+        assert_eq!(
+            outdent_for_input("    ", "}", ")", "    ", &rules, "  "),
+            None
+        );
+        // Already outdented, or no indent.
+        assert_eq!(outdent_for_input("  ", "}", "", "    ", &rules, "  "), None);
+        assert_eq!(outdent_for_input("", "}", "", "", &rules, "  "), None);
+    }
+}
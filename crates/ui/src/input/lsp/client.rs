@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use lsp_types::{notification::Notification, request::Request};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use smol::{
+    channel::{self, Receiver, Sender},
+    io::{AsyncBufRead, AsyncBufReadExt as _, AsyncRead, AsyncReadExt as _, AsyncWrite},
+    io::{AsyncWriteExt as _, BufReader},
+};
+
+const CONTENT_LENGTH: &str = "Content-Length";
+
+/// A notification sent by the language server, e.g.: `textDocument/publishDiagnostics`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerNotification {
+    pub method: String,
+    pub params: Value,
+}
+
+impl ServerNotification {
+    /// Parse the params if the method of this notification is `N::METHOD`.
+    pub fn parse<N: Notification>(&self) -> Option<N::Params> {
+        if self.method != N::METHOD {
+            return None;
+        }
+
+        serde_json::from_value(self.params.clone()).ok()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+/// The JSON-RPC message from the server, may be a response, a request or a notification.
+#[derive(Debug, Deserialize)]
+struct IncomingMessage {
+    id: Option<Value>,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    result: Option<Value>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Serialize)]
+struct OutgoingMessage<'a> {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
+    method: &'a str,
+    /// The `null` params is omitted, e.g.: `shutdown` request.
+    #[serde(skip_serializing_if = "Value::is_null")]
+    params: Value,
+}
+
+type PendingRequests = Arc<Mutex<HashMap<i64, Sender<Result<Value>>>>>;
+
+/// A JSON-RPC client of the Language Server Protocol, talks to the server over a pair of streams
+/// (usually the stdin and stdout of the server process).
+///
+/// The client is cheap to clone, all clones share the same connection.
+#[derive(Clone)]
+pub struct LspClient {
+    outgoing: Sender<String>,
+    pending: PendingRequests,
+    next_id: Arc<AtomicI64>,
+}
+
+impl LspClient {
+    /// Create a client writes to the `input` and reads from the `output` of the server.
+    ///
+    /// Returns the client, the receiver of the server notifications, and the IO future that
+    /// must be spawned to drive the connection, it is finished when the server closes the output.
+    pub fn new(
+        input: impl AsyncWrite + Unpin + Send + 'static,
+        output: impl AsyncRead + Unpin + Send + 'static,
+    ) -> (
+        Self,
+        Receiver<ServerNotification>,
+        impl Future<Output = Result<()>> + Send + 'static,
+    ) {
+        let (outgoing, outgoing_rx) = channel::unbounded();
+        let (notification_tx, notification_rx) = channel::unbounded();
+        let pending = PendingRequests::default();
+
+        let client = Self {
+            outgoing: outgoing.clone(),
+            pending: pending.clone(),
+            next_id: Arc::new(AtomicI64::new(1)),
+        };
+
+        let io = async move {
+            let write = write_loop(input, outgoing_rx);
+            let read = read_loop(output, outgoing, pending.clone(), notification_tx);
+            let result = smol::future::or(read, write).await;
+
+            // Fail all the pending requests, the server has gone.
+            for (_, tx) in pending.lock().unwrap().drain() {
+                _ = tx.try_send(Err(anyhow!("language server connection closed")));
+            }
+            result
+        };
+
+        (client, notification_rx, io)
+    }
+
+    /// Send a request to the server and wait for the response.
+    pub async fn request<R: Request>(&self, params: R::Params) -> Result<R::Result> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = channel::bounded(1);
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let message = serde_json::to_string(&OutgoingMessage {
+            jsonrpc: "2.0",
+            id: Some(id),
+            method: R::METHOD,
+            params: serde_json::to_value(params)?,
+        })?;
+        if self.outgoing.send(message).await.is_err() {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(anyhow!("language server connection closed"));
+        }
+
+        let result = rx
+            .recv()
+            .await
+            .map_err(|_| anyhow!("language server connection closed"))??;
+        serde_json::from_value(result).with_context(|| format!("invalid {} response", R::METHOD))
+    }
+
+    /// Send a notification to the server.
+    ///
+    /// The messages are sent in the order of calls, so it is safe to send the document changes
+    /// one by one without waiting.
+    pub fn notify<N: Notification>(&self, params: N::Params) -> Result<()> {
+        let message = serde_json::to_string(&OutgoingMessage {
+            jsonrpc: "2.0",
+            id: None,
+            method: N::METHOD,
+            params: serde_json::to_value(params)?,
+        })?;
+        self.outgoing
+            .try_send(message)
+            .map_err(|_| anyhow!("language server connection closed"))
+    }
+}
+
+async fn write_loop(input: impl AsyncWrite + Unpin, outgoing: Receiver<String>) -> Result<()> {
+    let mut input = input;
+    while let Ok(message) = outgoing.recv().await {
+        write_message(&mut input, &message).await?;
+    }
+    Ok(())
+}
+
+async fn read_loop(
+    output: impl AsyncRead + Unpin,
+    outgoing: Sender<String>,
+    pending: PendingRequests,
+    notifications: Sender<ServerNotification>,
+) -> Result<()> {
+    let mut output = BufReader::new(output);
+    while let Some(content) = read_message(&mut output).await? {
+        let message: IncomingMessage = match serde_json::from_str(&content) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::warn!("invalid language server message: {}, {}", err, content);
+                continue;
+            }
+        };
+
+        match (message.id, message.method) {
+            // Response of our request.
+            (Some(id), None) => {
+                let Some(tx) = id
+                    .as_i64()
+                    .and_then(|id| pending.lock().unwrap().remove(&id))
+                else {
+                    continue;
+                };
+
+                let result = match message.error {
+                    Some(err) => Err(anyhow!("{} (code: {})", err.message, err.code)),
+                    None => Ok(message.result.unwrap_or(Value::Null)),
+                };
+                _ = tx.try_send(result);
+            }
+            // Request from the server, we don't support any of them, but the server may wait
+            // for the response, e.g.: `workspace/configuration`, `window/workDoneProgress/create`.
+            (Some(id), Some(method)) => {
+                let result = match method.as_str() {
+                    "workspace/configuration" => {
+                        let len = message.params["items"].as_array().map_or(0, |v| v.len());
+                        Value::Array(vec![Value::Null; len])
+                    }
+                    _ => Value::Null,
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                _ = outgoing.send(response.to_string()).await;
+            }
+            (None, Some(method)) => {
+                _ = notifications
+                    .send(ServerNotification {
+                        method,
+                        params: message.params,
+                    })
+                    .await;
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a message content with the `Content-Length` header.
+///
+/// Returns None if the stream is closed.
+pub(super) async fn read_message(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Option<String>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case(CONTENT_LENGTH) {
+                content_length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+
+    let len = content_length.ok_or_else(|| anyhow!("missing {} header", CONTENT_LENGTH))?;
+    let mut content = vec![0; len];
+    reader.read_exact(&mut content).await?;
+    Ok(Some(String::from_utf8(content)?))
+}
+
+/// Write the message content with the `Content-Length` header.
+pub(super) async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    content: &str,
+) -> Result<()> {
+    let header = format!("{}: {}\r\n\r\n", CONTENT_LENGTH, content.len());
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(content.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use smol::io::{BufReader, Cursor};
+
+    use super::{read_message, write_message};
+
+    #[test]
+    fn test_read_write_message() {
+        smol::block_on(async {
+            let mut buf = Cursor::new(vec![]);
+            write_message(&mut buf, r#"{"id":1}"#).await.unwrap();
+            write_message(&mut buf, r#"{"text":"你好"}"#).await.unwrap();
+            assert!(String::from_utf8_lossy(buf.get_ref()).starts_with("Content-Length: 8\r\n\r\n"));
+
+            let mut reader = BufReader::new(Cursor::new(buf.into_inner()));
+            assert_eq!(
+                read_message(&mut reader).await.unwrap().as_deref(),
+                Some(r#"{"id":1}"#)
+            );
+            assert_eq!(
+                read_message(&mut reader).await.unwrap().as_deref(),
+                Some(r#"{"text":"你好"}"#)
+            );
+            assert_eq!(read_message(&mut reader).await.unwrap(), None);
+
+            // The header name is case insensitive, and the other headers are ignored.
+            let content = "content-length: 2\r\nContent-Type: utf-8\r\n\r\n{}";
+            let mut reader = BufReader::new(Cursor::new(content.as_bytes().to_vec()));
+            assert_eq!(
+                read_message(&mut reader).await.unwrap().as_deref(),
+                Some("{}")
+            );
+
+            let mut reader = BufReader::new(Cursor::new(b"\r\n{}".to_vec()));
+            assert!(read_message(&mut reader).await.is_err());
+        });
+    }
+}
@@ -0,0 +1,395 @@
+use std::{ops::Range, path::Path};
+
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, Documentation, HoverContents, MarkedString, ParameterLabel,
+    Position, ServerCapabilities, SignatureHelp, TextDocumentContentChangeEvent,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use ropey::Rope;
+
+use crate::input::{Marker, MarkerSeverity, RopeExt as _};
+
+/// Convert the byte `offset` to the LSP position, the character is in UTF-16 code units.
+pub(super) fn offset_to_position(text: &Rope, offset: usize) -> Position {
+    let offset = text.clip_offset(offset);
+    let row = text.byte_to_line(offset);
+    let line_start = text.line_start_offset(row);
+    let character = text.offset_to_utf16(offset) - text.offset_to_utf16(line_start);
+
+    Position::new(row as u32, character as u32)
+}
+
+/// Convert the LSP position to the byte offset, the position out of the line will be clipped to
+/// the line end.
+pub(super) fn position_to_offset(text: &Rope, position: Position) -> usize {
+    let row = position.line as usize;
+    if row >= text.len_lines() {
+        return text.len_bytes();
+    }
+
+    let line_start = text.line_start_offset(row);
+    let line_end = text.line_end_offset(row);
+    let offset =
+        text.offset_from_utf16(text.offset_to_utf16(line_start) + position.character as usize);
+    offset.min(line_end)
+}
+
+/// Convert the LSP range to the byte range.
+pub(super) fn range_to_offsets(text: &Rope, range: lsp_types::Range) -> Range<usize> {
+    let start = position_to_offset(text, range.start);
+    let end = position_to_offset(text, range.end);
+    start..end.max(start)
+}
+
+/// Convert the byte range to the LSP range.
+pub(super) fn offsets_to_range(text: &Rope, range: &Range<usize>) -> lsp_types::Range {
+    lsp_types::Range::new(
+        offset_to_position(text, range.start),
+        offset_to_position(text, range.end),
+    )
+}
+
+/// Convert the absolute `path` to the `file://` URI.
+pub(super) fn path_to_uri(path: &Path) -> Option<Uri> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // The Windows path starts with the drive letter, e.g.: `file:///C:/foo`.
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri.parse().ok()
+}
+
+pub(super) fn marker_severity(severity: Option<DiagnosticSeverity>) -> MarkerSeverity {
+    match severity {
+        Some(DiagnosticSeverity::WARNING) => MarkerSeverity::Warning,
+        Some(DiagnosticSeverity::INFORMATION) => MarkerSeverity::Info,
+        Some(DiagnosticSeverity::HINT) => MarkerSeverity::Hint,
+        // The client decides the missing severity, treat it as error like the most editors.
+        _ => MarkerSeverity::Error,
+    }
+}
+
+/// Convert the diagnostic to the marker of the code editor.
+pub(super) fn diagnostic_to_marker(text: &Rope, diagnostic: &Diagnostic) -> Marker {
+    let range = range_to_offsets(text, diagnostic.range);
+    let (start_row, start_column) = text.offset_to_row_column(range.start);
+    let (end_row, end_column) = text.offset_to_row_column(range.end);
+    Marker::new(
+        marker_severity(diagnostic.severity),
+        (start_row + 1, start_column + 1),
+        (end_row + 1, end_column + 1),
+        diagnostic.message.clone(),
+    )
+}
+
+/// Returns how the server wants the document changes, the missing capability means
+/// [`TextDocumentSyncKind::NONE`].
+pub(super) fn sync_kind(capabilities: Option<&ServerCapabilities>) -> TextDocumentSyncKind {
+    match capabilities.and_then(|capabilities| capabilities.text_document_sync.as_ref()) {
+        Some(TextDocumentSyncCapability::Kind(kind)) => *kind,
+        Some(TextDocumentSyncCapability::Options(options)) => {
+            options.change.unwrap_or(TextDocumentSyncKind::NONE)
+        }
+        None => TextDocumentSyncKind::NONE,
+    }
+}
+
+/// Returns the content change of replacing the `range` in the `text` with the `new_text`.
+///
+/// - [`TextDocumentSyncKind::FULL`]: the entire new text.
+/// - [`TextDocumentSyncKind::INCREMENTAL`]: the `range` and the `new_text`.
+/// - [`TextDocumentSyncKind::NONE`]: None, the changes should not be sent.
+pub(super) fn content_change(
+    sync_kind: TextDocumentSyncKind,
+    text: &Rope,
+    range: &Range<usize>,
+    new_text: &str,
+) -> Option<TextDocumentContentChangeEvent> {
+    if sync_kind == TextDocumentSyncKind::FULL {
+        let mut full_text = text.slice_text(0..range.start);
+        full_text.push_str(new_text);
+        full_text.push_str(&text.slice_text(range.end..text.len_bytes()));
+        return Some(TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: full_text,
+        });
+    }
+
+    if sync_kind == TextDocumentSyncKind::INCREMENTAL {
+        return Some(TextDocumentContentChangeEvent {
+            range: Some(offsets_to_range(text, range)),
+            range_length: None,
+            text: new_text.to_string(),
+        });
+    }
+
+    None
+}
+
+/// Convert the hover contents to Markdown.
+pub(super) fn hover_to_markdown(contents: HoverContents) -> String {
+    fn marked_string(value: MarkedString) -> String {
+        match value {
+            MarkedString::String(text) => text,
+            MarkedString::LanguageString(code) => {
+                format!("```{}\n{}\n```", code.language, code.value)
+            }
+        }
+    }
+
+    match contents {
+        HoverContents::Scalar(value) => marked_string(value),
+        HoverContents::Array(values) => values
+            .into_iter()
+            .map(marked_string)
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n"),
+        HoverContents::Markup(content) => content.value,
+    }
+}
+
+/// The active signature of the signature help.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ActiveSignature {
+    pub(super) label: String,
+    /// The byte range of the active parameter in the `label`.
+    pub(super) parameter: Option<Range<usize>>,
+    /// The documentation in Markdown.
+    pub(super) documentation: Option<String>,
+}
+
+/// Returns the active signature of the signature help, None if there is no signature.
+pub(super) fn active_signature(help: &SignatureHelp) -> Option<ActiveSignature> {
+    let signature = help
+        .signatures
+        .get(help.active_signature.unwrap_or(0) as usize)
+        .or(help.signatures.first())?;
+
+    let label = &signature.label;
+    let parameter = signature
+        .active_parameter
+        .or(help.active_parameter)
+        .and_then(|ix| signature.parameters.as_ref()?.get(ix as usize))
+        .and_then(|parameter| match &parameter.label {
+            ParameterLabel::Simple(name) => label
+                .find(name.as_str())
+                .map(|start| start..start + name.len()),
+            // The offsets are in UTF-16 code units.
+            ParameterLabel::LabelOffsets([start, end]) => {
+                let mut units = 0;
+                let mut start_ix = None;
+                let mut end_ix = None;
+                for (ix, c) in label.char_indices().chain([(label.len(), ' ')]) {
+                    if units == *start as usize {
+                        start_ix.get_or_insert(ix);
+                    }
+                    if units == *end as usize {
+                        end_ix.get_or_insert(ix);
+                    }
+                    units += c.len_utf16();
+                }
+                Some(start_ix?..end_ix?)
+            }
+        });
+
+    let documentation = signature
+        .documentation
+        .as_ref()
+        .map(|documentation| match documentation {
+            Documentation::String(text) => text.clone(),
+            Documentation::MarkupContent(content) => content.value.clone(),
+        });
+
+    Some(ActiveSignature {
+        label: label.clone(),
+        parameter,
+        documentation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lsp_types::{
+        Diagnostic, DiagnosticSeverity, Documentation, HoverContents, LanguageString, MarkedString,
+        ParameterInformation, ParameterLabel, Position, ServerCapabilities, SignatureHelp,
+        SignatureInformation, TextDocumentSyncCapability, TextDocumentSyncKind,
+        TextDocumentSyncOptions,
+    };
+    use ropey::Rope;
+
+    use super::{
+        active_signature, content_change, diagnostic_to_marker, hover_to_markdown,
+        offset_to_position, path_to_uri, position_to_offset, sync_kind,
+    };
+    use crate::input::MarkerSeverity;
+
+    #[test]
+    fn test_position_offset() {
+        let text = Rope::from_str("fn main() {\n    let 你好 = \"😀\";\n}");
+        assert_eq!(offset_to_position(&text, 0), Position::new(0, 0));
+        assert_eq!(offset_to_position(&text, 11), Position::new(0, 11));
+        assert_eq!(offset_to_position(&text, 12), Position::new(1, 0));
+        // `你` is 3 bytes and 1 UTF-16 code unit, `😀` is 4 bytes and 2 UTF-16 code units.
+        assert_eq!(offset_to_position(&text, 26), Position::new(1, 10));
+        assert_eq!(offset_to_position(&text, 30), Position::new(1, 14));
+        assert_eq!(offset_to_position(&text, 34), Position::new(1, 16));
+        assert_eq!(offset_to_position(&text, 999), Position::new(2, 1));
+
+        assert_eq!(position_to_offset(&text, Position::new(0, 0)), 0);
+        assert_eq!(position_to_offset(&text, Position::new(1, 10)), 26);
+        assert_eq!(position_to_offset(&text, Position::new(1, 16)), 34);
+        // Out of the line or the text.
+        assert_eq!(position_to_offset(&text, Position::new(0, 99)), 11);
+        assert_eq!(
+            position_to_offset(&text, Position::new(9, 0)),
+            text.len_bytes()
+        );
+    }
+
+    #[test]
+    fn test_path_to_uri() {
+        let uri = path_to_uri(Path::new("/tmp/my project/main.rs")).unwrap();
+        assert_eq!(uri.as_str(), "file:///tmp/my%20project/main.rs");
+        let uri = path_to_uri(Path::new("C:\\Users\\你好.rs")).unwrap();
+        assert_eq!(uri.as_str(), "file:///C:/Users/%E4%BD%A0%E5%A5%BD.rs");
+    }
+
+    #[test]
+    fn test_hover_to_markdown() {
+        let contents = HoverContents::Array(vec![
+            MarkedString::LanguageString(LanguageString {
+                language: "rust".into(),
+                value: "fn main()".into(),
+            }),
+            MarkedString::String("The entry.".into()),
+        ]);
+        assert_eq!(
+            hover_to_markdown(contents),
+            "```rust\nfn main()\n```\n\n---\n\nThe entry."
+        );
+    }
+
+    #[test]
+    fn test_active_signature() {
+        let parameter = |label: ParameterLabel| ParameterInformation {
+            label,
+            documentation: None,
+        };
+        let mut help = SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: "fn 你好(a: i32, b: i32)".into(),
+                documentation: Some(Documentation::String("Say hello.".into())),
+                parameters: Some(vec![
+                    parameter(ParameterLabel::LabelOffsets([6, 12])),
+                    parameter(ParameterLabel::Simple("b: i32".into())),
+                ]),
+                active_parameter: None,
+            }],
+            active_signature: None,
+            active_parameter: Some(0),
+        };
+
+        let signature = active_signature(&help).unwrap();
+        assert_eq!(signature.parameter, Some(10..16));
+        assert_eq!(&signature.label[10..16], "a: i32");
+        assert_eq!(signature.documentation.as_deref(), Some("Say hello."));
+
+        help.active_parameter = Some(1);
+        assert_eq!(active_signature(&help).unwrap().parameter, Some(18..24));
+        help.active_parameter = Some(2);
+        assert_eq!(active_signature(&help).unwrap().parameter, None);
+
+        help.signatures.clear();
+        assert_eq!(active_signature(&help), None);
+    }
+
+    #[test]
+    fn test_content_change() {
+        let text = Rope::from_str("let 你好 = 1;\nlet b = 2;");
+        // Replace `你好` with `a`.
+        let range = 4..10;
+
+        let change = content_change(TextDocumentSyncKind::INCREMENTAL, &text, &range, "a").unwrap();
+        assert_eq!(
+            change.range,
+            Some(lsp_types::Range::new(
+                Position::new(0, 4),
+                Position::new(0, 6)
+            ))
+        );
+        assert_eq!(change.text, "a");
+
+        let change = content_change(TextDocumentSyncKind::FULL, &text, &range, "a").unwrap();
+        assert_eq!(change.range, None);
+        assert_eq!(change.text, "let a = 1;\nlet b = 2;");
+
+        assert_eq!(
+            content_change(TextDocumentSyncKind::NONE, &text, &range, "a"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sync_kind() {
+        assert_eq!(sync_kind(None), TextDocumentSyncKind::NONE);
+
+        let mut capabilities = ServerCapabilities::default();
+        assert_eq!(sync_kind(Some(&capabilities)), TextDocumentSyncKind::NONE);
+
+        capabilities.text_document_sync =
+            Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL));
+        assert_eq!(sync_kind(Some(&capabilities)), TextDocumentSyncKind::FULL);
+
+        capabilities.text_document_sync = Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                ..Default::default()
+            },
+        ));
+        assert_eq!(
+            sync_kind(Some(&capabilities)),
+            TextDocumentSyncKind::INCREMENTAL
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_to_marker() {
+        let text = Rope::from_str("fn main() {\n    let 你好 = \"😀\";\n}");
+        let diagnostic = Diagnostic {
+            range: lsp_types::Range::new(Position::new(1, 8), Position::new(1, 10)),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message: "unused variable".into(),
+            ..Default::default()
+        };
+
+        let marker = diagnostic_to_marker(&text, &diagnostic);
+        assert_eq!(marker.severity, MarkerSeverity::Warning);
+        assert_eq!((marker.start.line, marker.start.column), (2, 9));
+        assert_eq!((marker.end.line, marker.end.column), (2, 11));
+        assert_eq!(marker.message, "unused variable");
+
+        // The range out of the text is clipped to the end.
+        let diagnostic = Diagnostic {
+            range: lsp_types::Range::new(Position::new(2, 0), Position::new(9, 0)),
+            ..Default::default()
+        };
+        let marker = diagnostic_to_marker(&text, &diagnostic);
+        assert_eq!(marker.severity, MarkerSeverity::Error);
+        assert_eq!((marker.start.line, marker.start.column), (3, 1));
+        assert_eq!((marker.end.line, marker.end.column), (3, 2));
+    }
+}
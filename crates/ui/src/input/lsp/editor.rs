@@ -0,0 +1,436 @@
+use std::{ops::Range, rc::Rc, time::Duration};
+
+use gpui::{App, AppContext as _, Context, Entity, Subscription, Task, Window};
+use lsp_types::{
+    notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument},
+    request::{GotoDefinition, HoverRequest, SignatureHelpRequest},
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, HoverParams, HoverProviderCapability, Location,
+    OneOf, SignatureHelpParams, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, Uri, VersionedTextDocumentIdentifier,
+};
+use ropey::Rope;
+use smol::Timer;
+
+use super::{
+    convert, signature_help::SignatureHelpPopover, GoToDefinition, LanguageServer,
+    LanguageServerEvent, LspClient,
+};
+use crate::input::{hover_popover::DiagnosticPopover, InputState};
+
+/// The delay to send the hover request after the mouse stopped.
+const HOVER_DELAY: Duration = Duration::from_millis(300);
+
+type DefinitionHandler = Rc<dyn Fn(&[Location], &mut Window, &mut App)>;
+
+/// The language server state of a code editor.
+pub(crate) struct LspState {
+    server: Entity<LanguageServer>,
+    client: LspClient,
+    uri: Uri,
+    version: i32,
+    /// Whether the `textDocument/didOpen` has been sent, it will be sent after the server
+    /// initialized.
+    opened: bool,
+    /// The offset of the pending hover request.
+    hover_offset: Option<usize>,
+    hover_task: Task<()>,
+    signature_help_task: Task<()>,
+    pub(crate) signature_help: Option<Entity<SignatureHelpPopover>>,
+    definition_handler: Option<DefinitionHandler>,
+    _subscription: Subscription,
+}
+
+impl Drop for LspState {
+    fn drop(&mut self) {
+        if self.opened {
+            _ = self
+                .client
+                .notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+                    text_document: TextDocumentIdentifier::new(self.uri.clone()),
+                });
+        }
+    }
+}
+
+impl LspState {
+    fn position_params(&self, text: &Rope, offset: usize) -> TextDocumentPositionParams {
+        TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(self.uri.clone()),
+            convert::offset_to_position(text, offset),
+        )
+    }
+}
+
+impl InputState {
+    /// Attach the code editor to the language server, the `uri` is the URI of the document.
+    ///
+    /// Only for the [`InputState::code_editor`], the previous attached language server will be
+    /// detached with `textDocument/didClose`.
+    pub fn set_language_server(
+        &mut self,
+        server: &Entity<LanguageServer>,
+        uri: Uri,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        self.lsp = Some(LspState {
+            client: server.read(cx).client().clone(),
+            server: server.clone(),
+            uri,
+            version: 0,
+            opened: false,
+            hover_offset: None,
+            hover_task: Task::ready(()),
+            signature_help_task: Task::ready(()),
+            signature_help: None,
+            definition_handler: self
+                .lsp
+                .take()
+                .and_then(|lsp| lsp.definition_handler.clone()),
+            _subscription: cx.subscribe_in(server, window, Self::on_language_server_event),
+        });
+
+        if server.read(cx).is_initialized() {
+            self.open_document(cx);
+        }
+    }
+
+    /// Detach the code editor from the language server.
+    pub fn remove_language_server(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.lsp.take().is_some() {
+            self.set_markers(vec![], window, cx);
+            cx.notify();
+        }
+    }
+
+    /// Set the handler of the go to definition, the handler will be called with the locations
+    /// of the definition.
+    ///
+    /// If there is no handler, only the definition in the same document can be jumped to.
+    /// The handler is kept when the language server is changed, so call this after
+    /// [`InputState::set_language_server`].
+    pub fn on_definition(
+        &mut self,
+        handler: impl Fn(&[Location], &mut Window, &mut App) + 'static,
+    ) {
+        if let Some(lsp) = self.lsp.as_mut() {
+            lsp.definition_handler = Some(Rc::new(handler));
+        }
+    }
+
+    fn open_document(&mut self, cx: &mut Context<Self>) {
+        let Some(lsp) = self.lsp.as_mut() else {
+            return;
+        };
+
+        let language_id = self
+            .mode
+            .language()
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        _ = lsp
+            .client
+            .notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    lsp.uri.clone(),
+                    language_id,
+                    lsp.version,
+                    self.text.to_string(),
+                ),
+            });
+        lsp.opened = true;
+        cx.notify();
+    }
+
+    fn on_language_server_event(
+        &mut self,
+        _: &Entity<LanguageServer>,
+        event: &LanguageServerEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(lsp) = self.lsp.as_ref() else {
+            return;
+        };
+
+        match event {
+            LanguageServerEvent::Initialized => {
+                if !lsp.opened {
+                    self.open_document(cx);
+                }
+            }
+            LanguageServerEvent::Diagnostics(params) => {
+                if params.uri != lsp.uri {
+                    return;
+                }
+                // Ignore the outdated diagnostics, the text has been changed.
+                if params
+                    .version
+                    .map_or(false, |version| version != lsp.version)
+                {
+                    return;
+                }
+
+                let markers = params
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| convert::diagnostic_to_marker(&self.text, diagnostic))
+                    .collect();
+                self.set_markers(markers, window, cx);
+                cx.notify();
+            }
+            LanguageServerEvent::Notification(_) => {}
+        }
+    }
+
+    /// Send the `textDocument/didChange` before the `range` (in the old text) is replaced by
+    /// the `new_text`, in the way of the `textDocument` sync capability of the server.
+    ///
+    /// Must be called for every change of the text, otherwise the document of the server will
+    /// be out of sync.
+    pub(crate) fn lsp_did_change(
+        &mut self,
+        range: &Range<usize>,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(lsp) = self.lsp.as_mut().filter(|lsp| lsp.opened) else {
+            return;
+        };
+
+        let sync_kind = convert::sync_kind(lsp.server.read(cx).capabilities().map(|c| c.as_ref()));
+        if let Some(change) = convert::content_change(sync_kind, &self.text, range, new_text) {
+            lsp.version += 1;
+            _ = lsp
+                .client
+                .notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier::new(
+                        lsp.uri.clone(),
+                        lsp.version,
+                    ),
+                    content_changes: vec![change],
+                });
+        }
+        lsp.hover_offset = None;
+
+        // Update the signature help if it is showing, or typing a trigger character.
+        let is_trigger = lsp.signature_help.is_some()
+            || lsp
+                .server
+                .read(cx)
+                .capabilities()
+                .and_then(|capabilities| capabilities.signature_help_provider.as_ref())
+                .and_then(|provider| provider.trigger_characters.as_ref())
+                .map_or(false, |chars| {
+                    chars
+                        .iter()
+                        .any(|c| !c.is_empty() && new_text.ends_with(c.as_str()))
+                });
+        if is_trigger {
+            self.request_signature_help(window, cx);
+        }
+    }
+
+    /// Request the hover at the `offset` after a delay, the hover will be shown in the hover
+    /// popover.
+    ///
+    /// Returns true if the hover popover is showing for the `offset`.
+    pub(crate) fn lsp_hover(
+        &mut self,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if let Some(popover) = self.diagnostic_popover.as_ref() {
+            let popover = popover.read(cx);
+            if popover.is_hover()
+                && popover
+                    .marker
+                    .range
+                    .as_ref()
+                    .map_or(false, |range| range.start <= offset && offset <= range.end)
+            {
+                return true;
+            }
+        }
+
+        let Some(lsp) = self.lsp.as_mut().filter(|lsp| lsp.opened) else {
+            return false;
+        };
+        let has_hover_provider = lsp
+            .server
+            .read(cx)
+            .capabilities()
+            .and_then(|capabilities| capabilities.hover_provider.as_ref())
+            .map_or(false, |provider| match provider {
+                HoverProviderCapability::Simple(enabled) => *enabled,
+                HoverProviderCapability::Options(_) => true,
+            });
+        if !has_hover_provider || lsp.hover_offset == Some(offset) {
+            return false;
+        }
+
+        lsp.hover_offset = Some(offset);
+        let params = HoverParams {
+            text_document_position_params: lsp.position_params(&self.text, offset),
+            work_done_progress_params: Default::default(),
+        };
+        let client = lsp.client.clone();
+        lsp.hover_task = cx.spawn_in(window, async move |this, cx| {
+            Timer::after(HOVER_DELAY).await;
+            let Ok(Some(hover)) = client.request::<HoverRequest>(params).await else {
+                return;
+            };
+
+            _ = this.update(cx, |this, cx| {
+                if this.lsp.as_ref().and_then(|lsp| lsp.hover_offset) != Some(offset) {
+                    return;
+                }
+
+                let range = match hover.range {
+                    Some(range) => convert::range_to_offsets(&this.text, range),
+                    None => offset..offset,
+                };
+                let message = convert::hover_to_markdown(hover.contents);
+                if message.trim().is_empty() {
+                    return;
+                }
+
+                this.diagnostic_popover =
+                    Some(DiagnosticPopover::hover(range, message, cx.entity(), cx));
+                cx.notify();
+            });
+        });
+
+        false
+    }
+
+    fn request_signature_help(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(lsp) = self.lsp.as_mut() else {
+            return;
+        };
+
+        let client = lsp.client.clone();
+        lsp.signature_help_task = cx.spawn_in(window, async move |this, cx| {
+            // The text and cursor are updated after the `lsp_did_change`.
+            let Ok((offset, params)) = this.update(cx, |this, _| {
+                let offset = this.cursor().offset;
+                let params = this.lsp.as_ref().map(|lsp| SignatureHelpParams {
+                    context: None,
+                    text_document_position_params: lsp.position_params(&this.text, offset),
+                    work_done_progress_params: Default::default(),
+                });
+                (offset, params)
+            }) else {
+                return;
+            };
+            let Some(params) = params else {
+                return;
+            };
+
+            let help = client
+                .request::<SignatureHelpRequest>(params)
+                .await
+                .ok()
+                .flatten();
+            _ = this.update(cx, |this, cx| {
+                let Some(lsp) = this.lsp.as_mut() else {
+                    return;
+                };
+
+                let signature = help.as_ref().and_then(convert::active_signature);
+                lsp.signature_help = signature.map(|signature| {
+                    SignatureHelpPopover::new(offset, signature, cx.entity().downgrade(), cx)
+                });
+                cx.notify();
+            });
+        });
+    }
+
+    /// Hide the signature help popover, returns true if it was showing.
+    pub(crate) fn hide_signature_help(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(lsp) = self.lsp.as_mut() else {
+            return false;
+        };
+
+        lsp.signature_help_task = Task::ready(());
+        if lsp.signature_help.take().is_some() {
+            cx.notify();
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn signature_help_popover(&self) -> Option<Entity<SignatureHelpPopover>> {
+        self.lsp.as_ref().and_then(|lsp| lsp.signature_help.clone())
+    }
+
+    pub(crate) fn go_to_definition(
+        &mut self,
+        _: &GoToDefinition,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(lsp) = self.lsp.as_ref().filter(|lsp| lsp.opened) else {
+            return;
+        };
+        let has_definition_provider = lsp
+            .server
+            .read(cx)
+            .capabilities()
+            .and_then(|capabilities| capabilities.definition_provider.as_ref())
+            .map_or(false, |provider| match provider {
+                OneOf::Left(enabled) => *enabled,
+                OneOf::Right(_) => true,
+            });
+        if !has_definition_provider {
+            return;
+        }
+
+        let params = GotoDefinitionParams {
+            text_document_position_params: lsp.position_params(&self.text, self.cursor().offset),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let client = lsp.client.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Some(response)) = client.request::<GotoDefinition>(params).await else {
+                return;
+            };
+            let locations = match response {
+                GotoDefinitionResponse::Scalar(location) => vec![location],
+                GotoDefinitionResponse::Array(locations) => locations,
+                GotoDefinitionResponse::Link(links) => links
+                    .into_iter()
+                    .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                    .collect(),
+            };
+            if locations.is_empty() {
+                return;
+            }
+
+            _ = this.update_in(cx, |this, window, cx| {
+                let Some(lsp) = this.lsp.as_ref() else {
+                    return;
+                };
+
+                if let Some(handler) = lsp.definition_handler.clone() {
+                    handler(&locations, window, cx);
+                    return;
+                }
+
+                if let Some(location) = locations.iter().find(|location| location.uri == lsp.uri) {
+                    let range = convert::range_to_offsets(&this.text, location.range);
+                    this.select_range(range, cx);
+                }
+            });
+        })
+        .detach();
+    }
+}
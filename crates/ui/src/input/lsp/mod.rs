@@ -0,0 +1,195 @@
+//! Connect the code editor to a language server by the Language Server Protocol.
+//!
+//! The [`LanguageServer`] starts the server process and talks to it over stdio, then use
+//! [`InputState::set_language_server`](crate::input::InputState::set_language_server) to attach
+//! a code editor to it, the editor will:
+//!
+//! - Sync the document by `textDocument/didOpen` and `textDocument/didChange`.
+//! - Show the `textDocument/publishDiagnostics` as the markers.
+//! - Show the `textDocument/hover` in the hover popover.
+//! - Go to definition by `textDocument/definition`.
+//! - Show the `textDocument/signatureHelp` when typing the trigger characters.
+mod client;
+mod convert;
+mod editor;
+mod signature_help;
+
+use std::{ffi::OsStr, path::Path, process::Stdio, rc::Rc};
+
+use anyhow::{Context as _, Result};
+use gpui::{actions, App, AppContext as _, Context, Entity, EventEmitter, Task};
+use lsp_types::{
+    notification::{Initialized, PublishDiagnostics},
+    request::Initialize,
+    ClientCapabilities, GotoCapability, HoverClientCapabilities, InitializeParams,
+    InitializedParams, MarkupKind, PublishDiagnosticsClientCapabilities, PublishDiagnosticsParams,
+    ServerCapabilities, SignatureHelpClientCapabilities, TextDocumentClientCapabilities,
+    TextDocumentSyncClientCapabilities, Uri, WorkspaceFolder,
+};
+use smol::process::{Child, Command};
+
+pub use client::*;
+pub(crate) use editor::LspState;
+pub use lsp_types;
+
+actions!(input, [GoToDefinition]);
+
+/// The events emitted by the [`LanguageServer`].
+#[derive(Debug, Clone)]
+pub enum LanguageServerEvent {
+    /// The server has been initialized, the documents can be opened now.
+    Initialized,
+    /// The diagnostics of a document have been published.
+    Diagnostics(Rc<PublishDiagnosticsParams>),
+    /// Other notifications from the server.
+    Notification(Rc<ServerNotification>),
+}
+
+/// A language server process communicates over stdio.
+///
+/// The server will be killed when this entity is dropped.
+pub struct LanguageServer {
+    client: LspClient,
+    capabilities: Option<Rc<ServerCapabilities>>,
+    _child: Child,
+    _tasks: Vec<Task<()>>,
+}
+
+impl EventEmitter<LanguageServerEvent> for LanguageServer {}
+
+impl LanguageServer {
+    /// Start the language server by the `command` and `args`, the `root` is the workspace folder.
+    ///
+    /// ```ignore
+    /// let server = LanguageServer::start("rust-analyzer", &[] as &[&str], Some(&root), cx)?;
+    /// ```
+    pub fn start(
+        command: impl AsRef<OsStr>,
+        args: &[impl AsRef<OsStr>],
+        root: Option<&Path>,
+        cx: &mut App,
+    ) -> Result<Entity<Self>> {
+        let mut command = Command::new(command);
+        command
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        if let Some(root) = root {
+            command.current_dir(root);
+        }
+
+        let mut child = command.spawn().context("failed to start language server")?;
+        let input = child.stdin.take().context("failed to open stdin")?;
+        let output = child.stdout.take().context("failed to open stdout")?;
+        let root_uri = root
+            .and_then(|root| root.canonicalize().ok())
+            .and_then(|root| convert::path_to_uri(&root));
+
+        Ok(cx.new(|cx| {
+            let (client, notifications, io) = LspClient::new(input, output);
+
+            let io_task = cx.background_spawn(async move {
+                if let Err(err) = io.await {
+                    tracing::error!("language server io error: {:?}", err);
+                }
+            });
+
+            let notification_task = cx.spawn(async move |this, cx| {
+                while let Ok(notification) = notifications.recv().await {
+                    let result = this.update(cx, |_, cx| {
+                        cx.emit(match notification.parse::<PublishDiagnostics>() {
+                            Some(params) => LanguageServerEvent::Diagnostics(Rc::new(params)),
+                            None => LanguageServerEvent::Notification(Rc::new(notification)),
+                        });
+                    });
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let initialize_task = cx.spawn({
+                let client = client.clone();
+                async move |this, cx| {
+                    let result = client
+                        .request::<Initialize>(initialize_params(root_uri))
+                        .await;
+                    let result = match result {
+                        Ok(result) => result,
+                        Err(err) => {
+                            tracing::error!("failed to initialize language server: {:?}", err);
+                            return;
+                        }
+                    };
+
+                    _ = client.notify::<Initialized>(InitializedParams {});
+                    _ = this.update(cx, |this, cx| {
+                        this.capabilities = Some(Rc::new(result.capabilities));
+                        cx.emit(LanguageServerEvent::Initialized);
+                    });
+                }
+            });
+
+            Self {
+                client,
+                capabilities: None,
+                _child: child,
+                _tasks: vec![io_task, notification_task, initialize_task],
+            }
+        }))
+    }
+
+    /// Returns the client to send requests and notifications to the server.
+    pub fn client(&self) -> &LspClient {
+        &self.client
+    }
+
+    /// Returns the capabilities of the server, None if the server has not been initialized.
+    pub fn capabilities(&self) -> Option<&Rc<ServerCapabilities>> {
+        self.capabilities.as_ref()
+    }
+
+    /// Returns true if the server has been initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.capabilities.is_some()
+    }
+}
+
+#[allow(deprecated)]
+fn initialize_params(root_uri: Option<Uri>) -> InitializeParams {
+    InitializeParams {
+        process_id: Some(std::process::id()),
+        root_uri: root_uri.clone(),
+        workspace_folders: root_uri.map(|uri| {
+            vec![WorkspaceFolder {
+                name: uri
+                    .path()
+                    .segments()
+                    .last()
+                    .map(|name| name.decode().into_string_lossy().to_string())
+                    .unwrap_or_default(),
+                uri,
+            }]
+        }),
+        capabilities: ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                synchronization: Some(TextDocumentSyncClientCapabilities::default()),
+                hover: Some(HoverClientCapabilities {
+                    content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                    ..Default::default()
+                }),
+                signature_help: Some(SignatureHelpClientCapabilities::default()),
+                definition: Some(GotoCapability::default()),
+                publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
+                    version_support: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
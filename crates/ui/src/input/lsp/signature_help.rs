@@ -0,0 +1,110 @@
+use gpui::{
+    canvas, deferred, div, prelude::FluentBuilder as _, px, App, AppContext as _, Bounds, Context,
+    Empty, Entity, FontWeight, HighlightStyle, InteractiveElement as _, IntoElement,
+    ParentElement as _, Pixels, Point, Render, Styled as _, StyledText, WeakEntity, Window,
+};
+
+use super::convert::ActiveSignature;
+use crate::{input::InputState, text::TextView, ActiveTheme as _, StyledExt as _};
+
+/// The popover to show the signature help above the cursor.
+pub(crate) struct SignatureHelpPopover {
+    state: WeakEntity<InputState>,
+    /// The offset of the cursor when the signature help is requested.
+    offset: usize,
+    signature: ActiveSignature,
+    bounds: Bounds<Pixels>,
+}
+
+impl SignatureHelpPopover {
+    pub(super) fn new(
+        offset: usize,
+        signature: ActiveSignature,
+        state: WeakEntity<InputState>,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        cx.new(|_| Self {
+            state,
+            offset,
+            signature,
+            bounds: Bounds::default(),
+        })
+    }
+
+    fn origin(&self, cx: &App) -> Option<Point<Pixels>> {
+        let state = self.state.upgrade()?;
+        let state = state.read(cx);
+        let (_, _, pos) = state.line_and_position_for_offset(self.offset);
+
+        pos.map(|pos| {
+            pos + Point::new(state.line_number_width, px(0.)) + state.scroll_handle.offset()
+        })
+    }
+}
+
+impl Render for SignatureHelpPopover {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(pos) = self.origin(cx) else {
+            return Empty.into_any_element();
+        };
+
+        let view = cx.entity();
+        let y = pos.y - self.bounds.size.height;
+        let max_width = px(500.).min(window.bounds().size.width - pos.x);
+        let highlights = self
+            .signature
+            .parameter
+            .clone()
+            .map(|range| {
+                (
+                    range,
+                    HighlightStyle {
+                        font_weight: Some(FontWeight::BOLD),
+                        color: Some(cx.theme().primary),
+                        ..Default::default()
+                    },
+                )
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        deferred(
+            div()
+                .id("signature-help-popover")
+                .absolute()
+                .left(pos.x)
+                .top(y)
+                .max_w(max_width)
+                .px_2()
+                .py_1()
+                .text_xs()
+                .popover_style(cx)
+                .child(StyledText::new(self.signature.label.clone()).with_highlights(highlights))
+                .when_some(
+                    self.signature.documentation.clone(),
+                    |this, documentation| {
+                        this.child(
+                            div()
+                                .mt_1()
+                                .pt_1()
+                                .border_t_1()
+                                .border_color(cx.theme().border)
+                                .text_color(cx.theme().muted_foreground)
+                                .child(TextView::markdown("documentation", documentation)),
+                        )
+                    },
+                )
+                .child(
+                    canvas(
+                        move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
+                        |_, _, _, _| {},
+                    )
+                    .top_0()
+                    .left_0()
+                    .absolute()
+                    .size_full(),
+                ),
+        )
+        .into_any_element()
+    }
+}
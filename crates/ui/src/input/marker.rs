@@ -1,9 +1,9 @@
 use crate::{
     highlighter::HighlightTheme,
-    input::{InputState, LineColumn},
+    input::{InputState, LineColumn, RopeExt as _},
 };
 use gpui::{px, HighlightStyle, Hsla, SharedString, UnderlineStyle};
-use itertools::Itertools;
+use ropey::Rope;
 use std::ops::Range;
 
 /// Marker represents a diagnostic message, such as an error or warning, in the code editor.
@@ -36,45 +36,30 @@ impl Marker {
 
     /// Prepare the marker to convert line, column to byte offsets.
     pub(super) fn prepare(&mut self, state: &InputState) {
-        let Some(start_line) = state
-            .text_wrapper
-            .lines
-            .get(self.start.line.saturating_sub(1))
-        else {
+        let text = &state.text;
+        let start_row = self.start.line.saturating_sub(1);
+        let end_row = self.end.line.saturating_sub(1);
+        if start_row >= text.len_lines() || end_row >= text.len_lines() {
             return;
-        };
+        }
 
-        let Some(start_line_str) = state.text.get(start_line.range.clone()) else {
-            return;
-        };
+        let start_byte = Self::column_to_offset(text, start_row, self.start.column);
+        let end_byte = Self::column_to_offset(text, end_row, self.end.column);
 
-        let Some(end_line) = state
-            .text_wrapper
-            .lines
-            .get(self.end.line.saturating_sub(1))
-        else {
-            return;
-        };
-        let Some(end_line_str) = state.text.get(end_line.range.clone()) else {
-            return;
-        };
+        self.range = Some(start_byte..end_byte);
+    }
 
-        let start_byte = start_line.range.start
-            + start_line_str
-                .chars()
-                .take(self.start.column.saturating_sub(1))
-                .counts_by(|c| c.len_utf8())
-                .values()
-                .sum::<usize>();
-        let end_byte = end_line.range.start
-            + end_line_str
-                .chars()
-                .take(self.end.column.saturating_sub(1))
-                .counts_by(|c| c.len_utf8())
-                .values()
-                .sum::<usize>();
+    /// Convert the column (1-based, in chars) of the line (zero-based) to the byte offset.
+    fn column_to_offset(text: &Rope, row: usize, column: usize) -> usize {
+        let line_start = text.line_start_offset(row);
+        let line = text.byte_range_slice(line_start..text.line_end_offset(row));
 
-        self.range = Some(start_byte..end_byte);
+        line_start
+            + line
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| c.len_utf8())
+                .sum::<usize>()
     }
 }
 
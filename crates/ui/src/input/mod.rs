@@ -1,24 +1,38 @@
 mod blink_cursor;
+mod brackets;
 mod change;
 mod clear_button;
+mod comment;
+mod completion;
 mod cursor;
 mod element;
+mod folding;
 mod hover_popover;
+mod indent;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 mod marker;
 mod mask_pattern;
 mod mode;
+mod multi_cursor;
 mod number_input;
 mod otp_input;
+mod rope_ext;
+mod search;
+mod search_panel;
 mod state;
 mod text_input;
 mod text_wrapper;
 
 pub(crate) use clear_button::*;
+pub use completion::{CompletionItem, CompletionKind, CompletionProvider, CompletionTrigger};
 pub(super) use cursor::*;
 pub use marker::*;
 pub use mask_pattern::MaskPattern;
 pub use mode::TabSize;
 pub use number_input::{NumberInput, NumberInputEvent, StepAction};
 pub use otp_input::*;
+pub(crate) use rope_ext::RopeExt;
+pub use search::SearchOptions;
 pub use state::*;
 pub use text_input::*;
@@ -2,10 +2,14 @@ use std::rc::Rc;
 use std::{cell::RefCell, ops::Range};
 
 use gpui::{App, DefiniteLength, SharedString};
+use ropey::Rope;
 
 use crate::{highlighter::SyntaxHighlighter, input::marker::Marker};
 
-use super::text_wrapper::TextWrapper;
+use super::{
+    folding::{self, FoldRange},
+    text_wrapper::TextWrapper,
+};
 
 #[derive(Debug, Copy, Clone)]
 pub struct TabSize {
@@ -117,7 +121,7 @@ impl InputMode {
     }
 
     pub(super) fn update_auto_grow(&mut self, text_wrapper: &TextWrapper) {
-        let wrapped_lines = text_wrapper.wrapped_lines.len();
+        let wrapped_lines = text_wrapper.wrapped_lines_count();
         self.set_rows(wrapped_lines);
     }
 
@@ -170,6 +174,15 @@ impl InputMode {
         }
     }
 
+    /// Returns the language of the code editor.
+    #[inline]
+    pub(super) fn language(&self) -> Option<&SharedString> {
+        match self {
+            InputMode::CodeEditor { language, .. } => Some(language),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub(super) fn tab_size(&self) -> Option<&TabSize> {
         match self {
@@ -179,13 +192,16 @@ impl InputMode {
         }
     }
 
+    /// Update the highlighter for the `full_text`, the `edit` is the replaced range (in the
+    /// previous text) and the new text, `None` to reparse the entire text.
+    ///
+    /// Returns the byte ranges of the syntax changed by the update.
     pub(super) fn update_highlighter(
         &mut self,
-        selected_range: &Range<usize>,
-        full_text: &SharedString,
-        new_text: &str,
+        edit: Option<(&Range<usize>, &str)>,
+        full_text: &Rope,
         cx: &mut App,
-    ) {
+    ) -> Vec<Range<usize>> {
         match &self {
             InputMode::CodeEditor {
                 language,
@@ -199,11 +215,31 @@ impl InputMode {
                 }
 
                 if let Some(highlighter) = highlighter.as_mut() {
-                    highlighter.update(selected_range, full_text, new_text, cx);
+                    highlighter.update_text(edit, full_text);
+                    return highlighter.changed_ranges().to_vec();
                 }
             }
             _ => {}
         }
+
+        vec![]
+    }
+
+    /// Returns the foldable ranges intersect with the `rows` of the code editor, from the syntax
+    /// tree if the language is supported, otherwise by the indentation.
+    pub(super) fn fold_ranges(&self, text: &Rope, rows: &Range<usize>) -> Vec<FoldRange> {
+        match self {
+            InputMode::CodeEditor {
+                tab, highlighter, ..
+            } => {
+                if let Some(tree) = highlighter.borrow().as_ref().and_then(|h| h.tree()) {
+                    return folding::syntax_fold_ranges(tree, rows);
+                }
+
+                folding::indent_fold_ranges(text, tab.tab_size, rows)
+            }
+            _ => vec![],
+        }
     }
 
     pub(super) fn clear_markers(&mut self) {
@@ -0,0 +1,146 @@
+use std::ops::Range;
+
+use ropey::Rope;
+
+use crate::input::{Cursor, RopeExt as _, Selection};
+
+/// A selection with the direction, used to keep the secondary cursors for multi-cursor editing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub(super) struct CursorSelection {
+    pub(super) range: Selection,
+    /// Same as the `selection_reversed` of the `InputState`, the cursor is at the start if true.
+    pub(super) reversed: bool,
+}
+
+impl CursorSelection {
+    pub(super) fn new(range: impl Into<Selection>, reversed: bool) -> Self {
+        Self {
+            range: range.into(),
+            reversed,
+        }
+    }
+
+    /// Returns the cursor of the selection.
+    pub(super) fn cursor(&self) -> Cursor {
+        if self.reversed {
+            self.range.start
+        } else {
+            self.range.end
+        }
+    }
+
+    /// Move the selection by the `delta` bytes.
+    pub(super) fn shift(&mut self, delta: isize) {
+        self.range.start = Cursor::new(self.range.start.offset.saturating_add_signed(delta));
+        self.range.end = Cursor::new(self.range.end.offset.saturating_add_signed(delta));
+    }
+}
+
+/// Sort the selections by the start offset, and merge the overlapped selections.
+///
+/// The empty selections (cursors) at the same offset will be merged into one.
+pub(super) fn merge_selections(mut selections: Vec<CursorSelection>) -> Vec<CursorSelection> {
+    selections.sort_by_key(|s| (s.range.start.offset, s.range.end.offset));
+
+    let mut merged: Vec<CursorSelection> = Vec::with_capacity(selections.len());
+    for selection in selections {
+        if let Some(last) = merged.last_mut() {
+            let overlapped = selection.range.start < last.range.end
+                || selection.range.start == last.range.start
+                || (selection.range.is_empty() && selection.range.start == last.range.end);
+            if overlapped {
+                if selection.range.end > last.range.end {
+                    last.range.end = selection.range.end;
+                }
+                continue;
+            }
+        }
+
+        merged.push(selection);
+    }
+
+    merged
+}
+
+/// Returns the byte ranges of all the occurrences of the `query` in the text.
+pub(super) fn find_occurrences(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return vec![];
+    }
+
+    text.match_indices(query)
+        .map(|(ix, _)| ix..ix + query.len())
+        .collect()
+}
+
+/// Returns the selections of the box (column) selection from the `anchor` offset to the `head` offset.
+///
+/// The columns are in chars, the lines shorter than the start column will get a cursor at the line end.
+pub(super) fn column_selections(text: &Rope, anchor: usize, head: usize) -> Vec<CursorSelection> {
+    let (anchor_row, anchor_column) = text.offset_to_row_column(anchor);
+    let (head_row, head_column) = text.offset_to_row_column(head);
+    let reversed = head_column < anchor_column;
+    let (start_column, end_column) = if reversed {
+        (head_column, anchor_column)
+    } else {
+        (anchor_column, head_column)
+    };
+
+    (anchor_row.min(head_row)..=anchor_row.max(head_row))
+        .map(|row| {
+            let start = text.row_column_to_offset(row, start_column);
+            let end = text.row_column_to_offset(row, end_column);
+            CursorSelection::new(start..end, reversed)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::{column_selections, find_occurrences, merge_selections, CursorSelection};
+
+    fn ranges(selections: &[CursorSelection]) -> Vec<std::ops::Range<usize>> {
+        selections.iter().map(|s| s.range.into()).collect()
+    }
+
+    #[test]
+    fn test_merge_selections() {
+        let selections = vec![
+            CursorSelection::new(10..10, false),
+            CursorSelection::new(0..5, false),
+            CursorSelection::new(3..8, false),
+            CursorSelection::new(10..10, false),
+            CursorSelection::new(12..15, true),
+            CursorSelection::new(15..15, false),
+            CursorSelection::new(15..18, false),
+        ];
+        let merged = merge_selections(selections);
+        assert_eq!(ranges(&merged), vec![0..8, 10..10, 12..15, 15..18]);
+        assert_eq!(merged[2].reversed, true);
+    }
+
+    #[test]
+    fn test_find_occurrences() {
+        let text = "foo bar\nfoo 世界 foo";
+        assert_eq!(find_occurrences(text, "foo"), vec![0..3, 8..11, 19..22]);
+        assert_eq!(find_occurrences(text, "世界"), vec![12..18]);
+        assert!(find_occurrences(text, "").is_empty());
+    }
+
+    #[test]
+    fn test_column_selections() {
+        let text = Rope::from_str("Hello\nab\n世界 Rust");
+        let selections = column_selections(&text, 1, 17);
+        assert_eq!(ranges(&selections), vec![1..4, 7..8, 12..17]);
+        assert_eq!(selections[0].reversed, false);
+
+        let selections = column_selections(&text, 17, 1);
+        assert_eq!(ranges(&selections), vec![1..4, 7..8, 12..17]);
+        assert_eq!(selections[0].reversed, true);
+
+        let selections = column_selections(&text, 2, 2);
+        assert_eq!(ranges(&selections), vec![2..2]);
+    }
+}
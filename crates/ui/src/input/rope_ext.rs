@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use ropey::{Rope, RopeSlice};
+
+/// An extension trait for [`Rope`] to work with UTF-8 byte offsets.
+///
+/// The [`InputState`](super::InputState) always uses UTF-8 byte offsets, but `ropey` is char based,
+/// so we need to convert them.
+pub(crate) trait RopeExt {
+    /// Clip the byte offset to the previous char boundary and the text length.
+    fn clip_offset(&self, offset: usize) -> usize;
+
+    /// Return the start byte offset of the line (zero-based).
+    fn line_start_offset(&self, row: usize) -> usize;
+
+    /// Return the end byte offset of the line (zero-based), exclude the `\n`.
+    fn line_end_offset(&self, row: usize) -> usize;
+
+    /// Return the text of the line (zero-based), exclude the `\n`.
+    fn line_text(&self, row: usize) -> String;
+
+    /// Return the text in the byte range.
+    fn slice_text(&self, range: Range<usize>) -> String;
+
+    /// Return a slice of the byte range, the range will be clipped to char boundaries.
+    fn byte_range_slice(&self, range: Range<usize>) -> RopeSlice<'_>;
+
+    /// Replace the text in the byte range with the new text.
+    fn replace(&mut self, range: Range<usize>, new_text: &str);
+
+    /// Return the row (zero-based) and the column (zero-based, in chars) of the byte offset.
+    fn offset_to_row_column(&self, offset: usize) -> (usize, usize);
+
+    /// Return the byte offset of the row (zero-based) and the column (zero-based, in chars).
+    ///
+    /// The column will be clipped to the end of the line.
+    fn row_column_to_offset(&self, row: usize, column: usize) -> usize;
+
+    /// Convert the UTF-8 byte offset to the UTF-16 offset.
+    fn offset_to_utf16(&self, offset: usize) -> usize;
+
+    /// Convert the UTF-16 offset to the UTF-8 byte offset.
+    fn offset_from_utf16(&self, offset: usize) -> usize;
+}
+
+impl RopeExt for Rope {
+    fn clip_offset(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len_bytes());
+        self.char_to_byte(self.byte_to_char(offset))
+    }
+
+    fn line_start_offset(&self, row: usize) -> usize {
+        if row >= self.len_lines() {
+            return self.len_bytes();
+        }
+
+        self.line_to_byte(row)
+    }
+
+    fn line_end_offset(&self, row: usize) -> usize {
+        if row + 1 >= self.len_lines() {
+            return self.len_bytes();
+        }
+
+        // -1 for skip the `\n`
+        self.line_to_byte(row + 1).saturating_sub(1)
+    }
+
+    fn line_text(&self, row: usize) -> String {
+        self.slice_text(self.line_start_offset(row)..self.line_end_offset(row))
+    }
+
+    fn slice_text(&self, range: Range<usize>) -> String {
+        self.byte_range_slice(range).to_string()
+    }
+
+    fn byte_range_slice(&self, range: Range<usize>) -> RopeSlice<'_> {
+        let start = self.clip_offset(range.start);
+        let end = self.clip_offset(range.end).max(start);
+        self.byte_slice(start..end)
+    }
+
+    fn replace(&mut self, range: Range<usize>, new_text: &str) {
+        let start = self.byte_to_char(self.clip_offset(range.start));
+        let end = self.byte_to_char(self.clip_offset(range.end)).max(start);
+        self.remove(start..end);
+        self.insert(start, new_text);
+    }
+
+    fn offset_to_row_column(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clip_offset(offset);
+        let row = self.byte_to_line(offset);
+        let column = self
+            .byte_range_slice(self.line_start_offset(row)..offset)
+            .len_chars();
+
+        (row, column)
+    }
+
+    fn row_column_to_offset(&self, row: usize, column: usize) -> usize {
+        let line_start = self.line_start_offset(row);
+        let line = self.byte_range_slice(line_start..self.line_end_offset(row));
+        line_start + line.char_to_byte(column.min(line.len_chars()))
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        self.char_to_utf16_cu(self.byte_to_char(offset.min(self.len_bytes())))
+    }
+
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        self.char_to_byte(self.utf16_cu_to_char(offset.min(self.len_utf16_cu())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::RopeExt as _;
+
+    #[test]
+    fn test_line_offsets() {
+        let rope = Rope::from_str("Hello\n世界\n\nRust");
+        assert_eq!(rope.len_lines(), 4);
+        assert_eq!(rope.line_start_offset(0), 0);
+        assert_eq!(rope.line_end_offset(0), 5);
+        assert_eq!(rope.line_start_offset(1), 6);
+        assert_eq!(rope.line_end_offset(1), 12);
+        assert_eq!(rope.line_start_offset(2), 13);
+        assert_eq!(rope.line_end_offset(2), 13);
+        assert_eq!(rope.line_start_offset(3), 14);
+        assert_eq!(rope.line_end_offset(3), 18);
+        assert_eq!(rope.line_start_offset(10), 18);
+        assert_eq!(rope.line_text(1), "世界");
+        assert_eq!(rope.line_text(2), "");
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut rope = Rope::from_str("Hello 世界");
+        rope.replace(6..12, "Rust");
+        assert_eq!(rope.to_string(), "Hello Rust");
+        rope.replace(5..5, ",");
+        assert_eq!(rope.to_string(), "Hello, Rust");
+        rope.replace(0..100, "");
+        assert_eq!(rope.to_string(), "");
+    }
+
+    #[test]
+    fn test_utf16_offset() {
+        let rope = Rope::from_str("a💝b世");
+        assert_eq!(rope.offset_to_utf16(0), 0);
+        assert_eq!(rope.offset_to_utf16(1), 1);
+        assert_eq!(rope.offset_to_utf16(5), 3);
+        assert_eq!(rope.offset_to_utf16(6), 4);
+        assert_eq!(rope.offset_to_utf16(9), 5);
+        assert_eq!(rope.offset_from_utf16(3), 5);
+        assert_eq!(rope.offset_from_utf16(5), 9);
+        assert_eq!(rope.offset_from_utf16(100), 9);
+    }
+
+    #[test]
+    fn test_row_column() {
+        let rope = Rope::from_str("Hello\n世界\n\nRust");
+        assert_eq!(rope.offset_to_row_column(0), (0, 0));
+        assert_eq!(rope.offset_to_row_column(5), (0, 5));
+        assert_eq!(rope.offset_to_row_column(9), (1, 1));
+        assert_eq!(rope.offset_to_row_column(13), (2, 0));
+        assert_eq!(rope.offset_to_row_column(100), (3, 4));
+        assert_eq!(rope.row_column_to_offset(1, 1), 9);
+        assert_eq!(rope.row_column_to_offset(1, 10), 12);
+        assert_eq!(rope.row_column_to_offset(2, 3), 13);
+        assert_eq!(rope.row_column_to_offset(3, 2), 16);
+    }
+
+    #[test]
+    fn test_clip_offset() {
+        let rope = Rope::from_str("a世b");
+        assert_eq!(rope.clip_offset(0), 0);
+        assert_eq!(rope.clip_offset(2), 1);
+        assert_eq!(rope.clip_offset(4), 4);
+        assert_eq!(rope.clip_offset(100), 5);
+        assert_eq!(rope.slice_text(2..5), "世b");
+    }
+}
@@ -0,0 +1,281 @@
+use std::ops::Range;
+
+use gpui::SharedString;
+use regex::{Regex, RegexBuilder};
+
+/// The options to search text in the [`super::InputState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    /// Match case sensitive, default: false
+    pub case_sensitive: bool,
+    /// Only match the whole word, default: false
+    pub whole_word: bool,
+    /// Use the query as a regular expression, default: false
+    pub regex: bool,
+}
+
+/// The search state of the [`super::InputState`], keep the query and the matches.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SearchState {
+    pub(super) query: SharedString,
+    pub(super) options: SearchOptions,
+    /// None if the query is empty or it is an invalid regular expression.
+    matcher: Option<Regex>,
+    /// The byte ranges of the matches, sorted by offset.
+    pub(super) matches: Vec<Range<usize>>,
+    /// Set true to update the matches in next [`Self::update_if_need`].
+    pub(super) dirty: bool,
+}
+
+impl SearchState {
+    pub(super) fn new(query: SharedString, options: SearchOptions) -> Self {
+        Self {
+            matcher: build_matcher(&query, options),
+            query,
+            options,
+            matches: vec![],
+            dirty: true,
+        }
+    }
+
+    /// Returns true if the query is not empty, but it can't be built as a regular expression.
+    pub(super) fn is_invalid(&self) -> bool {
+        !self.query.is_empty() && self.matcher.is_none()
+    }
+
+    /// Find all the matches if the text has been changed.
+    pub(super) fn update_if_need(&mut self, text: &str) {
+        if !self.dirty {
+            return;
+        }
+
+        self.dirty = false;
+        self.matches = match self.matcher.as_ref() {
+            Some(matcher) => matcher
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+            None => vec![],
+        };
+    }
+
+    /// Returns the index of the match that equals to the `range`.
+    pub(super) fn match_index(&self, range: &Range<usize>) -> Option<usize> {
+        self.matches
+            .binary_search_by_key(&range.start, |m| m.start)
+            .ok()
+            .filter(|ix| self.matches[*ix].end == range.end)
+    }
+
+    /// Returns the index of the first match starts at or after the `offset`, wrap to the first match.
+    pub(super) fn next_match_index(&self, offset: usize) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let ix = self.matches.partition_point(|m| m.start < offset);
+        Some(if ix < self.matches.len() { ix } else { 0 })
+    }
+
+    /// Returns the index of the last match ends at or before the `offset`, wrap to the last match.
+    pub(super) fn previous_match_index(&self, offset: usize) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let ix = self.matches.partition_point(|m| m.end <= offset);
+        Some(ix.checked_sub(1).unwrap_or(self.matches.len() - 1))
+    }
+
+    /// Returns the matches that intersect with the `range`.
+    pub(super) fn matches_in_range(&self, range: &Range<usize>) -> &[Range<usize>] {
+        let start = self.matches.partition_point(|m| m.end <= range.start);
+        let end = self.matches.partition_point(|m| m.start < range.end);
+        &self.matches[start..end.max(start)]
+    }
+
+    /// Returns the text to replace the match at `range`.
+    ///
+    /// In regex mode, the `$1`, `${name}` in the `replacement` will be expanded by the captures.
+    pub(super) fn expand_replacement(
+        &self,
+        text: &str,
+        range: &Range<usize>,
+        replacement: &str,
+    ) -> String {
+        let Some(matcher) = self.matcher.as_ref().filter(|_| self.options.regex) else {
+            return replacement.to_string();
+        };
+
+        let mut dst = String::new();
+        match matcher.captures_at(text, range.start) {
+            Some(captures) if captures.get(0).map(|m| m.range()) == Some(range.clone()) => {
+                captures.expand(replacement, &mut dst);
+            }
+            _ => dst.push_str(replacement),
+        }
+        dst
+    }
+
+    /// Returns the range from the first match start to the last match end, and the new text of
+    /// the range with all matches replaced.
+    pub(super) fn replace_all(
+        &self,
+        text: &str,
+        replacement: &str,
+    ) -> Option<(Range<usize>, String)> {
+        let matcher = self.matcher.as_ref()?;
+        let first = self.matches.first()?;
+        let last = self.matches.last()?;
+
+        let mut new_text = String::new();
+        let mut last_end = first.start;
+        for captures in matcher.captures_iter(text) {
+            let Some(m) = captures.get(0).filter(|m| !m.is_empty()) else {
+                continue;
+            };
+
+            new_text.push_str(&text[last_end..m.start()]);
+            if self.options.regex {
+                captures.expand(replacement, &mut new_text);
+            } else {
+                new_text.push_str(replacement);
+            }
+            last_end = m.end();
+        }
+
+        Some((first.start..last.end, new_text))
+    }
+}
+
+fn build_matcher(query: &str, options: SearchOptions) -> Option<Regex> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if options.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchOptions, SearchState};
+
+    fn search(text: &str, query: &str, options: SearchOptions) -> SearchState {
+        let mut state = SearchState::new(query.to_string().into(), options);
+        state.update_if_need(text);
+        state
+    }
+
+    #[test]
+    fn test_search_options() {
+        let text = "Foo foo.bar\nfoobar (foo)";
+
+        let state = search(&text, "foo", SearchOptions::default());
+        assert_eq!(state.matches, vec![0..3, 4..7, 12..15, 20..23]);
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(&text, "foo", options).matches,
+            vec![4..7, 12..15, 20..23]
+        );
+
+        let options = SearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(&text, "foo", options).matches,
+            vec![0..3, 4..7, 20..23]
+        );
+
+        // Plain text query should be escaped.
+        assert_eq!(
+            search(&text, "(foo)", SearchOptions::default()).matches,
+            vec![19..24]
+        );
+        assert_eq!(
+            search(&text, ".", SearchOptions::default()).matches,
+            vec![7..8]
+        );
+
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        assert_eq!(search(&text, "^foo", options).matches, vec![0..3, 12..15]);
+        assert_eq!(search(&text, "o*", options).matches.len(), 4);
+
+        let state = search(&text, "(foo", options);
+        assert!(state.is_invalid());
+        assert!(state.matches.is_empty());
+        assert!(!search(&text, "", options).is_invalid());
+    }
+
+    #[test]
+    fn test_match_navigation() {
+        let text = "foo bar foo bar foo";
+        let state = search(&text, "foo", SearchOptions::default());
+        assert_eq!(state.matches, vec![0..3, 8..11, 16..19]);
+
+        assert_eq!(state.match_index(&(8..11)), Some(1));
+        assert_eq!(state.match_index(&(8..10)), None);
+        assert_eq!(state.next_match_index(0), Some(0));
+        assert_eq!(state.next_match_index(3), Some(1));
+        assert_eq!(state.next_match_index(17), Some(0));
+        assert_eq!(state.previous_match_index(11), Some(1));
+        assert_eq!(state.previous_match_index(10), Some(0));
+        assert_eq!(state.previous_match_index(2), Some(2));
+        assert_eq!(state.matches_in_range(&(2..9)), &[0..3, 8..11]);
+        assert!(state.matches_in_range(&(4..7)).is_empty());
+
+        let state = search(&text, "baz", SearchOptions::default());
+        assert_eq!(state.next_match_index(0), None);
+        assert_eq!(state.previous_match_index(0), None);
+    }
+
+    #[test]
+    fn test_replace() {
+        let text = "let a = 1;\nlet bb = 22;";
+        let state = search(&text, "foo", SearchOptions::default());
+        assert_eq!(state.replace_all(&text, "bar"), None);
+
+        let state = search(&text, "let", SearchOptions::default());
+        assert_eq!(state.expand_replacement(&text, &(0..3), "$1"), "$1");
+        assert_eq!(
+            state.replace_all(&text, "const"),
+            Some((0..14, "const a = 1;\nconst".to_string()))
+        );
+
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let state = search(&text, r"(\w+) = (\d+)", options);
+        assert_eq!(state.matches, vec![4..9, 15..22]);
+        assert_eq!(
+            state.expand_replacement(&text, &(15..22), "$2 = $1"),
+            "22 = bb"
+        );
+        assert_eq!(
+            state.replace_all(&text, "${2} = $1"),
+            Some((4..22, "1 = a;\nlet 22 = bb".to_string()))
+        );
+    }
+}
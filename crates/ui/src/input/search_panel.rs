@@ -0,0 +1,374 @@
+use std::ops::Range;
+
+use gpui::{
+    div, prelude::FluentBuilder as _, px, App, AppContext as _, Context, Entity,
+    InteractiveElement as _, IntoElement, MouseButton, ParentElement as _, Render, SharedString,
+    Styled, Subscription, WeakEntity, Window,
+};
+use rust_i18n::t;
+
+use crate::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{
+        AddCursorAbove, AddCursorBelow, Escape, FindNext, FindPrevious, Indent, IndentInline,
+        InputEvent, InputState, MoveDown, MovePageDown, MovePageUp, MoveUp, Outdent, OutdentInline,
+        SearchOptions, SelectAllOccurrences, SelectDown, SelectNextOccurrence, SelectUp, TextInput,
+    },
+    v_flex, ActiveTheme as _, IconName, Selectable as _, Sizable as _, StyledExt as _,
+};
+
+/// The find and replace bar of the code editor.
+pub(super) struct SearchPanel {
+    editor: WeakEntity<InputState>,
+    pub(super) query_input: Entity<InputState>,
+    replace_input: Entity<InputState>,
+    options: SearchOptions,
+    show_replace: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SearchPanel {
+    pub(super) fn new(
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_input = cx.new(|cx| InputState::new(window, cx).placeholder(t!("Input.find")));
+        let replace_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder(t!("Input.replace")));
+
+        let _subscriptions = vec![
+            cx.subscribe_in(&query_input, window, Self::on_query_input_event),
+            cx.subscribe_in(&replace_input, window, Self::on_replace_input_event),
+            // Update the matches count when the editor changed.
+            cx.observe(editor, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            editor: editor.downgrade(),
+            query_input,
+            replace_input,
+            options: SearchOptions::default(),
+            show_replace: false,
+            _subscriptions,
+        }
+    }
+
+    pub(super) fn set_show_replace(&mut self, show_replace: bool, cx: &mut Context<Self>) {
+        self.show_replace = show_replace;
+        cx.notify();
+    }
+
+    /// Returns true if the find or replace input is focused.
+    pub(super) fn is_focused(&self, window: &Window, cx: &App) -> bool {
+        self.query_input.read(cx).focus_handle.is_focused(window)
+            || self.replace_input.read(cx).focus_handle.is_focused(window)
+    }
+
+    fn on_query_input_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::Change(_) => self.search(window, cx),
+            InputEvent::PressEnter { secondary } => {
+                if *secondary {
+                    self.select_previous(window, cx);
+                } else {
+                    self.select_next(window, cx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_replace_input_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::PressEnter { secondary } => {
+                if *secondary {
+                    self.replace_all(window, cx);
+                } else {
+                    self.replace(window, cx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.query_input.read(cx).value().clone();
+        let options = self.options;
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.search(query, options, window, cx);
+        });
+    }
+
+    fn toggle_option(
+        &mut self,
+        f: impl FnOnce(&mut SearchOptions),
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        f(&mut self.options);
+        self.search(window, cx);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.select_next_match(window, cx);
+        });
+    }
+
+    fn select_previous(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.select_previous_match(window, cx);
+        });
+    }
+
+    fn replace(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let replacement = self.replace_input.read(cx).value().clone();
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.replace_match(&replacement, window, cx);
+        });
+    }
+
+    fn replace_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let replacement = self.replace_input.read(cx).value().clone();
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.replace_all_matches(&replacement, window, cx);
+        });
+    }
+
+    fn close(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        _ = self.editor.update(cx, |editor, cx| {
+            editor.hide_search_panel(window, cx);
+        });
+    }
+
+    /// Move focus between the find and replace inputs.
+    fn toggle_focus(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.show_replace {
+            return;
+        }
+
+        if self.query_input.read(cx).focus_handle.is_focused(window) {
+            self.replace_input.read(cx).focus_handle.focus(window);
+        } else {
+            self.query_input.read(cx).focus_handle.focus(window);
+        }
+    }
+
+    fn render_option_button(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        tooltip: SharedString,
+        selected: bool,
+        f: fn(&mut SearchOptions),
+        cx: &mut Context<Self>,
+    ) -> Button {
+        Button::new(id)
+            .label(label)
+            .xsmall()
+            .ghost()
+            .selected(selected)
+            .tooltip(tooltip)
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.toggle_option(f, window, cx);
+            }))
+    }
+
+    fn match_label(&self, cx: &App) -> SharedString {
+        let Some(editor) = self.editor.upgrade() else {
+            return SharedString::default();
+        };
+        let editor = editor.read(cx);
+        let Some(search) = editor.search.as_ref() else {
+            return SharedString::default();
+        };
+
+        if search.query.is_empty() {
+            SharedString::default()
+        } else if search.is_invalid() {
+            t!("Input.invalid").into()
+        } else if search.matches.is_empty() {
+            t!("Input.no_results").into()
+        } else {
+            let range: Range<usize> = editor.selected_range.into();
+            match search.match_index(&range) {
+                Some(ix) => t!(
+                    "Input.match_of",
+                    index = ix + 1,
+                    total = search.matches.len()
+                )
+                .into(),
+                None => t!("Input.results", count = search.matches.len()).into(),
+            }
+        }
+    }
+}
+
+impl Render for SearchPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let options = self.options;
+        let match_label = self.match_label(cx);
+
+        v_flex()
+            .id("search-panel")
+            .absolute()
+            .top_1()
+            .right_4()
+            .w(px(420.))
+            .p_1()
+            .gap_1()
+            .popover_style(cx)
+            .cursor_default()
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .on_action(cx.listener(|this, _: &Escape, window, cx| this.close(window, cx)))
+            .on_action(
+                cx.listener(|this, _: &IndentInline, window, cx| this.toggle_focus(window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &OutdentInline, window, cx| this.toggle_focus(window, cx)),
+            )
+            .on_action(cx.listener(|this, _: &MoveUp, window, cx| this.select_previous(window, cx)))
+            .on_action(cx.listener(|this, _: &MoveDown, window, cx| this.select_next(window, cx)))
+            // Stop these actions from the inputs bubbling to the editor.
+            .on_action(|_: &SelectUp, _, _| {})
+            .on_action(|_: &SelectDown, _, _| {})
+            .on_action(|_: &MovePageUp, _, _| {})
+            .on_action(|_: &MovePageDown, _, _| {})
+            .on_action(|_: &Indent, _, _| {})
+            .on_action(|_: &Outdent, _, _| {})
+            .on_action(|_: &AddCursorAbove, _, _| {})
+            .on_action(|_: &AddCursorBelow, _, _| {})
+            .on_action(|_: &SelectNextOccurrence, _, _| {})
+            .on_action(|_: &SelectAllOccurrences, _, _| {})
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("toggle-replace")
+                            .icon(if self.show_replace {
+                                IconName::ChevronDown
+                            } else {
+                                IconName::ChevronRight
+                            })
+                            .xsmall()
+                            .ghost()
+                            .tooltip(t!("Input.toggle_replace"))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.set_show_replace(!this.show_replace, cx);
+                            })),
+                    )
+                    .child(
+                        TextInput::new(&self.query_input).xsmall().flex_1().suffix(
+                            h_flex()
+                                .child(self.render_option_button(
+                                    "case-sensitive",
+                                    "Aa",
+                                    t!("Input.match_case").into(),
+                                    options.case_sensitive,
+                                    |options| options.case_sensitive = !options.case_sensitive,
+                                    cx,
+                                ))
+                                .child(self.render_option_button(
+                                    "whole-word",
+                                    "ab",
+                                    t!("Input.match_whole_word").into(),
+                                    options.whole_word,
+                                    |options| options.whole_word = !options.whole_word,
+                                    cx,
+                                ))
+                                .child(self.render_option_button(
+                                    "regex",
+                                    ".*",
+                                    t!("Input.use_regex").into(),
+                                    options.regex,
+                                    |options| options.regex = !options.regex,
+                                    cx,
+                                )),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .w(px(64.))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(match_label),
+                    )
+                    .child(
+                        Button::new("previous-match")
+                            .icon(IconName::ArrowUp)
+                            .xsmall()
+                            .ghost()
+                            .tooltip_with_action(
+                                t!("Input.previous_match"),
+                                &FindPrevious,
+                                Some(super::CONTEXT),
+                            )
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.select_previous(window, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("next-match")
+                            .icon(IconName::ArrowDown)
+                            .xsmall()
+                            .ghost()
+                            .tooltip_with_action(
+                                t!("Input.next_match"),
+                                &FindNext,
+                                Some(super::CONTEXT),
+                            )
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.select_next(window, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("close")
+                            .icon(IconName::Close)
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, window, cx| this.close(window, cx))),
+                    ),
+            )
+            .when(self.show_replace, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .pl(px(24.))
+                        .child(TextInput::new(&self.replace_input).xsmall().flex_1())
+                        .child(
+                            Button::new("replace")
+                                .label(t!("Input.replace"))
+                                .xsmall()
+                                .ghost()
+                                .on_click(
+                                    cx.listener(|this, _, window, cx| this.replace(window, cx)),
+                                ),
+                        )
+                        .child(
+                            Button::new("replace-all")
+                                .label(t!("Input.replace_all"))
+                                .xsmall()
+                                .ghost()
+                                .on_click(
+                                    cx.listener(|this, _, window, cx| this.replace_all(window, cx)),
+                                ),
+                        ),
+                )
+            })
+    }
+}
@@ -3,9 +3,10 @@
 //! Based on the `Input` example from the `gpui` crate.
 //! https://github.com/zed-industries/zed/blob/main/crates/gpui/examples/input.rs
 use gpui::Action;
+use ropey::Rope;
 use serde::Deserialize;
 use smallvec::SmallVec;
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::ops::{Deref, Range};
 use std::rc::Rc;
 use unicode_segmentation::*;
@@ -13,9 +14,10 @@ use unicode_segmentation::*;
 use gpui::{
     actions, div, point, prelude::FluentBuilder as _, px, relative, App, AppContext, Bounds,
     ClipboardItem, Context, Entity, EntityInputHandler, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement as _, IntoElement, KeyBinding, KeyDownEvent, MouseButton, MouseDownEvent,
-    MouseMoveEvent, MouseUpEvent, ParentElement as _, Pixels, Point, Render, ScrollHandle,
-    ScrollWheelEvent, SharedString, Styled as _, Subscription, UTF16Selection, Window, WrappedLine,
+    InteractiveElement as _, IntoElement, KeyBinding, KeyContext, KeyDownEvent, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement as _, Pixels, Point, Render,
+    ScrollHandle, ScrollWheelEvent, SharedString, Styled as _, Subscription, UTF16Selection,
+    Window, WrappedLine,
 };
 
 // TODO:
@@ -23,16 +25,22 @@ use gpui::{
 
 use super::{
     blink_cursor::BlinkCursor,
+    brackets::MatchingBracketsCache,
     change::Change,
-    element::TextElement,
+    completion::CompletionState,
+    element::{TextElement, FOLD_GUTTER_WIDTH, LINE_NUMBER_MARGIN_RIGHT, RIGHT_MARGIN},
+    folding::FoldState,
     mask_pattern::MaskPattern,
     mode::{InputMode, TabSize},
+    multi_cursor::{self, CursorSelection},
     number_input,
+    search::{SearchOptions, SearchState},
+    search_panel::SearchPanel,
     text_wrapper::TextWrapper,
 };
 use crate::input::hover_popover::DiagnosticPopover;
 use crate::input::marker::Marker;
-use crate::input::{Cursor, LineColumn, Selection};
+use crate::input::{Cursor, LineColumn, RopeExt as _, Selection};
 use crate::{history::History, scroll::ScrollbarState, Root};
 
 #[derive(Action, Clone, PartialEq, Eq, Deserialize)]
@@ -86,7 +94,28 @@ actions!(
         MoveToEnd,
         MoveToPreviousWord,
         MoveToNextWord,
-        Escape
+        Escape,
+        AddCursorAbove,
+        AddCursorBelow,
+        SelectNextOccurrence,
+        SelectAllOccurrences,
+        Find,
+        FindNext,
+        FindPrevious,
+        FindAndReplace,
+        Fold,
+        Unfold,
+        FoldAll,
+        UnfoldAll,
+        ShowCompletions,
+        ConfirmCompletion,
+        SelectPreviousCompletion,
+        SelectNextCompletion,
+        NextTabstop,
+        PreviousTabstop,
+        MoveToMatchingBracket,
+        ToggleLineComment,
+        ToggleBlockComment
     ]
 );
 
@@ -99,6 +128,10 @@ pub enum InputEvent {
 }
 
 pub(super) const CONTEXT: &str = "Input";
+/// The key context when the completion menu is showing.
+const COMPLETION_CONTEXT: &str = "Input && showing_completions";
+/// The key context when moving between the tabstops of a snippet.
+const SNIPPET_CONTEXT: &str = "Input && in_snippet";
 
 pub fn init(cx: &mut App) {
     cx.bind_keys([
@@ -209,6 +242,72 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("ctrl-z", Undo, Some(CONTEXT)),
         #[cfg(not(target_os = "macos"))]
         KeyBinding::new("ctrl-y", Redo, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-up", AddCursorAbove, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-up", AddCursorAbove, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-down", AddCursorBelow, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-down", AddCursorBelow, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-d", SelectNextOccurrence, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-d", SelectNextOccurrence, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-l", SelectAllOccurrences, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-l", SelectAllOccurrences, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-f", Find, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-f", Find, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-f", FindAndReplace, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-h", FindAndReplace, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-g", FindNext, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("f3", FindNext, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-g", FindPrevious, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("shift-f3", FindPrevious, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-[", Fold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-[", Fold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-]", Unfold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-]", Unfold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-0", FoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-0", FoldAll, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-j", UnfoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-j", UnfoldAll, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-\\", MoveToMatchingBracket, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-\\", MoveToMatchingBracket, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-/", ToggleLineComment, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-/", ToggleLineComment, Some(CONTEXT)),
+        KeyBinding::new("alt-shift-a", ToggleBlockComment, Some(CONTEXT)),
+        #[cfg(feature = "lsp")]
+        KeyBinding::new("f12", super::lsp::GoToDefinition, Some(CONTEXT)),
+        KeyBinding::new("ctrl-space", ShowCompletions, Some(CONTEXT)),
+        KeyBinding::new("tab", NextTabstop, Some(SNIPPET_CONTEXT)),
+        KeyBinding::new("shift-tab", PreviousTabstop, Some(SNIPPET_CONTEXT)),
+        KeyBinding::new("enter", ConfirmCompletion, Some(COMPLETION_CONTEXT)),
+        KeyBinding::new("tab", ConfirmCompletion, Some(COMPLETION_CONTEXT)),
+        KeyBinding::new("up", SelectPreviousCompletion, Some(COMPLETION_CONTEXT)),
+        KeyBinding::new("down", SelectNextCompletion, Some(COMPLETION_CONTEXT)),
     ]);
 
     number_input::init(cx);
@@ -220,8 +319,16 @@ pub(super) struct LastLayout {
     pub(super) lines: Rc<SmallVec<[WrappedLine; 1]>>,
     /// The line_height of text layout, this will change will InputElement painted.
     pub(super) line_height: Pixels,
-    /// The visible range (no wrap) of lines in the viewport.
+    /// The visible range (no wrap) of lines in the viewport, the `lines` only contains these lines.
     pub(super) visible_range: Range<usize>,
+    /// The byte range of the visible lines in the entire text.
+    pub(super) visible_range_offset: Range<usize>,
+    /// The top position of the first visible line, relative to the text origin.
+    pub(super) visible_top: Pixels,
+    /// The row (zero-based) of each line in `lines`, the folded rows are not included.
+    pub(super) rows: Rc<Vec<usize>>,
+    /// The start offset (in the entire text) of each line in `lines`.
+    pub(super) line_offsets: Rc<Vec<usize>>,
 }
 
 impl Deref for LastLayout {
@@ -236,7 +343,11 @@ impl Deref for LastLayout {
 pub struct InputState {
     pub(super) focus_handle: FocusHandle,
     pub(super) mode: InputMode,
-    pub(super) text: SharedString,
+    pub(super) text: Rope,
+    /// The string of the `text`, built on first [`Self::value`] after the text changed.
+    value: OnceCell<SharedString>,
+    /// The matching brackets of the cursor, see [`Self::matching_brackets`].
+    pub(super) bracket_matches: RefCell<Option<MatchingBracketsCache>>,
     pub(super) text_wrapper: TextWrapper,
     pub(super) history: History<Change>,
     pub(super) blink_cursor: Entity<BlinkCursor>,
@@ -249,6 +360,12 @@ pub struct InputState {
     /// Range for save the selected word, use to keep word range when drag move.
     pub(super) selected_word_range: Option<Selection>,
     pub(super) selection_reversed: bool,
+    /// The secondary selections for multi-cursor editing, the `selected_range` is the primary one.
+    ///
+    /// Sorted by offset and never overlap with each other or the primary selection.
+    pub(super) extra_selections: Vec<CursorSelection>,
+    /// The anchor offset of the column (box) selection on alt + mouse dragging.
+    column_selection_anchor: Option<usize>,
     /// The marked range is the temporary insert text on IME typing.
     pub(super) marked_range: Option<Selection>,
     pub(super) last_layout: Option<LastLayout>,
@@ -275,7 +392,18 @@ pub struct InputState {
     pub(super) placeholder: SharedString,
 
     /// Popover
-    diagnostic_popover: Option<Entity<DiagnosticPopover>>,
+    pub(super) diagnostic_popover: Option<Entity<DiagnosticPopover>>,
+
+    /// The search query and matches, the matches will be highlighted.
+    pub(super) search: Option<SearchState>,
+    search_panel: Option<Entity<SearchPanel>>,
+    /// The foldable and folded ranges of the code editor.
+    pub(super) folds: FoldState,
+    /// The language server attached to the code editor.
+    #[cfg(feature = "lsp")]
+    pub(super) lsp: Option<super::lsp::LspState>,
+    /// The completion provider, menu and the active snippet.
+    pub(super) completion: CompletionState,
 
     /// To remember the horizontal column (x-coordinate) of the cursor position for keep column for move up/down.
     preferred_x_offset: Option<Pixels>,
@@ -315,7 +443,9 @@ impl InputState {
 
         Self {
             focus_handle: focus_handle.clone(),
-            text: "".into(),
+            text: Rope::new(),
+            value: OnceCell::new(),
+            bracket_matches: RefCell::new(None),
             text_wrapper: TextWrapper::new(
                 text_style.font(),
                 text_style.font_size.to_pixels(window.rem_size()),
@@ -326,6 +456,8 @@ impl InputState {
             selected_range: (Cursor::new(0)..Cursor::new(0)).into(),
             selected_word_range: None,
             selection_reversed: false,
+            extra_selections: vec![],
+            column_selection_anchor: None,
             marked_range: None,
             input_bounds: Bounds::default(),
             selecting: false,
@@ -348,6 +480,12 @@ impl InputState {
             placeholder: SharedString::default(),
             mask_pattern: MaskPattern::default(),
             diagnostic_popover: None,
+            search: None,
+            search_panel: None,
+            folds: FoldState::new(),
+            #[cfg(feature = "lsp")]
+            lsp: None,
+            completion: CompletionState::new(),
             _subscriptions,
         }
     }
@@ -392,6 +530,9 @@ impl InputState {
     /// - Syntax Highlighting
     /// - Auto Indent
     /// - Line Number
+    /// - Multiple Cursors
+    /// - Find and Replace
+    /// - Code Folding
     pub fn code_editor(mut self, language: impl Into<SharedString>) -> Self {
         let language: SharedString = language.into();
         self.mode = InputMode::CodeEditor {
@@ -543,18 +684,23 @@ impl InputState {
         };
         let line_height = last_layout.line_height;
 
-        let mut prev_lines_offset = 0;
-        let mut y_offset = px(0.);
-        for (line_index, line) in last_layout.lines.iter().enumerate() {
-            let local_offset = offset.saturating_sub(prev_lines_offset);
+        // The offset is not in the visible lines.
+        if offset < last_layout.visible_range_offset.start
+            || offset > last_layout.visible_range_offset.end
+        {
+            return (0, 0, None);
+        }
+
+        let mut y_offset = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            let local_offset = offset.saturating_sub(last_layout.line_offsets[ix]);
             if let Some(pos) = line.position_for_index(local_offset, line_height) {
                 let sub_line_index = (pos.y.0 / line_height.0) as usize;
                 let adjusted_pos = point(pos.x, pos.y + y_offset);
-                return (line_index, sub_line_index, Some(adjusted_pos));
+                return (last_layout.rows[ix], sub_line_index, Some(adjusted_pos));
             }
 
             y_offset += line.size(line_height).height;
-            prev_lines_offset += line.len() + 1;
         }
         (0, 0, None)
     }
@@ -578,9 +724,15 @@ impl InputState {
             self.line_and_position_for_offset(offset);
 
         let Some(current_pos) = current_pos else {
+            self.move_vertical_by_rows(move_lines, cx);
             return;
         };
 
+        // Index of the line in the `last_layout.lines`.
+        let current_line = last_layout
+            .rows
+            .binary_search(&current_line)
+            .unwrap_or_default();
         let current_x = self
             .preferred_x_offset
             .unwrap_or_else(|| current_pos.x + bounds.origin.x);
@@ -590,7 +742,7 @@ impl InputState {
         new_sub_line += if move_lines > 0 { 1 } else { -1 };
 
         // Handle moving above the first line
-        if move_lines < 0 && current_line == 0 && new_sub_line < 0 {
+        if move_lines < 0 && last_layout.rows[current_line] == 0 && new_sub_line < 0 {
             // Move cursor to the beginning of the text
             self.move_to(Cursor::new(0), window, cx);
             self.preferred_x_offset = preferred_x_offset;
@@ -598,22 +750,30 @@ impl InputState {
         }
 
         if new_sub_line < 0 {
-            new_line = new_line
-                .saturating_add_signed(move_lines)
-                .max(0)
-                .min(last_layout.lines.len().saturating_sub(1));
+            if current_line as isize + move_lines < 0 {
+                // The target line is out of the visible lines.
+                self.move_vertical_by_rows(move_lines, cx);
+                return;
+            }
+
+            new_line = new_line.saturating_add_signed(move_lines);
             new_sub_line = last_layout.lines[new_line].wrap_boundaries.len() as i32;
         } else {
             let max_sub_line = last_layout.lines[current_line].wrap_boundaries.len() as i32;
             if new_sub_line > max_sub_line {
-                if new_line < last_layout.lines.len() - 1 {
-                    new_line = new_line
-                        .saturating_add_signed(move_lines)
-                        .max(0)
-                        .min(last_layout.lines.len().saturating_sub(1));
-                    new_sub_line = 0;
-                } else {
+                let is_last_line = self
+                    .folds
+                    .visible_row(last_layout.rows[new_line] + 1, true)
+                    .map_or(true, |row| row >= self.text.len_lines());
+                if is_last_line {
                     new_sub_line = max_sub_line;
+                } else if new_line as isize + move_lines >= last_layout.lines.len() as isize {
+                    // The target line is out of the visible lines.
+                    self.move_vertical_by_rows(move_lines, cx);
+                    return;
+                } else {
+                    new_line = new_line.saturating_add_signed(move_lines);
+                    new_sub_line = 0;
                 }
             }
         }
@@ -635,17 +795,8 @@ impl InputState {
             Err(i) => i,
         };
 
-        let mut prev_lines_offset = 0;
-        for (_, line) in last_layout
-            .lines
-            .iter()
-            .enumerate()
-            .take_while(|(i, _)| *i < new_line)
-        {
-            prev_lines_offset += line.len() + 1;
-        }
-
-        let new_offset = (prev_lines_offset + new_local_index).min(self.text.len());
+        let prev_lines_offset = last_layout.line_offsets[new_line];
+        let new_offset = (prev_lines_offset + new_local_index).min(self.text.len_bytes());
         let new_cursor = Cursor::new(new_offset);
         self.selected_range = (new_cursor..new_cursor).into();
         self.pause_blink_cursor(cx);
@@ -654,6 +805,31 @@ impl InputState {
         cx.notify();
     }
 
+    /// Move the cursor vertically by rows (no wrap) and keep the column (in chars).
+    ///
+    /// This is used when the target line is not in the last layout (Out of the viewport),
+    /// so the soft wrapped lines will be ignored.
+    fn move_vertical_by_rows(&mut self, move_lines: isize, cx: &mut Context<Self>) {
+        let (row, column) = self.text.offset_to_row_column(self.cursor().offset);
+        let new_row = row
+            .saturating_add_signed(move_lines)
+            .min(self.text.len_lines().saturating_sub(1));
+        // Skip the folded rows.
+        let new_row = self
+            .folds
+            .visible_row(new_row, move_lines > 0)
+            .filter(|row| *row < self.text.len_lines())
+            .unwrap_or(row);
+        let new_offset = self.text.row_column_to_offset(new_row, column);
+
+        let preferred_x_offset = self.preferred_x_offset;
+        let new_cursor = Cursor::new(new_offset);
+        self.selected_range = (new_cursor..new_cursor).into();
+        self.pause_blink_cursor(cx);
+        self.preferred_x_offset = preferred_x_offset;
+        cx.notify();
+    }
+
     /// Set the text of the input field.
     ///
     /// And the selection_range will be reset to 0..0.
@@ -664,12 +840,13 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         self.history.ignore = true;
+        self.clear_extra_selections();
         self.replace_text(value, window, cx);
         self.history.ignore = false;
         // Ensure cursor to start when set text
         if self.mode.is_single_line() {
             self.selected_range =
-                (Cursor::new(self.text.len())..Cursor::new(self.text.len())).into();
+                (Cursor::new(self.text.len_bytes())..Cursor::new(self.text.len_bytes())).into();
         } else {
             self.selected_range = (Cursor::new(0)..Cursor::new(0)).into();
         }
@@ -704,7 +881,9 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         let text: SharedString = text.into();
+        self.completion.inserting = true;
         self.replace_text_in_range(None, &text, window, cx);
+        self.completion.inserting = false;
         self.selected_range = (self.selected_range.end..self.selected_range.end).into();
     }
 
@@ -715,7 +894,7 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         let text: SharedString = text.into();
-        let range = 0..self.text.chars().map(|c| c.len_utf16()).sum();
+        let range = 0..self.text.len_utf16_cu();
         self.replace_text_in_range(Some(range), &text, window, cx);
         self.reset_highlighter(cx);
     }
@@ -789,19 +968,33 @@ impl InputState {
 
     /// Set the default value of the input field.
     pub fn default_value(mut self, value: impl Into<SharedString>) -> Self {
-        self.text = value.into();
-        self.text_wrapper.text = self.text.clone();
+        let value: SharedString = value.into();
+        self.text = Rope::from_str(&value);
+        self.value = OnceCell::from(value);
+        self.text_wrapper.set_default_text(&self.text);
         self
     }
 
     /// Return the value of the input field.
+    ///
+    /// The string is built from the text buffer once after each change, use [`Self::text`] to
+    /// read a large text without building the string.
     pub fn value(&self) -> &SharedString {
-        &self.text
+        self.value.get_or_init(|| self.text.to_string().into())
     }
 
     /// Return the value without mask.
     pub fn unmask_value(&self) -> SharedString {
-        self.mask_pattern.unmask(&self.text).into()
+        if self.mask_pattern.is_none() {
+            return self.value().clone();
+        }
+
+        self.mask_pattern.unmask(self.value()).into()
+    }
+
+    /// Return the text buffer of the input field.
+    pub fn text(&self) -> &Rope {
+        &self.text
     }
 
     /// Return the line and column (1-based) of the cursor.
@@ -814,30 +1007,41 @@ impl InputState {
         self.focus_handle.focus(window);
     }
 
+    /// The key context of the input field, with the states for the completion and snippet keys.
+    pub(super) fn key_context(&self) -> KeyContext {
+        let mut context = KeyContext::default();
+        context.add(CONTEXT);
+        if self.completion.menu.is_some() {
+            context.add("showing_completions");
+        }
+        if self.completion.snippet.is_some() {
+            context.add("in_snippet");
+        }
+        context
+    }
+
     pub(super) fn left(&mut self, _: &MoveLeft, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        if self.selected_range.is_empty() {
-            self.move_to(
-                Cursor::new(self.previous_boundary(self.cursor().offset)),
-                window,
-                cx,
-            );
-        } else {
-            self.move_to(self.selected_range.start, window, cx)
-        }
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if this.selected_range.is_empty() {
+                let offset = this.previous_boundary(this.cursor().offset);
+                this.move_to(Cursor::new(this.skip_folded(offset, false)), window, cx);
+            } else {
+                this.move_to(this.selected_range.start, window, cx)
+            }
+        });
     }
 
     pub(super) fn right(&mut self, _: &MoveRight, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        if self.selected_range.is_empty() {
-            self.move_to(
-                Cursor::new(self.next_boundary(self.selected_range.end.offset)),
-                window,
-                cx,
-            );
-        } else {
-            self.move_to(self.selected_range.end, window, cx)
-        }
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if this.selected_range.is_empty() {
+                let offset = this.next_boundary(this.selected_range.end.offset);
+                this.move_to(Cursor::new(this.skip_folded(offset, true)), window, cx);
+            } else {
+                this.move_to(this.selected_range.end, window, cx)
+            }
+        });
     }
 
     pub(super) fn up(&mut self, _: &MoveUp, window: &mut Window, cx: &mut Context<Self>) {
@@ -845,15 +1049,19 @@ impl InputState {
             return;
         }
 
-        if !self.selected_range.is_empty() {
-            self.move_to(
-                Cursor::new(self.previous_boundary(self.selected_range.start.saturating_sub(1))),
-                window,
-                cx,
-            );
-        }
         self.pause_blink_cursor(cx);
-        self.move_vertical(-1, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.move_to(
+                    Cursor::new(
+                        this.previous_boundary(this.selected_range.start.saturating_sub(1)),
+                    ),
+                    window,
+                    cx,
+                );
+            }
+            this.move_vertical(-1, window, cx);
+        });
     }
 
     pub(super) fn down(&mut self, _: &MoveDown, window: &mut Window, cx: &mut Context<Self>) {
@@ -861,16 +1069,19 @@ impl InputState {
             return;
         }
 
-        if !self.selected_range.is_empty() {
-            self.move_to(
-                Cursor::new(self.next_boundary(self.selected_range.end.offset.saturating_sub(1))),
-                window,
-                cx,
-            );
-        }
-
         self.pause_blink_cursor(cx);
-        self.move_vertical(1, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.move_to(
+                    Cursor::new(
+                        this.next_boundary(this.selected_range.end.offset.saturating_sub(1)),
+                    ),
+                    window,
+                    cx,
+                );
+            }
+            this.move_vertical(1, window, cx);
+        });
     }
 
     pub(super) fn page_up(&mut self, _: &MovePageUp, window: &mut Window, cx: &mut Context<Self>) {
@@ -910,11 +1121,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.select_to(
-            Cursor::new(self.previous_boundary(self.cursor().offset)),
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.previous_boundary(this.cursor().offset);
+            this.select_to(Cursor::new(this.skip_folded(offset, false)), window, cx);
+        });
     }
 
     pub(super) fn select_right(
@@ -923,19 +1133,20 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.select_to(
-            Cursor::new(self.next_boundary(self.cursor().offset)),
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.next_boundary(this.cursor().offset);
+            this.select_to(Cursor::new(this.skip_folded(offset, true)), window, cx);
+        });
     }
 
     pub(super) fn select_up(&mut self, _: &SelectUp, window: &mut Window, cx: &mut Context<Self>) {
         if self.mode.is_single_line() {
             return;
         }
-        let offset = self.start_of_line(window, cx).saturating_sub(1);
-        self.select_to(Cursor::new(self.previous_boundary(offset)), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.start_of_line(window, cx).saturating_sub(1);
+            this.select_to(Cursor::new(this.previous_boundary(offset)), window, cx);
+        });
     }
 
     pub(super) fn select_down(
@@ -947,8 +1158,10 @@ impl InputState {
         if self.mode.is_single_line() {
             return;
         }
-        let offset = (self.end_of_line(window, cx) + 1).min(self.text.len());
-        self.select_to(Cursor::new(self.next_boundary(offset)), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = (this.end_of_line(window, cx) + 1).min(this.text.len_bytes());
+            this.select_to(Cursor::new(this.next_boundary(offset)), window, cx);
+        });
     }
 
     pub(super) fn select_all(
@@ -957,20 +1170,25 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections();
         self.move_to(Cursor::new(0), window, cx);
-        self.select_to(Cursor::new(self.text.len()), window, cx)
+        self.select_to(Cursor::new(self.text.len_bytes()), window, cx)
     }
 
     pub(super) fn home(&mut self, _: &MoveHome, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        let offset = self.start_of_line(window, cx);
-        self.move_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.start_of_line(window, cx);
+            this.move_to(Cursor::new(offset), window, cx);
+        });
     }
 
     pub(super) fn end(&mut self, _: &MoveEnd, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        let offset = self.end_of_line(window, cx);
-        self.move_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.end_of_line(window, cx);
+            this.move_to(Cursor::new(offset), window, cx);
+        });
     }
 
     pub(super) fn move_to_start(
@@ -979,6 +1197,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections();
         self.move_to(Cursor::new(0), window, cx);
     }
 
@@ -988,7 +1207,8 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let end = self.text.len();
+        self.clear_extra_selections();
+        let end = self.text.len_bytes();
         self.move_to(Cursor::new(end), window, cx);
     }
 
@@ -998,8 +1218,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.move_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.move_to(Cursor::new(offset), window, cx);
+        });
     }
 
     pub(super) fn move_to_next_word(
@@ -1008,8 +1230,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.move_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.next_end_of_word();
+            this.move_to(Cursor::new(offset), window, cx);
+        });
     }
 
     pub(super) fn select_to_start(
@@ -1027,7 +1251,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let end = self.text.len();
+        let end = self.text.len_bytes();
         self.select_to(Cursor::new(end), window, cx);
     }
 
@@ -1037,8 +1261,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.start_of_line(window, cx);
-        self.select_to(Cursor::new(self.previous_boundary(offset)), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.start_of_line(window, cx);
+            this.select_to(Cursor::new(this.previous_boundary(offset)), window, cx);
+        });
     }
 
     pub(super) fn select_to_end_of_line(
@@ -1047,8 +1273,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.end_of_line(window, cx);
-        self.select_to(Cursor::new(self.next_boundary(offset)), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.end_of_line(window, cx);
+            this.select_to(Cursor::new(this.next_boundary(offset)), window, cx);
+        });
     }
 
     pub(super) fn select_to_previous_word(
@@ -1057,8 +1285,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.select_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.select_to(Cursor::new(offset), window, cx);
+        });
     }
 
     pub(super) fn select_to_next_word(
@@ -1067,143 +1297,121 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.select_to(Cursor::new(offset), window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.next_end_of_word();
+            this.select_to(Cursor::new(offset), window, cx);
+        });
     }
 
     /// Return the start offset of the previous word.
+    ///
+    /// Only the lines before the offset will be scanned until a word is found.
     fn previous_start_of_word(&mut self) -> usize {
-        let offset = self.selected_range.start.offset;
-        let prev_str = self.text_for_range_utf8(0..offset);
-        UnicodeSegmentation::split_word_bound_indices(prev_str)
-            .filter(|(_, s)| !s.trim_start().is_empty())
-            .next_back()
-            .map(|(i, _)| i)
-            .unwrap_or(0)
+        let offset = self.text.clip_offset(self.selected_range.start.offset);
+        let mut row = self.text.byte_to_line(offset);
+        let mut end = offset;
+        loop {
+            let line_start = self.text.line_start_offset(row);
+            let prev_str = self.text.slice_text(line_start..end);
+            if let Some(ix) = UnicodeSegmentation::split_word_bound_indices(prev_str.as_str())
+                .filter(|(_, s)| !s.trim_start().is_empty())
+                .next_back()
+                .map(|(i, _)| i)
+            {
+                return line_start + ix;
+            }
+
+            if row == 0 {
+                return 0;
+            }
+            row -= 1;
+            end = line_start;
+        }
     }
 
     /// Return the next end offset of the next word.
+    ///
+    /// Only the lines after the offset will be scanned until a word is found.
     fn next_end_of_word(&mut self) -> usize {
-        let offset = self.cursor().offset;
-        let next_str = self.text_for_range_utf8(offset..self.text.len());
-        UnicodeSegmentation::split_word_bound_indices(next_str)
-            .find(|(_, s)| !s.trim_start().is_empty())
-            .map(|(i, s)| offset + i + s.len())
-            .unwrap_or(self.text.len())
+        let offset = self.text.clip_offset(self.cursor().offset);
+        let mut row = self.text.byte_to_line(offset);
+        let mut start = offset;
+        loop {
+            let line_end = self.text.line_start_offset(row + 1);
+            let next_str = self.text.slice_text(start..line_end);
+            if let Some(end) = UnicodeSegmentation::split_word_bound_indices(next_str.as_str())
+                .find(|(_, s)| !s.trim_start().is_empty())
+                .map(|(i, s)| start + i + s.len())
+            {
+                return end;
+            }
+
+            if line_end >= self.text.len_bytes() {
+                return self.text.len_bytes();
+            }
+            row += 1;
+            start = line_end;
+        }
     }
 
     /// Get start of line
-    fn start_of_line(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn start_of_line(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.mode.is_single_line() {
             return 0;
         }
 
-        let offset = self.previous_boundary(self.cursor().offset);
-        let line = self
-            .text_for_range(self.range_to_utf16(&(0..offset + 1)), &mut None, window, cx)
-            .unwrap_or_default()
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        line
+        let offset = self.text.clip_offset(self.cursor().offset);
+        self.text.line_start_offset(self.text.byte_to_line(offset))
     }
 
     /// Get start line of selection start or end (The min value).
     ///
     /// This is means is always get the first line of selection.
-    fn start_of_line_of_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn start_of_line_of_selection(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.mode.is_single_line() {
             return 0;
         }
 
-        let mut offset = self.previous_boundary(
+        let offset = self.text.clip_offset(
             self.selected_range
                 .start
                 .min(self.selected_range.end.offset),
         );
-        if self.text.chars().nth(offset) == Some('\r') {
-            offset += 1;
-        }
-
-        let line = self
-            .text_for_range(self.range_to_utf16(&(0..offset + 1)), &mut None, window, cx)
-            .unwrap_or_default()
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        line
+        self.text.line_start_offset(self.text.byte_to_line(offset))
     }
 
     /// Get end of line
-    fn end_of_line(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn end_of_line(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.mode.is_single_line() {
-            return self.text.len();
-        }
-
-        let offset = self.next_boundary(self.cursor().offset);
-        // ignore if offset is "\n"
-        if self
-            .text_for_range(
-                self.range_to_utf16(&(offset.saturating_sub(1)..offset)),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default()
-            .eq("\n")
-        {
-            return offset;
+            return self.text.len_bytes();
         }
 
-        let line = self
-            .text_for_range(
-                self.range_to_utf16(&(offset..self.text.len())),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default()
-            .find('\n')
-            .map(|i| i + offset)
-            .unwrap_or(self.text.len());
-        line
+        let offset = self.text.clip_offset(self.cursor().offset);
+        self.text.line_end_offset(self.text.byte_to_line(offset))
     }
 
     /// Get indent string of next line.
     ///
     /// To get current and next line indent, to return more depth one.
-    pub(super) fn indent_of_next_line(
-        &mut self,
-        window: &mut Window,
-        cx: &mut Context<Self>,
-    ) -> String {
+    pub(super) fn indent_of_next_line(&mut self, _: &mut Window, _: &mut Context<Self>) -> String {
         if self.mode.is_single_line() {
             return "".into();
         }
 
-        let mut current_indent = String::new();
-        let mut next_indent = String::new();
-        let current_line_start_pos = self.start_of_line(window, cx);
-        let next_line_start_pos = self.end_of_line(window, cx);
-        for c in self.text.chars().skip(current_line_start_pos) {
-            if !c.is_whitespace() {
-                break;
-            }
-            if c == '\n' || c == '\r' {
-                break;
-            }
-            current_indent.push(c);
+        fn indent_of(line: &str) -> String {
+            line.chars()
+                .take_while(|c| c.is_whitespace() && *c != '\n' && *c != '\r')
+                .collect()
         }
 
-        for c in self.text.chars().skip(next_line_start_pos) {
-            if !c.is_whitespace() {
-                break;
-            }
-            if c == '\n' || c == '\r' {
-                break;
-            }
-            next_indent.push(c);
-        }
+        let offset = self.text.clip_offset(self.cursor().offset);
+        let row = self.text.byte_to_line(offset);
+        let current_indent = indent_of(&self.text.line_text(row));
+        let next_indent = if row + 1 < self.text.len_lines() {
+            indent_of(&self.text.line_text(row + 1))
+        } else {
+            String::new()
+        };
 
         if next_indent.len() > current_indent.len() {
             return next_indent;
@@ -1213,26 +1421,32 @@ impl InputState {
     }
 
     pub(super) fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
-            self.select_to(
-                Cursor::new(self.previous_boundary(self.cursor().offset)),
-                window,
-                cx,
-            )
-        }
-        self.replace_text_in_range(None, "", window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if let Some(range) = this.empty_bracket_pair(cx) {
+                this.selected_range = range.into();
+            } else if this.selected_range.is_empty() {
+                this.select_to(
+                    Cursor::new(this.previous_boundary(this.cursor().offset)),
+                    window,
+                    cx,
+                )
+            }
+            this.replace_text_in_range(None, "", window, cx);
+        });
         self.pause_blink_cursor(cx);
     }
 
     pub(super) fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
-            self.select_to(
-                Cursor::new(self.next_boundary(self.cursor().offset)),
-                window,
-                cx,
-            )
-        }
-        self.replace_text_in_range(None, "", window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if this.selected_range.is_empty() {
+                this.select_to(
+                    Cursor::new(this.next_boundary(this.cursor().offset)),
+                    window,
+                    cx,
+                )
+            }
+            this.replace_text_in_range(None, "", window, cx);
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1242,16 +1456,18 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut offset = self.start_of_line(window, cx);
-        if offset == self.cursor().offset {
-            offset = offset.saturating_sub(1);
-        }
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(offset..self.cursor().offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let mut offset = this.start_of_line(window, cx);
+            if offset == this.cursor().offset {
+                offset = offset.saturating_sub(1);
+            }
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(offset..this.cursor().offset))),
+                "",
+                window,
+                cx,
+            );
+        });
 
         self.pause_blink_cursor(cx);
     }
@@ -1262,16 +1478,18 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut offset = self.end_of_line(window, cx);
-        if offset == self.cursor().offset {
-            offset = (offset + 1).clamp(0, self.text.len());
-        }
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(self.cursor().offset..offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let mut offset = this.end_of_line(window, cx);
+            if offset == this.cursor().offset {
+                offset = (offset + 1).clamp(0, this.text.len_bytes());
+            }
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(this.cursor().offset..offset))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1281,13 +1499,15 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(offset..self.cursor().offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(offset..this.cursor().offset))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1297,28 +1517,39 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(self.cursor().offset..offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            let offset = this.next_end_of_word();
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(this.cursor().offset..offset))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
     pub(super) fn enter(&mut self, action: &Enter, window: &mut Window, cx: &mut Context<Self>) {
         if self.mode.is_multi_line() {
-            // Get current line indent
-            let indent = if self.mode.is_code_editor() {
-                self.indent_of_next_line(window, cx)
-            } else {
-                "".to_string()
-            };
+            self.for_each_selection(window, cx, |this, _, window, cx| {
+                if let Some((new_line_text, cursor)) = this.auto_indent_new_line(cx) {
+                    let offset = this.selected_range.start.offset + cursor;
+                    this.replace_text_in_range(None, &new_line_text, window, cx);
+                    this.move_to(Cursor::new(offset), window, cx);
+                    return;
+                }
 
-            // Add newline and indent
-            let new_line_text = format!("\n{}", indent);
-            self.replace_text_in_range(None, &new_line_text, window, cx);
+                // Get current line indent
+                let indent = if this.mode.is_code_editor() {
+                    this.indent_of_next_line(window, cx)
+                } else {
+                    "".to_string()
+                };
+
+                // Add newline and indent
+                let new_line_text = format!("\n{}", indent);
+                this.replace_text_in_range(None, &new_line_text, window, cx);
+            });
         }
 
         cx.emit(InputEvent::PressEnter {
@@ -1332,11 +1563,15 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.indent(false, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.indent(false, window, cx);
+        });
     }
 
     pub(super) fn indent_block(&mut self, _: &Indent, window: &mut Window, cx: &mut Context<Self>) {
-        self.indent(true, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.indent(true, window, cx);
+        });
     }
 
     pub(super) fn outdent_inline(
@@ -1345,7 +1580,9 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.outdent(false, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.outdent(false, window, cx);
+        });
     }
 
     pub(super) fn outdent_block(
@@ -1354,7 +1591,9 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.outdent(true, window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.outdent(true, window, cx);
+        });
     }
 
     pub(super) fn indent(&mut self, block: bool, window: &mut Window, cx: &mut Context<Self>) {
@@ -1470,7 +1709,7 @@ impl InputState {
             let start_offset = self.selected_range.start;
             let offset = self.start_of_line_of_selection(window, cx);
             if self
-                .text_for_range_utf8(offset..self.text.len())
+                .text_for_range_utf8(offset..offset + tab_indent.len())
                 .starts_with(tab_indent.as_ref())
             {
                 self.replace_text_in_range(
@@ -1494,6 +1733,24 @@ impl InputState {
         if self.marked_range.is_some() {
             self.unmark_text(window, cx);
         }
+        if self.hide_completions(cx) {
+            return;
+        }
+        if self.finish_snippet() {
+            return;
+        }
+        #[cfg(feature = "lsp")]
+        if self.hide_signature_help(cx) {
+            return;
+        }
+        if self.has_extra_selections() {
+            self.clear_extra_selections();
+            cx.notify();
+            return;
+        }
+        if self.search_panel.is_some() {
+            return self.hide_search_panel(window, cx);
+        }
         if self.selected_range.len() > 0 {
             return self.unselect(window, cx);
         }
@@ -1519,8 +1776,25 @@ impl InputState {
             }
         }
 
+        // Click the fold toggle in the gutter.
+        if let Some(row) = self.fold_toggle_for_mouse_position(event.position, window, cx) {
+            self.toggle_fold_row(row, window, cx);
+            return;
+        }
+
         self.selecting = true;
         let offset = self.index_for_mouse_position(event.position, window, cx);
+
+        // Alt + click to add a cursor, and drag to make a column selection.
+        if self.mode.is_code_editor() && event.modifiers.alt && !event.modifiers.shift {
+            self.add_selection(CursorSelection::new(offset..offset, false));
+            self.column_selection_anchor = Some(offset);
+            self.pause_blink_cursor(cx);
+            cx.notify();
+            return;
+        }
+
+        self.clear_extra_selections();
         // Double click to select word
         if event.button == MouseButton::Left && event.click_count == 2 {
             self.select_word(offset, window, cx);
@@ -1542,6 +1816,7 @@ impl InputState {
     ) {
         self.selecting = false;
         self.selected_word_range = None;
+        self.column_selection_anchor = None;
     }
 
     pub(super) fn on_mouse_move(
@@ -1567,6 +1842,12 @@ impl InputState {
                 self.diagnostic_popover = Some(DiagnosticPopover::new(marker, cx.entity(), cx));
                 cx.notify();
             } else {
+                // Keep the hover popover if the mouse is still in the hover range.
+                #[cfg(feature = "lsp")]
+                if self.lsp_hover(offset, window, cx) {
+                    return;
+                }
+
                 if let Some(diagnostic_popover) = self.diagnostic_popover.as_mut() {
                     diagnostic_popover.update(cx, |this, cx| {
                         this.check_to_hide(event.position, cx);
@@ -1616,22 +1897,24 @@ impl InputState {
     }
 
     pub(super) fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
+        let Some(selected_text) = self.selected_texts() else {
             return;
-        }
+        };
 
-        let selected_text = self.text_for_range_utf8(self.selected_range).to_string();
         cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
     }
 
     pub(super) fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
+        let Some(selected_text) = self.selected_texts() else {
             return;
-        }
+        };
 
-        let selected_text = self.text_for_range_utf8(self.selected_range).to_string();
         cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
-        self.replace_text_in_range(None, "", window, cx);
+        self.for_each_selection(window, cx, |this, _, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.replace_text_in_range(None, "", window, cx);
+            }
+        });
     }
 
     pub(super) fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
@@ -1641,8 +1924,40 @@ impl InputState {
                 new_text = new_text.replace('\n', "");
             }
 
-            self.replace_text_in_range(None, &new_text, window, cx);
+            // Paste each line to each cursor, if the lines count is same as the cursors.
+            let lines = new_text.split('\n').collect::<Vec<_>>();
+            let split_lines =
+                self.has_extra_selections() && lines.len() == self.extra_selections.len() + 1;
+
+            self.for_each_selection(window, cx, |this, ix, window, cx| {
+                let new_text = if split_lines {
+                    lines[ix]
+                } else {
+                    new_text.as_str()
+                };
+                // Use the explicit range to avoid the auto closing brackets.
+                let range_utf16 = this.range_to_utf16(&this.selected_range.into());
+                this.replace_text_in_range(Some(range_utf16), new_text, window, cx);
+            });
+        }
+    }
+
+    /// Returns the selected texts of all selections, joined by `\n`.
+    ///
+    /// Returns None if nothing is selected.
+    fn selected_texts(&self) -> Option<String> {
+        let texts = self
+            .all_selections()
+            .into_iter()
+            .filter(|selection| !selection.range.is_empty())
+            .map(|selection| self.text_for_range_utf8(selection.range))
+            .collect::<Vec<_>>();
+
+        if texts.is_empty() {
+            return None;
         }
+
+        Some(texts.join("\n"))
     }
 
     fn push_history(
@@ -1667,6 +1982,7 @@ impl InputState {
     }
 
     pub(super) fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_selections();
         self.history.ignore = true;
         if let Some(changes) = self.history.undo() {
             for change in changes {
@@ -1678,6 +1994,7 @@ impl InputState {
     }
 
     pub(super) fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_selections();
         self.history.ignore = true;
         if let Some(changes) = self.history.redo() {
             for change in changes {
@@ -1693,8 +2010,8 @@ impl InputState {
     /// The offset is the UTF-8 offset.
     ///
     /// Ensure the offset use self.next_boundary or self.previous_boundary to get the correct offset.
-    fn move_to(&mut self, cursor: Cursor, _: &mut Window, cx: &mut Context<Self>) {
-        let cursor = Cursor::new(cursor.offset.clamp(0, self.text.len()));
+    pub(super) fn move_to(&mut self, cursor: Cursor, _: &mut Window, cx: &mut Context<Self>) {
+        let cursor = Cursor::new(cursor.offset.clamp(0, self.text.len_bytes()));
         self.selected_range = (cursor..cursor).into();
         self.pause_blink_cursor(cx);
         self.update_preferred_x_offset(cx);
@@ -1723,7 +2040,7 @@ impl InputState {
         _cx: &App,
     ) -> usize {
         // If the text is empty, always return 0
-        if self.text.is_empty() {
+        if self.text.len_bytes() == 0 {
             return 0;
         }
 
@@ -1747,10 +2064,12 @@ impl InputState {
         // - included the scroll offset.
         let inner_position = position - bounds.origin - point(self.line_number_width, px(0.));
 
-        let mut index = 0;
-        let mut y_offset = px(0.);
+        let mut index = last_layout.visible_range_offset.start;
+        let mut y_offset = last_layout.visible_top;
 
-        for (_, line) in last_layout.lines.iter().enumerate() {
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            // The folded lines are not in the layout, so start from the offset of each line.
+            index = last_layout.line_offsets[ix];
             let line_origin = self.line_origin_with_y_offset(&mut y_offset, &line, line_height);
             let pos = inner_position - line_origin;
 
@@ -1787,11 +2106,7 @@ impl InputState {
             index += 1;
         }
 
-        if index > self.text.len() {
-            self.text.len()
-        } else {
-            index
-        }
+        self.skip_folded(index.min(self.text.len_bytes()), false)
     }
 
     /// Returns a y offsetted point for the line origin.
@@ -1821,7 +2136,7 @@ impl InputState {
     ///
     /// Ensure the offset use self.next_boundary or self.previous_boundary to get the correct offset.
     fn select_to(&mut self, offset: Cursor, _: &mut Window, cx: &mut Context<Self>) {
-        let offset = offset.clamp(0, self.text.len());
+        let offset = offset.clamp(0, self.text.len_bytes());
         if self.selection_reversed {
             self.selected_range.start = Cursor::new(offset)
         } else {
@@ -1853,41 +2168,31 @@ impl InputState {
     /// The offset is the UTF-8 offset.
     ///
     /// FIXME: When click on a non-word character, the word is not selected.
-    fn select_word(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+    fn select_word(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
         #[inline(always)]
         fn is_word(c: char) -> bool {
             c.is_alphanumeric() || matches!(c, '_')
         }
 
+        let offset = self.text.clip_offset(offset);
+        let line_start = self.text.line_start_offset(self.text.byte_to_line(offset));
+        let line_end = self
+            .text
+            .line_start_offset(self.text.byte_to_line(offset) + 1);
+        let prev_text = self.text.slice_text(line_start..offset);
+        let next_text = self.text.slice_text(offset..line_end);
+
         let mut start = offset;
         let mut end = start;
-        let prev_text = self
-            .text_for_range(self.range_to_utf16(&(0..start)), &mut None, window, cx)
-            .unwrap_or_default();
-        let next_text = self
-            .text_for_range(
-                self.range_to_utf16(&(end..self.text.len())),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default();
-
-        let prev_chars = prev_text.chars().rev();
-        let next_chars = next_text.chars();
-
-        let pre_chars_count = prev_chars.clone().count();
-        for (ix, c) in prev_chars.enumerate() {
+        for c in prev_text.chars().rev() {
             if !is_word(c) {
                 break;
             }
 
-            if ix < pre_chars_count {
-                start = start.saturating_sub(c.len_utf8());
-            }
+            start = start.saturating_sub(c.len_utf8());
         }
 
-        for (_, c) in next_chars.enumerate() {
+        for c in next_text.chars() {
             if !is_word(c) {
                 break;
             }
@@ -1910,34 +2215,689 @@ impl InputState {
         cx.notify()
     }
 
-    pub(super) fn offset_from_utf16(&self, offset: usize) -> usize {
-        let mut utf8_offset = 0;
-        let mut utf16_count = 0;
+    pub(super) fn add_cursor_above(
+        &mut self,
+        _: &AddCursorAbove,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_cursor_vertical(-1, cx);
+    }
 
-        for ch in self.text.chars() {
-            if utf16_count >= offset {
-                break;
+    pub(super) fn add_cursor_below(
+        &mut self,
+        _: &AddCursorBelow,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_cursor_vertical(1, cx);
+    }
+
+    /// Add a cursor at the same column (in chars) of the above or below line of the primary cursor,
+    /// the new cursor will be the primary cursor.
+    fn add_cursor_vertical(&mut self, move_lines: isize, cx: &mut Context<Self>) {
+        if self.mode.is_single_line() {
+            return;
+        }
+
+        let (row, column) = self.text.offset_to_row_column(self.cursor().offset);
+        let Some(new_row) = row
+            .checked_add_signed(move_lines)
+            .and_then(|row| self.folds.visible_row(row, move_lines > 0))
+        else {
+            return;
+        };
+        if new_row >= self.text.len_lines() {
+            return;
+        }
+
+        let offset = self.text.row_column_to_offset(new_row, column);
+        self.add_selection(CursorSelection::new(offset..offset, false));
+        self.pause_blink_cursor(cx);
+        cx.notify();
+    }
+
+    /// Select the next occurrence of the selected text, the word under the cursor will be
+    /// selected first if there is no selection.
+    pub(super) fn select_next_occurrence(
+        &mut self,
+        _: &SelectNextOccurrence,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selected_range.is_empty() {
+            let offset = self.cursor().offset;
+            self.select_word(offset, window, cx);
+            self.selected_word_range = None;
+            return;
+        }
+
+        let query = self.text_for_range_utf8(self.selected_range);
+        let occurrences = multi_cursor::find_occurrences(self.value(), &query);
+        let selections = self.all_selections();
+        let offset = self.selected_range.end.offset;
+
+        // Find the next not selected occurrence after the primary selection, wrap to the start.
+        let next_range = occurrences
+            .iter()
+            .filter(|range| range.start >= offset)
+            .chain(occurrences.iter().filter(|range| range.start < offset))
+            .find(|range| {
+                !selections
+                    .iter()
+                    .any(|selection| Range::<usize>::from(selection.range) == **range)
+            });
+
+        if let Some(range) = next_range {
+            self.add_selection(CursorSelection::new(range.clone(), false));
+            cx.notify();
+        }
+    }
+
+    /// Select all occurrences of the selected text, the word under the cursor will be used
+    /// if there is no selection.
+    pub(super) fn select_all_occurrences(
+        &mut self,
+        _: &SelectAllOccurrences,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selected_range.is_empty() {
+            let offset = self.cursor().offset;
+            self.select_word(offset, window, cx);
+            self.selected_word_range = None;
+            if self.selected_range.is_empty() {
+                return;
             }
-            utf16_count += ch.len_utf16();
-            utf8_offset += ch.len_utf8();
         }
 
-        utf8_offset
+        let query = self.text_for_range_utf8(self.selected_range);
+        let primary_range: Range<usize> = self.selected_range.into();
+        self.extra_selections = multi_cursor::find_occurrences(self.value(), &query)
+            .into_iter()
+            .filter(|range| *range != primary_range)
+            .map(|range| CursorSelection::new(range, false))
+            .collect();
+        self.merge_selections();
+        cx.notify();
     }
 
-    pub(super) fn offset_to_utf16(&self, offset: usize) -> usize {
-        let mut utf16_offset = 0;
-        let mut utf8_count = 0;
+    pub(super) fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        self.show_search_panel(false, window, cx);
+    }
 
-        for ch in self.text.chars() {
-            if utf8_count >= offset {
-                break;
+    pub(super) fn find_and_replace(
+        &mut self,
+        _: &FindAndReplace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.show_search_panel(true, window, cx);
+    }
+
+    pub(super) fn find_next(&mut self, _: &FindNext, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_next_match(window, cx);
+    }
+
+    pub(super) fn find_previous(
+        &mut self,
+        _: &FindPrevious,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_previous_match(window, cx);
+    }
+
+    /// Show the find (and replace) panel, the selected text will be used as the query.
+    pub fn show_search_panel(
+        &mut self,
+        replace: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        let panel = match self.search_panel.clone() {
+            Some(panel) => panel,
+            None => {
+                let editor = cx.entity();
+                let panel = cx.new(|cx| SearchPanel::new(&editor, window, cx));
+                self.search_panel = Some(panel.clone());
+                panel
             }
-            utf8_count += ch.len_utf8();
-            utf16_offset += ch.len_utf16();
+        };
+
+        let selected_text = self.text_for_range_utf8(self.selected_range);
+        let query_input = panel.update(cx, |panel, cx| {
+            panel.set_show_replace(replace, cx);
+            panel.query_input.clone()
+        });
+        query_input.update(cx, |input, cx| {
+            if !selected_text.is_empty() && !selected_text.contains('\n') {
+                input.set_value(selected_text, window, cx);
+            }
+            input.select_all(&SelectAll, window, cx);
+            input.focus(window, cx);
+        });
+        cx.notify();
+    }
+
+    /// Hide the find panel and clear the search matches, the focus will back to the editor.
+    pub fn hide_search_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.search_panel.take().is_some() {
+            self.focus(window, cx);
         }
+        self.clear_search(cx);
+    }
 
-        utf16_offset
+    /// Search the `query` in the text, all the matches will be highlighted,
+    /// and the first match from the current selection will be selected.
+    ///
+    /// Returns the number of the matches.
+    pub fn search(
+        &mut self,
+        query: impl Into<SharedString>,
+        options: SearchOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> usize {
+        let mut search = SearchState::new(query.into(), options);
+        search.update_if_need(self.value());
+        let count = search.matches.len();
+        let ix = search.next_match_index(self.selected_range.start.offset);
+        self.search = Some(search);
+
+        if let Some(ix) = ix {
+            self.select_match(ix, window, cx);
+        }
+        cx.notify();
+        count
+    }
+
+    /// Clear the search query and the highlighted matches.
+    pub fn clear_search(&mut self, cx: &mut Context<Self>) {
+        self.search = None;
+        cx.notify();
+    }
+
+    /// Returns the byte ranges of the matches of the current search.
+    pub fn search_matches(&mut self) -> &[Range<usize>] {
+        let text = self.value().clone();
+        let Some(search) = self.search.as_mut() else {
+            return &[];
+        };
+
+        search.update_if_need(&text);
+        &search.matches
+    }
+
+    /// Select the next match after the cursor, wrap to the first match.
+    pub fn select_next_match(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.value().clone();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        search.update_if_need(&text);
+        if let Some(ix) = search.next_match_index(self.selected_range.end.offset) {
+            self.select_match(ix, window, cx);
+        }
+    }
+
+    /// Select the previous match before the cursor, wrap to the last match.
+    pub fn select_previous_match(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.value().clone();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        search.update_if_need(&text);
+        if let Some(ix) = search.previous_match_index(self.selected_range.start.offset) {
+            self.select_match(ix, window, cx);
+        }
+    }
+
+    fn select_match(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(range) = self
+            .search
+            .as_ref()
+            .and_then(|search| search.matches.get(ix).cloned())
+        else {
+            return;
+        };
+
+        self.select_range(range, cx);
+    }
+
+    /// Select the `range` as the only selection, the folded ranges hide it will be unfolded.
+    pub(super) fn select_range(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        self.clear_extra_selections();
+        self.unfold_range(&range);
+        self.selected_range = range.into();
+        self.selection_reversed = false;
+        self.update_preferred_x_offset(cx);
+        cx.notify();
+    }
+
+    /// Replace the selected match with the `replacement`, and select the next match.
+    ///
+    /// If the selection is not a match, only the next match will be selected.
+    pub fn replace_match(
+        &mut self,
+        replacement: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = self.value().clone();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        search.update_if_need(&text);
+        let range: Range<usize> = self.selected_range.into();
+        if search.match_index(&range).is_some() {
+            let new_text = search.expand_replacement(&text, &range, replacement);
+            self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        }
+
+        self.select_next_match(window, cx);
+    }
+
+    /// Replace all the matches with the `replacement` in one undo step.
+    ///
+    /// Returns the number of the replaced matches.
+    pub fn replace_all_matches(
+        &mut self,
+        replacement: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> usize {
+        let text = self.value().clone();
+        let Some(search) = self.search.as_mut() else {
+            return 0;
+        };
+
+        search.update_if_need(&text);
+        let count = search.matches.len();
+        let Some((range, new_text)) = search.replace_all(&text, replacement) else {
+            return 0;
+        };
+
+        self.clear_extra_selections();
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        count
+    }
+
+    pub(super) fn fold_at_cursor(&mut self, _: &Fold, window: &mut Window, cx: &mut Context<Self>) {
+        let row = self.text.byte_to_line(self.cursor().offset);
+        self.fold_row(row, window, cx);
+    }
+
+    pub(super) fn unfold_at_cursor(&mut self, _: &Unfold, _: &mut Window, cx: &mut Context<Self>) {
+        let row = self.text.byte_to_line(self.cursor().offset);
+        self.unfold_row(row, cx);
+    }
+
+    pub(super) fn fold_all_rows(
+        &mut self,
+        _: &FoldAll,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fold_all(window, cx);
+    }
+
+    pub(super) fn unfold_all_rows(
+        &mut self,
+        _: &UnfoldAll,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.unfold_all(cx);
+    }
+
+    /// Update the foldable ranges if the text has been changed.
+    ///
+    /// The ranges are from the syntax tree, or by the indentation if the language has no syntax tree.
+    ///
+    /// Only the ranges in the changed rows are updated after edit.
+    fn update_fold_ranges_if_need(&mut self) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        if self.folds.dirty {
+            let candidates = self.mode.fold_ranges(&self.text, &(0..usize::MAX));
+            self.folds.set_candidates(candidates);
+        } else if let Some(rows) = self.folds.dirty_rows() {
+            let candidates = self.mode.fold_ranges(&self.text, &rows);
+            self.folds.update_candidates(rows, candidates);
+        }
+    }
+
+    /// Update the highlighter for the `edit`, the foldable ranges in the rows that the syntax
+    /// changed will be updated in next render.
+    fn update_highlighter(&mut self, edit: Option<(&Range<usize>, &str)>, cx: &mut App) {
+        let changed_ranges = self.mode.update_highlighter(edit, &self.text, cx);
+        for range in changed_ranges {
+            let start_row = self.text.byte_to_line(self.text.clip_offset(range.start));
+            let end_row = self.text.byte_to_line(self.text.clip_offset(range.end));
+            self.folds.invalidate_rows(start_row..end_row + 1);
+        }
+    }
+
+    /// Update the folded ranges before replace the `range` (in the old text) with the `new_text`.
+    fn edit_folds(&mut self, range: &Range<usize>, new_text: &str) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        let start_row = self.text.byte_to_line(self.text.clip_offset(range.start));
+        let old_end_row = self.text.byte_to_line(self.text.clip_offset(range.end));
+        let new_end_row = start_row + new_text.matches('\n').count();
+        self.folds.edit(start_row, old_end_row, new_end_row);
+    }
+
+    /// Update the hidden lines of the text wrapper after the folded ranges changed.
+    fn sync_folds(&mut self) {
+        self.text_wrapper.set_hidden_rows(self.folds.hidden_rows());
+        self.mode.update_auto_grow(&self.text_wrapper);
+    }
+
+    /// Fold the foldable range starts at the `row` (zero-based), or the innermost foldable range
+    /// contains the `row`.
+    ///
+    /// The cursors in the folded rows will be moved to the end of the fold header line.
+    ///
+    /// Returns true if a range has been folded.
+    pub fn fold_row(&mut self, row: usize, _: &mut Window, cx: &mut Context<Self>) -> bool {
+        self.update_fold_ranges_if_need();
+        let Some(range) = self.folds.fold(row) else {
+            return false;
+        };
+
+        let header_end = self.text.line_end_offset(range.start);
+        let move_out = |selection: &mut CursorSelection, text: &Rope| {
+            let start_row = text.byte_to_line(selection.range.start.offset);
+            let end_row = text.byte_to_line(selection.range.end.offset);
+            if range.hides(start_row) || range.hides(end_row) {
+                *selection = CursorSelection::new(header_end..header_end, false);
+            }
+        };
+
+        let mut selection = CursorSelection::new(self.selected_range, self.selection_reversed);
+        move_out(&mut selection, &self.text);
+        self.selected_range = selection.range;
+        self.selection_reversed = selection.reversed;
+        for selection in self.extra_selections.iter_mut() {
+            move_out(selection, &self.text);
+        }
+        self.merge_selections();
+
+        self.sync_folds();
+        cx.notify();
+        true
+    }
+
+    /// Unfold the folded ranges start at the `row` (zero-based) or hide the `row`.
+    ///
+    /// Returns true if any range has been unfolded.
+    pub fn unfold_row(&mut self, row: usize, cx: &mut Context<Self>) -> bool {
+        if !self.folds.unfold(row) {
+            return false;
+        }
+
+        self.sync_folds();
+        cx.notify();
+        true
+    }
+
+    /// Fold or unfold the range starts at the `row` (zero-based).
+    pub fn toggle_fold_row(&mut self, row: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if self.folds.is_folded(row) {
+            self.unfold_row(row, cx);
+        } else {
+            self.fold_row(row, window, cx);
+        }
+    }
+
+    /// Returns true if the range starts at the `row` (zero-based) is folded.
+    pub fn is_row_folded(&self, row: usize) -> bool {
+        self.folds.is_folded(row)
+    }
+
+    /// Fold all the foldable ranges, all cursors will be moved out of the folded rows.
+    pub fn fold_all(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.update_fold_ranges_if_need();
+        self.folds.fold_all();
+        self.clear_extra_selections();
+        let offset = self.skip_folded(self.cursor().offset, false);
+        self.selected_range = (offset..offset).into();
+
+        self.sync_folds();
+        cx.notify();
+    }
+
+    /// Unfold all the folded ranges.
+    pub fn unfold_all(&mut self, cx: &mut Context<Self>) {
+        self.folds.unfold_all();
+        self.sync_folds();
+        cx.notify();
+    }
+
+    /// Unfold the folded ranges that hide any row of the byte `range`.
+    fn unfold_range(&mut self, range: &Range<usize>) {
+        let start_row = self.text.byte_to_line(self.text.clip_offset(range.start));
+        let end_row = self.text.byte_to_line(self.text.clip_offset(range.end));
+        if self.folds.unfold_rows(start_row..end_row + 1) {
+            self.sync_folds();
+        }
+    }
+
+    /// Returns the offset out of the folded rows.
+    ///
+    /// The offset in the folded rows will be moved to the start of the next visible line if
+    /// `forward`, otherwise the end of the fold header line.
+    fn skip_folded(&self, offset: usize, forward: bool) -> usize {
+        if !self.folds.has_folded() {
+            return offset;
+        }
+
+        let row = self.text.byte_to_line(self.text.clip_offset(offset));
+        if !self.folds.is_hidden(row) {
+            return offset;
+        }
+
+        if forward {
+            if let Some(row) = self
+                .folds
+                .visible_row(row, true)
+                .filter(|row| *row < self.text.len_lines())
+            {
+                return self.text.line_start_offset(row);
+            }
+        }
+
+        self.folds
+            .visible_row(row, false)
+            .map(|row| self.text.line_end_offset(row))
+            .unwrap_or(offset)
+    }
+
+    /// Returns the row (zero-based) of the fold toggle at the mouse `position`, None if the
+    /// position is not in the fold gutter.
+    fn fold_toggle_for_mouse_position(
+        &self,
+        position: Point<Pixels>,
+        window: &Window,
+        cx: &App,
+    ) -> Option<usize> {
+        if !self.mode.line_number() {
+            return None;
+        }
+
+        let bounds = self.last_bounds?;
+        let x = position.x - bounds.origin.x;
+        let gutter_end = self.line_number_width - LINE_NUMBER_MARGIN_RIGHT;
+        if x < gutter_end - FOLD_GUTTER_WIDTH || x >= gutter_end {
+            return None;
+        }
+
+        let offset = self.index_for_mouse_position(position, window, cx);
+        let row = self.text.byte_to_line(offset);
+        (self.folds.is_folded(row) || self.folds.candidate_at(row).is_some()).then_some(row)
+    }
+
+    /// Returns true if there have multiple cursors.
+    pub(super) fn has_extra_selections(&self) -> bool {
+        !self.extra_selections.is_empty()
+    }
+
+    /// Remove all the secondary cursors, only keep the primary one.
+    fn clear_extra_selections(&mut self) {
+        self.extra_selections.clear();
+        self.column_selection_anchor = None;
+    }
+
+    /// Returns all selections (include the primary selection) sorted by offset.
+    fn all_selections(&self) -> Vec<CursorSelection> {
+        let mut selections = self.extra_selections.clone();
+        selections.push(CursorSelection::new(
+            self.selected_range,
+            self.selection_reversed,
+        ));
+        selections.sort_by_key(|selection| selection.range.start.offset);
+        selections
+    }
+
+    /// Add a new selection as the primary selection, the current primary selection will be
+    /// kept as a secondary selection.
+    fn add_selection(&mut self, selection: CursorSelection) {
+        self.extra_selections.push(CursorSelection::new(
+            self.selected_range,
+            self.selection_reversed,
+        ));
+        self.selected_range = selection.range;
+        self.selection_reversed = selection.reversed;
+        self.merge_selections();
+    }
+
+    /// Sort and merge the overlapped selections.
+    ///
+    /// The merged selection that contains the primary selection will be the new primary.
+    fn merge_selections(&mut self) {
+        if !self.has_extra_selections() {
+            return;
+        }
+
+        let primary = CursorSelection::new(self.selected_range, self.selection_reversed);
+        let mut selections = multi_cursor::merge_selections(self.all_selections());
+        let primary_ix = selections
+            .iter()
+            .position(|selection| {
+                selection.range.start <= primary.range.start
+                    && primary.range.end <= selection.range.end
+            })
+            .unwrap_or(selections.len() - 1);
+
+        let primary = selections.remove(primary_ix);
+        self.selected_range = primary.range;
+        self.selection_reversed = primary.reversed;
+        self.extra_selections = selections;
+    }
+
+    /// Make a column (box) selection from the `anchor` to the `head` offset.
+    ///
+    /// This will replace all the selections, the selection on the row of `head` will be the primary.
+    fn select_columns(&mut self, anchor: usize, head: usize, cx: &mut Context<Self>) {
+        let mut selections = multi_cursor::column_selections(&self.text, anchor, head);
+        let primary = if head >= anchor {
+            selections.pop()
+        } else {
+            Some(selections.remove(0))
+        };
+        let Some(primary) = primary else {
+            return;
+        };
+
+        self.selected_range = primary.range;
+        self.selection_reversed = primary.reversed;
+        self.extra_selections = selections;
+        cx.notify();
+    }
+
+    /// Run `f` for each selection (include the primary selection) for multi-cursor editing.
+    ///
+    /// When running `f`, the selection is set as the primary selection, so the single cursor
+    /// methods can be reused. The selections are visited from the last to the first, so the text
+    /// changes made by `f` only shift the offsets of the visited selections.
+    ///
+    /// All the text changes will be one undo step, the `usize` argument of `f` is the index
+    /// of the selection in the sorted selections.
+    fn for_each_selection(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        mut f: impl FnMut(&mut Self, usize, &mut Window, &mut Context<Self>),
+    ) {
+        if !self.has_extra_selections() {
+            f(self, 0, window, cx);
+            return;
+        }
+
+        let primary = CursorSelection::new(self.selected_range, self.selection_reversed);
+        let mut selections = self.all_selections();
+        self.extra_selections.clear();
+        let primary_ix = selections
+            .iter()
+            .position(|selection| *selection == primary)
+            .unwrap_or(0);
+        let preferred_x_offset = self.preferred_x_offset;
+        let mut primary_preferred_x_offset = preferred_x_offset;
+
+        self.history.start_group();
+        for ix in (0..selections.len()).rev() {
+            self.selected_range = selections[ix].range;
+            self.selection_reversed = selections[ix].reversed;
+            self.preferred_x_offset = if ix == primary_ix {
+                preferred_x_offset
+            } else {
+                None
+            };
+
+            let old_len = self.text.len_bytes();
+            f(self, ix, window, cx);
+            let delta = self.text.len_bytes() as isize - old_len as isize;
+
+            selections[ix] = CursorSelection::new(self.selected_range, self.selection_reversed);
+            if ix == primary_ix {
+                primary_preferred_x_offset = self.preferred_x_offset;
+            }
+            if delta != 0 {
+                for selection in selections.iter_mut().skip(ix + 1) {
+                    selection.shift(delta);
+                }
+            }
+        }
+        self.history.end_group();
+
+        let primary = selections.remove(primary_ix);
+        self.selected_range = primary.range;
+        self.selection_reversed = primary.reversed;
+        self.preferred_x_offset = primary_preferred_x_offset;
+        self.extra_selections = selections;
+        self.merge_selections();
+        cx.notify();
+    }
+
+    pub(super) fn offset_from_utf16(&self, offset: usize) -> usize {
+        self.text.offset_from_utf16(offset)
+    }
+
+    pub(super) fn offset_to_utf16(&self, offset: usize) -> usize {
+        self.text.offset_to_utf16(offset)
     }
 
     pub(super) fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
@@ -1948,19 +2908,38 @@ impl InputState {
         self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
     }
 
+    /// Returns the previous grapheme boundary of the offset.
+    ///
+    /// Only the line of the offset will be scanned, the grapheme never cross lines except `\r\n`.
     fn previous_boundary(&self, offset: usize) -> usize {
+        let offset = self.text.clip_offset(offset);
+        if offset == 0 {
+            return 0;
+        }
+
+        let line_start = self
+            .text
+            .line_start_offset(self.text.byte_to_line(offset - 1));
         self.text
+            .slice_text(line_start..offset)
             .grapheme_indices(true)
-            .rev()
-            .find_map(|(idx, _)| (idx < offset).then_some(idx))
+            .next_back()
+            .map(|(idx, _)| line_start + idx)
             .unwrap_or(0)
     }
 
+    /// Returns the next grapheme boundary of the offset.
     fn next_boundary(&self, offset: usize) -> usize {
+        let offset = self.text.clip_offset(offset);
+        let line_end = self
+            .text
+            .line_start_offset(self.text.byte_to_line(offset) + 1);
         self.text
+            .slice_text(offset..line_end)
             .grapheme_indices(true)
-            .find_map(|(idx, _)| (idx > offset).then_some(idx))
-            .unwrap_or(self.text.len())
+            .nth(1)
+            .map(|(idx, _)| offset + idx)
+            .unwrap_or(line_end)
     }
 
     /// Returns the true to let InputElement to render cursor, when Input is focused and current BlinkCursor is visible.
@@ -1976,7 +2955,17 @@ impl InputState {
     }
 
     fn on_blur(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        self.unselect(window, cx);
+        // Keep the selected match when the focus moved to the search panel.
+        let search_panel_focused = self
+            .search_panel
+            .as_ref()
+            .map_or(false, |panel| panel.read(cx).is_focused(window, cx));
+        if !search_panel_focused {
+            self.unselect(window, cx);
+        }
+        self.hide_completions(cx);
+        #[cfg(feature = "lsp")]
+        self.hide_signature_help(cx);
         self.blink_cursor.update(cx, |cursor, cx| {
             cursor.stop(cx);
         });
@@ -2002,7 +2991,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if self.text.is_empty() {
+        if self.text.len_bytes() == 0 {
             return;
         }
 
@@ -2019,9 +3008,29 @@ impl InputState {
         }
 
         let offset = self.index_for_mouse_position(event.position, window, cx);
+        if let Some(anchor) = self.column_selection_anchor {
+            self.select_columns(anchor, offset, cx);
+            return;
+        }
+
         self.select_to(Cursor::new(offset), window, cx);
     }
 
+    /// Returns true if there have any validation (validate, pattern or mask_pattern) for the input.
+    ///
+    /// If not, we can skip to build the entire pending text for large text.
+    fn need_validate_input(&self) -> bool {
+        self.validate.is_some() || self.pattern.is_some() || !self.mask_pattern.is_none()
+    }
+
+    /// Returns the entire text after replacing the `range` with `new_text`.
+    fn pending_text(&self, range: &Range<usize>, new_text: &str) -> SharedString {
+        (self.text_for_range_utf8(0..range.start)
+            + new_text
+            + &self.text_for_range_utf8(range.end..self.text.len_bytes()))
+            .into()
+    }
+
     fn is_valid_input(&self, new_text: &str) -> bool {
         if new_text.is_empty() {
             return true;
@@ -2074,20 +3083,24 @@ impl InputState {
     }
 
     pub(super) fn set_input_bounds(&mut self, new_bounds: Bounds<Pixels>, cx: &mut Context<Self>) {
-        let wrap_width_changed = self.input_bounds.size.width != new_bounds.size.width;
         self.input_bounds = new_bounds;
 
+        // Same as the wrap width of the lines in `TextElement`.
+        let wrap_width = if self.mode.is_multi_line() {
+            Some(new_bounds.size.width - self.line_number_width - RIGHT_MARGIN)
+        } else {
+            None
+        };
+
         // Update text_wrapper wrap_width if changed.
-        if wrap_width_changed {
-            self.text_wrapper
-                .set_wrap_width(Some(new_bounds.size.width), cx);
+        if self.text_wrapper.wrap_width != wrap_width {
+            self.text_wrapper.set_wrap_width(wrap_width, cx);
             self.mode.update_auto_grow(&self.text_wrapper);
         }
     }
 
-    fn text_for_range_utf8(&mut self, range: impl Into<Range<usize>>) -> &str {
-        let range = self.range_from_utf16(&self.range_to_utf16(&range.into()));
-        &self.text[range]
+    fn text_for_range_utf8(&self, range: impl Into<Range<usize>>) -> String {
+        self.text.slice_text(range.into())
     }
 }
 
@@ -2101,7 +3114,7 @@ impl EntityInputHandler for InputState {
     ) -> Option<String> {
         let range = self.range_from_utf16(&range_utf16);
         adjusted_range.replace(self.range_to_utf16(&range));
-        Some(self.text[range].to_string())
+        Some(self.text.slice_text(range))
     }
 
     fn selected_text_range(
@@ -2144,36 +3157,77 @@ impl EntityInputHandler for InputState {
             return;
         }
 
+        // Typing with multiple cursors.
+        if range_utf16.is_none() && self.marked_range.is_none() && self.has_extra_selections() {
+            self.for_each_selection(window, cx, |this, _, window, cx| {
+                this.replace_text_in_range(None, new_text, window, cx);
+            });
+            return;
+        }
+
+        if range_utf16.is_none()
+            && self.marked_range.is_none()
+            && (self.handle_bracket_input(new_text, window, cx)
+                || self.handle_indent_input(new_text, window, cx))
+        {
+            return;
+        }
+
         let range = range_utf16
             .as_ref()
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.map(|range| range.into()))
             .unwrap_or(self.selected_range.into());
 
-        let pending_text: SharedString = (self.text_for_range_utf8(0..range.start).to_owned()
-            + new_text
-            + self.text_for_range_utf8(range.end..self.text.len()))
-        .into();
-        // Check if the new text is valid
-        if !self.is_valid_input(&pending_text) {
-            return;
-        }
+        let new_offset = if self.need_validate_input() {
+            let pending_text = self.pending_text(&range, new_text);
+            // Check if the new text is valid
+            if !self.is_valid_input(&pending_text) {
+                return;
+            }
 
-        let mask_text = self.mask_pattern.mask(&pending_text);
-        let new_text_len = (new_text.len() + mask_text.len()).saturating_sub(pending_text.len());
-        let new_offset = (range.start + new_text_len).min(mask_text.len());
+            let mask_text = self.mask_pattern.mask(&pending_text);
+            let new_text_len =
+                (new_text.len() + mask_text.len()).saturating_sub(pending_text.len());
+            let new_offset = (range.start + new_text_len).min(mask_text.len());
+
+            self.edit_folds(&range, new_text);
+            self.edit_snippet(&range, new_text);
+            // The entire text is replaced by the masked text.
+            #[cfg(feature = "lsp")]
+            self.lsp_did_change(&(0..self.text.len_bytes()), &mask_text, window, cx);
+            self.push_history(&range, &new_text, window, cx);
+            self.text = Rope::from_str(&mask_text);
+            self.value = OnceCell::from(mask_text);
+            self.text_wrapper.update_all(&self.text, cx);
+            new_offset
+        } else {
+            self.edit_folds(&range, new_text);
+            self.edit_snippet(&range, new_text);
+            #[cfg(feature = "lsp")]
+            self.lsp_did_change(&range, new_text, window, cx);
+            self.push_history(&range, &new_text, window, cx);
+            self.text.replace(range.clone(), new_text);
+            self.value.take();
+            self.text_wrapper.update(&self.text, &range, new_text, cx);
+            range.start + new_text.len()
+        };
 
-        self.push_history(&range, &new_text, window, cx);
-        self.text = mask_text.clone();
-        self.mode
-            .update_highlighter(&range, &self.text, &new_text, cx);
+        self.update_highlighter(Some((&range, new_text)), cx);
         self.mode.clear_markers();
-        self.text_wrapper.update(&self.text, false, cx);
+        if let Some(search) = self.search.as_mut() {
+            search.dirty = true;
+        }
+        self.text_wrapper.set_hidden_rows(self.folds.hidden_rows());
         self.selected_range = (new_offset..new_offset).into();
         self.marked_range.take();
         self.update_preferred_x_offset(cx);
         self.update_scroll_offset(None, cx);
         self.mode.update_auto_grow(&self.text_wrapper);
+        // Only the typed text has no explicit range, e.g.: paste, undo and `set_value` have.
+        if range_utf16.is_none() {
+            self.completion_did_change(new_text, window, cx);
+        }
         cx.emit(InputEvent::Change(self.unmask_value()));
         cx.notify();
     }
@@ -2196,20 +3250,27 @@ impl EntityInputHandler for InputState {
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.map(|range| range.into()))
             .unwrap_or(self.selected_range.into());
-        let pending_text: SharedString = (self.text_for_range_utf8(0..range.start).to_owned()
-            + new_text
-            + self.text_for_range_utf8(range.end..self.text.len()))
-        .into();
-        if !self.is_valid_input(&pending_text) {
-            return;
+        if self.need_validate_input() {
+            let pending_text = self.pending_text(&range, new_text);
+            if !self.is_valid_input(&pending_text) {
+                return;
+            }
         }
 
+        self.edit_folds(&range, new_text);
+        self.edit_snippet(&range, new_text);
+        #[cfg(feature = "lsp")]
+        self.lsp_did_change(&range, new_text, window, cx);
         self.push_history(&range, new_text, window, cx);
-        self.text = pending_text;
-        self.mode
-            .update_highlighter(&range, &self.text, &new_text, cx);
+        self.text.replace(range.clone(), new_text);
+        self.value.take();
+        self.text_wrapper.update(&self.text, &range, new_text, cx);
+        self.update_highlighter(Some((&range, new_text)), cx);
         self.mode.clear_markers();
-        self.text_wrapper.update(&self.text, false, cx);
+        if let Some(search) = self.search.as_mut() {
+            search.dirty = true;
+        }
+        self.text_wrapper.set_hidden_rows(self.folds.hidden_rows());
         if new_text.is_empty() {
             // Cancel selection, when cancel IME input.
             self.selected_range = (range.start..range.start).into();
@@ -2244,14 +3305,13 @@ impl EntityInputHandler for InputState {
         let mut start_origin = None;
         let mut end_origin = None;
         let line_number_origin = point(self.line_number_width, px(0.));
-        let mut y_offset = px(0.);
-        let mut index_offset = 0;
-
-        for line in last_layout.lines.iter() {
+        let mut y_offset = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
             if start_origin.is_some() && end_origin.is_some() {
                 break;
             }
 
+            let index_offset = last_layout.line_offsets[ix];
             if start_origin.is_none() {
                 if let Some(p) =
                     line.position_for_index(range.start.saturating_sub(index_offset), line_height)
@@ -2268,7 +3328,6 @@ impl EntityInputHandler for InputState {
                 }
             }
 
-            index_offset += line.len() + 1;
             y_offset += line.size(line_height).height;
         }
 
@@ -2294,10 +3353,14 @@ impl EntityInputHandler for InputState {
         let line_height = last_layout.line_height;
         let line_point = self.last_bounds?.localize(&point)?;
 
-        for line in last_layout.lines.iter() {
-            if let Ok(utf8_index) = line.index_for_position(line_point, line_height) {
-                return Some(self.offset_to_utf16(utf8_index));
+        let mut y_offset = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            let pos = line_point - gpui::point(self.line_number_width, y_offset);
+            if let Ok(utf8_index) = line.index_for_position(pos, line_height) {
+                return Some(self.offset_to_utf16(last_layout.line_offsets[ix] + utf8_index));
             }
+
+            y_offset += line.size(line_height).height;
         }
 
         None
@@ -2312,8 +3375,16 @@ impl Focusable for InputState {
 
 impl Render for InputState {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        self.text_wrapper.update(&self.text, false, cx);
-        self.mode.update_highlighter(&(0..0), &self.text, "", cx);
+        self.text_wrapper.prepare_if_need(&self.text, cx);
+        self.update_highlighter(None, cx);
+        if self.search.as_ref().map_or(false, |search| search.dirty) {
+            let text = self.value().clone();
+            if let Some(search) = self.search.as_mut() {
+                search.update_if_need(&text);
+            }
+        }
+        self.update_fold_ranges_if_need();
+        self.update_completion_if_need(cx);
 
         div()
             .id("input-state")
@@ -2323,5 +3394,12 @@ impl Render for InputState {
             .overflow_x_hidden()
             .child(TextElement::new(cx.entity().clone()).placeholder(self.placeholder.clone()))
             .children(self.diagnostic_popover.clone())
+            .map(|this| {
+                #[cfg(feature = "lsp")]
+                let this = this.children(self.signature_help_popover());
+                this
+            })
+            .children(self.completion_menu())
+            .children(self.search_panel.clone())
     }
 }
@@ -176,14 +176,14 @@ impl RenderOnce for TextInput {
         let suffix = self.suffix;
         let show_clear_button = self.cleanable
             && !state.loading
-            && !state.text.is_empty()
+            && state.text.len_bytes() > 0
             && state.mode.is_single_line();
         let has_suffix = suffix.is_some() || state.loading || self.mask_toggle || show_clear_button;
 
         div()
             .id(("input", self.state.entity_id()))
             .flex()
-            .key_context(crate::input::CONTEXT)
+            .key_context(state.key_context())
             .track_focus(&state.focus_handle)
             .when(!state.disabled, |this| {
                 this.on_action(window.listener_for(&self.state, InputState::backspace))
@@ -200,6 +200,14 @@ impl RenderOnce for TextInput {
                     .on_action(window.listener_for(&self.state, InputState::cut))
                     .on_action(window.listener_for(&self.state, InputState::undo))
                     .on_action(window.listener_for(&self.state, InputState::redo))
+                    .on_action(window.listener_for(&self.state, InputState::show_completions))
+                    .on_action(window.listener_for(&self.state, InputState::confirm_completion))
+                    .on_action(
+                        window.listener_for(&self.state, InputState::select_previous_completion),
+                    )
+                    .on_action(window.listener_for(&self.state, InputState::select_next_completion))
+                    .on_action(window.listener_for(&self.state, InputState::next_tabstop))
+                    .on_action(window.listener_for(&self.state, InputState::previous_tabstop))
                     .when(state.mode.is_multi_line(), |this| {
                         this.on_action(window.listener_for(&self.state, InputState::indent_inline))
                             .on_action(window.listener_for(&self.state, InputState::outdent_inline))
@@ -219,6 +227,32 @@ impl RenderOnce for TextInput {
                     .on_action(window.listener_for(&self.state, InputState::page_up))
                     .on_action(window.listener_for(&self.state, InputState::page_down))
             })
+            .when(state.mode.is_code_editor(), |this| {
+                this.on_action(window.listener_for(&self.state, InputState::add_cursor_above))
+                    .on_action(window.listener_for(&self.state, InputState::add_cursor_below))
+                    .on_action(window.listener_for(&self.state, InputState::select_next_occurrence))
+                    .on_action(window.listener_for(&self.state, InputState::select_all_occurrences))
+                    .on_action(window.listener_for(&self.state, InputState::find))
+                    .on_action(window.listener_for(&self.state, InputState::find_and_replace))
+                    .on_action(window.listener_for(&self.state, InputState::find_next))
+                    .on_action(window.listener_for(&self.state, InputState::find_previous))
+                    .on_action(window.listener_for(&self.state, InputState::fold_at_cursor))
+                    .on_action(window.listener_for(&self.state, InputState::unfold_at_cursor))
+                    .on_action(window.listener_for(&self.state, InputState::fold_all_rows))
+                    .on_action(window.listener_for(&self.state, InputState::unfold_all_rows))
+                    .on_action(
+                        window.listener_for(&self.state, InputState::move_to_matching_bracket),
+                    )
+                    .on_action(window.listener_for(&self.state, InputState::toggle_line_comment))
+                    .on_action(window.listener_for(&self.state, InputState::toggle_block_comment))
+                    .map(|this| {
+                        #[cfg(feature = "lsp")]
+                        let this = this.on_action(
+                            window.listener_for(&self.state, InputState::go_to_definition),
+                        );
+                        this
+                    })
+            })
             .on_action(window.listener_for(&self.state, InputState::select_all))
             .on_action(window.listener_for(&self.state, InputState::select_to_start_of_line))
             .on_action(window.listener_for(&self.state, InputState::select_to_end_of_line))
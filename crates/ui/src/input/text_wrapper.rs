@@ -1,19 +1,30 @@
 use std::ops::Range;
 
-use crate::input::LineColumn;
-use gpui::{App, Font, LineFragment, Pixels, SharedString};
+use crate::input::{LineColumn, RopeExt as _};
+use gpui::{px, App, Font, LineFragment, Pixels};
+use ropey::Rope;
 
 #[allow(unused)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub(super) struct LineWrap {
     /// The number of soft wrapped lines of this line (Not include first line.)
     pub(super) wrap_lines: usize,
-    /// The range of the line text in the entire text.
-    pub(super) range: Range<usize>,
+    /// The line is hidden by a folded range.
+    pub(super) hidden: bool,
 }
 
 impl LineWrap {
     pub(super) fn height(&self, line_height: Pixels) -> Pixels {
-        line_height * (self.wrap_lines + 1)
+        line_height * self.rows()
+    }
+
+    /// Returns the number of the displayed lines, include the soft wrapped lines.
+    pub(super) fn rows(&self) -> usize {
+        if self.hidden {
+            0
+        } else {
+            self.wrap_lines + 1
+        }
     }
 }
 
@@ -21,98 +32,269 @@ impl LineWrap {
 ///
 /// After use lines to calculate the scroll size of the TextArea
 pub(super) struct TextWrapper {
-    pub(super) text: SharedString,
-    /// The wrapped lines, value is start and end index of the line.
-    pub(super) wrapped_lines: Vec<Range<usize>>,
+    pub(super) text: Rope,
     /// The lines by split \n
     pub(super) lines: Vec<LineWrap>,
     pub(super) font: Font,
     pub(super) font_size: Pixels,
     /// If is none, it means the text is not wrapped
     pub(super) wrap_width: Option<Pixels>,
+    /// The rows hidden by the folded ranges, sorted and not overlapped.
+    hidden_rows: Vec<Range<usize>>,
+    /// The prefix sums of the displayed rows, `row_offsets[i]` is the rows before line `i`.
+    ///
+    /// Used to get the line top and the visible lines without iterating over all lines.
+    row_offsets: Vec<usize>,
+    /// Set true to rewrap all lines in next [`Self::prepare_if_need`].
+    dirty: bool,
 }
 
 #[allow(unused)]
 impl TextWrapper {
     pub(super) fn new(font: Font, font_size: Pixels, wrap_width: Option<Pixels>) -> Self {
         Self {
-            text: SharedString::default(),
+            text: Rope::new(),
             font,
             font_size,
             wrap_width,
-            wrapped_lines: Vec::new(),
             lines: Vec::new(),
+            hidden_rows: Vec::new(),
+            row_offsets: vec![0],
+            dirty: true,
         }
     }
 
+    /// Set the text without wrapping, the lines will be wrapped in next [`Self::prepare_if_need`].
+    pub(super) fn set_default_text(&mut self, text: &Rope) {
+        self.text = text.clone();
+        self.dirty = true;
+    }
+
     pub(super) fn set_wrap_width(&mut self, wrap_width: Option<Pixels>, cx: &mut App) {
+        if self.wrap_width == wrap_width {
+            return;
+        }
+
         self.wrap_width = wrap_width;
-        self.update(&self.text.clone(), true, cx);
+        self.update_all(&self.text.clone(), cx);
     }
 
     pub(super) fn set_font(&mut self, font: Font, font_size: Pixels, cx: &mut App) {
+        if self.font == font && self.font_size == font_size {
+            return;
+        }
+
         self.font = font;
         self.font_size = font_size;
-        self.update(&self.text.clone(), true, cx);
+        self.update_all(&self.text.clone(), cx);
     }
 
-    /// Update the text wrapper and recalculate the wrapped lines.
+    /// Set the rows hidden by the folded ranges, the hidden lines have no height.
     ///
-    /// If the `text` is the same as the current text, do nothing.
-    pub(super) fn update(&mut self, text: &SharedString, force: bool, cx: &mut App) {
-        if &self.text == text && !force {
+    /// Only the lines of the old and new hidden rows are updated.
+    pub(super) fn set_hidden_rows(&mut self, hidden_rows: Vec<Range<usize>>) {
+        if self.hidden_rows == hidden_rows {
             return;
         }
 
-        let mut wrapped_lines = vec![];
-        let mut lines = vec![];
+        let changed_row = self
+            .hidden_rows
+            .iter()
+            .zip(hidden_rows.iter())
+            .position(|(old, new)| old != new)
+            .unwrap_or(self.hidden_rows.len().min(hidden_rows.len()));
+        let changed_row = [
+            self.hidden_rows.get(changed_row),
+            hidden_rows.get(changed_row),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|rows| rows.start)
+        .min()
+        .unwrap_or(0);
+
+        set_lines_hidden(&mut self.lines, &self.hidden_rows, false);
+        self.hidden_rows = hidden_rows;
+        set_lines_hidden(&mut self.lines, &self.hidden_rows, true);
+        self.update_row_offsets(changed_row);
+    }
+
+    /// Recalculate the row offsets of the lines from the `row` to the end.
+    fn update_row_offsets(&mut self, row: usize) {
+        let row = row
+            .min(self.lines.len())
+            .min(self.row_offsets.len().saturating_sub(1));
+        self.row_offsets.truncate(row + 1);
+        let mut offset = self.row_offsets.last().copied().unwrap_or(0);
+        if self.row_offsets.is_empty() {
+            self.row_offsets.push(offset);
+        }
+        for line in self.lines[row..].iter() {
+            offset += line.rows();
+            self.row_offsets.push(offset);
+        }
+    }
+
+    /// Wrap all lines if the text has been changed by [`Self::set_default_text`].
+    pub(super) fn prepare_if_need(&mut self, text: &Rope, cx: &mut App) {
+        if self.dirty {
+            self.update_all(text, cx);
+        }
+    }
+
+    /// Update the wrapped lines after replace the `range` (in the old text) with `new_text`.
+    ///
+    /// Only the lines touched by the edit will be rewrapped.
+    pub(super) fn update(
+        &mut self,
+        text: &Rope,
+        range: &Range<usize>,
+        new_text: &str,
+        cx: &mut App,
+    ) {
+        if self.dirty || self.lines.len() != self.text.len_lines() {
+            self.update_all(text, cx);
+            return;
+        }
+
+        let start_row = self.text.byte_to_line(self.text.clip_offset(range.start));
+        let old_end_row = self.text.byte_to_line(self.text.clip_offset(range.end));
+        let new_end_offset = text.clip_offset(range.start + new_text.len());
+        let new_end_row = text.byte_to_line(new_end_offset);
+
+        let new_lines = self.wrap_rows(text, start_row..new_end_row + 1, cx);
+        let old_end_row = old_end_row.min(self.lines.len().saturating_sub(1));
+
+        // The hidden rows touched by the edit are shown, the rest after the edit are moved with
+        // the lines, see also `FoldState::edit`.
+        let (touched, hidden_rows): (Vec<_>, Vec<_>) = std::mem::take(&mut self.hidden_rows)
+            .into_iter()
+            .partition(|rows| rows.start <= old_end_row && rows.end > start_row);
+        set_lines_hidden(&mut self.lines, &touched, false);
+        let delta = new_end_row as isize - old_end_row as isize;
+        self.hidden_rows = hidden_rows
+            .into_iter()
+            .map(|rows| {
+                if rows.start > old_end_row {
+                    rows.start.saturating_add_signed(delta)..rows.end.saturating_add_signed(delta)
+                } else {
+                    rows
+                }
+            })
+            .collect();
+
+        self.lines.splice(start_row..old_end_row + 1, new_lines);
+        self.text = text.clone();
+        let changed_row = touched
+            .first()
+            .map_or(start_row, |rows| rows.start.min(start_row));
+        self.update_row_offsets(changed_row);
+    }
+
+    /// Rewrap all lines of the text.
+    pub(super) fn update_all(&mut self, text: &Rope, cx: &mut App) {
+        self.lines = self.wrap_rows(text, 0..text.len_lines(), cx);
+        self.text = text.clone();
+        self.dirty = false;
+        set_lines_hidden(&mut self.lines, &self.hidden_rows, true);
+        self.update_row_offsets(0);
+    }
+
+    fn wrap_rows(&self, text: &Rope, rows: Range<usize>, cx: &mut App) -> Vec<LineWrap> {
         let wrap_width = self.wrap_width.unwrap_or(Pixels::MAX);
         let mut line_wrapper = cx
             .text_system()
             .line_wrapper(self.font.clone(), self.font_size);
 
-        let mut prev_line_ix = 0;
-        for line in text.split('\n') {
-            let mut line_wraps = vec![];
-            let mut prev_boundary_ix = 0;
+        rows.map(|row| {
+            let line = text.line_text(row);
+            // Here only have wrapped line, if there is no wrap meet, the result will empty.
+            let wrap_lines = line_wrapper
+                .wrap_line(&[LineFragment::text(&line)], wrap_width)
+                .count();
 
-            // Here only have wrapped line, if there is no wrap meet, the `line_wraps` result will empty.
-            for boundary in line_wrapper.wrap_line(&[LineFragment::text(line)], wrap_width) {
-                line_wraps.push(prev_boundary_ix..boundary.ix);
-                prev_boundary_ix = boundary.ix;
+            LineWrap {
+                wrap_lines,
+                hidden: false,
             }
+        })
+        .collect()
+    }
 
-            lines.push(LineWrap {
-                wrap_lines: line_wraps.len(),
-                range: prev_line_ix..prev_line_ix + line.len(),
-            });
+    /// Returns the total number of the lines, include the soft wrapped lines.
+    pub(super) fn wrapped_lines_count(&self) -> usize {
+        self.row_offsets.last().copied().unwrap_or(0)
+    }
 
-            wrapped_lines.extend(line_wraps);
-            // Reset of the line
-            if !line[prev_boundary_ix..].is_empty() || prev_boundary_ix == 0 {
-                wrapped_lines.push(prev_line_ix + prev_boundary_ix..prev_line_ix + line.len());
+    /// Returns the y offset of the line top (zero-based row), include the soft wrapped lines.
+    pub(super) fn line_top(&self, row: usize, line_height: Pixels) -> Pixels {
+        let row = row.min(self.row_offsets.len().saturating_sub(1));
+        line_height * self.row_offsets.get(row).copied().unwrap_or(0)
+    }
+
+    /// Returns the range of the lines (zero-based) in the viewport and the top of the first line.
+    ///
+    /// The range starts with the line above the viewport, the hidden lines have no height.
+    pub(super) fn visible_range(
+        &self,
+        scroll_top: Pixels,
+        height: Pixels,
+        line_height: Pixels,
+    ) -> (Range<usize>, Pixels) {
+        let total_lines = self.lines.len();
+        let bottoms = self.row_offsets.get(1..).unwrap_or_default();
+
+        let mut visible_range = 0..total_lines;
+        let mut visible_top = px(0.);
+
+        // The lines end above the `scroll_top`, the last one is the first line of the range.
+        let above = bottoms.partition_point(|rows| line_height * *rows < scroll_top);
+        if above > 0 {
+            let mut start = above - 1;
+            // Skip the hidden lines, start from the fold header line.
+            let ix = self.hidden_rows.partition_point(|rows| rows.end <= start);
+            if let Some(rows) = self
+                .hidden_rows
+                .get(ix)
+                .filter(|rows| rows.contains(&start))
+            {
+                start = rows.start.saturating_sub(1);
             }
+            visible_range.start = start;
+            visible_top = self.line_top(start, line_height);
+        }
 
-            prev_line_ix += line.len() + 1;
+        // The first line ends below the viewport is the last line of the range.
+        let inside = bottoms.partition_point(|rows| line_height * *rows <= scroll_top + height);
+        if inside < total_lines {
+            visible_range.end = inside + 1;
         }
 
-        self.text = text.clone();
-        self.wrapped_lines = wrapped_lines;
-        self.lines = lines;
+        // Ensure at least 1 line (The empty text also has 1 line).
+        if visible_range.is_empty() {
+            visible_range = 0..1;
+            visible_top = px(0.);
+        }
+
+        (visible_range, visible_top)
     }
 
     /// Returns the line and column (1-based) of the given offset (Entire text).
     pub(super) fn line_column(&self, offset: usize) -> LineColumn {
-        if self.lines.is_empty() {
-            return LineColumn::default();
-        }
+        let offset = self.text.clip_offset(offset);
+        let row = self.text.byte_to_line(offset);
+        let column = offset.saturating_sub(self.text.line_start_offset(row));
 
-        let line = self
-            .lines
-            .binary_search_by_key(&offset, |line| line.range.end)
-            .unwrap_or_else(|i| i);
-        let column = offset.saturating_sub(self.lines[line].range.start);
+        (row + 1, column + 1).into()
+    }
+}
 
-        (line + 1, column + 1).into()
+/// Set the `hidden` of the lines in the `hidden_rows`, the rows out of the `lines` are ignored.
+fn set_lines_hidden(lines: &mut [LineWrap], hidden_rows: &[Range<usize>], hidden: bool) {
+    for rows in hidden_rows {
+        let end = rows.end.min(lines.len());
+        for line in lines[rows.start.min(end)..end].iter_mut() {
+            line.hidden = hidden;
+        }
     }
 }
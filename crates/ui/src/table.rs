@@ -1,47 +1,110 @@
-use std::{ops::Range, rc::Rc, time::Duration};
+use std::{cell::RefCell, ops::Range, rc::Rc, time::Duration};
 
 use crate::{
-    actions::{Cancel, SelectNext, SelectPrev},
+    actions::{Cancel, Confirm, SelectNext, SelectPrev},
     context_menu::ContextMenuExt,
     h_flex,
     popup_menu::PopupMenu,
     scroll::{self, ScrollableMask, Scrollbar, ScrollbarState},
-    v_flex, ActiveTheme, Icon, IconName, Sizable, Size, StyleSized as _, StyledExt,
+    v_flex,
+    virtual_list::v_virtual_list,
+    ActiveTheme, Icon, IconName, Sizable, Size, StyleSized as _, StyledExt,
 };
 use gpui::{
-    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, App, AppContext, Axis, Bounds,
-    Context, Div, DragMoveEvent, Edges, Empty, EntityId, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, KeyBinding, ListSizingBehavior, MouseButton, MouseDownEvent,
-    ParentElement, Pixels, Point, Render, ScrollHandle, ScrollStrategy, ScrollWheelEvent,
+    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, AnyElement, App, AppContext,
+    Axis, Bounds, Context, Div, DragMoveEvent, Edges, Empty, EntityId, EventEmitter, FocusHandle,
+    Focusable, InteractiveElement, IntoElement, KeyBinding, ListSizingBehavior, MouseButton,
+    MouseDownEvent, ParentElement, Pixels, Point, Render, ScrollHandle, ScrollWheelEvent,
     SharedString, Stateful, StatefulInteractiveElement as _, Styled, Task, UniformListScrollHandle,
     Window,
 };
+use serde::{Deserialize, Serialize};
 
+mod clipboard;
+mod editing;
+mod export;
+mod filter;
+mod footer;
 mod loading;
-
-actions!(table, [SelectPrevColumn, SelectNextColumn]);
+mod selection;
+mod sizing;
+mod state;
+mod tree;
+
+use editing::EditingCell;
+pub use export::ExportFormat;
+pub use filter::{ColFilter, ColFilterKind};
+pub use footer::Aggregate;
+use footer::Aggregates;
+pub use selection::*;
+pub use sizing::RowHeight;
+use sizing::{RowHeights, MAX_COL_WIDTH, MIN_COL_WIDTH};
+use state::ToggleColVisible;
+pub use state::{ColState, TableState};
+pub use tree::RowKind;
+
+actions!(
+    table,
+    [
+        SelectPrevColumn,
+        SelectNextColumn,
+        ExtendSelectionUp,
+        ExtendSelectionDown,
+        ExtendSelectionLeft,
+        ExtendSelectionRight,
+        SelectAll,
+        EditNextCell,
+        EditPrevCell,
+        Copy,
+        Paste,
+        ClearFilters
+    ]
+);
 
 pub fn init(cx: &mut App) {
     let context = Some("Table");
     cx.bind_keys([
         KeyBinding::new("escape", Cancel, context),
+        KeyBinding::new("enter", Confirm { secondary: false }, context),
+        KeyBinding::new("tab", EditNextCell, context),
+        KeyBinding::new("shift-tab", EditPrevCell, context),
         KeyBinding::new("up", SelectPrev, context),
         KeyBinding::new("down", SelectNext, context),
         KeyBinding::new("left", SelectPrevColumn, context),
         KeyBinding::new("right", SelectNextColumn, context),
+        KeyBinding::new("shift-up", ExtendSelectionUp, context),
+        KeyBinding::new("shift-down", ExtendSelectionDown, context),
+        KeyBinding::new("shift-left", ExtendSelectionLeft, context),
+        KeyBinding::new("shift-right", ExtendSelectionRight, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-a", SelectAll, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-a", SelectAll, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-c", Copy, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-c", Copy, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-v", Paste, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-v", Paste, context),
     ]);
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ColFixed {
     Left,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(crate) struct ColGroup {
     pub(crate) width: Pixels,
     pub(crate) bounds: Bounds<Pixels>,
     pub(crate) sort: Option<ColSort>,
+    pub(crate) filter_kind: Option<ColFilterKind>,
+    pub(crate) filter: Option<ColFilter>,
+    pub(crate) grouped: bool,
+    pub(crate) hidden: bool,
     pub(crate) fixed: Option<ColFixed>,
     pub(crate) padding: Option<Edges<Pixels>>,
 }
@@ -54,7 +117,7 @@ pub(crate) struct DragCol {
     pub(crate) col_ix: usize,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ColSort {
     /// No sorting.
     Default,
@@ -103,8 +166,16 @@ pub enum TableEvent {
     /// Double click on the row.
     DoubleClickedRow(usize),
     SelectCol(usize),
+    /// The selected rows changed in [`SelectionMode::MultiRow`].
+    SelectRows(RowSelection),
+    /// The selected cells changed in [`SelectionMode::Cell`].
+    SelectCells(CellRange),
     ColWidthsChanged(Vec<Pixels>),
     MoveCol(usize, usize),
+    /// The row is expanded (true) or collapsed (false).
+    ExpandRow(usize, bool),
+    /// The column is shown (true) or hidden (false).
+    ColVisibilityChanged(usize, bool),
 }
 
 #[derive(Clone, Copy, Default)]
@@ -150,10 +221,20 @@ pub struct Table<D: TableDelegate> {
     pub horizontal_scroll_state: ScrollbarState,
 
     scrollbar_visible: Edges<bool>,
+    selection_mode: SelectionMode,
     selected_row: Option<usize>,
+    /// The selected rows, the `selected_row` is the active one.
+    selected_rows: RowSelection,
+    /// The active cell `(row_ix, col_ix)` in [`SelectionMode::Cell`].
+    selected_cell: Option<(usize, usize)>,
+    /// The cell `(row_ix, col_ix)` to extend the selection from.
+    selection_anchor: Option<(usize, usize)>,
     selection_state: SelectionState,
     right_clicked_row: Option<usize>,
+    /// The column header that is right clicked to show the header context menu.
+    right_clicked_col: Option<usize>,
     selected_col: Option<usize>,
+    editing_cell: Option<EditingCell>,
 
     /// The column index that is being resized.
     resizing_col: Option<usize>,
@@ -166,6 +247,14 @@ pub struct Table<D: TableDelegate> {
     size: Size,
     /// The visible range of the rows and columns.
     visible_range: VisibleRangeState,
+    /// The row height mode and the measured row heights.
+    row_heights: RowHeights,
+    /// The cached aggregated values of the footer row.
+    aggregates: RefCell<Aggregates>,
+    /// Set to fit all columns to the width of the table.
+    fit_to_width: bool,
+    /// The table width that the columns were fitted to.
+    fitted_width: Option<Pixels>,
 
     _measure: Vec<Duration>,
     _load_more_task: Task<()>,
@@ -181,6 +270,12 @@ pub trait TableDelegate: Sized + 'static {
     /// Returns the name of the column at the given index.
     fn col_name(&self, col_ix: usize, cx: &App) -> SharedString;
 
+    /// Returns the unique key of the column at the given index to match the column in
+    /// [`TableState`], default to the column name.
+    fn col_key(&self, col_ix: usize, cx: &App) -> SharedString {
+        self.col_name(col_ix, cx)
+    }
+
     /// Returns whether the column at the given index can be resized. Default: true
     fn can_resize_col(&self, col_ix: usize, cx: &App) -> bool {
         true
@@ -208,6 +303,14 @@ pub trait TableDelegate: Sized + 'static {
         None
     }
 
+    /// Return the filter kind of the column at the given index, to show the filter popover
+    /// in the column header.
+    ///
+    /// This is only called when the table initializes.
+    fn col_filter(&self, col_ix: usize, cx: &App) -> Option<ColFilterKind> {
+        None
+    }
+
     /// Return the fixed side of the column at the given index.
     fn col_fixed(&self, col_ix: usize, cx: &App) -> Option<ColFixed> {
         None
@@ -230,6 +333,18 @@ pub trait TableDelegate: Sized + 'static {
     ) {
     }
 
+    /// Perform filter by the active filters of the columns, the `filters` is empty if all
+    /// filters are cleared.
+    ///
+    /// See also [`ColFilter::is_match`].
+    fn perform_filter(
+        &mut self,
+        filters: &[(usize, ColFilter)],
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
     /// Render the header cell at the given column index, default to the column name.
     fn render_th(
         &self,
@@ -250,6 +365,80 @@ pub trait TableDelegate: Sized + 'static {
         h_flex().id(("table-row", row_ix))
     }
 
+    /// Return the height of the row at the given index in [`RowHeight::Variable`], or the
+    /// minimum height in [`RowHeight::Measured`].
+    ///
+    /// Return None to use the row height of the table size.
+    fn row_height(&self, row_ix: usize, cx: &App) -> Option<Pixels> {
+        None
+    }
+
+    /// Return the kind of the row at the given index. Default: [`RowKind::Normal`]
+    fn row_kind(&self, row_ix: usize, cx: &App) -> RowKind {
+        RowKind::Normal
+    }
+
+    /// Return the depth of the row at the given index in the tree, the first column will be
+    /// indented by the depth. Default: 0
+    ///
+    /// The table is still a flat list of rows, the delegate should provide the visible rows
+    /// of the tree in order, the children rows follow their parent row.
+    fn row_depth(&self, row_ix: usize, cx: &App) -> usize {
+        0
+    }
+
+    /// Return true if the row at the given index has children or a detail row, to show the
+    /// expand toggle in the first column. Default: false
+    fn row_expandable(&self, row_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Return true if the row at the given index is expanded. Default: false
+    fn row_expanded(&self, row_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Expand or collapse the row at the given index.
+    ///
+    /// The delegate should insert (or remove) the children rows or the [`RowKind::Detail`]
+    /// row right after the row, then the `rows_count` will be changed.
+    fn perform_expand_row(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
+    /// Render the content of the [`RowKind::Detail`] row at the given index, it spans the
+    /// full width of the table.
+    fn render_detail_row(
+        &self,
+        row_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        Empty
+    }
+
+    /// Return true if the rows can be grouped by the column at the given index. Default: false
+    fn can_group_by_col(&self, col_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Group the rows by the column at the given index, `None` to ungroup.
+    ///
+    /// The delegate should provide the [`RowKind::Group`] rows as the group headers, and the
+    /// rows of the group as the children of it.
+    fn perform_group_by(
+        &mut self,
+        col_ix: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
     /// Render the context menu for the row at the given row index.
     fn context_menu(&self, row_ix: usize, menu: PopupMenu, window: &Window, cx: &App) -> PopupMenu {
         menu
@@ -264,6 +453,91 @@ pub trait TableDelegate: Sized + 'static {
         cx: &mut Context<Table<Self>>,
     ) -> impl IntoElement;
 
+    /// Return true if the cell at the given row and column can be edited. Default: false
+    ///
+    /// The editing is started by double-click or `enter` on the cell.
+    fn can_edit_cell(&self, row_ix: usize, col_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Called when start editing the cell at the given row and column.
+    ///
+    /// This is used to prepare the editor state with the cell value, and focus it.
+    fn start_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
+    /// Render the editor of the editing cell at the given row and column, e.g.:
+    /// [`TextInput`], [`NumberInput`], [`Dropdown`], [`Checkbox`] or [`DatePicker`].
+    ///
+    /// Default to the `render_td`.
+    ///
+    /// [`TextInput`]: crate::input::TextInput
+    /// [`NumberInput`]: crate::input::NumberInput
+    /// [`Dropdown`]: crate::dropdown::Dropdown
+    /// [`Checkbox`]: crate::checkbox::Checkbox
+    /// [`DatePicker`]: crate::date_picker::DatePicker
+    fn render_cell_editor(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        self.render_td(row_ix, col_ix, window, cx)
+            .into_any_element()
+    }
+
+    /// Commit the edited value of the cell at the given row and column.
+    ///
+    /// Return an error message to keep editing, it will be shown in the cell.
+    fn commit_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        Ok(())
+    }
+
+    /// Called when the editing of the cell at the given row and column is cancelled.
+    fn cancel_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
+    /// Return the plain text of the cell at the given row and column, to copy and export.
+    ///
+    /// Default: empty
+    fn cell_text(&self, row_ix: usize, col_ix: usize, cx: &App) -> SharedString {
+        SharedString::default()
+    }
+
+    /// Paste the `rows` of the cells text into the table, start from the cell at the given row
+    /// and column.
+    ///
+    /// The `rows` are parsed from the tab-separated text in the clipboard, e.g.: copied from
+    /// the table or spreadsheet, and clipped to the table bounds.
+    fn paste_cells(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        rows: Vec<Vec<String>>,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
     /// Return true to enable loop selection on the table.
     ///
     /// When the prev/next selection is out of the table bounds, the selection will loop to the other side.
@@ -339,6 +613,46 @@ pub trait TableDelegate: Sized + 'static {
     /// so you must check if there is more data to load or lock the loading state.
     fn load_more(&mut self, window: &mut Window, cx: &mut Context<Table<Self>>) {}
 
+    /// Return true to show the sticky footer row under the rows. Default: false
+    fn has_footer(&self, cx: &App) -> bool {
+        false
+    }
+
+    /// Return the built-in aggregate function of the column at the given index to show in
+    /// the footer row. Default: None
+    fn col_aggregate(&self, col_ix: usize, cx: &App) -> Option<Aggregate> {
+        None
+    }
+
+    /// Render the footer cell at the given column index, default to show the
+    /// [`TableDelegate::col_aggregate`] with the `value`.
+    ///
+    /// The `value` is the aggregate of the [`RowKind::Normal`] rows by the
+    /// [`TableDelegate::cell_text`], `None` if there is no aggregate or no value. It is cached
+    /// until the rows changed, see [`Table::invalidate_aggregates`].
+    fn render_tf(
+        &self,
+        col_ix: usize,
+        value: Option<f64>,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let Some(aggregate) = self.col_aggregate(col_ix, cx) else {
+            return Empty.into_any_element();
+        };
+
+        h_flex()
+            .gap_1()
+            .overflow_hidden()
+            .child(
+                div()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(aggregate.label()),
+            )
+            .child(aggregate.format(value))
+            .into_any_element()
+    }
+
     /// Render the last empty column, default to empty.
     fn render_last_empty_col(
         &mut self,
@@ -392,9 +706,15 @@ where
             vertical_scroll_state: ScrollbarState::default(),
             horizontal_scroll_state: ScrollbarState::default(),
             selection_state: SelectionState::Row,
+            selection_mode: SelectionMode::default(),
             selected_row: None,
+            selected_rows: RowSelection::default(),
+            selected_cell: None,
+            selection_anchor: None,
             right_clicked_row: None,
+            right_clicked_col: None,
             selected_col: None,
+            editing_cell: None,
             resizing_col: None,
             bounds: Bounds::default(),
             fixed_head_cols_bounds: Bounds::default(),
@@ -403,6 +723,10 @@ where
             size: Size::default(),
             scrollbar_visible: Edges::all(true),
             visible_range: VisibleRangeState::default(),
+            row_heights: RowHeights::default(),
+            aggregates: RefCell::default(),
+            fit_to_width: false,
+            fitted_width: None,
             _load_more_task: Task::ready(()),
             _measure: Vec::new(),
         };
@@ -439,6 +763,7 @@ where
     /// Set the size to the table.
     pub fn set_size(&mut self, size: Size, cx: &mut Context<Self>) {
         self.size = size;
+        self.row_heights.clear_measured();
         cx.notify();
     }
 
@@ -447,6 +772,18 @@ where
         self.size
     }
 
+    /// Set the selection mode of the table, default to [`SelectionMode::Row`].
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.selection_mode = mode;
+        self
+    }
+
+    /// Set the selection mode of the table, the current selection will be cleared.
+    pub fn set_selection_mode(&mut self, mode: SelectionMode, cx: &mut Context<Self>) {
+        self.selection_mode = mode;
+        self.clear_selection(cx);
+    }
+
     /// Set scrollbar visibility.
     pub fn scrollbar_visible(mut self, vertical: bool, horizontal: bool) -> Self {
         self.scrollbar_visible = Edges {
@@ -458,32 +795,58 @@ where
     }
 
     /// When we update columns or rows, we need to refresh the table.
+    ///
+    /// The width, sort, visibility and filter of the columns are kept, and the fixed columns
+    /// are reset by [`TableDelegate::col_fixed`].
     pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.row_heights.clear_measured();
+        self.aggregates.get_mut().clear();
         self.prepare_col_groups(cx);
     }
 
     fn prepare_col_groups(&mut self, cx: &mut Context<Self>) {
         self.col_groups = (0..self.delegate.cols_count(cx))
-            .map(|col_ix| ColGroup {
-                width: self.delegate.col_width(col_ix, cx),
-                padding: self.delegate.col_padding(col_ix, cx),
-                bounds: Bounds::default(),
-                sort: self.delegate.col_sort(col_ix, cx),
-                fixed: self.delegate.col_fixed(col_ix, cx),
+            .map(|col_ix| {
+                let filter_kind = self.delegate.col_filter(col_ix, cx);
+                // Keep the active filter of the column after refresh.
+                let filter = filter_kind.as_ref().and_then(|_| {
+                    self.col_groups
+                        .get(col_ix)
+                        .and_then(|col_group| col_group.filter.clone())
+                });
+                // Keep the layout of the column after refresh, e.g.: loaded by `Table::load`.
+                let old = self.col_groups.get(col_ix);
+                let hidden = old.map_or(false, |col_group| col_group.hidden);
+                let width = old.map_or_else(
+                    || self.delegate.col_width(col_ix, cx),
+                    |col_group| col_group.width,
+                );
+                // The column is not sortable if the delegate returns None.
+                let sort = self
+                    .delegate
+                    .col_sort(col_ix, cx)
+                    .map(|sort| old.and_then(|col_group| col_group.sort).unwrap_or(sort));
+
+                ColGroup {
+                    width,
+                    padding: self.delegate.col_padding(col_ix, cx),
+                    bounds: Bounds::default(),
+                    sort,
+                    filter_kind,
+                    filter,
+                    grouped: false,
+                    hidden,
+                    fixed: self.delegate.col_fixed(col_ix, cx),
+                }
             })
             .collect();
-        self.fixed_cols.left = self
-            .col_groups
-            .iter()
-            .filter(|col| col.fixed == Some(ColFixed::Left))
-            .count();
+        self.update_fixed_cols();
         cx.notify();
     }
 
     /// Scroll to the row at the given index.
     pub fn scroll_to_row(&mut self, row_ix: usize, cx: &mut Context<Self>) {
-        self.vertical_scroll_handle
-            .scroll_to_item(row_ix, ScrollStrategy::Top);
+        self.scroll_to_row_ix(row_ix, cx);
         cx.notify();
     }
 
@@ -500,15 +863,131 @@ where
     }
 
     /// Sets the selected row to the given index.
+    ///
+    /// In [`SelectionMode::Cell`], the cell of the active column in the row will be selected.
     pub fn set_selected_row(&mut self, row_ix: usize, cx: &mut Context<Self>) {
+        if self.selection_mode == SelectionMode::Cell {
+            let col_ix = self.selected_cell.map_or(0, |(_, col_ix)| col_ix);
+            self.select_cell(row_ix, col_ix, false, cx);
+        } else {
+            self.select_row(row_ix, false, false, cx);
+        }
+    }
+
+    /// Returns the selected rows.
+    ///
+    /// In [`SelectionMode::Cell`], it is the rows of the selected cells.
+    pub fn selected_rows(&self) -> &RowSelection {
+        &self.selected_rows
+    }
+
+    /// Sets the selected rows, the last one will be the active row.
+    ///
+    /// Only the last row will be selected if the selection mode is not
+    /// [`SelectionMode::MultiRow`].
+    pub fn set_selected_rows(
+        &mut self,
+        rows: impl IntoIterator<Item = usize>,
+        cx: &mut Context<Self>,
+    ) {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let Some(&last_row) = rows.last() else {
+            self.clear_selection(cx);
+            return;
+        };
+
+        if self.selection_mode != SelectionMode::MultiRow {
+            self.set_selected_row(last_row, cx);
+            return;
+        }
+
+        self.select_row(last_row, false, false, cx);
+        self.selected_rows = rows.into_iter().collect();
+        cx.emit(TableEvent::SelectRows(self.selected_rows.clone()));
+    }
+
+    /// Returns the selected cells in [`SelectionMode::Cell`].
+    pub fn selected_cells(&self) -> Option<CellRange> {
+        if self.selection_mode != SelectionMode::Cell {
+            return None;
+        }
+
+        let active = self.selected_cell?;
+        Some(CellRange::new(
+            self.selection_anchor.unwrap_or(active),
+            active,
+        ))
+    }
+
+    /// Sets the selected cells in [`SelectionMode::Cell`], the last cell of the range will
+    /// be the active one.
+    pub fn set_selected_cells(&mut self, range: CellRange, cx: &mut Context<Self>) {
+        if self.selection_mode != SelectionMode::Cell {
+            return;
+        }
+        if range.rows.is_empty() || range.cols.is_empty() {
+            self.clear_selection(cx);
+            return;
+        }
+
+        self.selection_anchor = Some((range.rows.start, range.cols.start));
+        self.select_cell(range.rows.end - 1, range.cols.end - 1, true, cx);
+    }
+
+    /// Select the row in [`SelectionMode::Row`] or [`SelectionMode::MultiRow`].
+    ///
+    /// In [`SelectionMode::MultiRow`], select the rows from the anchor row to the row if
+    /// `extend`, or toggle the row if `toggle`.
+    fn select_row(&mut self, row_ix: usize, extend: bool, toggle: bool, cx: &mut Context<Self>) {
+        let multiple = self.selection_mode == SelectionMode::MultiRow;
+        let anchor_row = self.selection_anchor.map(|(row_ix, _)| row_ix);
+
+        match anchor_row {
+            Some(anchor_row) if multiple && extend => {
+                self.selected_rows.clear();
+                self.selected_rows
+                    .insert(anchor_row.min(row_ix)..anchor_row.max(row_ix) + 1);
+            }
+            _ => {
+                if multiple && toggle {
+                    self.selected_rows.toggle(row_ix);
+                } else {
+                    self.selected_rows.clear();
+                    self.selected_rows.insert(row_ix..row_ix + 1);
+                }
+                self.selection_anchor = Some((row_ix, 0));
+            }
+        }
+
         self.selection_state = SelectionState::Row;
         self.right_clicked_row = None;
         self.selected_row = Some(row_ix);
-        if let Some(row_ix) = self.selected_row {
-            self.vertical_scroll_handle
-                .scroll_to_item(row_ix, ScrollStrategy::Top);
-        }
+        self.scroll_to_row_ix(row_ix, cx);
         cx.emit(TableEvent::SelectRow(row_ix));
+        if multiple {
+            cx.emit(TableEvent::SelectRows(self.selected_rows.clone()));
+        }
+        cx.notify();
+    }
+
+    /// Select the cell in [`SelectionMode::Cell`], extend the range from the anchor cell if
+    /// `extend`.
+    fn select_cell(&mut self, row_ix: usize, col_ix: usize, extend: bool, cx: &mut Context<Self>) {
+        if !extend || self.selection_anchor.is_none() {
+            self.selection_anchor = Some((row_ix, col_ix));
+        }
+
+        self.selection_state = SelectionState::Row;
+        self.right_clicked_row = None;
+        self.selected_row = Some(row_ix);
+        self.selected_cell = Some((row_ix, col_ix));
+        let Some(range) = self.selected_cells() else {
+            return;
+        };
+        self.selected_rows.clear();
+        self.selected_rows.insert(range.rows.clone());
+        self.scroll_to_row_ix(row_ix, cx);
+        cx.emit(TableEvent::SelectCells(range));
         cx.notify();
     }
 
@@ -535,6 +1014,9 @@ where
     pub fn clear_selection(&mut self, cx: &mut Context<Self>) {
         self.selection_state = SelectionState::Row;
         self.selected_row = None;
+        self.selected_rows.clear();
+        self.selected_cell = None;
+        self.selection_anchor = None;
         self.selected_col = None;
         cx.notify();
     }
@@ -548,13 +1030,25 @@ where
         &mut self,
         ev: &MouseDownEvent,
         row_ix: usize,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if !self.commit_cell_edit(window, cx) {
+            return;
+        }
+
         if ev.button == MouseButton::Right {
             self.right_clicked_row = Some(row_ix);
+            self.right_clicked_col = None;
         } else {
-            self.set_selected_row(row_ix, cx);
+            match self.selection_mode {
+                SelectionMode::Row => self.set_selected_row(row_ix, cx),
+                SelectionMode::MultiRow => {
+                    self.select_row(row_ix, ev.modifiers.shift, ev.modifiers.secondary(), cx)
+                }
+                // The cell is selected by `on_cell_click`.
+                SelectionMode::Cell => {}
+            }
 
             if ev.click_count == 2 {
                 cx.emit(TableEvent::DoubleClickedRow(row_ix));
@@ -562,6 +1056,30 @@ where
         }
     }
 
+    fn on_cell_click(
+        &mut self,
+        ev: &MouseDownEvent,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.commit_cell_edit(window, cx) {
+            cx.stop_propagation();
+            return;
+        }
+
+        if self.selection_mode == SelectionMode::Cell {
+            self.select_cell(row_ix, col_ix, ev.modifiers.shift, cx);
+        }
+
+        if ev.click_count == 2 && self.delegate.can_edit_cell(row_ix, col_ix, cx) {
+            self.edit_cell(row_ix, col_ix, window, cx);
+            // Keep the focus in the editor.
+            cx.stop_propagation();
+        }
+    }
+
     fn on_col_head_click(&mut self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) {
         if !self.delegate.can_select_col(col_ix, cx) {
             return;
@@ -570,13 +1088,51 @@ where
         self.set_selected_col(col_ix, cx)
     }
 
-    fn action_cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
+    fn action_cancel(&mut self, _: &Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing_cell.is_some() {
+            self.cancel_cell_edit(window, cx);
+            return;
+        }
+
         self.clear_selection(cx);
     }
 
+    fn action_confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing_cell.is_some() {
+            self.commit_cell_edit(window, cx);
+            return;
+        }
+
+        if !self.edit_selected_cell(window, cx) {
+            cx.propagate();
+        }
+    }
+
+    fn action_edit_next_cell(
+        &mut self,
+        _: &EditNextCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.edit_next_cell(false, window, cx) {
+            cx.propagate();
+        }
+    }
+
+    fn action_edit_prev_cell(
+        &mut self,
+        _: &EditPrevCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.edit_next_cell(true, window, cx) {
+            cx.propagate();
+        }
+    }
+
     fn action_select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
         let rows_count = self.delegate.rows_count(cx);
-        if rows_count < 1 {
+        if rows_count < 1 || self.editing_cell.is_some() {
             return;
         }
 
@@ -594,7 +1150,7 @@ where
 
     fn action_select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
         let rows_count = self.delegate.rows_count(cx);
-        if rows_count < 1 {
+        if rows_count < 1 || self.editing_cell.is_some() {
             return;
         }
 
@@ -616,9 +1172,18 @@ where
     fn action_select_prev_col(
         &mut self,
         _: &SelectPrevColumn,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.selection_mode == SelectionMode::Cell {
+            self.move_selected_cell(0, -1, false, cx);
+            return;
+        }
+
+        if self.expand_selected_row(false, window, cx) {
+            return;
+        }
+
         let mut selected_col = self.selected_col.unwrap_or(0);
         let cols_count = self.delegate.cols_count(cx);
         if selected_col > 0 {
@@ -634,9 +1199,18 @@ where
     fn action_select_next_col(
         &mut self,
         _: &SelectNextColumn,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.selection_mode == SelectionMode::Cell {
+            self.move_selected_cell(0, 1, false, cx);
+            return;
+        }
+
+        if self.expand_selected_row(true, window, cx) {
+            return;
+        }
+
         let mut selected_col = self.selected_col.unwrap_or(0);
         if selected_col < self.delegate.cols_count(cx).saturating_sub(1) {
             selected_col += 1;
@@ -649,6 +1223,135 @@ where
         self.set_selected_col(selected_col, cx);
     }
 
+    fn action_extend_selection_up(
+        &mut self,
+        _: &ExtendSelectionUp,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selected_cell(-1, 0, true, cx);
+    }
+
+    fn action_extend_selection_down(
+        &mut self,
+        _: &ExtendSelectionDown,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selected_cell(1, 0, true, cx);
+    }
+
+    fn action_extend_selection_left(
+        &mut self,
+        _: &ExtendSelectionLeft,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selected_cell(0, -1, true, cx);
+    }
+
+    fn action_extend_selection_right(
+        &mut self,
+        _: &ExtendSelectionRight,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selected_cell(0, 1, true, cx);
+    }
+
+    fn action_select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        let rows_count = self.delegate.rows_count(cx);
+        let cols_count = self.delegate.cols_count(cx);
+        if rows_count == 0 || cols_count == 0 || self.editing_cell.is_some() {
+            return;
+        }
+
+        match self.selection_mode {
+            SelectionMode::Row => {}
+            SelectionMode::MultiRow => {
+                self.selected_rows.clear();
+                self.selected_rows.insert(0..rows_count);
+                self.selection_anchor = Some((0, 0));
+                self.selection_state = SelectionState::Row;
+                cx.emit(TableEvent::SelectRows(self.selected_rows.clone()));
+                cx.notify();
+            }
+            SelectionMode::Cell => {
+                // Select from the first to the last visible column.
+                let col_visible = |ix| self.col_visible(ix);
+                let (Some(first_col), Some(last_col)) = (
+                    selection::move_visible_col(0, 0, cols_count, col_visible),
+                    selection::move_visible_col(cols_count - 1, 0, cols_count, col_visible),
+                ) else {
+                    return;
+                };
+                let (row_ix, col_ix) = self.selected_cell.unwrap_or((0, first_col));
+                let active = (
+                    row_ix,
+                    selection::move_visible_col(col_ix, 0, cols_count, col_visible)
+                        .unwrap_or(first_col),
+                );
+                let range = CellRange::new((0, first_col), (rows_count - 1, last_col));
+                // Keep the active cell, and the anchor is the opposite corner of it.
+                self.selection_anchor = Some((
+                    if active.0 == 0 { range.rows.end - 1 } else { 0 },
+                    if active.1 == first_col {
+                        last_col
+                    } else {
+                        first_col
+                    },
+                ));
+                self.selected_cell = Some(active);
+                self.selected_rows.clear();
+                self.selected_rows.insert(range.rows.clone());
+                cx.emit(TableEvent::SelectCells(range));
+                cx.notify();
+            }
+        }
+    }
+
+    /// Move the active row or cell by the offsets without looping, and extend the selection
+    /// if `extend`.
+    fn move_selected_cell(
+        &mut self,
+        row_offset: isize,
+        col_offset: isize,
+        extend: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let rows_count = self.delegate.rows_count(cx);
+        let cols_count = self.delegate.cols_count(cx);
+        if rows_count == 0 || cols_count == 0 || self.editing_cell.is_some() {
+            return;
+        }
+
+        let (row_ix, col_ix) = self
+            .selected_cell
+            .filter(|_| self.selection_mode == SelectionMode::Cell)
+            .or(self.selected_row.map(|row_ix| (row_ix, 0)))
+            .unwrap_or((0, 0));
+        let row_ix = row_ix.saturating_add_signed(row_offset).min(rows_count - 1);
+
+        match self.selection_mode {
+            SelectionMode::Row | SelectionMode::MultiRow => {
+                if row_offset != 0 {
+                    self.select_row(row_ix, extend, false, cx);
+                }
+            }
+            SelectionMode::Cell => {
+                // Skip the hidden columns.
+                let Some(col_ix) =
+                    selection::move_visible_col(col_ix, col_offset, cols_count, |ix| {
+                        self.col_visible(ix)
+                    })
+                else {
+                    return;
+                };
+                self.select_cell(row_ix, col_ix, extend, cx);
+            }
+        }
+    }
+
     /// Scroll table when mouse position is near the edge of the table bounds.
     fn scroll_table_by_col_resizing(&mut self, mouse_position: Point<Pixels>, col_group: ColGroup) {
         // Do nothing if pos out of the table bounds right for avoid scroll to the right.
@@ -675,9 +1378,6 @@ where
     /// The `ix`` is the index of the col to resize,
     /// and the `size` is the new size for the col.
     fn resize_cols(&mut self, ix: usize, size: Pixels, _: &mut Window, cx: &mut Context<Self>) {
-        const MIN_WIDTH: Pixels = px(10.0);
-        const MAX_WIDTH: Pixels = px(1200.0);
-
         if !self.delegate.can_resize_col(ix, cx) {
             return;
         }
@@ -685,7 +1385,7 @@ where
 
         let old_width = self.col_groups[ix].width;
         let new_width = size;
-        if new_width < MIN_WIDTH {
+        if new_width < MIN_COL_WIDTH {
             return;
         }
         let changed_width = new_width - old_width;
@@ -693,11 +1393,11 @@ where
         if changed_width > px(-1.0) && changed_width < px(1.0) {
             return;
         }
-        self.col_groups[ix].width = new_width.min(MAX_WIDTH);
+        self.col_groups[ix].width = new_width.min(MAX_COL_WIDTH);
 
         // Resize next col, table not need to resize the right cols.
         // let next_width = self.col_groups[ix + 1].width.unwrap_or_default();
-        // let next_width = (next_width - changed_width).max(MIN_WIDTH);
+        // let next_width = (next_width - changed_width).max(MIN_COL_WIDTH);
         // self.col_groups[ix + 1].width = Some(next_width);
 
         cx.notify();
@@ -726,6 +1426,7 @@ where
             }
         }
 
+        self.row_heights.clear_measured();
         self.delegate_mut().perform_sort(col_ix, sort, window, cx);
 
         cx.notify();
@@ -809,6 +1510,11 @@ where
             return div();
         };
 
+        // Keep the hidden column in the layout with zero width, the column index is not changed.
+        if col_group.hidden {
+            return div().w(px(0.)).h_full().flex_shrink_0().overflow_hidden();
+        }
+
         let col_width = col_group.width;
         let col_padding = col_group.padding;
 
@@ -830,9 +1536,56 @@ where
     }
 
     /// Show Column selection style, when the column is selected and the selection state is Column.
-    fn render_col_wrap(&self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) -> Div {
+    ///
+    /// Or the cell selection style in [`SelectionMode::Cell`].
+    ///
+    /// The cell click is handled here to select the cell or start editing.
+    fn render_col_wrap(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Div {
         let el = h_flex().h_full();
 
+        if self.selection_mode == SelectionMode::Cell {
+            let is_selected = self
+                .selected_cells()
+                .map_or(false, |range| range.contains(row_ix, col_ix));
+            let is_active = self.selected_cell == Some((row_ix, col_ix));
+
+            return el
+                .relative()
+                .when(is_selected, |this| this.bg(cx.theme().table_active))
+                .when(is_active, |this| {
+                    this.child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .left_0()
+                            .size_full()
+                            .border_1()
+                            .border_color(cx.theme().table_active_border),
+                    )
+                })
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, ev, window, cx| {
+                        this.on_cell_click(ev, row_ix, col_ix, window, cx);
+                    }),
+                );
+        }
+
+        let el = el.when(self.delegate.can_edit_cell(row_ix, col_ix, cx), |this| {
+            this.on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, ev, window, cx| {
+                    this.on_cell_click(ev, row_ix, col_ix, window, cx);
+                }),
+            )
+        });
+
         if self.delegate().can_select_col(col_ix, cx)
             && self.selected_col == Some(col_ix)
             && self.selection_state == SelectionState::Column
@@ -936,8 +1689,11 @@ where
                             let ix = *ix;
                             view.resizing_col = Some(ix);
 
-                            let col_group =
-                                *view.col_groups.get(ix).expect("BUG: invalid col index");
+                            let col_group = view
+                                .col_groups
+                                .get(ix)
+                                .expect("BUG: invalid col index")
+                                .clone();
 
                             view.resize_cols(
                                 ix,
@@ -956,6 +1712,15 @@ where
                 cx.stop_propagation();
                 cx.new(|_| drag.clone())
             })
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |view, e: &MouseDownEvent, window, cx| {
+                    // Double click to fit the column to the content.
+                    if e.click_count == 2 {
+                        view.auto_fit_col(ix, window, cx);
+                    }
+                }),
+            )
             .on_mouse_up_out(
                 MouseButton::Left,
                 cx.listener(|view, _, _, cx| {
@@ -964,6 +1729,7 @@ where
                     }
 
                     view.resizing_col = None;
+                    view.row_heights.clear_measured();
 
                     let new_widths = view.col_groups.iter().map(|g| g.width).collect();
                     cx.emit(TableEvent::ColWidthsChanged(new_widths));
@@ -1038,6 +1804,14 @@ where
                             this.on_col_head_click(col_ix, window, cx);
                         }),
                     )
+                    .on_mouse_down(
+                        MouseButton::Right,
+                        cx.listener(move |this, _, _, cx| {
+                            this.right_clicked_col = Some(col_ix);
+                            this.right_clicked_row = None;
+                            cx.notify();
+                        }),
+                    )
                     .child(
                         h_flex()
                             .size_full()
@@ -1050,7 +1824,14 @@ where
                                     self.size.table_cell_padding().right - paddings.right;
                                 this.pr(offset_pr.max(px(0.)))
                             })
-                            .children(self.render_sort_icon(col_ix, &col_group, window, cx)),
+                            .child(
+                                h_flex()
+                                    .gap_0p5()
+                                    .children(self.render_filter_icon(col_ix, &col_group, cx))
+                                    .children(
+                                        self.render_sort_icon(col_ix, &col_group, window, cx),
+                                    ),
+                            ),
                     )
                     .when(moveable, |this| {
                         this.on_drag(
@@ -1084,7 +1865,9 @@ where
                     }),
             )
             // resize handle
-            .child(self.render_resize_handle(col_ix, window, cx))
+            .when(!col_group.hidden, |this| {
+                this.child(self.render_resize_handle(col_ix, window, cx))
+            })
             // to save the bounds of this col.
             .child({
                 let view = cx.entity().clone();
@@ -1198,21 +1981,49 @@ where
     ) -> impl IntoElement {
         let horizontal_scroll_handle = self.horizontal_scroll_handle.clone();
         let is_stripe_row = self.stripe && row_ix % 2 != 0;
-        let is_selected = self.selected_row == Some(row_ix);
+        let is_selected = match self.selection_mode {
+            SelectionMode::Row => self.selected_row == Some(row_ix),
+            SelectionMode::MultiRow => self.selected_rows.contains(row_ix),
+            SelectionMode::Cell => false,
+        };
         let view = cx.entity().clone();
+        let row_kind = if row_ix < rows_count {
+            self.delegate.row_kind(row_ix, cx)
+        } else {
+            RowKind::Normal
+        };
 
-        if row_ix < rows_count {
+        if row_ix < rows_count && row_kind == RowKind::Detail {
+            self.delegate
+                .render_tr(row_ix, window, cx)
+                .h_flex()
+                .w_full()
+                .h(self.row_height_of(row_ix, cx))
+                .overflow_hidden()
+                .border_b_1()
+                .border_color(cx.theme().table_row_border)
+                .bg(cx.theme().table_head)
+                .child(
+                    h_flex()
+                        .size_full()
+                        .table_cell_size(self.size)
+                        .child(self.delegate.render_detail_row(row_ix, window, cx)),
+                )
+        } else if row_ix < rows_count {
             self.delegate
                 .render_tr(row_ix, window, cx)
                 .h_flex()
                 .w_full()
-                .h(self.size.table_row_height())
+                .h(self.row_height_of(row_ix, cx))
                 .border_b_1()
                 .when(row_ix == rows_count, |this| {
                     this.border_color(gpui::transparent_white())
                 })
                 .border_color(cx.theme().table_row_border)
                 .when(is_stripe_row, |this| this.bg(cx.theme().table_even))
+                .when(row_kind == RowKind::Group, |this| {
+                    this.bg(cx.theme().table_head).font_semibold()
+                })
                 .hover(|this| {
                     if is_selected || self.right_clicked_row == Some(row_ix) {
                         this
@@ -1230,11 +2041,12 @@ where
                                 let mut items = Vec::with_capacity(left_cols_count);
 
                                 (0..left_cols_count).for_each(|col_ix| {
-                                    items.push(self.render_col_wrap(col_ix, window, cx).child(
-                                        self.render_cell(col_ix, window, cx).child(
-                                            self.measure_render_td(row_ix, col_ix, window, cx),
+                                    items.push(
+                                        self.render_col_wrap(row_ix, col_ix, window, cx).child(
+                                            self.render_cell(col_ix, window, cx)
+                                                .child(self.render_td(row_ix, col_ix, window, cx)),
                                         ),
-                                    ));
+                                    );
                                 });
 
                                 items
@@ -1280,12 +2092,11 @@ where
 
                                         visible_range.for_each(|col_ix| {
                                             let col_ix = col_ix + left_cols_count;
-                                            let el =
-                                                table.render_col_wrap(col_ix, window, cx).child(
+                                            let el = table
+                                                .render_col_wrap(row_ix, col_ix, window, cx)
+                                                .child(
                                                     table.render_cell(col_ix, window, cx).child(
-                                                        table.measure_render_td(
-                                                            row_ix, col_ix, window, cx,
-                                                        ),
+                                                        table.render_td(row_ix, col_ix, window, cx),
                                                     ),
                                                 );
 
@@ -1301,24 +2112,22 @@ where
                         .child(self.delegate.render_last_empty_col(window, cx)),
                 )
                 // Row selected style
-                .when_some(self.selected_row, |this, _| {
-                    this.when(
-                        is_selected && self.selection_state == SelectionState::Row,
-                        |this| {
-                            this.border_color(gpui::transparent_white()).child(
-                                div()
-                                    .top(if row_ix == 0 { px(0.) } else { px(-1.) })
-                                    .left(px(0.))
-                                    .right(px(0.))
-                                    .bottom_0()
-                                    .absolute()
-                                    .bg(cx.theme().table_active)
-                                    .border_1()
-                                    .border_color(cx.theme().table_active_border),
-                            )
-                        },
-                    )
-                })
+                .when(
+                    is_selected && self.selection_state == SelectionState::Row,
+                    |this| {
+                        this.border_color(gpui::transparent_white()).child(
+                            div()
+                                .top(if row_ix == 0 { px(0.) } else { px(-1.) })
+                                .left(px(0.))
+                                .right(px(0.))
+                                .bottom_0()
+                                .absolute()
+                                .bg(cx.theme().table_active)
+                                .border_1()
+                                .border_color(cx.theme().table_active_border),
+                        )
+                    },
+                )
                 // Row right click row style
                 .when(self.right_clicked_row == Some(row_ix), |this| {
                     this.border_color(gpui::transparent_white()).child(
@@ -1363,8 +2172,56 @@ where
         }
     }
 
+    /// Render the rows in the visible range of the table body.
+    fn render_rows(
+        &mut self,
+        visible_range: Range<usize>,
+        rows_count: usize,
+        left_cols_count: usize,
+        cols_count: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<impl IntoElement> {
+        // We must calculate the col sizes here, because the col sizes
+        // need render_th first, then that method will set the bounds of each col.
+        let col_sizes: Rc<Vec<gpui::Size<Pixels>>> = Rc::new(
+            self.col_groups
+                .iter()
+                .skip(left_cols_count)
+                .map(|col| col.bounds.size)
+                .collect(),
+        );
+
+        self.load_more_if_need(rows_count, visible_range.end, window, cx);
+        self.update_visible_range_if_need(visible_range.clone(), Axis::Vertical, window, cx);
+        self.measure_rows(visible_range.clone(), rows_count, window, cx);
+
+        if visible_range.end > rows_count {
+            self.scroll_to_row(
+                std::cmp::min(visible_range.start, rows_count.saturating_sub(1)),
+                cx,
+            );
+        }
+
+        // Render fake rows to fill the table
+        visible_range
+            .map(|row_ix| {
+                // Render real rows for available data
+                self.render_table_row(
+                    row_ix,
+                    rows_count,
+                    left_cols_count,
+                    col_sizes.clone(),
+                    cols_count,
+                    window,
+                    cx,
+                )
+            })
+            .collect()
+    }
+
     /// Calculate the extra rows needed to fill the table empty space when `stripe` is true.
-    fn calculate_extra_rows_needed(&self, rows_count: usize) -> usize {
+    fn calculate_extra_rows_needed(&self, rows_count: usize, cx: &App) -> usize {
         if !self.stripe {
             return 0;
         }
@@ -1381,7 +2238,12 @@ where
             .size
             .height;
 
-        let actual_height = row_height * rows_count as f32;
+        let actual_height = match self.row_heights.mode {
+            RowHeight::Fixed => row_height * rows_count as f32,
+            _ => (0..rows_count).fold(px(0.), |height, row_ix| {
+                height + self.row_height_of(row_ix, cx)
+            }),
+        };
         let remaining_height = total_height - actual_height;
 
         if remaining_height > px(0.) {
@@ -1391,6 +2253,35 @@ where
         extra_rows_needed
     }
 
+    /// Render the cell content, or the editor if the cell is being edited.
+    fn render_td(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let td = if self.editing_cell() == Some((row_ix, col_ix)) {
+            self.render_editing_cell(row_ix, col_ix, window, cx)
+        } else {
+            self.measure_render_td(row_ix, col_ix, window, cx)
+                .into_any_element()
+        };
+
+        if col_ix > 0 {
+            return td;
+        }
+
+        match self.render_row_toggle(row_ix, cx) {
+            Some(toggle) => h_flex()
+                .size_full()
+                .child(toggle)
+                .child(div().flex_1().h_full().overflow_hidden().child(td))
+                .into_any_element(),
+            None => td,
+        }
+    }
+
     #[inline]
     fn measure_render_td(
         &mut self,
@@ -1460,6 +2351,7 @@ where
 {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         self.measure(window, cx);
+        self.fit_cols_if_need(window, cx);
 
         let view = cx.entity().clone();
         let vertical_scroll_handle = self.vertical_scroll_handle.clone();
@@ -1468,24 +2360,38 @@ where
         let left_cols_count = self.fixed_cols.left;
         let rows_count = self.delegate.rows_count(cx);
         let loading = self.delegate.loading(cx);
-        let extra_rows_needed = self.calculate_extra_rows_needed(rows_count);
+        let extra_rows_needed = self.calculate_extra_rows_needed(rows_count, cx);
 
         let inner_table = v_flex()
             .key_context("Table")
             .id("table")
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(Self::action_cancel))
+            .on_action(cx.listener(Self::action_confirm))
+            .on_action(cx.listener(Self::action_edit_next_cell))
+            .on_action(cx.listener(Self::action_edit_prev_cell))
             .on_action(cx.listener(Self::action_select_next))
             .on_action(cx.listener(Self::action_select_prev))
             .on_action(cx.listener(Self::action_select_next_col))
             .on_action(cx.listener(Self::action_select_prev_col))
+            .on_action(cx.listener(Self::action_extend_selection_up))
+            .on_action(cx.listener(Self::action_extend_selection_down))
+            .on_action(cx.listener(Self::action_extend_selection_left))
+            .on_action(cx.listener(Self::action_extend_selection_right))
+            .on_action(cx.listener(Self::action_select_all))
+            .on_action(cx.listener(Self::action_copy))
+            .on_action(cx.listener(Self::action_paste))
+            .on_action(cx.listener(Self::action_clear_filters))
+            .on_action(cx.listener(Self::action_toggle_col_visible))
             .size_full()
             .overflow_hidden()
             .child(self.render_table_head(left_cols_count, window, cx))
             .context_menu({
                 let view = view.clone();
                 move |this, window: &mut Window, cx: &mut Context<PopupMenu>| {
-                    if let Some(row_ix) = view.read(cx).right_clicked_row {
+                    if let Some(col_ix) = view.read(cx).right_clicked_col {
+                        view.read(cx).header_context_menu(col_ix, this, cx)
+                    } else if let Some(row_ix) = view.read(cx).right_clicked_row {
                         view.read(cx)
                             .delegate
                             .context_menu(row_ix, this, window, cx)
@@ -1502,77 +2408,69 @@ where
                             .child(self.delegate.render_empty(window, cx)),
                     )
                 } else {
-                    this.child(
-                        h_flex().id("table-body").flex_grow().size_full().child(
-                            uniform_list(
-                                "table-uniform-list",
-                                rows_count + extra_rows_needed,
-                                cx.processor(
-                                    move |table, visible_range: Range<usize>, window, cx| {
-                                        // We must calculate the col sizes here, because the col sizes
-                                        // need render_th first, then that method will set the bounds of each col.
-                                        let col_sizes: Rc<Vec<gpui::Size<Pixels>>> = Rc::new(
-                                            table
-                                                .col_groups
-                                                .iter()
-                                                .skip(left_cols_count)
-                                                .map(|col| col.bounds.size)
-                                                .collect(),
-                                        );
-
-                                        table.load_more_if_need(
-                                            rows_count,
-                                            visible_range.end,
-                                            window,
-                                            cx,
-                                        );
-                                        table.update_visible_range_if_need(
-                                            visible_range.clone(),
-                                            Axis::Vertical,
-                                            window,
-                                            cx,
-                                        );
-
-                                        if visible_range.end > rows_count {
-                                            table.scroll_to_row(
-                                                std::cmp::min(
-                                                    visible_range.start,
-                                                    rows_count.saturating_sub(1),
-                                                ),
-                                                cx,
-                                            );
-                                        }
-
-                                        let mut items = Vec::with_capacity(
-                                            visible_range.end.saturating_sub(visible_range.start),
-                                        );
-
-                                        // Render fake rows to fill the table
-                                        visible_range.for_each(|row_ix| {
-                                            // Render real rows for available data
-                                            items.push(table.render_table_row(
-                                                row_ix,
-                                                rows_count,
-                                                left_cols_count,
-                                                col_sizes.clone(),
-                                                cols_count,
-                                                window,
-                                                cx,
-                                            ));
-                                        });
-
-                                        items
-                                    },
-                                ),
+                    let rows_count_with_extra = rows_count + extra_rows_needed;
+                    let render_rows =
+                        move |table: &mut Self,
+                              visible_range: Range<usize>,
+                              window: &mut Window,
+                              cx: &mut Context<Self>| {
+                            table.render_rows(
+                                visible_range,
+                                rows_count,
+                                left_cols_count,
+                                cols_count,
+                                window,
+                                cx,
                             )
+                        };
+
+                    this.child(
+                        h_flex()
+                            .id("table-body")
                             .flex_grow()
                             .size_full()
-                            .with_sizing_behavior(ListSizingBehavior::Auto)
-                            .track_scroll(vertical_scroll_handle)
-                            .into_any_element(),
-                        ),
+                            .map(|this| {
+                                if self.row_heights.mode == RowHeight::Fixed {
+                                    this.child(
+                                        uniform_list(
+                                            "table-uniform-list",
+                                            rows_count_with_extra,
+                                            cx.processor(
+                                                move |table, visible_range, window, cx| {
+                                                    render_rows(table, visible_range, window, cx)
+                                                },
+                                            ),
+                                        )
+                                        .flex_grow()
+                                        .size_full()
+                                        .with_sizing_behavior(ListSizingBehavior::Auto)
+                                        .track_scroll(vertical_scroll_handle),
+                                    )
+                                } else {
+                                    // The uniform list requires the same height of the rows,
+                                    // use the virtual list with the scroll handle of it.
+                                    let scroll_handle =
+                                        vertical_scroll_handle.0.borrow().base_handle.clone();
+                                    this.child(
+                                        v_virtual_list(
+                                            view.clone(),
+                                            "table-virtual-list",
+                                            self.row_sizes(rows_count_with_extra, cx),
+                                            move |table, visible_range, _, window, cx| {
+                                                render_rows(table, visible_range, window, cx)
+                                            },
+                                        )
+                                        .flex_grow()
+                                        .size_full()
+                                        .track_scroll(&scroll_handle),
+                                    )
+                                }
+                            }),
                     )
                 }
+            })
+            .when(self.delegate.has_footer(cx), |this| {
+                this.child(self.render_table_foot(left_cols_count, window, cx))
             });
 
         let view = cx.entity().clone();
@@ -1594,15 +2492,27 @@ where
                         Axis::Horizontal,
                         &horizontal_scroll_handle,
                     ))
-                    .when(self.right_clicked_row.is_some(), |this| {
-                        this.on_mouse_down_out(cx.listener(|this, _, _, cx| {
-                            this.right_clicked_row = None;
-                            cx.notify();
-                        }))
-                    })
+                    .when(
+                        self.right_clicked_row.is_some() || self.right_clicked_col.is_some(),
+                        |this| {
+                            this.on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                                this.right_clicked_row = None;
+                                this.right_clicked_col = None;
+                                cx.notify();
+                            }))
+                        },
+                    )
             })
             .child(canvas(
-                move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
+                move |bounds, _, cx| {
+                    view.update(cx, |r, cx| {
+                        // Render again to fit the columns to the new width.
+                        if r.fit_to_width && r.bounds.size.width != bounds.size.width {
+                            cx.notify();
+                        }
+                        r.bounds = bounds;
+                    })
+                },
                 |_, _, _, _| {},
             ))
             .child(
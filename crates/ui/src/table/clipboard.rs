@@ -0,0 +1,216 @@
+use gpui::{App, ClipboardItem, Context, SharedString, Window};
+
+use super::{
+    export::{format_cells, parse_tsv},
+    CellRange, Copy, ExportFormat, Paste, RowSelection, SelectionMode, SelectionState, Table,
+    TableDelegate,
+};
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Returns the `(row_ix, col_ix)` of the cells in the selection, the hidden columns are
+    /// skipped.
+    ///
+    /// - The selected column of all rows, if the column is selected.
+    /// - The selected cells in [`SelectionMode::Cell`].
+    /// - The all columns of the selected rows, otherwise.
+    fn selected_cells_ix(&self, cx: &App) -> (Vec<usize>, Vec<usize>) {
+        let selected_col = self
+            .selected_col
+            .filter(|_| self.selection_state == SelectionState::Column);
+        cells_ix(
+            selected_col,
+            self.selected_cells(),
+            &self.selected_rows,
+            self.delegate.rows_count(cx),
+            self.delegate.cols_count(cx),
+            |col_ix| self.col_visible(col_ix),
+        )
+    }
+
+    /// Copy the selected cells to the clipboard in the `format`, the cells text is provided by
+    /// [`TableDelegate::cell_text`].
+    ///
+    /// The Markdown and HTML formats contain the header of the columns, the HTML `<table>` is
+    /// written as the plain text.
+    pub fn copy_selection(&self, format: ExportFormat, cx: &mut App) {
+        let (rows, cols) = self.selected_cells_ix(cx);
+        if rows.is_empty() || cols.is_empty() {
+            return;
+        }
+
+        let header = cols
+            .iter()
+            .map(|&col_ix| self.delegate.col_name(col_ix, cx))
+            .collect::<Vec<_>>();
+        let cells = rows
+            .iter()
+            .map(|&row_ix| {
+                cols.iter()
+                    .map(|&col_ix| self.delegate.cell_text(row_ix, col_ix, cx))
+                    .collect()
+            })
+            .collect::<Vec<Vec<SharedString>>>();
+
+        let text = match format {
+            ExportFormat::Tsv | ExportFormat::Csv => format_cells(None, &cells, format),
+            ExportFormat::Markdown | ExportFormat::Html => {
+                format_cells(Some(&header), &cells, format)
+            }
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    /// Paste the tab-separated text to the table by [`TableDelegate::paste_cells`], start
+    /// from the top-left cell of the selection.
+    ///
+    /// The cells out of the table bounds are clipped.
+    pub fn paste(&mut self, text: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let (rows, cols) = self.selected_cells_ix(cx);
+        let (Some(&row_ix), Some(&col_ix)) = (rows.first(), cols.first()) else {
+            return;
+        };
+
+        let cells = clip_cells(
+            parse_tsv(text),
+            row_ix,
+            col_ix,
+            self.delegate.rows_count(cx),
+            self.delegate.cols_count(cx),
+        );
+        if cells.is_empty() {
+            return;
+        }
+
+        self.delegate.paste_cells(row_ix, col_ix, cells, window, cx);
+        self.invalidate_aggregates(cx);
+    }
+
+    pub(super) fn action_copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if self.editing_cell.is_some() {
+            return;
+        }
+
+        self.copy_selection(ExportFormat::Tsv, cx);
+    }
+
+    pub(super) fn action_paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing_cell.is_some() {
+            return;
+        }
+
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        self.paste(&text, window, cx);
+    }
+}
+
+/// Returns the `(row_ix, col_ix)` of the cells in the selection, see
+/// [`Table::selected_cells_ix`].
+fn cells_ix(
+    selected_col: Option<usize>,
+    selected_cells: Option<CellRange>,
+    selected_rows: &RowSelection,
+    rows_count: usize,
+    cols_count: usize,
+    col_visible: impl Fn(usize) -> bool,
+) -> (Vec<usize>, Vec<usize>) {
+    let (rows, cols) = if let Some(col_ix) = selected_col {
+        ((0..rows_count).collect(), vec![col_ix])
+    } else if let Some(range) = selected_cells {
+        (range.rows.collect(), range.cols.collect())
+    } else {
+        (selected_rows.iter().collect(), (0..cols_count).collect())
+    };
+
+    let cols = cols
+        .into_iter()
+        .filter(|&col_ix| col_visible(col_ix))
+        .collect();
+    (rows, cols)
+}
+
+/// Clip the pasted `rows` of cells to the table bounds, start from the cell at `row_ix` and
+/// `col_ix`, the empty rows are removed.
+fn clip_cells(
+    mut rows: Vec<Vec<String>>,
+    row_ix: usize,
+    col_ix: usize,
+    rows_count: usize,
+    cols_count: usize,
+) -> Vec<Vec<String>> {
+    rows.truncate(rows_count.saturating_sub(row_ix));
+    for row in rows.iter_mut() {
+        row.truncate(cols_count.saturating_sub(col_ix));
+    }
+    rows.retain(|row| !row.is_empty());
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cells_ix, clip_cells, CellRange, RowSelection};
+
+    #[test]
+    fn test_cells_ix() {
+        let visible = |_: usize| true;
+        let selected_rows = [1, 3, 2].into_iter().collect::<RowSelection>();
+        assert_eq!(
+            cells_ix(None, None, &selected_rows, 5, 3, visible),
+            (vec![1, 2, 3], vec![0, 1, 2])
+        );
+        assert_eq!(
+            cells_ix(None, None, &RowSelection::default(), 5, 3, visible),
+            (vec![], vec![0, 1, 2])
+        );
+
+        // The cells range is from the anchor to the active cell.
+        let cells = CellRange::new((3, 2), (1, 1));
+        assert_eq!(
+            cells_ix(None, Some(cells.clone()), &selected_rows, 5, 3, visible),
+            (vec![1, 2, 3], vec![1, 2])
+        );
+
+        // The selected column is prior to the others.
+        assert_eq!(
+            cells_ix(Some(1), Some(cells.clone()), &selected_rows, 3, 3, visible),
+            (vec![0, 1, 2], vec![1])
+        );
+
+        // Skip the hidden column 1.
+        let visible = |col_ix: usize| col_ix != 1;
+        assert_eq!(
+            cells_ix(None, None, &selected_rows, 5, 3, visible),
+            (vec![1, 2, 3], vec![0, 2])
+        );
+        assert_eq!(
+            cells_ix(None, Some(cells), &selected_rows, 5, 3, visible),
+            (vec![1, 2, 3], vec![2])
+        );
+        assert_eq!(
+            cells_ix(Some(1), None, &selected_rows, 3, 3, visible),
+            (vec![0, 1, 2], vec![])
+        );
+    }
+
+    #[test]
+    fn test_clip_cells() {
+        let cells = || {
+            vec![
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                vec!["d".to_string(), "e".to_string(), "f".to_string()],
+            ]
+        };
+
+        assert_eq!(clip_cells(cells(), 0, 0, 5, 5), cells());
+        assert_eq!(
+            clip_cells(cells(), 4, 1, 5, 3),
+            vec![vec!["a".to_string(), "b".to_string()]]
+        );
+        assert_eq!(clip_cells(cells(), 5, 0, 5, 3), Vec::<Vec<String>>::new());
+        assert_eq!(clip_cells(cells(), 0, 3, 5, 3), Vec::<Vec<String>>::new());
+    }
+}
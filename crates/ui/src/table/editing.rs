@@ -0,0 +1,287 @@
+use gpui::{
+    prelude::FluentBuilder as _, AnyElement, App, Context, InteractiveElement as _, IntoElement,
+    MouseButton, ParentElement as _, SharedString, StatefulInteractiveElement as _, Styled as _,
+    Window,
+};
+
+use super::{SelectionMode, Table, TableDelegate};
+use crate::{
+    h_flex,
+    input::{Enter, Escape, IndentInline, OutdentInline},
+    tooltip::Tooltip,
+    ActiveTheme as _, Icon, IconName, Sizable as _,
+};
+
+/// The cell that is being edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct EditingCell {
+    pub(super) row_ix: usize,
+    pub(super) col_ix: usize,
+    /// The validation error of the last commit.
+    pub(super) error: Option<SharedString>,
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Returns the `(row_ix, col_ix)` of the cell that is being edited.
+    pub fn editing_cell(&self) -> Option<(usize, usize)> {
+        self.editing_cell
+            .as_ref()
+            .map(|editing| (editing.row_ix, editing.col_ix))
+    }
+
+    /// Start editing the cell at the given row and column.
+    ///
+    /// The cell that is being edited will be committed first, and nothing happens if it
+    /// fails to commit or the cell can't be edited.
+    pub fn edit_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.editing_cell() == Some((row_ix, col_ix))
+            || !self.delegate.can_edit_cell(row_ix, col_ix, cx)
+            || !self.commit_cell_edit(window, cx)
+        {
+            return;
+        }
+
+        if self.selection_mode == SelectionMode::Cell {
+            self.select_cell(row_ix, col_ix, false, cx);
+        } else if !self.selected_rows.contains(row_ix) {
+            self.select_row(row_ix, false, false, cx);
+        }
+
+        self.editing_cell = Some(EditingCell {
+            row_ix,
+            col_ix,
+            error: None,
+        });
+        self.delegate.start_cell_edit(row_ix, col_ix, window, cx);
+        cx.notify();
+    }
+
+    /// Commit the editing cell, returns false if the delegate returns a validation error.
+    ///
+    /// Returns true if there is no editing cell.
+    pub fn commit_cell_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some((row_ix, col_ix)) = self.editing_cell() else {
+            return true;
+        };
+
+        match self.delegate.commit_cell_edit(row_ix, col_ix, window, cx) {
+            Ok(()) => {
+                self.editing_cell = None;
+                self.invalidate_aggregates(cx);
+                self.focus_handle.focus(window);
+                cx.notify();
+                true
+            }
+            Err(error) => {
+                if let Some(editing) = self.editing_cell.as_mut() {
+                    editing.error = Some(error);
+                }
+                cx.notify();
+                false
+            }
+        }
+    }
+
+    /// Cancel the editing cell, the changes will be discarded.
+    pub fn cancel_cell_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((row_ix, col_ix)) = self.editing_cell() else {
+            return;
+        };
+
+        self.delegate.cancel_cell_edit(row_ix, col_ix, window, cx);
+        self.editing_cell = None;
+        self.focus_handle.focus(window);
+        cx.notify();
+    }
+
+    /// Returns true if the cell can be edited, the cells of the hidden columns can't.
+    fn cell_editable(&self, row_ix: usize, col_ix: usize, cx: &App) -> bool {
+        self.col_visible(col_ix) && self.delegate.can_edit_cell(row_ix, col_ix, cx)
+    }
+
+    /// Start editing the active cell, or the first editable cell of the selected row, returns
+    /// false if there is no editable cell.
+    pub(super) fn edit_selected_cell(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let cell = match self.selection_mode {
+            SelectionMode::Cell => self
+                .selected_cell
+                .filter(|&(row_ix, col_ix)| self.cell_editable(row_ix, col_ix, cx)),
+            SelectionMode::Row | SelectionMode::MultiRow => self.selected_row.and_then(|row_ix| {
+                (0..self.delegate.cols_count(cx))
+                    .find(|&col_ix| self.cell_editable(row_ix, col_ix, cx))
+                    .map(|col_ix| (row_ix, col_ix))
+            }),
+        };
+
+        let Some((row_ix, col_ix)) = cell else {
+            return false;
+        };
+
+        self.edit_cell(row_ix, col_ix, window, cx);
+        self.editing_cell.is_some()
+    }
+
+    /// Commit the editing cell and move to edit the next (or previous if `backward`) editable
+    /// cell, returns false if there is no editing cell.
+    pub(super) fn edit_next_cell(
+        &mut self,
+        backward: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(from) = self.editing_cell() else {
+            return false;
+        };
+
+        let rows_count = self.delegate.rows_count(cx);
+        let cols_count = self.delegate.cols_count(cx);
+        let next = next_editable_cell(from, rows_count, cols_count, backward, |row_ix, col_ix| {
+            self.cell_editable(row_ix, col_ix, cx)
+        });
+
+        match next {
+            Some((row_ix, col_ix)) => self.edit_cell(row_ix, col_ix, window, cx),
+            None => {
+                self.commit_cell_edit(window, cx);
+            }
+        }
+        true
+    }
+
+    /// Render the editor of the editing cell, with the validation error.
+    pub(super) fn render_editing_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let error = self
+            .editing_cell
+            .as_ref()
+            .and_then(|editing| editing.error.clone());
+
+        h_flex()
+            .id("table-cell-editor")
+            .size_full()
+            .gap_1()
+            // Handle the keys of the input editors in the cell.
+            .capture_action(cx.listener(|this, _: &Enter, window, cx| {
+                this.commit_cell_edit(window, cx);
+                cx.stop_propagation();
+            }))
+            .capture_action(cx.listener(|this, _: &Escape, window, cx| {
+                this.cancel_cell_edit(window, cx);
+                cx.stop_propagation();
+            }))
+            .capture_action(cx.listener(|this, _: &IndentInline, window, cx| {
+                this.edit_next_cell(false, window, cx);
+                cx.stop_propagation();
+            }))
+            .capture_action(cx.listener(|this, _: &OutdentInline, window, cx| {
+                this.edit_next_cell(true, window, cx);
+                cx.stop_propagation();
+            }))
+            // Avoid to select the row or cell when clicking the editor.
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .child(
+                h_flex()
+                    .flex_1()
+                    .h_full()
+                    .overflow_hidden()
+                    .child(self.delegate.render_cell_editor(row_ix, col_ix, window, cx)),
+            )
+            .when_some(error, |this, error| {
+                this.child(
+                    h_flex()
+                        .id("error")
+                        .flex_shrink_0()
+                        .text_color(cx.theme().danger)
+                        .child(Icon::new(IconName::CircleX).small())
+                        .tooltip(move |window, cx| Tooltip::new(error.clone()).build(window, cx)),
+                )
+                .border_1()
+                .border_color(cx.theme().danger)
+            })
+            .into_any_element()
+    }
+}
+
+/// Returns the next (or previous if `backward`) editable cell from the cell `from`, in the
+/// order of the columns and then the rows.
+fn next_editable_cell(
+    from: (usize, usize),
+    rows_count: usize,
+    cols_count: usize,
+    backward: bool,
+    is_editable: impl Fn(usize, usize) -> bool,
+) -> Option<(usize, usize)> {
+    let count = rows_count * cols_count;
+    let ix = from.0 * cols_count + from.1;
+    if cols_count == 0 || ix >= count {
+        return None;
+    }
+
+    let cells: Box<dyn Iterator<Item = usize>> = if backward {
+        Box::new((0..ix).rev())
+    } else {
+        Box::new(ix + 1..count)
+    };
+
+    cells
+        .map(|ix| (ix / cols_count, ix % cols_count))
+        .find(|&(row_ix, col_ix)| is_editable(row_ix, col_ix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_editable_cell;
+
+    #[test]
+    fn test_next_editable_cell() {
+        let is_editable = |_: usize, col_ix: usize| col_ix == 1 || col_ix == 3;
+
+        assert_eq!(
+            next_editable_cell((0, 1), 3, 4, false, is_editable),
+            Some((0, 3))
+        );
+        assert_eq!(
+            next_editable_cell((0, 3), 3, 4, false, is_editable),
+            Some((1, 1))
+        );
+        assert_eq!(next_editable_cell((2, 3), 3, 4, false, is_editable), None);
+        assert_eq!(
+            next_editable_cell((1, 1), 3, 4, true, is_editable),
+            Some((0, 3))
+        );
+        assert_eq!(next_editable_cell((0, 1), 3, 4, true, is_editable), None);
+        assert_eq!(next_editable_cell((0, 0), 0, 4, false, is_editable), None);
+        assert_eq!(next_editable_cell((0, 0), 3, 0, false, is_editable), None);
+
+        // Skip the hidden column 3.
+        let is_visible = |col_ix: usize| col_ix != 3;
+        let is_editable =
+            |row_ix: usize, col_ix: usize| is_visible(col_ix) && is_editable(row_ix, col_ix);
+        assert_eq!(
+            next_editable_cell((0, 1), 3, 4, false, is_editable),
+            Some((1, 1))
+        );
+        assert_eq!(
+            next_editable_cell((1, 1), 3, 4, true, is_editable),
+            Some((0, 1))
+        );
+    }
+}
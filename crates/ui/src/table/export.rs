@@ -0,0 +1,265 @@
+use gpui::{App, SharedString};
+
+use super::{Table, TableDelegate};
+
+/// The text format to copy or export the table cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Tab-separated values, the cells contains tab, newline or quote will be quoted.
+    #[default]
+    Tsv,
+    /// Comma-separated values, see [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180).
+    Csv,
+    /// Markdown table, the first row is the header.
+    Markdown,
+    /// HTML `<table>`, the first row is the header.
+    Html,
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Export all visible columns and rows of the table (with the header) to the text in
+    /// the `format`, the hidden columns are skipped.
+    pub fn export(&self, format: ExportFormat, cx: &App) -> String {
+        let cols = (0..self.delegate.cols_count(cx)).filter(|&col_ix| self.col_visible(col_ix));
+        self.export_cols(format, cols, cx)
+    }
+
+    /// Export the given columns of all rows (with the header) to the text in the `format`.
+    ///
+    /// For example, export the visible columns by [`Table::visible_range`].
+    pub fn export_cols(
+        &self,
+        format: ExportFormat,
+        cols: impl IntoIterator<Item = usize>,
+        cx: &App,
+    ) -> String {
+        let cols = cols.into_iter().collect::<Vec<_>>();
+        let header = cols
+            .iter()
+            .map(|&col_ix| self.delegate.col_name(col_ix, cx))
+            .collect::<Vec<_>>();
+        let rows = (0..self.delegate.rows_count(cx))
+            .map(|row_ix| {
+                cols.iter()
+                    .map(|&col_ix| self.delegate.cell_text(row_ix, col_ix, cx))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        format_cells(Some(&header), &rows, format)
+    }
+}
+
+/// Format the cells `rows` to the text in the `format`.
+///
+/// The Markdown table requires a header, an empty header will be used if `header` is `None`.
+pub(crate) fn format_cells(
+    header: Option<&[SharedString]>,
+    rows: &[Vec<SharedString>],
+    format: ExportFormat,
+) -> String {
+    match format {
+        ExportFormat::Tsv => format_separated(header, rows, '\t'),
+        ExportFormat::Csv => format_separated(header, rows, ','),
+        ExportFormat::Markdown => format_markdown(header, rows),
+        ExportFormat::Html => format_html(header, rows),
+    }
+}
+
+fn format_separated(
+    header: Option<&[SharedString]>,
+    rows: &[Vec<SharedString>],
+    separator: char,
+) -> String {
+    let escape = |cell: &str| -> String {
+        if cell.contains([separator, '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    };
+
+    header
+        .into_iter()
+        .chain(rows.iter().map(|row| row.as_slice()))
+        .map(|row| {
+            row.iter()
+                .map(|cell| escape(cell))
+                .collect::<Vec<_>>()
+                .join(&separator.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_markdown(header: Option<&[SharedString]>, rows: &[Vec<SharedString>]) -> String {
+    let cols_count = header
+        .map(|header| header.len())
+        .or(rows.first().map(|row| row.len()))
+        .unwrap_or(0);
+    let line = |cells: &mut dyn Iterator<Item = String>| {
+        let cells = cells.collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    };
+    let escape = |cell: &SharedString| {
+        cell.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    lines.push(match header {
+        Some(header) => line(&mut header.iter().map(escape)),
+        None => line(&mut (0..cols_count).map(|_| String::new())),
+    });
+    lines.push(line(&mut (0..cols_count).map(|_| "---".to_string())));
+    lines.extend(rows.iter().map(|row| line(&mut row.iter().map(escape))));
+    lines.join("\n")
+}
+
+fn format_html(header: Option<&[SharedString]>, rows: &[Vec<SharedString>]) -> String {
+    let escape = |cell: &str| {
+        cell.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let line = |row: &[SharedString], tag: &str| {
+        let cells = row
+            .iter()
+            .map(|cell| format!("<{tag}>{}</{tag}>", escape(cell)))
+            .collect::<String>();
+        format!("<tr>{}</tr>", cells)
+    };
+
+    let mut html = String::from("<table>");
+    if let Some(header) = header {
+        html.push_str(&format!("<thead>{}</thead>", line(header, "th")));
+    }
+    html.push_str("<tbody>");
+    for row in rows {
+        html.push_str(&line(row, "td"));
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+/// Parse the tab-separated `text` (e.g.: copied from the spreadsheet) to the rows of cells.
+///
+/// The quoted cells can contain tabs, newlines and the escaped quotes (`""`).
+pub(crate) fn parse_tsv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if cell.is_empty() => in_quotes = true,
+            '\t' if !in_quotes => row.push(std::mem::take(&mut cell)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+
+    // Ignore the trailing newline.
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::SharedString;
+
+    use super::{format_cells, parse_tsv, ExportFormat};
+
+    fn rows(rows: &[&[&str]]) -> Vec<Vec<SharedString>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| SharedString::from(*cell)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_format_cells() {
+        let header = rows(&[&["Name", "Note"]]).remove(0);
+        let cells = rows(&[&["foo", "a\tb"], &["b|ar", "say \"hi\"\nbye"]]);
+
+        assert_eq!(
+            format_cells(None, &cells, ExportFormat::Tsv),
+            "foo\t\"a\tb\"\nb|ar\t\"say \"\"hi\"\"\nbye\""
+        );
+        assert_eq!(
+            format_cells(Some(&header), &cells, ExportFormat::Csv),
+            "Name,Note\nfoo,a\tb\nb|ar,\"say \"\"hi\"\"\nbye\""
+        );
+        assert_eq!(
+            format_cells(Some(&header), &cells, ExportFormat::Markdown),
+            "| Name | Note |\n| --- | --- |\n| foo | a\tb |\n| b\\|ar | say \"hi\"<br>bye |"
+        );
+        assert_eq!(
+            format_cells(None, &cells[..1], ExportFormat::Markdown),
+            "|  |  |\n| --- | --- |\n| foo | a\tb |"
+        );
+        assert_eq!(
+            format_cells(Some(&header), &rows(&[&["<b>", "&"]]), ExportFormat::Html),
+            "<table><thead><tr><th>Name</th><th>Note</th></tr></thead>\
+            <tbody><tr><td>&lt;b&gt;</td><td>&amp;</td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_format_tsv_escape() {
+        let cells = rows(&[&["\"quoted\"", "a\r\nb", "a\"b"], &["", "plain", "a,b"]]);
+        assert_eq!(
+            format_cells(None, &cells, ExportFormat::Tsv),
+            "\"\"\"quoted\"\"\"\t\"a\r\nb\"\t\"a\"\"b\"\n\tplain\ta,b"
+        );
+        assert_eq!(
+            parse_tsv(&format_cells(None, &cells, ExportFormat::Tsv)),
+            vec![
+                vec!["\"quoted\"", "a\r\nb", "a\"b"],
+                vec!["", "plain", "a,b"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tsv() {
+        assert_eq!(
+            parse_tsv("a\tb\r\nc\t\n"),
+            vec![vec!["a", "b"], vec!["c", ""]]
+        );
+        assert_eq!(
+            parse_tsv("\"a\tb\"\t\"say \"\"hi\"\"\nbye\"\nc"),
+            vec![vec!["a\tb", "say \"hi\"\nbye"], vec!["c"]]
+        );
+        assert_eq!(parse_tsv("a\"b"), vec![vec!["a\"b"]]);
+        assert_eq!(parse_tsv(""), Vec::<Vec<String>>::new());
+
+        let cells = vec![
+            vec!["x\ty".into(), "\"".into()],
+            vec!["z".into(), "".into()],
+        ];
+        let text = format_cells(None, &cells, ExportFormat::Tsv);
+        assert_eq!(parse_tsv(&text), vec![vec!["x\ty", "\""], vec!["z", ""]]);
+    }
+}
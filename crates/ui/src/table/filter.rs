@@ -0,0 +1,447 @@
+use std::rc::Rc;
+
+use chrono::NaiveDate;
+use gpui::{
+    prelude::FluentBuilder as _, App, AppContext as _, Context, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, InteractiveElement as _, IntoElement, ParentElement as _, Render,
+    SharedString, Styled as _, Subscription, Window,
+};
+use rust_i18n::t;
+
+use super::{ClearFilters, ColGroup, Table, TableDelegate};
+use crate::{
+    button::{Button, ButtonVariants as _},
+    calendar::Date,
+    checkbox::Checkbox,
+    date_picker::{DatePicker, DatePickerState},
+    h_flex,
+    input::{InputEvent, InputState, TextInput},
+    popover::Popover,
+    v_flex, ActiveTheme as _, Icon, IconName, Sizable as _,
+};
+
+/// The kind of the filter of a column, to show the filter UI in the column header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColFilterKind {
+    /// Filter by the text contains.
+    Text,
+    /// Filter by a numeric range.
+    Number,
+    /// Filter by the selected options.
+    Enum(Vec<SharedString>),
+    /// Filter by a date range.
+    Date,
+}
+
+/// The filter of a column, it is passed to [`TableDelegate::perform_filter`].
+///
+/// [`TableDelegate::perform_filter`]: super::TableDelegate::perform_filter
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColFilter {
+    /// The text contains the value, case-insensitive.
+    Contains(SharedString),
+    /// The number is in the range, both ends are inclusive.
+    Range { min: Option<f64>, max: Option<f64> },
+    /// The value is one of the options.
+    OneOf(Vec<SharedString>),
+    /// The date is in the range, both ends are inclusive.
+    DateRange {
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    },
+}
+
+impl ColFilter {
+    /// Returns true if the cell `text` matches the filter.
+    ///
+    /// The [`ColFilter::Range`] parses the text as number, and the [`ColFilter::DateRange`]
+    /// parses the text as date in `%Y-%m-%d` format, the text is not matched if fails to parse.
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Contains(value) => text.to_lowercase().contains(&value.to_lowercase()),
+            Self::Range { min, max } => text.trim().parse::<f64>().map_or(false, |value| {
+                min.map_or(true, |min| value >= min) && max.map_or(true, |max| value <= max)
+            }),
+            Self::OneOf(options) => options.iter().any(|option| option.as_ref() == text),
+            Self::DateRange { start, end } => NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                .map_or(false, |date| {
+                    start.map_or(true, |start| date >= start) && end.map_or(true, |end| date <= end)
+                }),
+        }
+    }
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Returns the active filter of the column at the given index.
+    pub fn col_filter(&self, col_ix: usize) -> Option<&ColFilter> {
+        self.col_groups
+            .get(col_ix)
+            .and_then(|col_group| col_group.filter.as_ref())
+    }
+
+    /// Returns the active filters of the columns, in the order of the columns.
+    pub fn filters(&self) -> Vec<(usize, ColFilter)> {
+        self.col_groups
+            .iter()
+            .enumerate()
+            .filter_map(|(col_ix, col_group)| {
+                col_group.filter.clone().map(|filter| (col_ix, filter))
+            })
+            .collect()
+    }
+
+    /// Set the filter of the column at the given index, `None` to clear it.
+    ///
+    /// The [`TableDelegate::perform_filter`] will be called with all active filters, nothing
+    /// happens if the column has no [`TableDelegate::col_filter`].
+    pub fn set_col_filter(
+        &mut self,
+        col_ix: usize,
+        filter: Option<ColFilter>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(col_group) = self.col_groups.get(col_ix) else {
+            return;
+        };
+        if col_group.filter_kind.is_none()
+            || col_group.filter == filter
+            || !self.commit_cell_edit(window, cx)
+        {
+            return;
+        }
+
+        self.col_groups[col_ix].filter = filter;
+        self.perform_filter(window, cx);
+    }
+
+    /// Clear the filters of all columns.
+    pub fn clear_filters(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self
+            .col_groups
+            .iter()
+            .all(|col_group| col_group.filter.is_none())
+            || !self.commit_cell_edit(window, cx)
+        {
+            return;
+        }
+
+        for col_group in self.col_groups.iter_mut() {
+            col_group.filter = None;
+        }
+        self.perform_filter(window, cx);
+    }
+
+    fn perform_filter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let filters = self.filters();
+        // The rows are changed, the selection is meaningless.
+        self.clear_selection(cx);
+        self.row_heights.clear_measured();
+        self.delegate_mut().perform_filter(&filters, window, cx);
+        self.invalidate_aggregates(cx);
+        cx.notify();
+    }
+
+    pub(super) fn action_clear_filters(
+        &mut self,
+        _: &ClearFilters,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.clear_filters(window, cx);
+    }
+
+    /// Render the filter button of the column header, it is highlighted if the filter is active.
+    pub(super) fn render_filter_icon(
+        &self,
+        col_ix: usize,
+        col_group: &ColGroup,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let kind = col_group.filter_kind.clone()?;
+        let filter = col_group.filter.clone();
+        let is_active = filter.is_some();
+        let table = cx.entity().downgrade();
+
+        Some(
+            Popover::new(("col-filter", col_ix))
+                .trigger(
+                    Button::new(("icon-filter", col_ix))
+                        .ghost()
+                        .xsmall()
+                        .selected(is_active)
+                        .icon(
+                            Icon::new(IconName::Funnel)
+                                .size_3()
+                                .text_color(match is_active {
+                                    true => cx.theme().primary,
+                                    false => cx.theme().muted_foreground,
+                                }),
+                        ),
+                )
+                .content(move |window, cx| {
+                    let table = table.clone();
+                    cx.new(|cx| {
+                        ColFilterPanel::new(
+                            &kind,
+                            filter.as_ref(),
+                            move |filter, window, cx| {
+                                _ = table.update(cx, |table, cx| {
+                                    table.set_col_filter(col_ix, filter, window, cx);
+                                });
+                            },
+                            window,
+                            cx,
+                        )
+                    })
+                }),
+        )
+    }
+}
+
+enum FilterEditor {
+    Text(Entity<InputState>),
+    Number(Entity<InputState>, Entity<InputState>),
+    Enum {
+        options: Vec<SharedString>,
+        selected: Vec<SharedString>,
+    },
+    Date(Entity<DatePickerState>),
+}
+
+/// The popover content to edit the filter of a column.
+pub(super) struct ColFilterPanel {
+    focus_handle: FocusHandle,
+    editor: FilterEditor,
+    on_apply: Rc<dyn Fn(Option<ColFilter>, &mut Window, &mut App)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ColFilterPanel {
+    pub(super) fn new(
+        kind: &ColFilterKind,
+        filter: Option<&ColFilter>,
+        on_apply: impl Fn(Option<ColFilter>, &mut Window, &mut App) + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut inputs = vec![];
+        let mut new_input = |placeholder: SharedString, value: String, cx: &mut Context<Self>| {
+            let input = cx.new(|cx| {
+                let mut input = InputState::new(window, cx).placeholder(placeholder);
+                input.set_value(value, window, cx);
+                input
+            });
+            inputs.push(input.clone());
+            input
+        };
+
+        let editor = match kind {
+            ColFilterKind::Text => {
+                let value = match filter {
+                    Some(ColFilter::Contains(value)) => value.to_string(),
+                    _ => String::new(),
+                };
+                FilterEditor::Text(new_input(t!("Table.filter_placeholder").into(), value, cx))
+            }
+            ColFilterKind::Number => {
+                let (min, max) = match filter {
+                    Some(ColFilter::Range { min, max }) => (*min, *max),
+                    _ => (None, None),
+                };
+                let to_string =
+                    |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+                FilterEditor::Number(
+                    new_input(t!("Table.filter_min").into(), to_string(min), cx),
+                    new_input(t!("Table.filter_max").into(), to_string(max), cx),
+                )
+            }
+            ColFilterKind::Enum(options) => FilterEditor::Enum {
+                options: options.clone(),
+                selected: match filter {
+                    Some(ColFilter::OneOf(selected)) => selected.clone(),
+                    _ => vec![],
+                },
+            },
+            ColFilterKind::Date => {
+                let date = match filter {
+                    Some(ColFilter::DateRange { start, end }) => Date::Range(*start, *end),
+                    _ => Date::Range(None, None),
+                };
+                FilterEditor::Date(cx.new(|cx| {
+                    // Same as the format to match the cell text, see `ColFilter::is_match`.
+                    let mut picker = DatePickerState::range(window, cx).date_format("%Y-%m-%d");
+                    picker.set_date(date, window, cx);
+                    picker
+                }))
+            }
+        };
+
+        let _subscriptions = inputs
+            .iter()
+            .map(|input| {
+                cx.subscribe_in(input, window, |this, _, event: &InputEvent, window, cx| {
+                    if let InputEvent::PressEnter { .. } = event {
+                        this.apply(window, cx);
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            editor,
+            on_apply: Rc::new(on_apply),
+            _subscriptions,
+        }
+    }
+
+    /// Returns the filter of the editor, `None` if it is empty.
+    fn filter(&self, cx: &App) -> Option<ColFilter> {
+        match &self.editor {
+            FilterEditor::Text(input) => {
+                let value = input.read(cx).value().trim().to_string();
+                (!value.is_empty()).then(|| ColFilter::Contains(value.into()))
+            }
+            FilterEditor::Number(min, max) => {
+                let parse = |input: &Entity<InputState>| input.read(cx).value().trim().parse().ok();
+                match (parse(min), parse(max)) {
+                    (None, None) => None,
+                    (min, max) => Some(ColFilter::Range { min, max }),
+                }
+            }
+            FilterEditor::Enum { selected, .. } => {
+                (!selected.is_empty()).then(|| ColFilter::OneOf(selected.clone()))
+            }
+            FilterEditor::Date(picker) => match picker.read(cx).date() {
+                Date::Range(None, None) | Date::Single(None) => None,
+                Date::Range(start, end) => Some(ColFilter::DateRange { start, end }),
+                Date::Single(date) => Some(ColFilter::DateRange {
+                    start: date,
+                    end: date,
+                }),
+            },
+        }
+    }
+
+    fn apply(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let filter = self.filter(cx);
+        (self.on_apply)(filter, window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn clear(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        (self.on_apply)(None, window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn toggle_option(&mut self, option: SharedString, checked: bool, cx: &mut Context<Self>) {
+        if let FilterEditor::Enum { selected, .. } = &mut self.editor {
+            selected.retain(|item| item != &option);
+            if checked {
+                selected.push(option);
+            }
+            cx.notify();
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for ColFilterPanel {}
+
+impl Focusable for ColFilterPanel {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        // Focus the first input when the popover opens.
+        match &self.editor {
+            FilterEditor::Text(input) | FilterEditor::Number(input, _) => input.focus_handle(cx),
+            FilterEditor::Enum { .. } | FilterEditor::Date(_) => self.focus_handle.clone(),
+        }
+    }
+}
+
+impl Render for ColFilterPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .gap_2()
+            .min_w_48()
+            .map(|this| match &self.editor {
+                FilterEditor::Text(input) => this.child(TextInput::new(input).small()),
+                FilterEditor::Number(min, max) => this
+                    .child(TextInput::new(min).small())
+                    .child(TextInput::new(max).small()),
+                FilterEditor::Enum { options, selected } => {
+                    this.children(options.iter().enumerate().map(|(ix, option)| {
+                        let option = option.clone();
+                        Checkbox::new(("option", ix))
+                            .label(option.clone())
+                            .checked(selected.contains(&option))
+                            .on_click(cx.listener(move |this, checked: &bool, _, cx| {
+                                this.toggle_option(option.clone(), *checked, cx);
+                            }))
+                    }))
+                }
+                FilterEditor::Date(picker) => {
+                    this.child(DatePicker::new(picker).small().cleanable())
+                }
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_end()
+                    .child(
+                        Button::new("clear")
+                            .small()
+                            .outline()
+                            .label(t!("Table.filter_clear"))
+                            .on_click(cx.listener(|this, _, window, cx| this.clear(window, cx))),
+                    )
+                    .child(
+                        Button::new("apply")
+                            .small()
+                            .primary()
+                            .label(t!("Table.filter_apply"))
+                            .on_click(cx.listener(|this, _, window, cx| this.apply(window, cx))),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::ColFilter;
+
+    #[test]
+    fn test_col_filter_is_match() {
+        let filter = ColFilter::Contains("App".into());
+        assert!(filter.is_match("Apple"));
+        assert!(filter.is_match("pineapple"));
+        assert!(!filter.is_match("Banana"));
+
+        let filter = ColFilter::Range {
+            min: Some(1.5),
+            max: None,
+        };
+        assert!(filter.is_match("1.5"));
+        assert!(filter.is_match(" 100 "));
+        assert!(!filter.is_match("1"));
+        assert!(!filter.is_match("n/a"));
+
+        let filter = ColFilter::OneOf(vec!["Open".into(), "Closed".into()]);
+        assert!(filter.is_match("Open"));
+        assert!(!filter.is_match("open"));
+
+        let filter = ColFilter::DateRange {
+            start: NaiveDate::from_ymd_opt(2024, 1, 1),
+            end: NaiveDate::from_ymd_opt(2024, 1, 31),
+        };
+        assert!(filter.is_match("2024-01-01"));
+        assert!(filter.is_match("2024-01-31"));
+        assert!(!filter.is_match("2024-02-01"));
+        assert!(!filter.is_match("01/15/2024"));
+    }
+}
@@ -0,0 +1,247 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, App, Context, InteractiveElement as _, IntoElement,
+    ParentElement as _, SharedString, Styled as _, Window,
+};
+use rust_i18n::t;
+
+use super::{ColFixed, RowKind, Table, TableDelegate};
+use crate::{h_flex, ActiveTheme as _, StyledExt as _};
+
+/// The built-in aggregate function of the column in the footer row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    /// Count the non-empty cells.
+    Count,
+}
+
+impl Aggregate {
+    /// Returns the localized label of the aggregate function.
+    pub fn label(&self) -> SharedString {
+        match self {
+            Self::Sum => t!("Table.sum"),
+            Self::Avg => t!("Table.avg"),
+            Self::Min => t!("Table.min"),
+            Self::Max => t!("Table.max"),
+            Self::Count => t!("Table.count"),
+        }
+        .into()
+    }
+
+    /// Apply the aggregate function to the `values`, returns `None` if there is no value,
+    /// except for [`Aggregate::Count`].
+    pub fn apply(&self, values: impl IntoIterator<Item = f64>) -> Option<f64> {
+        let mut values = values.into_iter();
+        match self {
+            Self::Count => Some(values.count() as f64),
+            Self::Sum => values.reduce(|a, b| a + b),
+            Self::Min => values.reduce(f64::min),
+            Self::Max => values.reduce(f64::max),
+            Self::Avg => {
+                let (sum, count) = values.fold((0., 0), |(sum, count), v| (sum + v, count + 1));
+                (count > 0).then(|| sum / count as f64)
+            }
+        }
+    }
+
+    /// Apply the aggregate function to the cells text, the text that is not a number is
+    /// ignored, and the empty text is not counted.
+    pub fn apply_texts<T: AsRef<str>>(&self, texts: impl IntoIterator<Item = T>) -> Option<f64> {
+        let texts = texts.into_iter().filter_map(|text| {
+            let text = text.as_ref().trim();
+            (!text.is_empty()).then(|| text.to_string())
+        });
+        match self {
+            Self::Count => self.apply(texts.map(|_| 0.)),
+            _ => self.apply(texts.filter_map(|text| parse_number(&text))),
+        }
+    }
+
+    /// Format the aggregated value to show in the footer, `-` for `None`.
+    pub fn format(&self, value: Option<f64>) -> SharedString {
+        match (self, value) {
+            (_, None) => "-".into(),
+            (Self::Count, Some(value)) => format!("{}", value as usize).into(),
+            (_, Some(value)) => format!("{:.2}", value).into(),
+        }
+    }
+}
+
+/// Parse the number from the cell text, the thousands separators and the `%` suffix are
+/// ignored.
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim()
+        .trim_end_matches('%')
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+}
+
+/// The aggregated values of the columns in the footer row, by the column index.
+///
+/// They are calculated on the first render after the rows changed, the cache is cleared by
+/// [`Table::invalidate_aggregates`].
+#[derive(Default)]
+pub(super) struct Aggregates {
+    /// The rows count of the values, to recalculate them if the rows are loaded or removed.
+    rows_count: usize,
+    values: HashMap<usize, Option<f64>>,
+}
+
+impl Aggregates {
+    pub(super) fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Clear the cached aggregated values of the footer row, they will be calculated again
+    /// in the next render.
+    ///
+    /// Call this when the rows data changed.
+    pub fn invalidate_aggregates(&mut self, cx: &mut Context<Self>) {
+        self.aggregates.get_mut().clear();
+        cx.notify();
+    }
+
+    /// Returns the value of the [`TableDelegate::col_aggregate`] of the [`RowKind::Normal`]
+    /// rows by the [`TableDelegate::cell_text`], it is cached until the rows changed.
+    fn aggregate_value(&self, col_ix: usize, cx: &App) -> Option<f64> {
+        let aggregate = self.delegate.col_aggregate(col_ix, cx)?;
+        let rows_count = self.delegate.rows_count(cx);
+
+        let mut aggregates = self.aggregates.borrow_mut();
+        if aggregates.rows_count != rows_count {
+            aggregates.rows_count = rows_count;
+            aggregates.values.clear();
+        }
+        *aggregates.values.entry(col_ix).or_insert_with(|| {
+            let texts = (0..rows_count)
+                .filter(|&row_ix| self.delegate.row_kind(row_ix, cx) == RowKind::Normal)
+                .map(|row_ix| self.delegate.cell_text(row_ix, col_ix, cx));
+            aggregate.apply_texts(texts)
+        })
+    }
+
+    /// Render the sticky footer row, the fixed columns and the horizontal scroll are same as
+    /// the header.
+    pub(super) fn render_table_foot(
+        &self,
+        left_cols_count: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let offset_x = self.horizontal_scroll_handle.offset().x;
+
+        h_flex()
+            .w_full()
+            .h(self.size.table_row_height())
+            .flex_shrink_0()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().table_head)
+            .text_color(cx.theme().table_head_foreground)
+            .font_medium()
+            .when(left_cols_count > 0, |this| {
+                // Render left fixed columns
+                this.child(
+                    h_flex()
+                        .relative()
+                        .h_full()
+                        .children(
+                            self.col_groups
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, col)| col.fixed == Some(ColFixed::Left))
+                                .map(|(col_ix, _)| self.render_tf(col_ix, window, cx)),
+                        )
+                        .child(
+                            // Fixed columns border
+                            div()
+                                .absolute()
+                                .top_0()
+                                .right_0()
+                                .bottom_0()
+                                .w_0()
+                                .flex_shrink_0()
+                                .border_r_1()
+                                .border_color(cx.theme().border),
+                        ),
+                )
+            })
+            .child(
+                // Columns, follow the horizontal scroll of the header.
+                h_flex()
+                    .id("table-foot")
+                    .size_full()
+                    .overflow_hidden()
+                    .relative()
+                    .child(
+                        h_flex().relative().left(offset_x).children(
+                            self.col_groups
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, col)| col.fixed.is_none())
+                                .map(|(col_ix, _)| self.render_tf(col_ix, window, cx)),
+                        ),
+                    ),
+            )
+    }
+
+    fn render_tf(
+        &self,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        self.render_cell(col_ix, window, cx)
+            .flex()
+            .items_center()
+            .child(
+                self.delegate
+                    .render_tf(col_ix, self.aggregate_value(col_ix, cx), window, cx),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_number, Aggregate};
+
+    #[test]
+    fn test_aggregate() {
+        let values = [3., 1., 2.];
+        assert_eq!(Aggregate::Sum.apply(values), Some(6.));
+        assert_eq!(Aggregate::Avg.apply(values), Some(2.));
+        assert_eq!(Aggregate::Min.apply(values), Some(1.));
+        assert_eq!(Aggregate::Max.apply(values), Some(3.));
+        assert_eq!(Aggregate::Count.apply(values), Some(3.));
+
+        assert_eq!(Aggregate::Sum.apply([]), None);
+        assert_eq!(Aggregate::Avg.apply([]), None);
+        assert_eq!(Aggregate::Count.apply([]), Some(0.));
+
+        let texts = ["1,000.5", "", "n/a", "-0.5"];
+        assert_eq!(Aggregate::Sum.apply_texts(texts), Some(1000.));
+        assert_eq!(Aggregate::Count.apply_texts(texts), Some(3.));
+        assert_eq!(Aggregate::Sum.format(Some(1000.)).to_string(), "1000.00");
+        assert_eq!(Aggregate::Count.format(Some(3.)).to_string(), "3");
+        assert_eq!(Aggregate::Max.format(None).to_string(), "-");
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(" 12.5 "), Some(12.5));
+        assert_eq!(parse_number("1,234"), Some(1234.));
+        assert_eq!(parse_number("-3.5%"), Some(-3.5));
+        assert_eq!(parse_number("abc"), None);
+    }
+}
@@ -0,0 +1,238 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// The selection mode of the [`Table`](super::Table).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SelectionMode {
+    /// Select a single row.
+    #[default]
+    Row,
+    /// Select multiple rows.
+    ///
+    /// - `shift-click` or `shift-up/down` to extend a contiguous range.
+    /// - `cmd-click` (`ctrl-click` on Windows and Linux) to toggle a row.
+    MultiRow,
+    /// Select a rectangular range of cells, `shift-click` or `shift-arrows` to extend it.
+    Cell,
+}
+
+/// The selected rows of the table, stored as the sorted and non-overlapping ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RowSelection {
+    ranges: Vec<Range<usize>>,
+}
+
+impl RowSelection {
+    /// Returns the sorted and non-overlapping ranges of the selected rows.
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Returns true if no row is selected.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the number of the selected rows.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    /// Returns true if the row is selected.
+    pub fn contains(&self, row_ix: usize) -> bool {
+        let ix = self.ranges.partition_point(|range| range.end <= row_ix);
+        self.ranges
+            .get(ix)
+            .map_or(false, |range| range.contains(&row_ix))
+    }
+
+    /// Returns the iterator of the selected row indices in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map(|range| range.clone())
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Add the rows in the `range` to the selection.
+    pub(crate) fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // The first range that is overlapped or adjacent to the `range`.
+        let start_ix = self.ranges.partition_point(|r| r.end < range.start);
+        let end_ix = self.ranges.partition_point(|r| r.start <= range.end);
+        let mut merged = range;
+        if start_ix < end_ix {
+            merged.start = merged.start.min(self.ranges[start_ix].start);
+            merged.end = merged.end.max(self.ranges[end_ix - 1].end);
+        }
+        self.ranges.splice(start_ix..end_ix, [merged]);
+    }
+
+    /// Remove the row from the selection.
+    pub(crate) fn remove(&mut self, row_ix: usize) {
+        let ix = self.ranges.partition_point(|range| range.end <= row_ix);
+        let Some(range) = self.ranges.get(ix).cloned() else {
+            return;
+        };
+        if !range.contains(&row_ix) {
+            return;
+        }
+
+        let parts = [range.start..row_ix, row_ix + 1..range.end]
+            .into_iter()
+            .filter(|range| !range.is_empty());
+        self.ranges.splice(ix..ix + 1, parts);
+    }
+
+    /// Toggle the row, returns true if it is selected after toggling.
+    pub(crate) fn toggle(&mut self, row_ix: usize) -> bool {
+        if self.contains(row_ix) {
+            self.remove(row_ix);
+            false
+        } else {
+            self.insert(row_ix..row_ix + 1);
+            true
+        }
+    }
+}
+
+impl FromIterator<usize> for RowSelection {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut selection = Self::default();
+        for row_ix in iter {
+            selection.insert(row_ix..row_ix + 1);
+        }
+        selection
+    }
+}
+
+/// A rectangular range of the cells in the table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellRange {
+    pub rows: Range<usize>,
+    pub cols: Range<usize>,
+}
+
+impl CellRange {
+    /// Create the range between the two cells (inclusive), the cell is `(row_ix, col_ix)`.
+    pub fn new(from: (usize, usize), to: (usize, usize)) -> Self {
+        Self {
+            rows: from.0.min(to.0)..from.0.max(to.0) + 1,
+            cols: from.1.min(to.1)..from.1.max(to.1) + 1,
+        }
+    }
+
+    /// Returns true if the cell is in the range.
+    pub fn contains(&self, row_ix: usize, col_ix: usize) -> bool {
+        self.rows.contains(&row_ix) && self.cols.contains(&col_ix)
+    }
+}
+
+/// Returns the visible column by moving `offset` visible columns from `col_ix`, the hidden
+/// columns are skipped, and it stops at the first or the last visible column.
+///
+/// If `col_ix` is hidden, the `offset` of 0 returns the next visible column of it.
+pub(super) fn move_visible_col(
+    col_ix: usize,
+    offset: isize,
+    cols_count: usize,
+    col_visible: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    let cols = (0..cols_count)
+        .filter(|&ix| col_visible(ix))
+        .collect::<Vec<_>>();
+    let last = cols.len().checked_sub(1)? as isize;
+    let pos = match cols.binary_search(&col_ix) {
+        Ok(pos) => pos as isize + offset,
+        // The `pos` is the next visible column of the hidden `col_ix`.
+        Err(pos) if offset > 0 => pos as isize + offset - 1,
+        Err(pos) => pos as isize + offset,
+    };
+
+    Some(cols[pos.clamp(0, last) as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{move_visible_col, CellRange, RowSelection};
+
+    #[test]
+    fn test_row_selection() {
+        let mut selection = RowSelection::default();
+        assert!(selection.is_empty());
+
+        selection.insert(5..8);
+        selection.insert(1..2);
+        selection.insert(10..12);
+        assert_eq!(selection.ranges(), &[1..2, 5..8, 10..12]);
+
+        // Merge the adjacent and overlapped ranges.
+        selection.insert(8..10);
+        assert_eq!(selection.ranges(), &[1..2, 5..12]);
+        selection.insert(0..6);
+        assert_eq!(selection.ranges(), &[0..12]);
+        assert_eq!(selection.len(), 12);
+
+        selection.remove(4);
+        assert_eq!(selection.ranges(), &[0..4, 5..12]);
+        selection.remove(0);
+        selection.remove(11);
+        selection.remove(20);
+        assert_eq!(selection.ranges(), &[1..4, 5..11]);
+        assert!(selection.contains(1));
+        assert!(!selection.contains(4));
+        assert!(selection.contains(10));
+        assert!(!selection.contains(11));
+
+        assert!(selection.toggle(4));
+        assert_eq!(selection.ranges(), &[1..11]);
+        assert!(!selection.toggle(1));
+        assert_eq!(selection.ranges(), &[2..11]);
+
+        let selection = [3, 1, 2, 7].into_iter().collect::<RowSelection>();
+        assert_eq!(selection.ranges(), &[1..4, 7..8]);
+        assert_eq!(selection.iter().collect::<Vec<_>>(), vec![1, 2, 3, 7]);
+    }
+
+    #[test]
+    fn test_cell_range() {
+        let range = CellRange::new((5, 3), (2, 4));
+        assert_eq!(range.rows, 2..6);
+        assert_eq!(range.cols, 3..5);
+        assert!(range.contains(2, 3));
+        assert!(range.contains(5, 4));
+        assert!(!range.contains(6, 4));
+        assert!(!range.contains(3, 2));
+    }
+
+    #[test]
+    fn test_move_visible_col() {
+        // The column 2 and 3 are hidden.
+        let visible = |col_ix: usize| col_ix != 2 && col_ix != 3;
+        assert_eq!(move_visible_col(1, 1, 6, visible), Some(4));
+        assert_eq!(move_visible_col(4, -1, 6, visible), Some(1));
+        assert_eq!(move_visible_col(0, 2, 6, visible), Some(4));
+        assert_eq!(move_visible_col(5, 1, 6, visible), Some(5));
+        assert_eq!(move_visible_col(0, -1, 6, visible), Some(0));
+        assert_eq!(move_visible_col(4, 0, 6, visible), Some(4));
+
+        // Move from the hidden column.
+        assert_eq!(move_visible_col(2, 0, 6, visible), Some(4));
+        assert_eq!(move_visible_col(2, 1, 6, visible), Some(4));
+        assert_eq!(move_visible_col(3, -1, 6, visible), Some(1));
+
+        // The first and the last columns are hidden.
+        let visible = |col_ix: usize| col_ix != 0 && col_ix != 5;
+        assert_eq!(move_visible_col(1, -1, 6, visible), Some(1));
+        assert_eq!(move_visible_col(0, 0, 6, visible), Some(1));
+        assert_eq!(move_visible_col(5, 0, 6, visible), Some(4));
+        assert_eq!(move_visible_col(4, 1, 6, visible), Some(4));
+
+        assert_eq!(move_visible_col(0, 1, 6, |_| false), None);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range, rc::Rc};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, px, size, AnyElement, App, AvailableSpace, Context,
+    IntoElement, ParentElement as _, Pixels, Point, ScrollStrategy, Size, Styled as _, Window,
+};
+
+use super::{tree::shift_row_ix, RowKind, Table, TableDelegate, TableEvent};
+use crate::{h_flex, scroll, StyleSized as _};
+
+pub(super) const MIN_COL_WIDTH: Pixels = px(10.);
+pub(super) const MAX_COL_WIDTH: Pixels = px(1200.);
+
+/// The row height mode of the table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowHeight {
+    /// All rows have the same height by the table size, this is the fastest.
+    #[default]
+    Fixed,
+    /// The row heights are provided by [`TableDelegate::row_height`].
+    Variable,
+    /// The row heights are measured by the cells content when the rows are visible, for
+    /// wrapped text or multi-line cells.
+    ///
+    /// The [`TableDelegate::row_height`] is used as the minimum height, and as the estimated
+    /// height before the row is measured.
+    Measured,
+}
+
+/// The row height state of the table.
+#[derive(Default)]
+pub(super) struct RowHeights {
+    pub(super) mode: RowHeight,
+    /// The measured heights of the rows in [`RowHeight::Measured`], by the row index.
+    measured: HashMap<usize, Pixels>,
+    /// The cached row sizes for the virtual list, see [`Table::row_sizes`].
+    sizes: RefCell<Option<Rc<Vec<Size<Pixels>>>>>,
+    /// The cached top offsets of the rows, see [`Table::row_top`].
+    tops: RefCell<Option<Vec<Pixels>>>,
+}
+
+impl RowHeights {
+    /// Clear the measured heights, the rows will be measured again when visible.
+    ///
+    /// Call this when the rows are changed, e.g.: sorted or filtered, because the heights are
+    /// measured by the row index.
+    pub(super) fn clear_measured(&mut self) {
+        self.measured.clear();
+        self.clear_sizes();
+    }
+
+    /// Clear the cached row sizes and offsets, they will be calculated again by the heights.
+    fn clear_sizes(&self) {
+        self.sizes.take();
+        self.tops.take();
+    }
+
+    /// Shift the measured heights after the row at `row_ix` by `delta` rows, the heights of
+    /// the removed rows are dropped, same as the selection when the row is expanded or collapsed.
+    pub(super) fn shift(&mut self, row_ix: usize, delta: isize) {
+        if delta == 0 {
+            return;
+        }
+
+        let removed = row_ix + 1..=row_ix + delta.min(0).unsigned_abs();
+        self.measured = std::mem::take(&mut self.measured)
+            .into_iter()
+            .filter(|(ix, _)| !removed.contains(ix))
+            .map(|(ix, height)| (shift_row_ix(ix, row_ix, delta), height))
+            .collect();
+        self.clear_sizes();
+    }
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Set the row height mode of the table, default to [`RowHeight::Fixed`].
+    pub fn row_height(mut self, mode: RowHeight) -> Self {
+        self.row_heights.mode = mode;
+        self
+    }
+
+    /// Set the row height mode of the table.
+    pub fn set_row_height(&mut self, mode: RowHeight, cx: &mut Context<Self>) {
+        self.row_heights.mode = mode;
+        self.row_heights.clear_measured();
+        cx.notify();
+    }
+
+    /// Clear the measured row heights in [`RowHeight::Measured`], they will be measured again
+    /// when the rows are visible.
+    ///
+    /// Call this when the rows data changed.
+    pub fn invalidate_row_heights(&mut self, cx: &mut Context<Self>) {
+        self.row_heights.clear_measured();
+        cx.notify();
+    }
+
+    /// Set to fit all columns to the width of the table, the columns will be resized in
+    /// proportion when the table is resized. Default: false
+    pub fn fit_to_width(mut self, fit: bool) -> Self {
+        self.fit_to_width = fit;
+        self
+    }
+
+    /// Set to fit all columns to the width of the table.
+    pub fn set_fit_to_width(&mut self, fit: bool, cx: &mut Context<Self>) {
+        self.fit_to_width = fit;
+        self.fitted_width = None;
+        cx.notify();
+    }
+
+    /// Returns the height of the row at the given index.
+    pub(super) fn row_height_of(&self, row_ix: usize, cx: &App) -> Pixels {
+        let default_height = self.size.table_row_height();
+        // The fake rows to fill the table space.
+        if row_ix >= self.delegate.rows_count(cx) {
+            return default_height;
+        }
+
+        match self.row_heights.mode {
+            RowHeight::Fixed => default_height,
+            RowHeight::Variable => self
+                .delegate
+                .row_height(row_ix, cx)
+                .unwrap_or(default_height),
+            RowHeight::Measured => match self.row_heights.measured.get(&row_ix) {
+                Some(height) => *height,
+                None => self
+                    .delegate
+                    .row_height(row_ix, cx)
+                    .unwrap_or(default_height),
+            },
+        }
+    }
+
+    /// Returns the size of the rows for the virtual list, the width is not used.
+    ///
+    /// The sizes are cached until the rows count or the heights are changed.
+    pub(super) fn row_sizes(&self, count: usize, cx: &App) -> Rc<Vec<Size<Pixels>>> {
+        if let Some(sizes) = self.row_heights.sizes.borrow().as_ref() {
+            if sizes.len() == count {
+                return sizes.clone();
+            }
+        }
+
+        let sizes = Rc::new(
+            (0..count)
+                .map(|row_ix| size(px(0.), self.row_height_of(row_ix, cx)))
+                .collect::<Vec<_>>(),
+        );
+        self.row_heights.sizes.replace(Some(sizes.clone()));
+        sizes
+    }
+
+    /// Returns the top offset of the row at the given index.
+    ///
+    /// The offsets of all rows are cached until the rows count or the heights are changed.
+    pub(super) fn row_top(&self, row_ix: usize, cx: &App) -> Pixels {
+        let rows_count = self.delegate.rows_count(cx);
+        let mut tops = self.row_heights.tops.borrow_mut();
+        if tops.as_ref().map(|tops| tops.len()) != Some(rows_count + 1) {
+            *tops = Some(row_tops(
+                (0..rows_count).map(|row_ix| self.row_height_of(row_ix, cx)),
+            ));
+        }
+
+        tops.as_ref()
+            .map_or(px(0.), |tops| tops[row_ix.min(rows_count)])
+    }
+
+    /// Measure the heights of the visible rows in [`RowHeight::Measured`], the measured rows
+    /// are skipped.
+    pub(super) fn measure_rows(
+        &mut self,
+        visible_range: Range<usize>,
+        rows_count: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.row_heights.mode != RowHeight::Measured {
+            return;
+        }
+
+        let mut changed = false;
+        for row_ix in visible_range.start..visible_range.end.min(rows_count) {
+            if self.row_heights.measured.contains_key(&row_ix) {
+                continue;
+            }
+
+            let mut height = self
+                .delegate
+                .row_height(row_ix, cx)
+                .unwrap_or(self.size.table_row_height());
+            if self.delegate.row_kind(row_ix, cx) == RowKind::Detail {
+                let width = self
+                    .col_groups
+                    .iter()
+                    .filter(|col_group| !col_group.hidden)
+                    .fold(px(0.), |sum, col_group| sum + col_group.width);
+                let detail = self
+                    .delegate
+                    .render_detail_row(row_ix, window, cx)
+                    .into_any_element();
+                let cell_size =
+                    self.measure_cell(None, detail, AvailableSpace::Definite(width), window, cx);
+                height = height.max(cell_size.height.ceil());
+            } else {
+                for col_ix in 0..self.col_groups.len() {
+                    // The hidden columns are not rendered.
+                    if !self.col_visible(col_ix) {
+                        continue;
+                    }
+
+                    let width = self.col_groups[col_ix].width;
+                    let td = self.render_td(row_ix, col_ix, window, cx);
+                    let cell_size = self.measure_cell(
+                        Some(col_ix),
+                        td,
+                        AvailableSpace::Definite(width),
+                        window,
+                        cx,
+                    );
+                    height = height.max(cell_size.height.ceil());
+                }
+            }
+
+            changed |= height != self.row_height_of(row_ix, cx);
+            self.row_heights.measured.insert(row_ix, height);
+        }
+
+        // The row sizes of the current frame are estimated, render again with the measured.
+        if changed {
+            self.row_heights.clear_sizes();
+            cx.notify();
+        }
+    }
+
+    /// Resize the column at the given index to fit the header and the cells content of the
+    /// visible rows.
+    pub fn auto_fit_col(&mut self, col_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(col_group) = self.col_groups.get(col_ix).cloned() else {
+            return;
+        };
+        if !self.delegate.can_resize_col(col_ix, cx) {
+            return;
+        }
+
+        let th = h_flex()
+            .gap_0p5()
+            .child(self.delegate.render_th(col_ix, window, cx))
+            .children(self.render_filter_icon(col_ix, &col_group, cx))
+            .children(self.render_sort_icon(col_ix, &col_group, window, cx))
+            .into_any_element();
+        let mut width = self
+            .measure_cell(Some(col_ix), th, AvailableSpace::MinContent, window, cx)
+            .width;
+
+        let rows = self.visible_range.rows();
+        for row_ix in rows.start..rows.end.min(self.delegate.rows_count(cx)) {
+            let td = self.render_td(row_ix, col_ix, window, cx);
+            let cell_size =
+                self.measure_cell(Some(col_ix), td, AvailableSpace::MinContent, window, cx);
+            width = width.max(cell_size.width);
+        }
+
+        self.col_groups[col_ix].width = width.ceil().clamp(MIN_COL_WIDTH, MAX_COL_WIDTH);
+        self.row_heights.clear_measured();
+        cx.emit(TableEvent::ColWidthsChanged(self.col_widths()));
+        cx.notify();
+    }
+
+    /// Resize all visible columns in proportion to fit the width of the table, the hidden
+    /// columns keep their widths.
+    pub fn fit_cols_to_width(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        let width = self.bounds.size.width - scroll::WIDTH;
+        let cols = (0..self.col_groups.len())
+            .filter(|&col_ix| self.col_visible(col_ix))
+            .collect::<Vec<_>>();
+        if width <= px(0.) || cols.is_empty() {
+            return;
+        }
+
+        let widths = cols
+            .iter()
+            .map(|&col_ix| self.col_groups[col_ix].width)
+            .collect::<Vec<_>>();
+        let widths = fit_widths(&widths, width, MIN_COL_WIDTH);
+        for (col_ix, width) in cols.into_iter().zip(widths) {
+            self.col_groups[col_ix].width = width;
+        }
+        self.fitted_width = Some(self.bounds.size.width);
+        self.row_heights.clear_measured();
+        cx.emit(TableEvent::ColWidthsChanged(self.col_widths()));
+        cx.notify();
+    }
+
+    /// Fit the columns to the width if the table is resized in `fit_to_width` mode.
+    pub(super) fn fit_cols_if_need(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.fit_to_width || self.fitted_width == Some(self.bounds.size.width) {
+            return;
+        }
+
+        self.fit_cols_to_width(window, cx);
+    }
+
+    /// Scroll to make the row at the given index visible.
+    pub(super) fn scroll_to_row_ix(&mut self, row_ix: usize, cx: &App) {
+        if self.row_heights.mode == RowHeight::Fixed {
+            self.vertical_scroll_handle
+                .scroll_to_item(row_ix, ScrollStrategy::Top);
+            return;
+        }
+
+        // The uniform list is not used for the variable heights, calculate the offset by
+        // the row heights.
+        let top = self.row_top(row_ix, cx);
+        let bottom = top + self.row_height_of(row_ix, cx);
+        let scroll_handle = self.vertical_scroll_handle.0.borrow().base_handle.clone();
+        let offset = scroll_handle.offset();
+        let viewport_height = scroll_handle.bounds().size.height;
+
+        let offset_y = if top < -offset.y {
+            -top
+        } else if bottom > -offset.y + viewport_height {
+            -(bottom - viewport_height)
+        } else {
+            return;
+        };
+        scroll_handle.set_offset(Point::new(offset.x, offset_y));
+    }
+
+    fn col_widths(&self) -> Vec<Pixels> {
+        self.col_groups
+            .iter()
+            .map(|col_group| col_group.width)
+            .collect()
+    }
+
+    /// Measure the size of the cell content with the padding of the column.
+    fn measure_cell(
+        &self,
+        col_ix: Option<usize>,
+        content: AnyElement,
+        available_width: AvailableSpace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Size<Pixels> {
+        let padding = col_ix
+            .and_then(|col_ix| self.col_groups.get(col_ix))
+            .and_then(|col| col.padding);
+        let mut cell = div()
+            .flex_shrink_0()
+            // Measure the single line width to fit the column.
+            .when(
+                matches!(available_width, AvailableSpace::MinContent),
+                |this| this.whitespace_nowrap(),
+            )
+            .table_cell_size(self.size)
+            .when_some(padding, |this, padding| {
+                this.pl(padding.left)
+                    .pr(padding.right)
+                    .pt(padding.top)
+                    .pb(padding.bottom)
+            })
+            .child(content)
+            .into_any_element();
+
+        cell.layout_as_root(
+            size(available_width, AvailableSpace::MinContent),
+            window,
+            cx,
+        )
+    }
+}
+
+/// Resize the `widths` in proportion to fit the `total` width, each width is at least `min`.
+fn fit_widths(widths: &[Pixels], total: Pixels, min: Pixels) -> Vec<Pixels> {
+    let sum = widths.iter().fold(px(0.), |sum, width| sum + *width);
+    if widths.is_empty() || sum <= px(0.) {
+        return widths.to_vec();
+    }
+
+    let mut widths = widths
+        .iter()
+        .map(|width| (*width * (total / sum)).floor().max(min))
+        .collect::<Vec<_>>();
+
+    // Give the remaining pixels of the rounding to the last column.
+    let sum = widths.iter().fold(px(0.), |sum, width| sum + *width);
+    if let Some(last) = widths.last_mut() {
+        *last = (*last + total - sum).max(min);
+    }
+    widths
+}
+
+/// Returns the top offsets of the rows by the `heights`, with the bottom of the last row at
+/// the end.
+fn row_tops(heights: impl Iterator<Item = Pixels>) -> Vec<Pixels> {
+    let mut tops = vec![px(0.)];
+    let mut top = px(0.);
+    for height in heights {
+        top = top + height;
+        tops.push(top);
+    }
+    tops
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::px;
+
+    use super::{fit_widths, row_tops, RowHeights};
+
+    #[test]
+    fn test_fit_widths() {
+        assert_eq!(
+            fit_widths(&[px(100.), px(100.), px(200.)], px(800.), px(10.)),
+            vec![px(200.), px(200.), px(400.)]
+        );
+        assert_eq!(
+            fit_widths(&[px(100.), px(100.), px(100.)], px(100.), px(10.)),
+            vec![px(33.), px(33.), px(34.)]
+        );
+        assert_eq!(
+            fit_widths(&[px(1.), px(99.)], px(50.), px(10.)),
+            vec![px(10.), px(40.)]
+        );
+        assert_eq!(fit_widths(&[], px(100.), px(10.)), vec![]);
+    }
+
+    #[test]
+    fn test_row_tops() {
+        assert_eq!(
+            row_tops([px(10.), px(20.), px(30.)].into_iter()),
+            vec![px(0.), px(10.), px(30.), px(60.)]
+        );
+        assert_eq!(row_tops(std::iter::empty()), vec![px(0.)]);
+    }
+
+    #[test]
+    fn test_row_heights_shift() {
+        let mut heights = RowHeights::default();
+        heights.measured = (0..10).map(|ix| (ix, px(ix as f32))).collect();
+
+        // Expand the row 2 with 3 children.
+        heights.shift(2, 3);
+        assert_eq!(heights.measured.len(), 10);
+        assert_eq!(heights.measured.get(&2), Some(&px(2.)));
+        assert_eq!(heights.measured.get(&3), None);
+        assert_eq!(heights.measured.get(&6), Some(&px(3.)));
+        assert_eq!(heights.measured.get(&12), Some(&px(9.)));
+
+        // Collapse it.
+        heights.shift(2, -3);
+        assert_eq!(
+            heights.measured,
+            (0..10).map(|ix| (ix, px(ix as f32))).collect()
+        );
+
+        // Collapse the row 5 with 2 children, the heights of them are dropped.
+        heights.shift(5, -2);
+        assert_eq!(heights.measured.len(), 8);
+        assert_eq!(heights.measured.get(&5), Some(&px(5.)));
+        assert_eq!(heights.measured.get(&6), Some(&px(8.)));
+        assert_eq!(heights.measured.get(&7), Some(&px(9.)));
+        assert_eq!(heights.measured.get(&8), None);
+    }
+}
@@ -0,0 +1,261 @@
+use gpui::{Action, App, Context, Pixels, SharedString, Window};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+use super::{ColFixed, ColSort, Table, TableDelegate, TableEvent, MAX_COL_WIDTH, MIN_COL_WIDTH};
+use crate::popup_menu::PopupMenu;
+
+#[derive(Action, Debug, Clone, Copy, PartialEq, Eq)]
+#[action(namespace = table, no_json)]
+pub(super) struct ToggleColVisible(usize);
+
+/// Used to serialize and deserialize the layout of the Table.
+///
+/// The columns are matched by [`TableDelegate::col_key`] when loading, so the state is still
+/// usable after the columns are added or removed.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TableState {
+    /// The columns in the display order.
+    pub cols: Vec<ColState>,
+}
+
+/// Used to serialize and deserialize the layout of a column.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColState {
+    pub key: SharedString,
+    pub width: Pixels,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ColSort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<ColFixed>,
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Returns true if the column at the given index is visible.
+    pub fn col_visible(&self, col_ix: usize) -> bool {
+        self.col_groups
+            .get(col_ix)
+            .map_or(false, |col_group| !col_group.hidden)
+    }
+
+    /// Show or hide the column at the given index, the last visible column can't be hidden.
+    pub fn set_col_visible(&mut self, col_ix: usize, visible: bool, cx: &mut Context<Self>) {
+        if col_ix >= self.col_groups.len() || self.col_visible(col_ix) == visible {
+            return;
+        }
+        if !visible && self.visible_cols_count() <= 1 {
+            return;
+        }
+
+        self.col_groups[col_ix].hidden = !visible;
+        // Fit the visible columns to the width again.
+        self.fitted_width = None;
+        if !visible && self.selected_col == Some(col_ix) {
+            self.selected_col = None;
+        }
+        cx.emit(TableEvent::ColVisibilityChanged(col_ix, visible));
+        cx.notify();
+    }
+
+    /// Dump the columns layout of the table to [`TableState`].
+    ///
+    /// See also [`Table::load`].
+    pub fn dump(&self, cx: &App) -> TableState {
+        TableState {
+            cols: self
+                .col_groups
+                .iter()
+                .enumerate()
+                .map(|(col_ix, col_group)| ColState {
+                    key: self.delegate.col_key(col_ix, cx),
+                    width: col_group.width,
+                    hidden: col_group.hidden,
+                    sort: col_group.sort,
+                    fixed: col_group.fixed,
+                })
+                .collect(),
+        }
+    }
+
+    /// Load the columns layout of the table from the [`TableState`].
+    ///
+    /// The columns are moved by [`TableDelegate::move_col`] to the order of the state, and
+    /// the changed sort is performed by [`TableDelegate::perform_sort`]. The columns not in
+    /// the state are kept after the others.
+    ///
+    /// The left fixed columns must be the leading columns, the `fixed` of a column after an
+    /// unfixed column is ignored. The fixed columns are reset by [`TableDelegate::col_fixed`]
+    /// after [`Table::refresh`], load the state again to restore them.
+    ///
+    /// See also [`Table::dump`].
+    pub fn load(&mut self, state: TableState, window: &mut Window, cx: &mut Context<Self>) {
+        let mut sorts = vec![];
+        let mut next_ix = 0;
+        for col_state in state.cols {
+            let Some(col_ix) = (next_ix..self.col_groups.len())
+                .find(|&col_ix| self.delegate.col_key(col_ix, cx) == col_state.key)
+            else {
+                continue;
+            };
+
+            if col_ix != next_ix {
+                if !self.delegate.can_move_col(col_ix, cx) {
+                    continue;
+                }
+                self.move_col(col_ix, next_ix, window, cx);
+            }
+
+            let col_group = &mut self.col_groups[next_ix];
+            col_group.width = col_state.width.clamp(MIN_COL_WIDTH, MAX_COL_WIDTH);
+            col_group.hidden = col_state.hidden;
+            col_group.fixed = col_state.fixed;
+            // Only the sortable columns have the sort state.
+            if col_group.sort.is_some()
+                && col_state.sort.is_some()
+                && col_group.sort != col_state.sort
+            {
+                col_group.sort = col_state.sort;
+                sorts.extend(col_state.sort.map(|sort| (next_ix, sort)));
+            }
+            next_ix += 1;
+        }
+
+        // Keep one visible column at least.
+        if self.visible_cols_count() == 0 {
+            if let Some(col_group) = self.col_groups.first_mut() {
+                col_group.hidden = false;
+            }
+        }
+
+        self.update_fixed_cols();
+
+        // Perform the active sort at last, after the columns are reset to default.
+        sorts.sort_by_key(|(_, sort)| *sort != ColSort::Default);
+        for (col_ix, sort) in sorts {
+            self.delegate.perform_sort(col_ix, sort, window, cx);
+        }
+
+        self.row_heights.clear_measured();
+        cx.emit(TableEvent::ColWidthsChanged(
+            self.col_groups.iter().map(|col| col.width).collect(),
+        ));
+        cx.notify();
+    }
+
+    /// Update the count of the left fixed columns, the `fixed` of the columns after the first
+    /// unfixed column is cleared, because the fixed columns are rendered as the leading ones.
+    pub(super) fn update_fixed_cols(&mut self) {
+        let left = fixed_left_count(self.col_groups.iter().map(|col_group| col_group.fixed));
+        for col_group in &mut self.col_groups[left..] {
+            col_group.fixed = None;
+        }
+        self.fixed_cols.left = left;
+    }
+
+    fn visible_cols_count(&self) -> usize {
+        self.col_groups
+            .iter()
+            .filter(|col_group| !col_group.hidden)
+            .count()
+    }
+
+    pub(super) fn action_toggle_col_visible(
+        &mut self,
+        action: &ToggleColVisible,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let col_ix = action.0;
+        let visible = self.col_visible(col_ix);
+        self.set_col_visible(col_ix, !visible, cx);
+    }
+
+    /// Build the context menu of the column header to hide the column or show the hidden.
+    pub(super) fn header_context_menu(
+        &self,
+        col_ix: usize,
+        menu: PopupMenu,
+        cx: &App,
+    ) -> PopupMenu {
+        let can_hide = self.visible_cols_count() > 1;
+        let menu = menu
+            .menu_with_disabled(
+                t!("Table.hide_column"),
+                Box::new(ToggleColVisible(col_ix)),
+                !can_hide,
+            )
+            .separator()
+            .label(t!("Table.columns"));
+
+        (0..self.col_groups.len()).fold(menu, |menu, ix| {
+            let visible = self.col_visible(ix);
+            menu.menu_with_check_and_disabled(
+                self.delegate.col_name(ix, cx),
+                visible,
+                Box::new(ToggleColVisible(ix)),
+                visible && !can_hide,
+            )
+        })
+    }
+}
+
+/// Returns the count of the leading left fixed columns by the `fixed` of the columns.
+fn fixed_left_count(fixed: impl Iterator<Item = Option<ColFixed>>) -> usize {
+    fixed
+        .take_while(|fixed| *fixed == Some(ColFixed::Left))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::px;
+
+    use super::{fixed_left_count, ColFixed, ColSort, ColState, TableState};
+
+    #[test]
+    fn test_fixed_left_count() {
+        let left = Some(ColFixed::Left);
+        assert_eq!(fixed_left_count([left, left, None].into_iter()), 2);
+        // The fixed column after an unfixed column is not counted.
+        assert_eq!(fixed_left_count([left, None, left].into_iter()), 1);
+        assert_eq!(fixed_left_count([None, left, left].into_iter()), 0);
+        assert_eq!(fixed_left_count(std::iter::empty()), 0);
+    }
+
+    #[test]
+    fn test_table_state_serde() {
+        let state: TableState =
+            serde_json::from_str(r#"{"cols":[{"key":"name","width":120.0}]}"#).unwrap();
+        assert_eq!(
+            state.cols,
+            vec![ColState {
+                key: "name".into(),
+                width: px(120.),
+                hidden: false,
+                sort: None,
+                fixed: None,
+            }]
+        );
+
+        let state = TableState {
+            cols: vec![ColState {
+                key: "price".into(),
+                width: px(80.),
+                hidden: true,
+                sort: Some(ColSort::Descending),
+                fixed: None,
+            }],
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(
+            json,
+            r#"{"cols":[{"key":"price","width":80.0,"hidden":true,"sort":"Descending"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<TableState>(&json).unwrap(), state);
+    }
+}
@@ -0,0 +1,222 @@
+use gpui::{
+    div, prelude::FluentBuilder as _, px, AnyElement, Context, InteractiveElement as _,
+    IntoElement, ParentElement as _, Pixels, StatefulInteractiveElement as _, Styled as _, Window,
+};
+
+use super::{SelectionState, Table, TableDelegate, TableEvent};
+use crate::{h_flex, ActiveTheme as _, Icon, IconName};
+
+/// The indent width of each depth of the tree rows.
+const INDENT_WIDTH: Pixels = px(16.);
+
+/// The kind of the row, see [`TableDelegate::row_kind`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowKind {
+    /// The normal row to render the cells.
+    #[default]
+    Normal,
+    /// The group header row, the cells are rendered by [`TableDelegate::render_td`] to show the
+    /// aggregated values of the group.
+    Group,
+    /// The full-width detail row under a row, rendered by [`TableDelegate::render_detail_row`].
+    ///
+    /// The height of the detail row is same as the other rows, use
+    /// [`RowHeight::Variable`](super::RowHeight::Variable) for a taller detail row.
+    Detail,
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Expand or collapse the row at the given index by [`TableDelegate::perform_expand_row`].
+    ///
+    /// The selection after the row will be moved with the inserted or removed child rows.
+    pub fn set_row_expanded(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.delegate.row_expandable(row_ix, cx)
+            || self.delegate.row_expanded(row_ix, cx) == expanded
+            || !self.commit_cell_edit(window, cx)
+        {
+            return;
+        }
+
+        let rows_count = self.delegate.rows_count(cx);
+        self.delegate
+            .perform_expand_row(row_ix, expanded, window, cx);
+        let delta = self.delegate.rows_count(cx) as isize - rows_count as isize;
+        self.shift_selection(row_ix, delta);
+        self.row_heights.shift(row_ix, delta);
+        self.invalidate_aggregates(cx);
+
+        cx.emit(TableEvent::ExpandRow(row_ix, expanded));
+        cx.notify();
+    }
+
+    /// Toggle to expand or collapse the row at the given index.
+    pub fn toggle_row_expanded(
+        &mut self,
+        row_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let expanded = self.delegate.row_expanded(row_ix, cx);
+        self.set_row_expanded(row_ix, !expanded, window, cx);
+    }
+
+    /// Returns the column index that the rows are grouped by.
+    pub fn group_by(&self) -> Option<usize> {
+        self.col_groups
+            .iter()
+            .position(|col_group| col_group.grouped)
+    }
+
+    /// Group the rows by the column at the given index by [`TableDelegate::perform_group_by`],
+    /// `None` to ungroup.
+    ///
+    /// Nothing happens if the column can't be grouped by [`TableDelegate::can_group_by_col`].
+    pub fn set_group_by(
+        &mut self,
+        col_ix: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.group_by() == col_ix
+            || col_ix.map_or(false, |col_ix| !self.delegate.can_group_by_col(col_ix, cx))
+            || !self.commit_cell_edit(window, cx)
+        {
+            return;
+        }
+
+        for (ix, col_group) in self.col_groups.iter_mut().enumerate() {
+            col_group.grouped = col_ix == Some(ix);
+        }
+
+        // All rows are changed, the selection is meaningless.
+        self.clear_selection(cx);
+        self.row_heights.clear_measured();
+        self.delegate.perform_group_by(col_ix, window, cx);
+        self.invalidate_aggregates(cx);
+        cx.notify();
+    }
+
+    /// Expand or collapse the selected row, returns false if it is not expandable.
+    pub(super) fn expand_selected_row(
+        &mut self,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.selection_state != SelectionState::Row {
+            return false;
+        }
+        let Some(row_ix) = self.selected_row else {
+            return false;
+        };
+        if !self.delegate.row_expandable(row_ix, cx) {
+            return false;
+        }
+
+        self.set_row_expanded(row_ix, expanded, window, cx);
+        true
+    }
+
+    /// Move the selected rows and cells after the `row_ix` by `delta` rows.
+    fn shift_selection(&mut self, row_ix: usize, delta: isize) {
+        if delta == 0 {
+            return;
+        }
+
+        let shift = |ix: usize| shift_row_ix(ix, row_ix, delta);
+        self.selected_row = self.selected_row.map(shift);
+        self.selected_rows = self.selected_rows.iter().map(shift).collect();
+        self.selected_cell = self.selected_cell.map(|(ix, col_ix)| (shift(ix), col_ix));
+        self.selection_anchor = self
+            .selection_anchor
+            .map(|(ix, col_ix)| (shift(ix), col_ix));
+        self.right_clicked_row = None;
+    }
+
+    /// Render the indent and the expand toggle of the tree row, in the first column.
+    ///
+    /// Returns `None` for the rows in the depth 0 without children.
+    pub(super) fn render_row_toggle(
+        &self,
+        row_ix: usize,
+        cx: &mut Context<Self>,
+    ) -> Option<AnyElement> {
+        let depth = self.delegate.row_depth(row_ix, cx);
+        let expandable = self.delegate.row_expandable(row_ix, cx);
+        if depth == 0 && !expandable {
+            return None;
+        }
+
+        let expanded = expandable && self.delegate.row_expanded(row_ix, cx);
+        Some(
+            h_flex()
+                .flex_shrink_0()
+                .pl(INDENT_WIDTH * depth as f32)
+                .child(
+                    div()
+                        .id(("row-toggle", row_ix))
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .size(INDENT_WIDTH)
+                        .when(expandable, |this| {
+                            this.rounded(cx.theme().radius / 2.)
+                                .cursor_pointer()
+                                .hover(|this| this.bg(cx.theme().secondary))
+                                .child(
+                                    Icon::new(match expanded {
+                                        true => IconName::ChevronDown,
+                                        false => IconName::ChevronRight,
+                                    })
+                                    .size_3()
+                                    .text_color(cx.theme().muted_foreground),
+                                )
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.toggle_row_expanded(row_ix, window, cx);
+                                }))
+                        }),
+                )
+                .into_any_element(),
+        )
+    }
+}
+
+/// Returns the new index of the row `ix` after the rows inserted (`delta > 0`) or
+/// removed (`delta < 0`) after the row `row_ix`, the removed rows are moved to `row_ix`.
+pub(super) fn shift_row_ix(ix: usize, row_ix: usize, delta: isize) -> usize {
+    if ix <= row_ix {
+        return ix;
+    }
+
+    if delta < 0 && ix <= row_ix + delta.unsigned_abs() {
+        return row_ix;
+    }
+
+    ix.saturating_add_signed(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shift_row_ix;
+
+    #[test]
+    fn test_shift_row_ix() {
+        assert_eq!(shift_row_ix(2, 5, 3), 2);
+        assert_eq!(shift_row_ix(5, 5, 3), 5);
+        assert_eq!(shift_row_ix(6, 5, 3), 9);
+
+        assert_eq!(shift_row_ix(5, 5, -3), 5);
+        assert_eq!(shift_row_ix(6, 5, -3), 5);
+        assert_eq!(shift_row_ix(8, 5, -3), 5);
+        assert_eq!(shift_row_ix(9, 5, -3), 6);
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::Range;
+use std::{ops::Range, rc::Rc};
 
 use gpui::{
     div, img, prelude::FluentBuilder as _, px, relative, rems, AnyElement, App, DefiniteLength,
@@ -7,8 +7,11 @@ use gpui::{
     SharedUri, Styled, StyledImage as _, StyledText, Window,
 };
 use markdown::mdast;
+use ropey::Rope;
+use rust_i18n::t;
 
 use crate::{
+    alert::Alert,
     h_flex,
     highlighter::{LanguageRegistry, SyntaxHighlighter},
     v_flex, ActiveTheme as _, Icon, IconName,
@@ -30,8 +33,14 @@ pub struct InlineTextStyle {
     pub strikethrough: bool,
     pub code: bool,
     pub link: Option<LinkMark>,
+    /// The footnote reference, e.g. `[^1]`.
+    pub footnote: bool,
 }
 
+/// The handler of toggling a task list item, with the line (0-based) of the item in the source
+/// and the new checked state.
+pub(crate) type TaskToggleHandler = dyn Fn(usize, bool, &mut Window, &mut App);
+
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Span {
     pub start: usize,
@@ -242,7 +251,8 @@ impl CodeBlock {
         let mut styles = vec![];
         if let Some(lang) = &lang {
             let mut highlighter = SyntaxHighlighter::new(&lang, cx);
-            highlighter.update(&(0..0), &code, "", cx);
+            highlighter.update_text(None, &Rope::from_str(&code));
+            highlighter.highlight(&(0..code.len()), cx);
             styles = highlighter.styles(&(0..code.len()), &theme);
         };
 
@@ -250,6 +260,58 @@ impl CodeBlock {
     }
 }
 
+/// The kind of the GitHub style alert, e.g. `> [!NOTE]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    /// Parse the name in the alert marker, case-insensitive.
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "NOTE" => Some(Self::Note),
+            "TIP" => Some(Self::Tip),
+            "IMPORTANT" => Some(Self::Important),
+            "WARNING" => Some(Self::Warning),
+            "CAUTION" => Some(Self::Caution),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Note => "NOTE",
+            Self::Tip => "TIP",
+            Self::Important => "IMPORTANT",
+            Self::Warning => "WARNING",
+            Self::Caution => "CAUTION",
+        }
+    }
+
+    fn alert(&self, id: impl Into<ElementId>) -> Alert {
+        match self {
+            Self::Note => Alert::info(id, "").title(t!("Markdown.Note")),
+            Self::Tip => Alert::success(id, "").title(t!("Markdown.Tip")),
+            Self::Important => Alert::new(id, "").title(t!("Markdown.Important")),
+            Self::Warning => Alert::warning(id, "").title(t!("Markdown.Warning")),
+            Self::Caution => Alert::error(id, "").title(t!("Markdown.Caution")),
+        }
+    }
+}
+
+/// The footnote definition, e.g. `[^1]: Footnote`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Footnote {
+    /// The number of the footnote, in the order of the first reference.
+    pub label: SharedString,
+    pub children: Vec<Node>,
+}
+
 /// Ref:
 /// https://ui.shadcn.com/docs/components/typography
 #[allow(unused)]
@@ -274,9 +336,21 @@ pub enum Node {
         spread: bool,
         /// Whether the list item is checked, if None, it's not a checkbox
         checked: Option<bool>,
+        /// The line (0-based) of the list item in the source, used to toggle the task.
+        line: Option<usize>,
     },
     CodeBlock(CodeBlock),
     Table(Table),
+    /// The math block, the TeX has been converted to the text.
+    Math(SharedString),
+    /// The GitHub style alert, e.g. `> [!NOTE]`.
+    Alert {
+        kind: AlertKind,
+        span: Option<Span>,
+        children: Vec<Node>,
+    },
+    /// The footnote definitions, rendered at the end of the document.
+    Footnotes(Vec<Footnote>),
     Break {
         html: bool,
     },
@@ -349,6 +423,10 @@ impl RenderOnce for Paragraph {
                             highlight.background_color = Some(cx.theme().accent);
                         }
 
+                        if style.footnote {
+                            highlight.color = Some(cx.theme().link);
+                        }
+
                         if let Some(link_mark) = style.link {
                             highlight.color = Some(cx.theme().link);
                             highlight.underline = Some(gpui::UnderlineStyle {
@@ -408,11 +486,13 @@ pub(crate) struct ListState {
 }
 
 impl Node {
+    #[allow(clippy::too_many_arguments)]
     fn render_list_item(
         item: Node,
         ix: usize,
         state: ListState,
         text_view_style: &TextViewStyle,
+        on_task_toggle: Option<&Rc<TaskToggleHandler>>,
         window: &mut Window,
         cx: &mut App,
     ) -> impl IntoElement {
@@ -421,6 +501,7 @@ impl Node {
                 children,
                 spread,
                 checked,
+                line,
             } => v_flex()
                 .when(spread, |this| this.child(div()))
                 .children({
@@ -440,6 +521,7 @@ impl Node {
                                     false,
                                     true,
                                     text_view_style,
+                                    on_task_toggle,
                                     window,
                                     cx,
                                 );
@@ -470,8 +552,10 @@ impl Node {
                                         })
                                         .when_some(checked, |this, checked| {
                                             // Todo list checkbox
+                                            let on_task_toggle = on_task_toggle.cloned().zip(line);
                                             this.child(
                                                 div()
+                                                    .id(("task-item", line.unwrap_or_default()))
                                                     .flex()
                                                     .mt(rems(0.4))
                                                     .mr_1p5()
@@ -488,7 +572,20 @@ impl Node {
                                                                 .size_2()
                                                                 .text_xs(),
                                                         )
-                                                    }),
+                                                    })
+                                                    .when_some(
+                                                        on_task_toggle,
+                                                        |this, (on_task_toggle, line)| {
+                                                            this.cursor_pointer().on_click(
+                                                                move |_, window, cx| {
+                                                                    cx.stop_propagation();
+                                                                    on_task_toggle(
+                                                                        line, !checked, window, cx,
+                                                                    );
+                                                                },
+                                                            )
+                                                        },
+                                                    ),
                                             )
                                         })
                                         .child(div().overflow_hidden().child(text)),
@@ -504,6 +601,7 @@ impl Node {
                                     true,
                                     true,
                                     text_view_style,
+                                    on_task_toggle,
                                     window,
                                     cx,
                                 )))
@@ -622,12 +720,77 @@ impl Node {
             .into_any_element()
     }
 
+    fn render_footnotes(
+        footnotes: Vec<Footnote>,
+        style: &TextViewStyle,
+        on_task_toggle: Option<&Rc<TaskToggleHandler>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> AnyElement {
+        v_flex()
+            .mt(style.paragraph_gap)
+            .pt_2()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .text_sm()
+            .text_color(cx.theme().muted_foreground)
+            .children(footnotes.into_iter().map(|footnote| {
+                h_flex()
+                    .items_start()
+                    .child(div().mr_1p5().child(format!("{}.", footnote.label)))
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .children(Self::render_children(
+                                footnote.children,
+                                style,
+                                on_task_toggle,
+                                window,
+                                cx,
+                            )),
+                    )
+            }))
+            .into_any_element()
+    }
+
+    /// Render the children of the block, e.g. Alert, Footnote.
+    fn render_children(
+        children: Vec<Node>,
+        style: &TextViewStyle,
+        on_task_toggle: Option<&Rc<TaskToggleHandler>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        let children_len = children.len();
+        children
+            .into_iter()
+            .enumerate()
+            .map(|(ix, c)| {
+                let is_last_child = ix == children_len - 1;
+                c.render(
+                    None,
+                    false,
+                    is_last_child,
+                    style,
+                    on_task_toggle,
+                    window,
+                    cx,
+                )
+                .into_any_element()
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         self,
         list_state: Option<ListState>,
         is_root: bool,
         is_last_child: bool,
         style: &TextViewStyle,
+        on_task_toggle: Option<&Rc<TaskToggleHandler>>,
         window: &mut Window,
         cx: &mut App,
     ) -> impl IntoElement {
@@ -644,7 +807,15 @@ impl Node {
                     let children_len = children.len();
                     children.into_iter().enumerate().map(move |(index, c)| {
                         let is_last_child = is_root && index == children_len - 1;
-                        c.render(None, false, is_last_child, style, window, cx)
+                        c.render(
+                            None,
+                            false,
+                            is_last_child,
+                            style,
+                            on_task_toggle,
+                            window,
+                            cx,
+                        )
                     })
                 })
                 .into_any_element(),
@@ -697,6 +868,7 @@ impl Node {
                                 depth: list_state.depth,
                             },
                             style,
+                            on_task_toggle,
                             window,
                             cx,
                         ));
@@ -712,6 +884,37 @@ impl Node {
                 Self::render_codeblock(code_block, mb, style, window, cx)
             }
             Node::Table { .. } => Self::render_table(&self, window, cx).into_any_element(),
+            Node::Math(text) => div()
+                .w_full()
+                .mb(mb)
+                .py_2()
+                .flex()
+                .justify_center()
+                .italic()
+                .text_size(rems(1.125))
+                .whitespace_normal()
+                .child(text)
+                .into_any_element(),
+            Node::Alert {
+                kind,
+                span,
+                children,
+            } => div()
+                .mb(mb)
+                .child(
+                    kind.alert(span.unwrap_or_default())
+                        .children(Self::render_children(
+                            children,
+                            style,
+                            on_task_toggle,
+                            window,
+                            cx,
+                        )),
+                )
+                .into_any_element(),
+            Node::Footnotes(footnotes) => {
+                Self::render_footnotes(footnotes, style, on_task_toggle, window, cx)
+            }
             Node::Divider => div()
                 .bg(cx.theme().border)
                 .h(px(2.))
@@ -752,6 +955,10 @@ impl Paragraph {
                         if let Some(link) = &style.link {
                             text = format!("[{}]({})", &text_node.text[range.clone()], link.url);
                         }
+                        if style.footnote {
+                            let label = &text_node.text[range.clone()];
+                            text = format!("[^{}]", label.trim_matches(['[', ']']));
+                        }
                     }
                     text
                 })
@@ -886,6 +1093,31 @@ impl Node {
                     "\n".to_string()
                 }
             }
+            Node::Math(text) => format!("$$\n{}\n$$", text),
+            Node::Alert { kind, children, .. } => {
+                let content = children
+                    .iter()
+                    .map(|child| child.to_markdown())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                std::iter::once(format!("> [!{}]", kind.name()))
+                    .chain(content.lines().map(|line| format!("> {}", line)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Node::Footnotes(footnotes) => footnotes
+                .iter()
+                .map(|footnote| {
+                    let content = footnote
+                        .children
+                        .iter()
+                        .map(|child| child.to_markdown())
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+                    format!("[^{}]: {}", footnote.label, content)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
             Node::Divider => "---".to_string(),
             Node::Unknown => "".to_string(),
         }
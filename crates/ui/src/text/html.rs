@@ -174,7 +174,9 @@ impl Element for HtmlElement {
 
             let mut el = div()
                 .map(|this| match root {
-                    Ok(node) => this.child(node.render(None, true, true, &self.style, window, cx)),
+                    Ok(node) => {
+                        this.child(node.render(None, true, true, &self.style, None, window, cx))
+                    }
                     Err(err) => this.child(
                         v_flex()
                             .gap_1()
@@ -688,6 +690,7 @@ fn parse_node(node: &Rc<Node>, paragraph: &mut Paragraph) -> Option<element::Nod
                     children,
                     spread: false,
                     checked: None,
+                    line: None,
                 })
             }
             local_name!("table") => {
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::{rc::Rc, time::Instant};
 
 use gpui::{
     div, prelude::FluentBuilder as _, AnyElement, App, Element, ElementId, IntoElement,
@@ -13,9 +13,11 @@ use crate::v_flex;
 
 use super::{
     element::{
-        self, CodeBlock, ImageNode, InlineTextStyle, LinkMark, Paragraph, Span, Table, TableRow,
+        self, AlertKind, CodeBlock, Footnote, ImageNode, InlineTextStyle, LinkMark, Paragraph,
+        Span, Table, TableRow, TaskToggleHandler,
     },
     html::parse_html,
+    math::tex_to_unicode,
     TextViewStyle,
 };
 
@@ -30,6 +32,7 @@ pub(super) struct MarkdownElement {
     id: ElementId,
     pub(super) text: SharedString,
     style: TextViewStyle,
+    on_task_toggle: Option<Rc<TaskToggleHandler>>,
 }
 
 impl MarkdownElement {
@@ -38,6 +41,7 @@ impl MarkdownElement {
             id: id.into(),
             text: raw.into(),
             style: TextViewStyle::default(),
+            on_task_toggle: None,
         }
     }
 
@@ -52,6 +56,15 @@ impl MarkdownElement {
         self.style = style.into();
         self
     }
+
+    /// Set the handler when a task list item is toggled.
+    pub(crate) fn on_task_toggle(
+        mut self,
+        handler: impl Fn(usize, bool, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_task_toggle = Some(Rc::new(handler));
+        self
+    }
 }
 
 #[derive(Default)]
@@ -124,7 +137,15 @@ impl Element for MarkdownElement {
 
             let mut el = div()
                 .map(|this| match root {
-                    Ok(node) => this.child(node.render(None, true, true, &self.style, window, cx)),
+                    Ok(node) => this.child(node.render(
+                        None,
+                        true,
+                        true,
+                        &self.style,
+                        self.on_task_toggle.as_ref(),
+                        window,
+                        cx,
+                    )),
                     Err(err) => this.child(
                         v_flex()
                             .gap_1()
@@ -172,9 +193,138 @@ fn parse_markdown(
     style: &TextViewStyle,
     cx: &mut App,
 ) -> Result<element::Node, SharedString> {
-    markdown::to_mdast(&raw, &ParseOptions::gfm())
-        .map(|n| ast_to_node(n, style, cx))
-        .map_err(|e| e.to_string().into())
+    let mut options = ParseOptions::gfm();
+    options.constructs.math_flow = true;
+    options.constructs.math_text = true;
+
+    let mut root = markdown::to_mdast(&raw, &options).map_err(|e| e.to_string())?;
+    let identifiers = number_footnotes(&mut root);
+    let definitions = take_footnote_definitions(&mut root);
+
+    let mut node = ast_to_node(root, style, cx);
+
+    // Only the referenced footnotes are rendered, in the order of the number.
+    let footnotes = identifiers
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, identifier)| {
+            let definition = definitions.iter().find(|d| &d.identifier == identifier)?;
+            Some(Footnote {
+                label: (ix + 1).to_string().into(),
+                children: definition
+                    .children
+                    .iter()
+                    .map(|c| ast_to_node(c.clone(), style, cx))
+                    .collect(),
+            })
+        })
+        .collect::<Vec<_>>();
+    if !footnotes.is_empty() {
+        if let element::Node::Root { children } = &mut node {
+            children.push(element::Node::Footnotes(footnotes));
+        }
+    }
+
+    Ok(node)
+}
+
+/// Number the footnote references in the order of the first reference like GitHub, the number
+/// is set as the label of the reference.
+///
+/// Returns the identifiers of the footnotes in the order of the number.
+fn number_footnotes(root: &mut Node) -> Vec<String> {
+    fn visit(node: &mut Node, identifiers: &mut Vec<String>) {
+        if let Node::FootnoteReference(reference) = node {
+            let ix = match identifiers
+                .iter()
+                .position(|id| id == &reference.identifier)
+            {
+                Some(ix) => ix,
+                None => {
+                    identifiers.push(reference.identifier.clone());
+                    identifiers.len() - 1
+                }
+            };
+            reference.label = Some((ix + 1).to_string());
+            return;
+        }
+
+        if let Some(children) = node.children_mut() {
+            for child in children.iter_mut() {
+                visit(child, identifiers);
+            }
+        }
+    }
+
+    let mut identifiers = vec![];
+    if let Some(children) = root.children_mut() {
+        // The references in the footnote definitions are numbered after the content.
+        let (definitions, content): (Vec<_>, Vec<_>) = children
+            .iter_mut()
+            .partition(|c| matches!(c, Node::FootnoteDefinition(_)));
+        for child in content.into_iter().chain(definitions) {
+            visit(child, &mut identifiers);
+        }
+    }
+    identifiers
+}
+
+/// Remove the footnote definitions from the root, they are rendered at the end.
+fn take_footnote_definitions(root: &mut Node) -> Vec<mdast::FootnoteDefinition> {
+    let Some(children) = root.children_mut() else {
+        return vec![];
+    };
+
+    let mut definitions = vec![];
+    children.retain(|c| match c {
+        Node::FootnoteDefinition(definition) => {
+            definitions.push(definition.clone());
+            false
+        }
+        _ => true,
+    });
+    definitions
+}
+
+/// Parse the GitHub style alert marker at the start of the blockquote, e.g. `[!NOTE]`.
+///
+/// The marker must be the only content of the first line, returns the kind and the rest text.
+fn parse_alert_marker(text: &str) -> Option<(AlertKind, &str)> {
+    let rest = text.strip_prefix("[!")?;
+    let end = rest.find(']')?;
+    let kind = AlertKind::parse(&rest[..end])?;
+
+    let rest = rest[end + 1..].trim_start_matches([' ', '\t']);
+    if !rest.is_empty() && !rest.starts_with(['\r', '\n']) {
+        return None;
+    }
+
+    Some((kind, rest.trim_start()))
+}
+
+/// Take the alert marker from the first paragraph of the blockquote `children`.
+fn take_alert_marker(children: &mut Vec<Node>) -> Option<AlertKind> {
+    let Some(Node::Paragraph(paragraph)) = children.first_mut() else {
+        return None;
+    };
+    let Some(Node::Text(text)) = paragraph.children.first_mut() else {
+        return None;
+    };
+
+    let (kind, rest) = parse_alert_marker(&text.value)?;
+    text.value = rest.to_string();
+    if text.value.is_empty() {
+        paragraph.children.remove(0);
+        // Remove the line break after the marker.
+        if matches!(paragraph.children.first(), Some(Node::Break(_))) {
+            paragraph.children.remove(0);
+        }
+    }
+    if paragraph.children.is_empty() {
+        children.remove(0);
+    }
+
+    Some(kind)
 }
 
 fn parse_table_row(table: &mut Table, node: &mdast::TableRow) {
@@ -312,13 +462,27 @@ fn parse_paragraph(paragraph: &mut Paragraph, node: &mdast::Node) -> String {
             });
         }
         Node::InlineMath(raw) => {
-            text = raw.value.clone();
+            text = tex_to_unicode(&raw.value);
             paragraph.push(element::TextNode {
                 text: text.clone(),
                 marks: vec![(
                     0..text.len(),
                     InlineTextStyle {
-                        code: true,
+                        italic: true,
+                        ..Default::default()
+                    },
+                )],
+            });
+        }
+        Node::FootnoteReference(val) => {
+            let label = val.label.as_ref().unwrap_or(&val.identifier);
+            text = format!("[{}]", label);
+            paragraph.push(element::TextNode {
+                text: text.clone(),
+                marks: vec![(
+                    0..text.len(),
+                    InlineTextStyle {
+                        footnote: true,
                         ..Default::default()
                     },
                 )],
@@ -381,7 +545,24 @@ fn ast_to_node(value: mdast::Node, style: &TextViewStyle, cx: &mut App) -> eleme
 
             element::Node::Paragraph(paragraph)
         }
-        Node::Blockquote(val) => {
+        Node::Blockquote(mut val) => {
+            if let Some(kind) = take_alert_marker(&mut val.children) {
+                let span = val.position.as_ref().map(|pos| Span {
+                    start: pos.start.offset,
+                    end: pos.end.offset,
+                });
+                let children = val
+                    .children
+                    .into_iter()
+                    .map(|c| ast_to_node(c, style, cx))
+                    .collect();
+                return element::Node::Alert {
+                    kind,
+                    span,
+                    children,
+                };
+            }
+
             let mut paragraph = Paragraph::default();
             val.children.iter().for_each(|c| {
                 parse_paragraph(&mut paragraph, c);
@@ -410,6 +591,7 @@ fn ast_to_node(value: mdast::Node, style: &TextViewStyle, cx: &mut App) -> eleme
                 children,
                 spread: val.spread,
                 checked: val.checked,
+                line: val.position.map(|pos| pos.start.line.saturating_sub(1)),
             }
         }
         Node::Break(_) => element::Node::Break { html: false },
@@ -430,9 +612,7 @@ fn ast_to_node(value: mdast::Node, style: &TextViewStyle, cx: &mut App) -> eleme
                 children: paragraph,
             }
         }
-        Node::Math(val) => {
-            element::Node::CodeBlock(CodeBlock::new(val.value.into(), None, style, cx))
-        }
+        Node::Math(val) => element::Node::Math(tex_to_unicode(&val.value).into()),
         Node::Html(val) => match parse_html(&val.value) {
             Ok(el) => el,
             Err(err) => {
@@ -500,3 +680,42 @@ fn ast_to_node(value: mdast::Node, style: &TextViewStyle, cx: &mut App) -> eleme
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use markdown::{mdast::Node, ParseOptions};
+
+    use super::{number_footnotes, parse_alert_marker, take_footnote_definitions};
+    use crate::text::element::AlertKind;
+
+    #[test]
+    fn test_parse_alert_marker() {
+        assert_eq!(parse_alert_marker("[!NOTE]"), Some((AlertKind::Note, "")));
+        assert_eq!(
+            parse_alert_marker("[!warning]\nBe careful"),
+            Some((AlertKind::Warning, "Be careful"))
+        );
+        assert_eq!(
+            parse_alert_marker("[!TIP]  \r\nUse it"),
+            Some((AlertKind::Tip, "Use it"))
+        );
+        assert_eq!(parse_alert_marker("[!NOTE] inline"), None);
+        assert_eq!(parse_alert_marker("[!UNKNOWN]"), None);
+        assert_eq!(parse_alert_marker("Note"), None);
+    }
+
+    #[test]
+    fn test_number_footnotes() {
+        let raw = "Second[^b] and first[^a], again[^b].\n\n[^a]: A\n\n[^b]: B\n\n[^c]: C\n";
+        let mut root = markdown::to_mdast(raw, &ParseOptions::gfm()).unwrap();
+        assert_eq!(number_footnotes(&mut root), vec!["b", "a"]);
+
+        let definitions = take_footnote_definitions(&mut root);
+        assert_eq!(definitions.len(), 3);
+        assert!(root
+            .children()
+            .unwrap()
+            .iter()
+            .all(|c| !matches!(c, Node::FootnoteDefinition(_))));
+    }
+}
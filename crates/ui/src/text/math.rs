@@ -0,0 +1,385 @@
+//! A lightweight TeX math renderer, converts the common TeX commands into Unicode text.
+//!
+//! This is not a full TeX engine, it only covers the common used symbols, scripts, fractions
+//! and roots to make the formulas in README (e.g. `$E = mc^2$`) readable.
+
+const SYMBOLS: &[(&str, &str)] = &[
+    // Greek letters
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("rho", "ρ"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    // Operators
+    ("times", "×"),
+    ("div", "÷"),
+    ("cdot", "·"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("ast", "∗"),
+    ("circ", "∘"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("oint", "∮"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("infty", "∞"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("wedge", "∧"),
+    ("vee", "∨"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    // Relations
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("propto", "∝"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("perp", "⊥"),
+    ("parallel", "∥"),
+    ("mid", "∣"),
+    // Arrows
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⟹"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("uparrow", "↑"),
+    ("downarrow", "↓"),
+    // Misc
+    ("ldots", "…"),
+    ("cdots", "⋯"),
+    ("dots", "…"),
+    ("vdots", "⋮"),
+    ("ddots", "⋱"),
+    ("prime", "′"),
+    ("degree", "°"),
+    ("angle", "∠"),
+    ("hbar", "ℏ"),
+    ("ell", "ℓ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("aleph", "ℵ"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("quad", "  "),
+    ("qquad", "    "),
+    // Functions
+    ("sin", "sin"),
+    ("cos", "cos"),
+    ("tan", "tan"),
+    ("log", "log"),
+    ("ln", "ln"),
+    ("exp", "exp"),
+    ("lim", "lim"),
+    ("max", "max"),
+    ("min", "min"),
+    ("det", "det"),
+];
+
+/// The commands that render the argument as it is, e.g. `\text{if}`.
+const TEXT_COMMANDS: &[&str] = &[
+    "text",
+    "textrm",
+    "textbf",
+    "textit",
+    "mathrm",
+    "mathbf",
+    "mathit",
+    "mathsf",
+    "mathtt",
+    "operatorname",
+    "boldsymbol",
+];
+
+/// The commands that are ignored, the following delimiter is kept, e.g. `\left(`.
+const IGNORED_COMMANDS: &[&str] = &["left", "right", "displaystyle", "limits", "nolimits"];
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('i', 'ⁱ'),
+    ('n', 'ⁿ'),
+    ('T', 'ᵀ'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('t', 'ₜ'),
+    ('x', 'ₓ'),
+];
+
+/// Convert the TeX math into Unicode text, the unknown commands are kept as they are.
+pub(crate) fn tex_to_unicode(tex: &str) -> String {
+    let mut parser = Parser {
+        chars: tex.chars().collect(),
+        ix: 0,
+    };
+    let text = parser.parse_until(None);
+    text.trim().to_string()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    ix: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.ix).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.ix += 1;
+        Some(c)
+    }
+
+    /// Parse until the `end` char (consumed) or the end of input.
+    fn parse_until(&mut self, end: Option<char>) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.next() {
+            if Some(c) == end {
+                break;
+            }
+
+            match c {
+                '\\' => out.push_str(&self.parse_command()),
+                '{' => out.push_str(&self.parse_until(Some('}'))),
+                '^' => {
+                    let arg = self.parse_arg();
+                    out.push_str(&script(&arg, SUPERSCRIPTS, '^'));
+                }
+                '_' => {
+                    let arg = self.parse_arg();
+                    out.push_str(&script(&arg, SUBSCRIPTS, '_'));
+                }
+                '~' => out.push(' '),
+                // The alignment points, e.g. in `\begin{aligned}`.
+                '&' => {}
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Parse a single argument, a `{...}` group, a command or a char.
+    fn parse_arg(&mut self) -> String {
+        while self.peek() == Some(' ') {
+            self.ix += 1;
+        }
+
+        match self.next() {
+            Some('{') => self.parse_until(Some('}')),
+            Some('\\') => self.parse_command(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Parse the command after the `\`.
+    fn parse_command(&mut self) -> String {
+        let Some(c) = self.next() else {
+            return "\\".to_string();
+        };
+
+        if !c.is_ascii_alphabetic() {
+            return match c {
+                ',' | ':' | ';' | ' ' => " ".to_string(),
+                '!' => String::new(),
+                '\\' => "\n".to_string(),
+                c => c.to_string(),
+            };
+        }
+
+        let mut name = c.to_string();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+            self.ix += 1;
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_arg();
+                let denominator = self.parse_arg();
+                format!("{}/{}", wrap(&numerator), wrap(&denominator))
+            }
+            "sqrt" => format!("√{}", wrap(&self.parse_arg())),
+            "mathbb" => self.parse_arg().chars().map(double_struck).collect(),
+            "begin" | "end" => {
+                // The environment, e.g. `aligned`, `cases`, only the content is kept.
+                self.parse_arg();
+                String::new()
+            }
+            name if TEXT_COMMANDS.contains(&name) => self.parse_arg(),
+            name if IGNORED_COMMANDS.contains(&name) => String::new(),
+            name => match SYMBOLS.iter().find(|(cmd, _)| *cmd == name) {
+                Some((_, symbol)) => symbol.to_string(),
+                None => format!("\\{}", name),
+            },
+        }
+    }
+}
+
+/// Convert the `text` into the superscript or subscript chars, or keep the `mark` if any char
+/// has no script form, e.g. `x^{a+b}` to `x^(a+b)`.
+fn script(text: &str, table: &[(char, char)], mark: char) -> String {
+    let chars = text
+        .chars()
+        .map(|c| table.iter().find(|(k, _)| *k == c).map(|(_, v)| *v))
+        .collect::<Option<String>>();
+
+    match chars {
+        Some(chars) if !chars.is_empty() => chars,
+        // The prime symbols are already in the superscript position.
+        _ if mark == '^' && !text.is_empty() && text.chars().all(|c| c == '′') => {
+            text.to_string()
+        }
+        _ => format!("{}{}", mark, wrap(text)),
+    }
+}
+
+/// Wrap the `text` with the parentheses if it is more than one char.
+fn wrap(text: &str) -> String {
+    if text.chars().count() > 1 {
+        format!("({})", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn double_struck(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tex_to_unicode;
+
+    #[test]
+    fn test_tex_to_unicode() {
+        assert_eq!(tex_to_unicode("E = mc^2"), "E = mc²");
+        assert_eq!(tex_to_unicode("x_{i+1} = x_i^{10}"), "xᵢ₊₁ = xᵢ¹⁰");
+        assert_eq!(tex_to_unicode("e^{i\\pi} + 1 = 0"), "e^(iπ) + 1 = 0");
+        assert_eq!(tex_to_unicode("\\alpha \\leq \\beta"), "α ≤ β");
+        assert_eq!(tex_to_unicode("\\frac{a+b}{2}"), "(a+b)/2");
+        assert_eq!(tex_to_unicode("\\sqrt{x^2 + y^2}"), "√(x² + y²)");
+        assert_eq!(tex_to_unicode("\\sum_{i=1}^{n} i"), "∑ᵢ₌₁ⁿ i");
+        assert_eq!(tex_to_unicode("x \\in \\mathbb{R}"), "x ∈ ℝ");
+        assert_eq!(tex_to_unicode("\\left( a \\right)"), "( a )");
+        assert_eq!(tex_to_unicode("f(x) = \\text{if } x"), "f(x) = if  x");
+        assert_eq!(tex_to_unicode("f'^{\\prime}"), "f'′");
+        assert_eq!(tex_to_unicode("\\unknown{x}"), "\\unknownx");
+        assert_eq!(
+            tex_to_unicode("\\begin{aligned}\nx^2 &= 1 \\\\\ny &= 2\n\\end{aligned}"),
+            "x² = 1 \n\ny = 2"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 mod element;
 mod html;
 mod markdown;
+mod math;
 mod text_view;
 mod utils;
 
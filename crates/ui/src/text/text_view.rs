@@ -14,6 +14,7 @@ use super::{html::HtmlElement, markdown::MarkdownElement};
 /// used to display rich text in GPUI application (e.g., Help messages, Release notes)
 /// - Support Markdown GFM and HTML (Simple HTML like Safari Reader Mode) for showing most common used markups.
 /// - Support Heading, Paragraph, Bold, Italic, StrikeThrough, Code, Link, Image, Blockquote, List, Table, HorizontalRule, CodeBlock ...
+/// - Support Markdown TaskList, Footnote, Math and GitHub style Alert (e.g. `> [!NOTE]`).
 ///
 /// ## Not Goals
 ///
@@ -60,6 +61,14 @@ impl From<TextView> for Text {
 }
 
 impl Text {
+    /// Returns true if this is an empty `String`.
+    pub(crate) fn is_empty(&self) -> bool {
+        match self {
+            Self::String(s) => s.is_empty(),
+            Self::TextView(_) => false,
+        }
+    }
+
     /// Set the style for [`TextView`].
     ///
     /// Do nothing if this is `String`.
@@ -145,6 +154,21 @@ impl TextView {
             Self::Html(el) => Self::Html(el.style(style)),
         }
     }
+
+    /// Set the handler when the checkbox of a task list item (e.g. `- [ ] Todo`) is clicked,
+    /// only for Markdown.
+    ///
+    /// The handler receives the line (0-based) of the task list item in the source and the new
+    /// checked state. The source is not changed, update it by [`TextView::text`] if needed.
+    pub fn on_task_toggle(
+        self,
+        handler: impl Fn(usize, bool, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        match self {
+            Self::Markdown(el) => Self::Markdown(el.on_task_toggle(handler)),
+            Self::Html(el) => Self::Html(el),
+        }
+    }
 }
 
 impl RenderOnce for TextView {
@@ -0,0 +1,162 @@
+//! Test the LSP client against the fake language server in `support`.
+use std::str::FromStr as _;
+
+use gpui_component::input::lsp::{
+    lsp_types::{
+        notification::{DidChangeTextDocument, DidOpenTextDocument, Exit, PublishDiagnostics},
+        request::{GotoDefinition, HoverRequest, Initialize, Shutdown, SignatureHelpRequest},
+        DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+        GotoDefinitionParams, GotoDefinitionResponse, HoverContents, HoverParams, InitializeParams,
+        Position, Range, SignatureHelpParams, TextDocumentContentChangeEvent,
+        TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Uri,
+        VersionedTextDocumentIdentifier,
+    },
+    LspClient,
+};
+
+mod support;
+
+fn position_params(uri: &Uri, line: u32, character: u32) -> TextDocumentPositionParams {
+    TextDocumentPositionParams::new(
+        TextDocumentIdentifier::new(uri.clone()),
+        Position::new(line, character),
+    )
+}
+
+#[test]
+fn test_lsp_client() {
+    smol::block_on(async {
+        let (input, output) = support::fake_lsp_server::spawn();
+        let (client, notifications, io) = LspClient::new(input, output);
+        let io = smol::spawn(io);
+
+        let result = client
+            .request::<Initialize>(InitializeParams::default())
+            .await
+            .unwrap();
+        assert!(result.capabilities.hover_provider.is_some());
+
+        let uri = Uri::from_str("file:///tmp/main.rs").unwrap();
+        client
+            .notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "rust".into(),
+                    1,
+                    "fn main() {\n    // 你好 TODO\n}".into(),
+                ),
+            })
+            .unwrap();
+
+        let params = notifications
+            .recv()
+            .await
+            .unwrap()
+            .parse::<PublishDiagnostics>()
+            .unwrap();
+        assert_eq!(params.uri, uri);
+        assert_eq!(params.version, Some(1));
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(
+            params.diagnostics[0].range,
+            Range::new(Position::new(1, 10), Position::new(1, 14))
+        );
+        assert_eq!(
+            params.diagnostics[0].severity,
+            Some(DiagnosticSeverity::WARNING)
+        );
+
+        // Remove the `TODO` by an incremental change.
+        client
+            .notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(1, 9), Position::new(1, 14))),
+                    range_length: None,
+                    text: "".into(),
+                }],
+            })
+            .unwrap();
+        let params = notifications
+            .recv()
+            .await
+            .unwrap()
+            .parse::<PublishDiagnostics>()
+            .unwrap();
+        assert_eq!(params.version, Some(2));
+        assert!(params.diagnostics.is_empty());
+
+        // Replace the entire text by a full change.
+        client
+            .notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 3),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "TODO\nfn main() { TODO }".into(),
+                }],
+            })
+            .unwrap();
+        let params = notifications
+            .recv()
+            .await
+            .unwrap()
+            .parse::<PublishDiagnostics>()
+            .unwrap();
+        assert_eq!(params.version, Some(3));
+        assert_eq!(params.diagnostics.len(), 2);
+        assert_eq!(
+            params.diagnostics[1].range,
+            Range::new(Position::new(1, 12), Position::new(1, 16))
+        );
+
+        let hover = client
+            .request::<HoverRequest>(HoverParams {
+                text_document_position_params: position_params(&uri, 0, 3),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup hover contents");
+        };
+        assert_eq!(content.value, "**hover** at 0:3");
+
+        let definition = client
+            .request::<GotoDefinition>(GotoDefinitionParams {
+                text_document_position_params: position_params(&uri, 1, 4),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        let Some(GotoDefinitionResponse::Scalar(location)) = definition else {
+            panic!("expected a definition location");
+        };
+        assert_eq!(location.uri, uri);
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(0, 0), Position::new(0, 4))
+        );
+
+        let help = client
+            .request::<SignatureHelpRequest>(SignatureHelpParams {
+                context: None,
+                text_document_position_params: position_params(&uri, 2, 1),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(help.signatures[0].label, "fn add(a: i32, b: i32) -> i32");
+        assert_eq!(help.active_parameter, Some(0));
+
+        client.request::<Shutdown>(()).await.unwrap();
+        client.notify::<Exit>(()).unwrap();
+        io.await.unwrap();
+
+        // The requests fail after the server exited.
+        assert!(client.request::<Shutdown>(()).await.is_err());
+    });
+}
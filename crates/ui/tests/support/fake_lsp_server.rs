@@ -0,0 +1,273 @@
+//! A tiny language server for testing the LSP client, runs in a thread and communicates over
+//! a pair of pipes.
+//!
+//! - Publishes a warning diagnostic for each `TODO` in the opened documents.
+//! - Hover returns the position in Markdown.
+//! - Go to definition returns the first word of the document.
+//! - Signature help returns `fn add(a: i32, b: i32) -> i32`, the active parameter is the
+//!   count of `,` before the position in the line.
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, PipeReader, PipeWriter, Write},
+    thread,
+};
+
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{
+        GotoDefinition, HoverRequest, Initialize, Request as _, Shutdown, SignatureHelpRequest,
+    },
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, InitializeResult, Location, MarkupContent, MarkupKind, OneOf,
+    ParameterInformation, ParameterLabel, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, SignatureHelp, SignatureHelpOptions, SignatureHelpParams,
+    SignatureInformation, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use serde_json::{json, Value};
+use smol::Unblock;
+
+/// Start the server in a thread.
+///
+/// Returns the streams to write the messages to the server and read the messages from it, the
+/// server exits on the `exit` notification or the input is closed.
+pub fn spawn() -> (Unblock<PipeWriter>, Unblock<PipeReader>) {
+    let (client_reader, server_writer) = io::pipe().unwrap();
+    let (server_reader, client_writer) = io::pipe().unwrap();
+    thread::spawn(move || run(BufReader::new(server_reader), server_writer));
+
+    (Unblock::new(client_writer), Unblock::new(client_reader))
+}
+
+fn run(mut reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    let writer = &mut writer;
+    let mut documents: HashMap<Uri, String> = HashMap::new();
+
+    while let Some(message) = read_message(&mut reader)? {
+        let id = message.get("id").cloned();
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = message["params"].clone();
+
+        match method {
+            Initialize::METHOD => respond(writer, id, initialize_result())?,
+            Shutdown::METHOD => respond(writer, id, Value::Null)?,
+            Exit::METHOD => break,
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = serde_json::from_value(params)?;
+                let doc = params.text_document;
+                documents.insert(doc.uri.clone(), doc.text);
+                publish_diagnostics(writer, &doc.uri, &documents[&doc.uri], doc.version)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = serde_json::from_value(params)?;
+                let doc = params.text_document;
+                let text = documents.entry(doc.uri.clone()).or_default();
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            let start = offset_of(text, range.start);
+                            let end = offset_of(text, range.end);
+                            text.replace_range(start..end, &change.text);
+                        }
+                        None => *text = change.text,
+                    }
+                }
+                publish_diagnostics(writer, &doc.uri, text, doc.version)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = serde_json::from_value(params)?;
+                documents.remove(&params.text_document.uri);
+            }
+            HoverRequest::METHOD => {
+                let params: HoverParams = serde_json::from_value(params)?;
+                let pos = params.text_document_position_params.position;
+                let hover = Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: format!("**hover** at {}:{}", pos.line, pos.character),
+                    }),
+                    range: Some(Range::new(pos, pos)),
+                };
+                respond(writer, id, serde_json::to_value(hover)?)?;
+            }
+            GotoDefinition::METHOD => {
+                let params: GotoDefinitionParams = serde_json::from_value(params)?;
+                let uri = params.text_document_position_params.text_document.uri;
+                let len = documents
+                    .get(&uri)
+                    .and_then(|text| text.split(|c: char| !c.is_alphanumeric()).next())
+                    .map_or(0, |word| word.len());
+                let location = Location::new(
+                    uri,
+                    Range::new(Position::new(0, 0), Position::new(0, len as u32)),
+                );
+                respond(writer, id, serde_json::to_value(location)?)?;
+            }
+            SignatureHelpRequest::METHOD => {
+                let params: SignatureHelpParams = serde_json::from_value(params)?;
+                let pos = params.text_document_position_params;
+                let active_parameter = documents
+                    .get(&pos.text_document.uri)
+                    .and_then(|text| text.lines().nth(pos.position.line as usize))
+                    .map_or(0, |line| {
+                        line.chars()
+                            .take(pos.position.character as usize)
+                            .filter(|c| *c == ',')
+                            .count() as u32
+                    });
+                respond(
+                    writer,
+                    id,
+                    serde_json::to_value(signature_help(active_parameter))?,
+                )?;
+            }
+            _ => {
+                // Unknown request must be responded, the notification can be ignored.
+                if id.is_some() {
+                    respond(writer, id, Value::Null)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn initialize_result() -> Value {
+    let result = InitializeResult {
+        capabilities: ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL,
+            )),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".into(), ",".into()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        server_info: None,
+    };
+    serde_json::to_value(result).unwrap()
+}
+
+fn signature_help(active_parameter: u32) -> SignatureHelp {
+    SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label: "fn add(a: i32, b: i32) -> i32".into(),
+            documentation: None,
+            parameters: Some(vec![
+                ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([7, 13]),
+                    documentation: None,
+                },
+                ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([15, 21]),
+                    documentation: None,
+                },
+            ]),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    }
+}
+
+fn publish_diagnostics(
+    writer: &mut impl Write,
+    uri: &Uri,
+    text: &str,
+    version: i32,
+) -> io::Result<()> {
+    let mut diagnostics = vec![];
+    for (line, line_text) in text.lines().enumerate() {
+        for (start, _) in line_text.match_indices("TODO") {
+            diagnostics.push(Diagnostic {
+                range: Range::new(
+                    Position::new(line as u32, utf16_len(&line_text[..start])),
+                    Position::new(line as u32, utf16_len(&line_text[..start + 4])),
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: "Found TODO".into(),
+                ..Default::default()
+            });
+        }
+    }
+
+    let params = PublishDiagnosticsParams::new(uri.clone(), diagnostics, Some(version));
+    send(
+        writer,
+        json!({
+        "jsonrpc": "2.0",
+        "method": PublishDiagnostics::METHOD,
+        "params": params,
+        }),
+    )
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+/// Returns the byte offset of the LSP position (the character is in UTF-16 code units).
+fn offset_of(text: &str, position: Position) -> usize {
+    let mut offset = 0;
+    for (ix, line) in text.split_inclusive('\n').enumerate() {
+        if ix == position.line as usize {
+            let mut units = 0;
+            for (byte_ix, c) in line.char_indices() {
+                if units >= position.character as usize {
+                    return offset + byte_ix;
+                }
+                units += c.len_utf16();
+            }
+            return offset + line.len();
+        }
+        offset += line.len();
+    }
+    text.len()
+}
+
+fn respond(writer: &mut impl Write, id: Option<Value>, result: Value) -> io::Result<()> {
+    send(
+        writer,
+        json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    )
+}
+
+fn send(writer: &mut impl Write, message: Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
@@ -0,0 +1 @@
+pub mod fake_lsp_server;